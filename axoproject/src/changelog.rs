@@ -1,10 +1,21 @@
 //! Support for interpretting changelogs
 
+use std::fmt::Write;
+
 use camino::Utf8Path;
 
 use crate::errors::Result;
+use crate::local_repo::CommitInfo;
 use crate::{PackageInfo, Version, WorkspaceInfo};
 
+/// The kinds of conventional commit we put in release notes, and their headings
+const CONVENTIONAL_SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance"),
+    ("revert", "Reverts"),
+];
+
 /// Info about a changelog entry
 #[derive(PartialEq, Eq, PartialOrd, Ord, Debug, Clone)]
 pub struct ChangelogInfo {
@@ -64,10 +75,11 @@ pub fn changelog_for_version_inner(
     // of the release notes.
     //
     // If that fails, try to find a section called "Unreleased" and use that (if it's a prerelease).
-    if let Some(info) = try_extract_changelog_exact(&changelogs, version)
+    if let Some(mut info) = try_extract_changelog_exact(&changelogs, version)
         .or_else(|| try_extract_changelog_normalized(&changelogs, version))
         .or_else(|| try_extract_changelog_unreleased(&changelogs, version))
     {
+        info.body = resolve_reference_links(changelog_str, &info.body);
         Ok(Some(info))
     } else {
         Err(crate::errors::AxoprojectError::ChangelogVersionNotFound {
@@ -143,3 +155,316 @@ fn try_extract_changelog_unreleased(
         body: release_notes.notes.to_string(),
     })
 }
+
+/// Add the definitions of any reference-style links an entry uses but doesn't define
+///
+/// Keep a Changelog puts these at the bottom of the file (`[1.0.0]: https://...`),
+/// so they're lost when we pull out a single entry.
+pub fn resolve_reference_links(changelog_str: &str, body: &str) -> String {
+    let defined_in_body = link_definitions(body)
+        .into_iter()
+        .map(|(label, _)| label)
+        .collect::<Vec<_>>();
+    let body_lower = body.to_lowercase();
+    let mut needed = vec![];
+    for (label, line) in link_definitions(changelog_str) {
+        if defined_in_body.contains(&label) || needed.iter().any(|(l, _)| *l == label) {
+            continue;
+        }
+        if body_lower.contains(&format!("[{label}]")) {
+            needed.push((label, line));
+        }
+    }
+    if needed.is_empty() {
+        return body.to_owned();
+    }
+
+    let mut result = body.trim_end().to_owned();
+    result.push('\n');
+    for (_, line) in needed {
+        result.push('\n');
+        result.push_str(line);
+    }
+    result
+}
+
+/// Find the reference-style link definitions in some markdown, with their (lowercased) labels
+fn link_definitions(markdown: &str) -> Vec<(String, &str)> {
+    markdown
+        .lines()
+        .filter_map(|line| {
+            let trimmed = line.trim_start();
+            // Definitions can be indented by at most 3 spaces
+            if line.len() - trimmed.len() > 3 {
+                return None;
+            }
+            let (label, rest) = trimmed.strip_prefix('[')?.split_once("]:")?;
+            if label.is_empty() || rest.trim().is_empty() {
+                return None;
+            }
+            Some((label.to_lowercase(), trimmed))
+        })
+        .collect()
+}
+
+/// Combine the changelog entries of several packages being released together
+///
+/// Each entry gets its own heading. If there's only one entry, it's returned as-is.
+pub fn combine_changelogs(
+    title: String,
+    entries: Vec<(String, ChangelogInfo)>,
+) -> Option<ChangelogInfo> {
+    if entries.len() <= 1 {
+        return entries.into_iter().next().map(|(_, info)| info);
+    }
+    let mut body = String::new();
+    for (heading, info) in entries {
+        writeln!(body, "## {heading}\n\n{}\n", info.body.trim()).unwrap();
+    }
+    Some(ChangelogInfo {
+        title,
+        body: body.trim_end().to_owned(),
+    })
+}
+
+/// A commit message in the conventional commits format
+#[derive(Debug, PartialEq, Eq)]
+struct ConventionalCommit<'a> {
+    /// The type, like `feat` or `fix`
+    kind: &'a str,
+    /// The optional scope, like `installer` in `fix(installer): ...`
+    scope: Option<&'a str>,
+    /// Whether this was marked with `!` or a `BREAKING CHANGE` footer
+    breaking: bool,
+    /// The rest of the summary
+    description: &'a str,
+}
+
+/// Parse a commit as a conventional commit, if it is one
+fn parse_conventional_commit(commit: &CommitInfo) -> Option<ConventionalCommit<'_>> {
+    let (prefix, description) = commit.summary.split_once(": ")?;
+    let (prefix, bang) = match prefix.strip_suffix('!') {
+        Some(prefix) => (prefix, true),
+        None => (prefix, false),
+    };
+    let (kind, scope) = match prefix.split_once('(') {
+        Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?)),
+        None => (prefix, None),
+    };
+    if kind.is_empty() || !kind.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let breaking = bang
+        || commit.body.lines().any(|line| {
+            line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+        });
+    Some(ConventionalCommit {
+        kind,
+        scope,
+        breaking,
+        description: description.trim(),
+    })
+}
+
+/// Generate release notes from a list of conventional commits
+///
+/// Breaking changes come first, followed by features, fixes, performance
+/// improvements and reverts. Anything else is left out, and if that leaves
+/// nothing we return None.
+///
+/// If `pull_request_url` is given (like `https://github.com/owner/repo/pull`),
+/// any `(#123)` references to pull requests become links.
+pub fn changelog_from_commits(
+    title: String,
+    commits: &[CommitInfo],
+    pull_request_url: Option<&str>,
+) -> Option<ChangelogInfo> {
+    notes_from_commits(title, commits, pull_request_url, false)
+}
+
+/// Generate release notes from the git history of a release
+///
+/// This is [`changelog_from_commits`][] for projects that don't necessarily
+/// use conventional commits: everything that doesn't fit in one of its sections
+/// ends up under "Other Changes", and merged pull requests are listed by their title.
+pub fn changelog_from_history(
+    title: String,
+    commits: &[CommitInfo],
+    pull_request_url: Option<&str>,
+) -> Option<ChangelogInfo> {
+    notes_from_commits(title, commits, pull_request_url, true)
+}
+
+/// Shared implementation of [`changelog_from_commits`][] and [`changelog_from_history`][]
+fn notes_from_commits(
+    title: String,
+    commits: &[CommitInfo],
+    pull_request_url: Option<&str>,
+    include_other: bool,
+) -> Option<ChangelogInfo> {
+    let commits = commits
+        .iter()
+        .filter_map(squash_pull_request_merge)
+        .collect::<Vec<_>>();
+    let parsed = commits
+        .iter()
+        .map(|commit| (commit, parse_conventional_commit(commit)))
+        .collect::<Vec<_>>();
+
+    let mut sections = vec![];
+    let breaking = parsed
+        .iter()
+        .filter(|(_, c)| c.as_ref().is_some_and(|c| c.breaking))
+        .collect::<Vec<_>>();
+    sections.push(("Breaking Changes", breaking));
+    for (kind, heading) in CONVENTIONAL_SECTIONS {
+        let commits = parsed
+            .iter()
+            .filter(|(_, c)| {
+                c.as_ref()
+                    .is_some_and(|c| !c.breaking && c.kind.eq_ignore_ascii_case(kind))
+            })
+            .collect::<Vec<_>>();
+        sections.push((heading, commits));
+    }
+    if include_other {
+        let other = parsed
+            .iter()
+            .filter(|(_, c)| match c {
+                Some(c) => {
+                    !c.breaking
+                        && !CONVENTIONAL_SECTIONS
+                            .iter()
+                            .any(|(kind, _)| c.kind.eq_ignore_ascii_case(kind))
+                }
+                None => true,
+            })
+            .collect::<Vec<_>>();
+        sections.push(("Other Changes", other));
+    }
+
+    let mut body = String::new();
+    for (heading, commits) in sections {
+        if commits.is_empty() {
+            continue;
+        }
+        writeln!(body, "### {heading}\n").unwrap();
+        for (commit, parsed) in commits {
+            let short_sha = commit.sha.get(..7).unwrap_or(&commit.sha);
+            let line = match parsed {
+                Some(ConventionalCommit {
+                    scope: Some(scope),
+                    description,
+                    ..
+                }) => format!("**{scope}:** {description}"),
+                Some(ConventionalCommit { description, .. }) => description.to_string(),
+                None => commit.summary.clone(),
+            };
+            let line = link_pull_requests(&line, pull_request_url);
+            writeln!(body, "- {line} ({short_sha})").unwrap();
+        }
+        body.push('\n');
+    }
+
+    if body.is_empty() {
+        return None;
+    }
+    Some(ChangelogInfo {
+        title,
+        body: body.trim_end().to_owned(),
+    })
+}
+
+/// Turn GitHub's "Merge pull request #123 from ..." commits into the PR's title
+///
+/// Other merge commits aren't interesting enough to mention, so they're dropped.
+fn squash_pull_request_merge(commit: &CommitInfo) -> Option<CommitInfo> {
+    let Some(rest) = commit.summary.strip_prefix("Merge pull request #") else {
+        if commit.summary.starts_with("Merge branch ")
+            || commit.summary.starts_with("Merge remote-tracking branch ")
+        {
+            return None;
+        }
+        return Some(commit.clone());
+    };
+    let number = rest.split_whitespace().next().unwrap_or_default();
+    let (title, body) = commit
+        .body
+        .split_once('\n')
+        .unwrap_or((commit.body.as_str(), ""));
+    let title = title.trim();
+    let title = if title.is_empty() {
+        commit.summary.as_str()
+    } else {
+        title
+    };
+    Some(CommitInfo {
+        sha: commit.sha.clone(),
+        summary: format!("{title} (#{number})"),
+        body: body.trim().to_owned(),
+    })
+}
+
+/// Turn `(#123)` references into links to pull requests
+fn link_pull_requests(line: &str, pull_request_url: Option<&str>) -> String {
+    let Some(pull_request_url) = pull_request_url else {
+        return line.to_owned();
+    };
+    let mut output = String::new();
+    let mut rest = line;
+    while let Some(idx) = rest.find("(#") {
+        let (before, after) = rest.split_at(idx);
+        output.push_str(before);
+        let digits = after[2..]
+            .chars()
+            .take_while(|c| c.is_ascii_digit())
+            .count();
+        if digits > 0 && after[2 + digits..].starts_with(')') {
+            let number = &after[2..2 + digits];
+            write!(output, "([#{number}]({pull_request_url}/{number}))").unwrap();
+            rest = &after[3 + digits..];
+        } else {
+            output.push_str("(#");
+            rest = &after[2..];
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+/// Rename a changelog's "Unreleased" heading to a version, for cutting a release
+///
+/// Brackets (as in Keep a Changelog's `## [Unreleased]`) are kept. Returns None
+/// if there's no such heading.
+pub fn stamp_unreleased(changelog_str: &str, version: &str) -> Option<String> {
+    let mut output = String::with_capacity(changelog_str.len());
+    let mut found = false;
+    for line in changelog_str.split_inclusive('\n') {
+        match stamp_heading(line, version) {
+            Some(stamped) if !found => {
+                output.push_str(&stamped);
+                found = true;
+            }
+            _ => output.push_str(line),
+        }
+    }
+    found.then_some(output)
+}
+
+/// If this line is an "Unreleased" heading, rename it to `version`
+fn stamp_heading(line: &str, version: &str) -> Option<String> {
+    let text = line.trim_start_matches('#');
+    let hashes = &line[..line.len() - text.len()];
+    if hashes.is_empty() {
+        return None;
+    }
+    let (open, name, close) = match text.trim().strip_prefix('[') {
+        Some(inner) => ("[", inner.strip_suffix(']')?, "]"),
+        None => ("", text.trim(), ""),
+    };
+    if !name.eq_ignore_ascii_case("unreleased") {
+        return None;
+    }
+    let newline = &text[text.trim_end().len()..];
+    Some(format!("{hashes} {open}{version}{close}{newline}"))
+}
@@ -0,0 +1,530 @@
+//! Support for CMake projects (CMakeLists.txt)
+//!
+//! We don't run CMake to find out about a project, we just read enough of its
+//! CMakeLists.txt to get the `project()` and the targets it `install()`s. The
+//! install tree is where the build will find them later.
+
+use axoasset::SourceFile;
+use camino::Utf8Path;
+
+use crate::{
+    errors::AxoprojectError, PackageInfo, Result, SortedMap, Version, WorkspaceInfo, WorkspaceKind,
+    WorkspaceSearch, WorkspaceStructure,
+};
+
+/// The file that defines a CMake project
+pub const CMAKE_LISTS: &str = "CMakeLists.txt";
+
+/// Arguments to `install(TARGETS ...)` that end the list of targets
+const INSTALL_KEYWORDS: &[&str] = &[
+    "EXPORT",
+    "RUNTIME_DEPENDENCIES",
+    "RUNTIME_DEPENDENCY_SET",
+    "ARCHIVE",
+    "LIBRARY",
+    "RUNTIME",
+    "OBJECTS",
+    "FRAMEWORK",
+    "BUNDLE",
+    "PRIVATE_HEADER",
+    "PUBLIC_HEADER",
+    "RESOURCE",
+    "FILE_SET",
+    "CXX_MODULES_BMI",
+    "DESTINATION",
+    "PERMISSIONS",
+    "CONFIGURATIONS",
+    "COMPONENT",
+    "NAMELINK_COMPONENT",
+    "OPTIONAL",
+    "EXCLUDE_FROM_ALL",
+    "NAMELINK_ONLY",
+    "NAMELINK_SKIP",
+    "INCLUDES",
+];
+
+/// Try to find a CMake project at start_dir, walking up
+/// ancestors as necessary until we reach clamp_to_dir (or run out of ancestors).
+///
+/// Like npm projects, these are only supported as members of a dist-workspace.toml.
+pub fn get_workspace(start_dir: &Utf8Path, clamp_to_dir: Option<&Utf8Path>) -> WorkspaceSearch {
+    let manifest_path = match crate::find_file(CMAKE_LISTS, start_dir, clamp_to_dir) {
+        Ok(path) => path,
+        Err(e) => {
+            return WorkspaceSearch::Missing(e);
+        }
+    };
+    match read_workspace(&manifest_path) {
+        Ok(workspace) => WorkspaceSearch::Found(workspace),
+        Err(e) => WorkspaceSearch::Broken {
+            manifest_path,
+            cause: e,
+        },
+    }
+}
+
+fn read_workspace(manifest_path: &Utf8Path) -> Result<WorkspaceStructure> {
+    let root = manifest_path.parent().unwrap().to_owned();
+    let mut project = CmakeProject::default();
+    project.read_lists(manifest_path, SortedMap::new())?;
+
+    let Some(name) = project.name.clone() else {
+        return Err(AxoprojectError::NamelessCmakeProject {
+            manifest: manifest_path.to_owned(),
+        });
+    };
+    let version = project
+        .version
+        .as_deref()
+        .map(|version| {
+            cmake_version_to_semver(version).ok_or_else(|| AxoprojectError::CmakeVersionParse {
+                manifest_path: manifest_path.to_owned(),
+                version: version.to_owned(),
+            })
+        })
+        .transpose()?
+        .map(Version::Generic);
+    let InstalledTargets {
+        binaries,
+        cdylibs,
+        cstaticlibs,
+    } = project.installed_targets();
+
+    let root_auto_includes = crate::find_auto_includes(&root)?;
+    let mut info = PackageInfo {
+        true_name: name.clone(),
+        true_version: version.clone(),
+        name,
+        npm_scope: None,
+        python_build_backend: None,
+        version,
+        manifest_path: manifest_path.to_owned(),
+        dist_manifest_path: None,
+        package_root: root.clone(),
+        description: project.description,
+        authors: vec![],
+        license: None,
+        publish: true,
+        repository_url: None,
+        homepage_url: project.homepage_url,
+        keywords: None,
+        documentation_url: None,
+        readme_file: None,
+        license_files: vec![],
+        changelog_file: None,
+        binaries,
+        cdylibs,
+        cstaticlibs,
+        #[cfg(feature = "cargo-projects")]
+        cargo_metadata_table: None,
+        #[cfg(feature = "cargo-projects")]
+        cargo_package_id: None,
+        build_command: None,
+        axoupdater_versions: Default::default(),
+        local_dependencies: vec![],
+    };
+    crate::merge_auto_includes(&mut info, &root_auto_includes);
+
+    Ok(WorkspaceStructure {
+        sub_workspaces: vec![],
+        packages: vec![info],
+        workspace: WorkspaceInfo {
+            kind: WorkspaceKind::Cmake,
+            target_dir: root.join("target"),
+            workspace_dir: root,
+            manifest_path: manifest_path.to_owned(),
+            dist_manifest_path: None,
+            root_auto_includes,
+            #[cfg(feature = "cargo-projects")]
+            cargo_metadata_table: None,
+            #[cfg(feature = "cargo-projects")]
+            cargo_profiles: crate::rust::CargoProfiles::new(),
+        },
+    })
+}
+
+/// The targets a CMake project installs, grouped by kind
+#[derive(Debug, Default)]
+struct InstalledTargets {
+    /// executables (`add_executable`)
+    binaries: Vec<String>,
+    /// shared libraries (`add_library(... SHARED)`)
+    cdylibs: Vec<String>,
+    /// static libraries (`add_library(... STATIC)`)
+    cstaticlibs: Vec<String>,
+}
+
+/// What kind of thing a CMake target builds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TargetKind {
+    Executable,
+    SharedLibrary,
+    StaticLibrary,
+    /// MODULE/OBJECT/INTERFACE libraries, which we don't ship
+    Other,
+}
+
+/// Everything we learned from a project's CMakeLists.txt (and its subdirectories)
+#[derive(Debug, Default)]
+struct CmakeProject {
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    homepage_url: Option<String>,
+    /// Every target we saw defined, by name
+    targets: SortedMap<String, TargetKind>,
+    /// Every target passed to `install(TARGETS ...)`, in order
+    installed: Vec<String>,
+}
+
+impl CmakeProject {
+    /// Read a CMakeLists.txt, following `add_subdirectory`
+    ///
+    /// `vars` are the variables set by the parent directory, which we copy
+    /// just like CMake does.
+    fn read_lists(
+        &mut self,
+        lists_path: &Utf8Path,
+        mut vars: SortedMap<String, String>,
+    ) -> Result<()> {
+        let dir = lists_path.parent().unwrap();
+        let source = SourceFile::load_local(lists_path)?;
+        for Command { name, args } in parse_commands(source.contents()) {
+            let args = args
+                .iter()
+                .map(|arg| expand_vars(arg, &vars))
+                .collect::<Vec<_>>();
+            let Some(first) = args.first().cloned() else {
+                continue;
+            };
+            match name.as_str() {
+                "project" if self.name.is_none() => {
+                    vars.insert("PROJECT_NAME".to_owned(), first.clone());
+                    vars.insert("CMAKE_PROJECT_NAME".to_owned(), first.clone());
+                    self.name = Some(first);
+                    self.version = keyword_value(&args, "VERSION");
+                    self.description = keyword_value(&args, "DESCRIPTION");
+                    self.homepage_url = keyword_value(&args, "HOMEPAGE_URL");
+                    if let Some(version) = &self.version {
+                        vars.insert("PROJECT_VERSION".to_owned(), version.clone());
+                    }
+                }
+                "set" => {
+                    // Only simple single-value variables, that's all we need to find targets
+                    if let [_, value] = &args[..] {
+                        vars.insert(first, value.clone());
+                    }
+                }
+                "add_executable" => {
+                    if !args.iter().any(|a| a == "IMPORTED" || a == "ALIAS") {
+                        self.targets.insert(first, TargetKind::Executable);
+                    }
+                }
+                "add_library" => {
+                    let kind = match args.get(1).map(|a| a.as_str()) {
+                        Some("IMPORTED") | Some("ALIAS") => continue,
+                        Some("STATIC") => TargetKind::StaticLibrary,
+                        Some("SHARED") => TargetKind::SharedLibrary,
+                        Some("MODULE") | Some("OBJECT") | Some("INTERFACE") => TargetKind::Other,
+                        // The default depends on this variable
+                        _ if vars.get("BUILD_SHARED_LIBS").is_some_and(|v| is_truthy(v)) => {
+                            TargetKind::SharedLibrary
+                        }
+                        _ => TargetKind::StaticLibrary,
+                    };
+                    self.targets.insert(first, kind);
+                }
+                "install" if first == "TARGETS" => {
+                    let targets = args[1..]
+                        .iter()
+                        .take_while(|a| !INSTALL_KEYWORDS.contains(&a.as_str()));
+                    for target in targets {
+                        if !self.installed.contains(target) {
+                            self.installed.push(target.clone());
+                        }
+                    }
+                }
+                "add_subdirectory" => {
+                    let sub_lists = dir.join(&first).join(CMAKE_LISTS);
+                    // Anything we couldn't expand won't exist, so just skip it
+                    if sub_lists.is_file() {
+                        self.read_lists(&sub_lists, vars.clone())?;
+                    }
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the installed targets that we know how to ship
+    fn installed_targets(&self) -> InstalledTargets {
+        let mut result = InstalledTargets::default();
+        for target in &self.installed {
+            let list = match self.targets.get(target) {
+                Some(TargetKind::Executable) => &mut result.binaries,
+                Some(TargetKind::SharedLibrary) => &mut result.cdylibs,
+                Some(TargetKind::StaticLibrary) => &mut result.cstaticlibs,
+                Some(TargetKind::Other) | None => continue,
+            };
+            list.push(target.clone());
+        }
+        result
+    }
+}
+
+/// Convert a CMake `project(... VERSION)` to semver
+///
+/// CMake versions are `major[.minor[.patch[.tweak]]]`, we don't support a tweak.
+pub fn cmake_version_to_semver(version: &str) -> Option<semver::Version> {
+    let mut parts = version.split('.');
+    let mut next = || -> Option<u64> {
+        match parts.next() {
+            Some(part) => part.parse().ok(),
+            None => Some(0),
+        }
+    };
+    let version = semver::Version::new(next()?, next()?, next()?);
+    parts.next().is_none().then_some(version)
+}
+
+/// Get the argument after a keyword, like `VERSION 1.0`
+fn keyword_value(args: &[String], keyword: &str) -> Option<String> {
+    let idx = args.iter().position(|a| a == keyword)?;
+    args.get(idx + 1).cloned()
+}
+
+/// Whether CMake considers a value true
+fn is_truthy(value: &str) -> bool {
+    matches!(
+        value.to_ascii_uppercase().as_str(),
+        "1" | "ON" | "YES" | "TRUE" | "Y"
+    )
+}
+
+/// Expand `${VAR}` references to variables we know about
+///
+/// Unknown variables are left alone, so anything using them won't match.
+fn expand_vars(arg: &str, vars: &SortedMap<String, String>) -> String {
+    let mut result = String::new();
+    let mut rest = arg;
+    while let Some(start) = rest.find("${") {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        let var = &rest[start + 2..start + len];
+        result.push_str(&rest[..start]);
+        match vars.get(var) {
+            Some(value) => result.push_str(value),
+            None => result.push_str(&rest[start..=start + len]),
+        }
+        rest = &rest[start + len + 1..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// A command invocation in a CMake script, like `project(foo VERSION 1.0)`
+#[derive(Debug, PartialEq, Eq)]
+struct Command {
+    /// The command name, lowercased (command names are case-insensitive)
+    name: String,
+    /// The arguments, with any quotes removed
+    args: Vec<String>,
+}
+
+/// Split a CMake script into commands
+///
+/// This is a simplified version of CMake's own grammar: it understands comments,
+/// quoted and bracket arguments, but doesn't evaluate anything.
+fn parse_commands(source: &str) -> Vec<Command> {
+    let mut commands = vec![];
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c == '#' {
+            skip_comment(&mut chars);
+            continue;
+        }
+        if !(c.is_ascii_alphanumeric() || c == '_') {
+            continue;
+        }
+        let mut name = c.to_string();
+        while let Some(&c) = chars.peek() {
+            if !(c.is_ascii_alphanumeric() || c == '_') {
+                break;
+            }
+            name.push(c);
+            chars.next();
+        }
+        while chars.peek().is_some_and(|c| *c == ' ' || *c == '\t') {
+            chars.next();
+        }
+        if chars.peek() != Some(&'(') {
+            continue;
+        }
+        chars.next();
+        commands.push(Command {
+            name: name.to_ascii_lowercase(),
+            args: parse_args(&mut chars),
+        });
+    }
+    commands
+}
+
+type Chars<'a> = std::iter::Peekable<std::str::Chars<'a>>;
+
+/// Parse the arguments of a command, consuming the closing paren
+fn parse_args(chars: &mut Chars) -> Vec<String> {
+    let mut args = vec![];
+    let mut arg = String::new();
+    let mut depth = 0;
+    while let Some(c) = chars.next() {
+        match c {
+            '#' => skip_comment(chars),
+            '"' => {
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' => arg.extend(chars.next()),
+                        c => arg.push(c),
+                    }
+                }
+                args.push(std::mem::take(&mut arg));
+            }
+            '[' if matches!(chars.peek(), Some('[') | Some('=')) && arg.is_empty() => {
+                match read_bracket(chars) {
+                    Some(contents) => args.push(contents),
+                    None => arg.push('['),
+                }
+            }
+            '(' => depth += 1,
+            ')' if depth == 0 => break,
+            ')' => depth -= 1,
+            c if c.is_whitespace() => {
+                if !arg.is_empty() {
+                    args.push(std::mem::take(&mut arg));
+                }
+            }
+            c => arg.push(c),
+        }
+    }
+    if !arg.is_empty() {
+        args.push(arg);
+    }
+    args
+}
+
+/// Skip a comment, after its `#`
+fn skip_comment(chars: &mut Chars) {
+    if chars.peek() == Some(&'[') {
+        chars.next();
+        if read_bracket(chars).is_some() {
+            return;
+        }
+    }
+    for c in chars.by_ref() {
+        if c == '\n' {
+            break;
+        }
+    }
+}
+
+/// Read a bracket argument like `[==[contents]==]`, after its first `[`
+///
+/// Returns None if this wasn't actually a bracket (and the rest of the line should
+/// be treated normally).
+fn read_bracket(chars: &mut Chars) -> Option<String> {
+    let mut level = 0;
+    while chars.peek() == Some(&'=') {
+        chars.next();
+        level += 1;
+    }
+    if chars.next() != Some('[') {
+        return None;
+    }
+    let close = format!("]{}]", "=".repeat(level));
+    let mut contents = String::new();
+    for c in chars.by_ref() {
+        contents.push(c);
+        if contents.ends_with(&close) {
+            contents.truncate(contents.len() - close.len());
+            break;
+        }
+    }
+    Some(contents)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_commands() {
+        let source = r#"
+# comment (with parens)
+cmake_minimum_required(VERSION 3.20)
+PROJECT(my-app VERSION 1.2 DESCRIPTION "my \"great\" app" LANGUAGES C)
+#[[ a bracket
+comment ]]
+add_executable(my-app
+  main.c # the main file
+  $<$<CONFIG:Debug>:debug.c>
+)
+set(MSG [=[hi]=])
+if((A OR B) AND C)
+"#;
+        assert_eq!(
+            parse_commands(source),
+            vec![
+                Command {
+                    name: "cmake_minimum_required".to_owned(),
+                    args: vec!["VERSION".to_owned(), "3.20".to_owned()],
+                },
+                Command {
+                    name: "project".to_owned(),
+                    args: vec![
+                        "my-app".to_owned(),
+                        "VERSION".to_owned(),
+                        "1.2".to_owned(),
+                        "DESCRIPTION".to_owned(),
+                        "my \"great\" app".to_owned(),
+                        "LANGUAGES".to_owned(),
+                        "C".to_owned(),
+                    ],
+                },
+                Command {
+                    name: "add_executable".to_owned(),
+                    args: vec![
+                        "my-app".to_owned(),
+                        "main.c".to_owned(),
+                        "$<$<CONFIG:Debug>:debug.c>".to_owned(),
+                    ],
+                },
+                Command {
+                    name: "set".to_owned(),
+                    args: vec!["MSG".to_owned(), "hi".to_owned()],
+                },
+                Command {
+                    name: "if".to_owned(),
+                    args: vec![
+                        "A".to_owned(),
+                        "OR".to_owned(),
+                        "B".to_owned(),
+                        "AND".to_owned(),
+                        "C".to_owned(),
+                    ],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn expands_known_vars() {
+        let vars = [("PROJECT_NAME".to_owned(), "app".to_owned())]
+            .into_iter()
+            .collect();
+        assert_eq!(expand_vars("${PROJECT_NAME}-cli", &vars), "app-cli");
+        assert_eq!(expand_vars("${UNKNOWN}/x", &vars), "${UNKNOWN}/x");
+        assert_eq!(expand_vars("${broken", &vars), "${broken");
+    }
+}
@@ -78,6 +78,70 @@ pub enum AxoprojectError {
         details: std::io::Error,
     },
 
+    /// We found a pyproject.toml but it didn't have [project] and name set
+    #[cfg(feature = "python-projects")]
+    #[error("your Python package doesn't have a name:\n{manifest}")]
+    #[diagnostic(help(
+        "we only understand the standard [project] table, does your build tool use something else?"
+    ))]
+    NamelessPythonPackage {
+        /// path to the pyproject.toml
+        manifest: Utf8PathBuf,
+    },
+
+    /// We couldn't understand the version in a pyproject.toml
+    #[cfg(feature = "python-projects")]
+    #[error(
+        "couldn't convert your Python package's version {version} to semver:\n{manifest_path}"
+    )]
+    #[diagnostic(help("epochs and versions with more than 3 parts aren't supported"))]
+    PythonVersionParse {
+        /// path to the pyproject.toml
+        manifest_path: Utf8PathBuf,
+        /// the version we found
+        version: String,
+    },
+
+    /// We found a go.mod but it didn't have a module directive
+    #[cfg(feature = "go-projects")]
+    #[error("your Go module doesn't have a module path:\n{manifest}")]
+    #[diagnostic(help("go.mod should start with a line like `module example.com/my-tool`"))]
+    NamelessGoModule {
+        /// path to the go.mod
+        manifest: Utf8PathBuf,
+    },
+
+    /// We couldn't look for main packages in a Go module
+    #[cfg(feature = "go-projects")]
+    #[error("failed to read {dir} while looking for Go main packages")]
+    GoPackageSearch {
+        /// The dir we were searching
+        dir: Utf8PathBuf,
+        /// The underlying IO error
+        #[source]
+        details: std::io::Error,
+    },
+
+    /// We found a CMakeLists.txt but it didn't have a project()
+    #[cfg(feature = "cmake-projects")]
+    #[error("your CMake project doesn't have a name:\n{manifest}")]
+    #[diagnostic(help("the top-level CMakeLists.txt should call project(my-project ...)"))]
+    NamelessCmakeProject {
+        /// path to the CMakeLists.txt
+        manifest: Utf8PathBuf,
+    },
+
+    /// We couldn't understand the version in a CMakeLists.txt
+    #[cfg(feature = "cmake-projects")]
+    #[error("couldn't convert your CMake project's version {version} to semver:\n{manifest_path}")]
+    #[diagnostic(help("versions with a 4th (tweak) component aren't supported"))]
+    CmakeVersionParse {
+        /// path to the CMakeLists.txt
+        manifest_path: Utf8PathBuf,
+        /// the version we found
+        version: String,
+    },
+
     /// Your workspace gave several different values for "repository"
     #[error("your workspace has inconsistent values for 'repository', refusing to select one:\n  {file1}:\n    {url1}\n  {file2}:\n    {url2}")]
     #[diagnostic(severity("warning"))]
@@ -167,7 +231,7 @@ pub enum GenericManifestParseError {
     #[error(
         r#"dist workspace member {val} is missing prefix
 members should be formatted like "dist:some/path
-possible prefixes are: dist, cargo, npm"#
+possible prefixes are: dist, cargo, npm, python, go, cmake"#
     )]
     NoPrefix {
         /// Raw entry in the list of members
@@ -176,7 +240,7 @@ possible prefixes are: dist, cargo, npm"#
     /// Unknown prefix in member
     #[error(
         "dist workspace member {val} has unknown {prefix} prefix
-possible prefixes are: dist, cargo, npm"
+possible prefixes are: dist, cargo, npm, python, go, cmake"
     )]
     UnknownPrefix {
         /// prefix parsed out
@@ -18,6 +18,12 @@ const MEMBER_GENERIC: &str = "dist";
 const MEMBER_CARGO: &str = "cargo";
 #[cfg(feature = "npm-projects")]
 const MEMBER_NPM: &str = "npm";
+#[cfg(feature = "python-projects")]
+const MEMBER_PYTHON: &str = "python";
+#[cfg(feature = "go-projects")]
+const MEMBER_GO: &str = "go";
+#[cfg(feature = "cmake-projects")]
+const MEMBER_CMAKE: &str = "cmake";
 
 #[derive(Deserialize, Debug)]
 struct WorkspaceManifest {
@@ -32,12 +38,18 @@ struct Workspace {
 }
 
 #[derive(Debug)]
-enum WorkspaceMember {
+pub(crate) enum WorkspaceMember {
     Generic(Utf8PathBuf),
     #[cfg(feature = "cargo-projects")]
     Cargo(Utf8PathBuf),
     #[cfg(feature = "npm-projects")]
     Npm(Utf8PathBuf),
+    #[cfg(feature = "python-projects")]
+    Python(Utf8PathBuf),
+    #[cfg(feature = "go-projects")]
+    Go(Utf8PathBuf),
+    #[cfg(feature = "cmake-projects")]
+    Cmake(Utf8PathBuf),
 }
 
 impl std::str::FromStr for WorkspaceMember {
@@ -54,6 +66,12 @@ impl std::str::FromStr for WorkspaceMember {
             MEMBER_CARGO => WorkspaceMember::Cargo(path.into()),
             #[cfg(feature = "npm-projects")]
             MEMBER_NPM => WorkspaceMember::Npm(path.into()),
+            #[cfg(feature = "python-projects")]
+            MEMBER_PYTHON => WorkspaceMember::Python(path.into()),
+            #[cfg(feature = "go-projects")]
+            MEMBER_GO => WorkspaceMember::Go(path.into()),
+            #[cfg(feature = "cmake-projects")]
+            MEMBER_CMAKE => WorkspaceMember::Cmake(path.into()),
             other => {
                 return Err(GenericManifestParseError::UnknownPrefix {
                     prefix: other.to_owned(),
@@ -70,9 +88,15 @@ impl std::fmt::Display for WorkspaceMember {
         match self {
             WorkspaceMember::Generic(path) => write!(f, "{MEMBER_GENERIC}:{path}"),
             #[cfg(feature = "cargo-projects")]
-            WorkspaceMember::Cargo(path) => write!(f, "{MEMBER_CARGO}/{path}"),
+            WorkspaceMember::Cargo(path) => write!(f, "{MEMBER_CARGO}:{path}"),
             #[cfg(feature = "npm-projects")]
-            WorkspaceMember::Npm(path) => write!(f, "${MEMBER_NPM}/{path}"),
+            WorkspaceMember::Npm(path) => write!(f, "{MEMBER_NPM}:{path}"),
+            #[cfg(feature = "python-projects")]
+            WorkspaceMember::Python(path) => write!(f, "{MEMBER_PYTHON}:{path}"),
+            #[cfg(feature = "go-projects")]
+            WorkspaceMember::Go(path) => write!(f, "{MEMBER_GO}:{path}"),
+            #[cfg(feature = "cmake-projects")]
+            WorkspaceMember::Cmake(path) => write!(f, "{MEMBER_CMAKE}:{path}"),
         }
     }
 }
@@ -224,13 +248,38 @@ fn process_virtual_workspace(
                         .into_result()?;
                 sub_workspaces.push(search);
             }
+            #[cfg(feature = "python-projects")]
+            WorkspaceMember::Python(member_reldir) => {
+                let python_workspace_dir = workspace_dir.join(member_reldir);
+                let search = crate::python::get_workspace(
+                    &python_workspace_dir,
+                    Some(&python_workspace_dir),
+                )
+                .into_result()?;
+                sub_workspaces.push(search);
+            }
+            #[cfg(feature = "go-projects")]
+            WorkspaceMember::Go(member_reldir) => {
+                let go_workspace_dir = workspace_dir.join(member_reldir);
+                let search = crate::go::get_workspace(&go_workspace_dir, Some(&go_workspace_dir))
+                    .into_result()?;
+                sub_workspaces.push(search);
+            }
+            #[cfg(feature = "cmake-projects")]
+            WorkspaceMember::Cmake(member_reldir) => {
+                let cmake_workspace_dir = workspace_dir.join(member_reldir);
+                let search =
+                    crate::cmake::get_workspace(&cmake_workspace_dir, Some(&cmake_workspace_dir))
+                        .into_result()?;
+                sub_workspaces.push(search);
+            }
         }
     }
     for sub_workspace in &mut sub_workspaces {
         // Process packages
         for package in &mut sub_workspace.packages {
             // If there's a dist.toml in the same dir, load it with less validation
-            // and merge the results into the npm package
+            // and merge the results into the npm/python/go/cmake package
             let paired_manifest = package.package_root.join(DIST_PACKAGE_TOML);
             if paired_manifest.exists() {
                 let generic = raw_package_from(&paired_manifest)?;
@@ -378,7 +427,9 @@ fn process_package(manifest_path: &Utf8Path, package: Package) -> Result<Package
         #[cfg(feature = "cargo-projects")]
         cargo_package_id: None,
         npm_scope: None,
+        python_build_backend: None,
         axoupdater_versions: Default::default(),
+        local_dependencies: vec![],
     };
 
     // Load and apply auto-includes
@@ -0,0 +1,242 @@
+//! Support for Go modules (go.mod)
+//!
+//! go.mod doesn't have much metadata, so we just take the module path from it,
+//! and look for `main` packages to find the binaries. Everything else (like the
+//! version) has to come from a dist.toml next to it.
+
+use axoasset::{LocalAsset, SourceFile};
+use camino::Utf8Path;
+
+use crate::{
+    errors::AxoprojectError, PackageInfo, Result, WorkspaceInfo, WorkspaceKind, WorkspaceSearch,
+    WorkspaceStructure,
+};
+
+/// The directory Go projects conventionally keep their commands in
+pub const GO_CMD_DIR: &str = "cmd";
+
+/// Try to find a Go module at start_dir, walking up
+/// ancestors as necessary until we reach clamp_to_dir (or run out of ancestors).
+///
+/// Like npm projects, these are only supported as members of a dist-workspace.toml.
+pub fn get_workspace(start_dir: &Utf8Path, clamp_to_dir: Option<&Utf8Path>) -> WorkspaceSearch {
+    let manifest_path = match crate::find_file("go.mod", start_dir, clamp_to_dir) {
+        Ok(path) => path,
+        Err(e) => {
+            return WorkspaceSearch::Missing(e);
+        }
+    };
+    match read_workspace(&manifest_path) {
+        Ok(workspace) => WorkspaceSearch::Found(workspace),
+        Err(e) => WorkspaceSearch::Broken {
+            manifest_path,
+            cause: e,
+        },
+    }
+}
+
+fn read_workspace(manifest_path: &Utf8Path) -> Result<WorkspaceStructure> {
+    let root = manifest_path.parent().unwrap().to_owned();
+    let manifest = SourceFile::load_local(manifest_path)?;
+    let Some(module_path) = module_path(manifest.contents()) else {
+        return Err(AxoprojectError::NamelessGoModule {
+            manifest: manifest_path.to_owned(),
+        });
+    };
+    let name = binary_name_for_import_path(&module_path).to_owned();
+
+    let binaries = main_packages(&root, &module_path)?
+        .into_iter()
+        .map(|(binary, _)| binary)
+        .collect();
+
+    let root_auto_includes = crate::find_auto_includes(&root)?;
+    let mut info = PackageInfo {
+        true_name: module_path.clone(),
+        true_version: None,
+        name,
+        npm_scope: None,
+        python_build_backend: None,
+        // go.mod has no version, it comes from the tag (or a dist.toml)
+        version: None,
+        manifest_path: manifest_path.to_owned(),
+        dist_manifest_path: None,
+        package_root: root.clone(),
+        description: None,
+        authors: vec![],
+        license: None,
+        publish: true,
+        repository_url: repository_url(&module_path),
+        homepage_url: None,
+        keywords: None,
+        documentation_url: None,
+        readme_file: None,
+        license_files: vec![],
+        changelog_file: None,
+        binaries,
+        cdylibs: vec![],
+        cstaticlibs: vec![],
+        #[cfg(feature = "cargo-projects")]
+        cargo_metadata_table: None,
+        #[cfg(feature = "cargo-projects")]
+        cargo_package_id: None,
+        build_command: None,
+        axoupdater_versions: Default::default(),
+        local_dependencies: vec![],
+    };
+    crate::merge_auto_includes(&mut info, &root_auto_includes);
+
+    Ok(WorkspaceStructure {
+        sub_workspaces: vec![],
+        packages: vec![info],
+        workspace: WorkspaceInfo {
+            kind: WorkspaceKind::Go,
+            target_dir: root.join("target"),
+            workspace_dir: root,
+            manifest_path: manifest_path.to_owned(),
+            dist_manifest_path: None,
+            root_auto_includes,
+            #[cfg(feature = "cargo-projects")]
+            cargo_metadata_table: None,
+            #[cfg(feature = "cargo-projects")]
+            cargo_profiles: crate::rust::CargoProfiles::new(),
+        },
+    })
+}
+
+/// Find the `main` packages of a Go module, and the binaries they build
+///
+/// These are the module root and each directory under `cmd/`, if they're
+/// `package main`. Returns `(binary name, package dir relative to the module root)`,
+/// with the dir in the `./cmd/foo` form `go build` wants.
+pub fn main_packages(root: &Utf8Path, module_path: &str) -> Result<Vec<(String, String)>> {
+    let mut packages = vec![];
+    if is_main_package(root)? {
+        packages.push((
+            binary_name_for_import_path(module_path).to_owned(),
+            ".".to_owned(),
+        ));
+    }
+
+    let cmd_dir = root.join(GO_CMD_DIR);
+    if cmd_dir.is_dir() {
+        let mut commands = vec![];
+        for entry in read_dir(&cmd_dir)? {
+            let Ok(entry) = entry else {
+                continue;
+            };
+            let path = entry.into_path();
+            if path.is_dir() && is_main_package(&path)? {
+                if let Some(name) = path.file_name() {
+                    commands.push((name.to_owned(), format!("./{GO_CMD_DIR}/{name}")));
+                }
+            }
+        }
+        commands.sort();
+        packages.extend(commands);
+    }
+    Ok(packages)
+}
+
+/// Get the module path out of the contents of a go.mod
+pub fn module_path(go_mod: &str) -> Option<String> {
+    go_mod.lines().find_map(|line| {
+        let line = line.split("//").next().unwrap_or_default().trim();
+        let path = line.strip_prefix("module")?;
+        // Make sure this was actually the `module` keyword
+        if !path.starts_with(char::is_whitespace) {
+            return None;
+        }
+        let path = path.trim().trim_matches('"');
+        (!path.is_empty()).then(|| path.to_owned())
+    })
+}
+
+/// Get the name `go build` gives a binary for an import path
+///
+/// That's the last element of the path, unless it's a major version suffix
+/// (`example.com/tool/v2` builds `tool`).
+fn binary_name_for_import_path(import_path: &str) -> &str {
+    let mut elements = import_path.rsplit('/');
+    let last = elements.next().unwrap_or(import_path);
+    let is_major_version = last
+        .strip_prefix('v')
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()));
+    if is_major_version {
+        elements.next().unwrap_or(last)
+    } else {
+        last
+    }
+}
+
+/// Guess the repository of a module from its path, for the forges we know
+fn repository_url(module_path: &str) -> Option<String> {
+    let mut elements = module_path.split('/');
+    let host = elements.next()?;
+    if !matches!(host, "github.com" | "gitlab.com" | "codeberg.org") {
+        return None;
+    }
+    let owner = elements.next()?;
+    let repo = elements.next()?;
+    Some(format!("https://{host}/{owner}/{repo}"))
+}
+
+/// Check whether a directory has a Go file in `package main`
+fn is_main_package(dir: &Utf8Path) -> Result<bool> {
+    for entry in read_dir(dir)? {
+        let Ok(entry) = entry else {
+            continue;
+        };
+        let path = entry.path();
+        let is_source = path.extension() == Some("go")
+            && !path.as_str().ends_with("_test.go")
+            && path.is_file();
+        if !is_source {
+            continue;
+        }
+        let source = LocalAsset::load_string(path)?;
+        if package_clause(&source) == Some("main") {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+/// Get the package name out of a Go file's `package` clause
+fn package_clause(source: &str) -> Option<&str> {
+    let mut in_block_comment = false;
+    for line in source.lines() {
+        let mut line = line.trim();
+        if in_block_comment {
+            let Some((_, rest)) = line.split_once("*/") else {
+                continue;
+            };
+            in_block_comment = false;
+            line = rest.trim();
+        }
+        if let Some(rest) = line.strip_prefix("/*") {
+            match rest.split_once("*/") {
+                Some((_, rest)) => line = rest.trim(),
+                None => {
+                    in_block_comment = true;
+                    continue;
+                }
+            }
+        }
+        if line.is_empty() || line.starts_with("//") {
+            continue;
+        }
+        // The package clause has to be the first thing in the file
+        let name = line.strip_prefix("package")?;
+        return name.split("//").next()?.split_whitespace().next();
+    }
+    None
+}
+
+fn read_dir(dir: &Utf8Path) -> Result<camino::ReadDirUtf8> {
+    dir.read_dir_utf8()
+        .map_err(|details| AxoprojectError::GoPackageSearch {
+            dir: dir.to_owned(),
+            details,
+        })
+}
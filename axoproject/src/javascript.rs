@@ -132,6 +132,7 @@ fn read_workspace(manifest_path: &Utf8Path) -> Result<WorkspaceStructure> {
         true_version: version.clone(),
         name: package_name,
         npm_scope: package_scope,
+        python_build_backend: None,
         version,
         manifest_path: manifest_path.to_owned(),
         dist_manifest_path: None,
@@ -163,6 +164,7 @@ fn read_workspace(manifest_path: &Utf8Path) -> Result<WorkspaceStructure> {
         cargo_package_id: None,
         build_command,
         axoupdater_versions: Default::default(),
+        local_dependencies: vec![],
     };
     crate::merge_auto_includes(&mut info, &root_auto_includes);
 
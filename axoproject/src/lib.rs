@@ -20,13 +20,19 @@ use tracing::info;
 pub use guppy::PackageId;
 
 pub mod changelog;
+#[cfg(feature = "cmake-projects")]
+pub mod cmake;
 pub mod errors;
 #[cfg(feature = "generic-projects")]
 pub mod generic;
+#[cfg(feature = "go-projects")]
+pub mod go;
 #[cfg(feature = "npm-projects")]
 pub mod javascript;
 pub mod local_repo;
 pub mod platforms;
+#[cfg(feature = "python-projects")]
+pub mod python;
 mod repo;
 #[cfg(feature = "cargo-projects")]
 pub mod rust;
@@ -289,6 +295,15 @@ pub enum WorkspaceKind {
     /// npm/js workspace
     #[cfg(feature = "npm-projects")]
     Javascript,
+    /// python workspace (pyproject.toml)
+    #[cfg(feature = "python-projects")]
+    Python,
+    /// go module (go.mod)
+    #[cfg(feature = "go-projects")]
+    Go,
+    /// cmake project (CMakeLists.txt)
+    #[cfg(feature = "cmake-projects")]
+    Cmake,
 }
 
 /// Raw type of a WorkspaceSearch, should be processed by WorkspaceGraph
@@ -525,8 +540,15 @@ pub struct PackageInfo {
     pub axoupdater_versions: Vec<(String, Version)>,
     /// npm scope (with the @, like "@axodotdev")
     pub npm_scope: Option<String>,
+    /// The PEP 517 build backend of a Python package (like "maturin")
+    pub python_build_backend: Option<String>,
     /// Command to run to build this package
     pub build_command: Option<Vec<String>>,
+    /// Manifests of the local (path) packages this package's builds depend on
+    ///
+    /// This is everything reachable through normal and build dependencies,
+    /// so changes to any of them can change what this package ships.
+    pub local_dependencies: Vec<Utf8PathBuf>,
 }
 
 impl PackageInfo {
@@ -565,6 +587,9 @@ pub enum Version {
     /// npm version
     #[cfg(feature = "npm-projects")]
     Npm(node_semver::Version),
+    /// python version (converted from PEP 440 to semver)
+    #[cfg(feature = "python-projects")]
+    Python(semver::Version),
 }
 
 impl Display for Version {
@@ -576,6 +601,8 @@ impl Display for Version {
             Version::Cargo(v) => v.fmt(f),
             #[cfg(feature = "npm-projects")]
             Version::Npm(v) => v.fmt(f),
+            #[cfg(feature = "python-projects")]
+            Version::Python(v) => v.fmt(f),
         }
     }
 }
@@ -595,6 +622,8 @@ impl Version {
                 .to_string()
                 .parse()
                 .expect("version wasn't in semver format"),
+            #[cfg(feature = "python-projects")]
+            Version::Python(v) => v.clone(),
         }
     }
 
@@ -618,6 +647,8 @@ impl Version {
             Version::Cargo(v) => v.pre.is_empty() && v.build.is_empty(),
             #[cfg(feature = "npm-projects")]
             Version::Npm(v) => v.pre_release.is_empty() && v.build.is_empty(),
+            #[cfg(feature = "python-projects")]
+            Version::Python(v) => v.pre.is_empty() && v.build.is_empty(),
         }
     }
 
@@ -638,6 +669,8 @@ impl Version {
                 build: vec![],
                 pre_release: vec![],
             }),
+            #[cfg(feature = "python-projects")]
+            Version::Python(v) => Version::Python(semver::Version::new(v.major, v.minor, v.patch)),
         }
     }
 }
@@ -29,6 +29,113 @@ impl LocalRepo {
     }
 }
 
+/// A commit in a repo's history
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// The full hash of the commit
+    pub sha: String,
+    /// The first line of the commit message
+    pub summary: String,
+    /// The rest of the commit message
+    pub body: String,
+}
+
+impl LocalRepo {
+    /// Check whether a revision (like a tag) exists in the repo
+    pub fn has_revision(&self, git: &str, rev: &str) -> bool {
+        let mut cmd = Cmd::new(git, "check for a revision");
+        cmd.arg("rev-parse")
+            .arg("--verify")
+            .arg("--quiet")
+            .arg(format!("{rev}^{{commit}}"))
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .check(false)
+            .current_dir(&self.path);
+        cmd.status().map(|s| s.success()).unwrap_or(false)
+    }
+
+    /// Get every tag that's an ancestor of (or points at) `rev`
+    pub fn tags(&self, git: &str, rev: &str) -> Result<Vec<String>> {
+        let mut cmd = Cmd::new(git, "list tags");
+        cmd.arg("tag")
+            .arg("--merged")
+            .arg(rev)
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .current_dir(&self.path);
+
+        let result = cmd.output()?;
+        let tags = String::from_utf8(result.stdout)?;
+        Ok(tags
+            .lines()
+            .map(|tag| tag.trim().to_owned())
+            .filter(|tag| !tag.is_empty())
+            .collect())
+    }
+
+    /// Get the commits after `since` up to and including `until`, newest first
+    ///
+    /// If `since` is None, this is the entire history up to `until`.
+    pub fn commits(&self, git: &str, since: Option<&str>, until: &str) -> Result<Vec<CommitInfo>> {
+        let range = match since {
+            Some(since) => format!("{since}..{until}"),
+            None => until.to_owned(),
+        };
+        let mut cmd = Cmd::new(git, "read the git history");
+        cmd.arg("log")
+            .arg("--format=%H%x1f%s%x1f%b%x1e")
+            .arg(range)
+            .arg("--")
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .current_dir(&self.path);
+
+        let result = cmd.output()?;
+        let log = String::from_utf8(result.stdout)?;
+        Ok(parse_log(&log))
+    }
+
+    /// Get the files that changed between `since` and `until`, as absolute paths
+    pub fn changed_files(&self, git: &str, since: &str, until: &str) -> Result<Vec<Utf8PathBuf>> {
+        let mut cmd = Cmd::new(git, "find changed files");
+        cmd.arg("diff")
+            .arg("--name-only")
+            .arg(since)
+            .arg(until)
+            .arg("--")
+            .stdout(std::process::Stdio::piped())
+            .stderr(std::process::Stdio::piped())
+            .current_dir(&self.path);
+
+        let result = cmd.output()?;
+        let files = String::from_utf8(result.stdout)?;
+        Ok(files
+            .lines()
+            .filter(|file| !file.is_empty())
+            .map(|file| self.path.join(file))
+            .collect())
+    }
+}
+
+/// Parse the output of `git log --format=%H%x1f%s%x1f%b%x1e`
+fn parse_log(log: &str) -> Vec<CommitInfo> {
+    log.split('\x1e')
+        .filter_map(|record| {
+            let mut fields = record.trim_start_matches('\n').splitn(3, '\x1f');
+            let sha = fields.next()?.trim();
+            if sha.is_empty() {
+                return None;
+            }
+            Some(CommitInfo {
+                sha: sha.to_owned(),
+                summary: fields.next()?.trim().to_owned(),
+                body: fields.next().unwrap_or_default().trim().to_owned(),
+            })
+        })
+        .collect()
+}
+
 fn get_root(git: &str, working_dir: &Utf8Path) -> Result<Utf8PathBuf> {
     let mut cmd = Cmd::new(git, "detect a git repo");
     cmd.arg("rev-parse")
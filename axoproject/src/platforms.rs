@@ -31,8 +31,22 @@ pub const TARGET_X64_MAC: &str = "x86_64-apple-darwin";
 /// ARM64 macOS (11.0+, Big Sur+) -- AKA "Apple Silicon"
 pub const TARGET_ARM64_MAC: &str = "aarch64-apple-darwin";
 
+/// Universal macOS (x64 and ARM64 merged into one "fat" binary)
+///
+/// This isn't a real rustc target: we build the targets in
+/// [`MACOS_UNIVERSAL2_COMPONENTS`][] and merge the results.
+pub const TARGET_MACOS_UNIVERSAL2: &str = "universal2-apple-darwin";
+
+/// The real targets that make up [`TARGET_MACOS_UNIVERSAL2`][]
+pub const MACOS_UNIVERSAL2_COMPONENTS: &[&str] = &[TARGET_X64_MAC, TARGET_ARM64_MAC];
+
 /// List of all recognized Mac targets
-pub const KNOWN_MAC_TARGETS: &[&str] = &[TARGET_X86_MAC, TARGET_X64_MAC, TARGET_ARM64_MAC];
+pub const KNOWN_MAC_TARGETS: &[&str] = &[
+    TARGET_X86_MAC,
+    TARGET_X64_MAC,
+    TARGET_ARM64_MAC,
+    TARGET_MACOS_UNIVERSAL2,
+];
 
 /// 32-bit Linux (kernel 3.2+, glibc 2.17+)
 pub const TARGET_X86_LINUX_GNU: &str = "i686-unknown-linux-gnu";
@@ -218,6 +232,7 @@ pub fn triple_to_display_name(name: &str) -> Option<&str> {
         TARGET_X86_MAC => Some("x86 macOS"),
         TARGET_X64_MAC => Some("Intel macOS"),
         TARGET_ARM64_MAC => Some("Apple Silicon macOS"),
+        TARGET_MACOS_UNIVERSAL2 => Some("Universal macOS"),
 
         TARGET_X64_FREEBSD => Some("x64 FreeBSD"),
         TARGET_X64_ILLUMOS => Some("x64 IllumOS"),
@@ -0,0 +1,327 @@
+//! Support for Python projects (pyproject.toml)
+//!
+//! We only understand the standard `[project]` table (PEP 621), which is what
+//! everything from setuptools to maturin uses these days.
+
+use axoasset::SourceFile;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Deserialize;
+
+use crate::{
+    errors::AxoprojectError, PackageInfo, Result, SortedMap, Version, WorkspaceInfo, WorkspaceKind,
+    WorkspaceSearch, WorkspaceStructure,
+};
+
+/// The PEP 517 build backend used by maturin
+pub const BUILD_BACKEND_MATURIN: &str = "maturin";
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct PyProject {
+    project: Option<Project>,
+    build_system: Option<BuildSystem>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(rename_all = "kebab-case")]
+struct BuildSystem {
+    build_backend: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct Project {
+    name: Option<String>,
+    version: Option<String>,
+    description: Option<String>,
+    readme: Option<ProjectFile>,
+    license: Option<ProjectFile>,
+    authors: Option<Vec<Person>>,
+    keywords: Option<Vec<String>>,
+    urls: Option<SortedMap<String, String>>,
+    scripts: Option<SortedMap<String, String>>,
+}
+
+/// `readme` and `license` can either be a plain string or a table
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+enum ProjectFile {
+    /// For `readme` this is a path, for `license` it's an SPDX expression
+    Str(String),
+    Table {
+        file: Option<Utf8PathBuf>,
+        text: Option<String>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+struct Person {
+    name: Option<String>,
+    email: Option<String>,
+}
+
+/// Just enough of a Cargo.toml to find the version maturin will use
+#[derive(Deserialize, Debug)]
+struct CargoManifest {
+    package: Option<CargoPackage>,
+}
+
+#[derive(Deserialize, Debug)]
+struct CargoPackage {
+    // Can also be `{ workspace = true }`, which we don't try to resolve
+    version: Option<axoasset::toml::Value>,
+}
+
+/// Try to find a Python project at start_dir, walking up
+/// ancestors as necessary until we reach clamp_to_dir (or run out of ancestors).
+///
+/// Like npm projects, these are only supported as members of a dist-workspace.toml.
+pub fn get_workspace(start_dir: &Utf8Path, clamp_to_dir: Option<&Utf8Path>) -> WorkspaceSearch {
+    let manifest_path = match crate::find_file("pyproject.toml", start_dir, clamp_to_dir) {
+        Ok(path) => path,
+        Err(e) => {
+            return WorkspaceSearch::Missing(e);
+        }
+    };
+    match read_workspace(&manifest_path) {
+        Ok(workspace) => WorkspaceSearch::Found(workspace),
+        Err(e) => WorkspaceSearch::Broken {
+            manifest_path,
+            cause: e,
+        },
+    }
+}
+
+fn read_workspace(manifest_path: &Utf8Path) -> Result<WorkspaceStructure> {
+    let root = manifest_path.parent().unwrap().to_owned();
+    let manifest: PyProject = SourceFile::load_local(manifest_path)?.deserialize_toml()?;
+    let build_backend = manifest.build_system.and_then(|b| b.build_backend);
+    let is_maturin = build_backend.as_deref() == Some(BUILD_BACKEND_MATURIN);
+
+    // Not having a [project] table is common for projects that still use
+    // tool-specific metadata (like poetry), but we don't handle those!
+    let Some(project) = manifest.project else {
+        return Err(AxoprojectError::NamelessPythonPackage {
+            manifest: manifest_path.to_owned(),
+        });
+    };
+    let Some(name) = project.name else {
+        return Err(AxoprojectError::NamelessPythonPackage {
+            manifest: manifest_path.to_owned(),
+        });
+    };
+
+    // maturin projects usually let the Cargo.toml provide the version
+    // (`dynamic = ["version"]`), so look there if it's not here
+    let raw_version = match project.version {
+        Some(version) => Some(version),
+        None if is_maturin => cargo_version(&root.join("Cargo.toml"))?,
+        None => None,
+    };
+    let version = raw_version
+        .map(|raw| {
+            pep440_to_semver(&raw).map(Version::Python).ok_or_else(|| {
+                AxoprojectError::PythonVersionParse {
+                    manifest_path: manifest_path.to_owned(),
+                    version: raw.clone(),
+                }
+            })
+        })
+        .transpose()?;
+
+    let authors = project
+        .authors
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|person| match (person.name, person.email) {
+            (Some(name), Some(email)) => Some(format!("{name} <{email}>")),
+            (Some(name), None) => Some(name),
+            (None, Some(email)) => Some(email),
+            (None, None) => None,
+        })
+        .collect();
+
+    let (license, license_files) = match project.license {
+        Some(ProjectFile::Str(expr)) => (Some(expr), vec![]),
+        Some(ProjectFile::Table { file, text }) => {
+            (text, file.map(|f| root.join(f)).into_iter().collect())
+        }
+        None => (None, vec![]),
+    };
+    let readme_file = match project.readme {
+        Some(ProjectFile::Str(path)) => Some(root.join(path)),
+        Some(ProjectFile::Table { file, .. }) => file.map(|f| root.join(f)),
+        None => None,
+    };
+
+    // [project.urls] is free-form, but these are the conventional names
+    let mut repository_url = None;
+    let mut homepage_url = None;
+    let mut documentation_url = None;
+    for (key, url) in project.urls.unwrap_or_default() {
+        let key = key.to_ascii_lowercase().replace([' ', '-', '_', '.'], "");
+        let slot = match key.as_str() {
+            "repository" | "source" | "sourcecode" | "github" => &mut repository_url,
+            "homepage" => &mut homepage_url,
+            "documentation" | "docs" => &mut documentation_url,
+            _ => continue,
+        };
+        slot.get_or_insert(url);
+    }
+
+    // Each script is a console entry point that gets installed as a binary
+    let binaries = project.scripts.unwrap_or_default().into_keys().collect();
+
+    let root_auto_includes = crate::find_auto_includes(&root)?;
+    let mut info = PackageInfo {
+        true_name: name.clone(),
+        true_version: version.clone(),
+        name,
+        npm_scope: None,
+        version,
+        manifest_path: manifest_path.to_owned(),
+        dist_manifest_path: None,
+        package_root: root.clone(),
+        description: project.description,
+        authors,
+        license,
+        // FIXME: is there any Python equivalent to this?
+        publish: true,
+        repository_url,
+        homepage_url,
+        keywords: project.keywords,
+        documentation_url,
+        readme_file,
+        license_files,
+        changelog_file: None,
+        binaries,
+        cdylibs: vec![],
+        cstaticlibs: vec![],
+        #[cfg(feature = "cargo-projects")]
+        cargo_metadata_table: None,
+        #[cfg(feature = "cargo-projects")]
+        cargo_package_id: None,
+        build_command: None,
+        axoupdater_versions: Default::default(),
+        local_dependencies: vec![],
+        python_build_backend: build_backend,
+    };
+    crate::merge_auto_includes(&mut info, &root_auto_includes);
+
+    // maturin builds with cargo, so its output goes wherever cargo's does
+    let target_dir = if is_maturin {
+        root.join("target")
+    } else {
+        root.join("dist")
+    };
+
+    Ok(WorkspaceStructure {
+        sub_workspaces: vec![],
+        packages: vec![info],
+        workspace: WorkspaceInfo {
+            kind: WorkspaceKind::Python,
+            target_dir,
+            workspace_dir: root,
+            manifest_path: manifest_path.to_owned(),
+            dist_manifest_path: None,
+            root_auto_includes,
+            #[cfg(feature = "cargo-projects")]
+            cargo_metadata_table: None,
+            #[cfg(feature = "cargo-projects")]
+            cargo_profiles: crate::rust::CargoProfiles::new(),
+        },
+    })
+}
+
+/// Get the version out of a Cargo.toml, if it has a literal one
+fn cargo_version(manifest_path: &Utf8Path) -> Result<Option<String>> {
+    if !manifest_path.exists() {
+        return Ok(None);
+    }
+    let manifest: CargoManifest = SourceFile::load_local(manifest_path)?.deserialize_toml()?;
+    Ok(manifest
+        .package
+        .and_then(|package| package.version)
+        .and_then(|version| version.as_str().map(ToOwned::to_owned)))
+}
+
+/// Convert a PEP 440 version to the equivalent SemVer version
+///
+/// * release segments get padded to 3 (`1.2` => `1.2.0`)
+/// * pre-releases become SemVer pre-releases (`1.0.0a1` => `1.0.0-alpha.1`,
+///   `1.0.0rc1.dev2` => `1.0.0-rc.1.dev.2`)
+/// * post-releases and local versions become build metadata (`1.0.0.post1+ubuntu`
+///   => `1.0.0+post.1.ubuntu`)
+///
+/// Returns None for things SemVer can't express, like epochs or more than
+/// 3 release segments.
+pub fn pep440_to_semver(version: &str) -> Option<semver::Version> {
+    let version = version.trim().to_ascii_lowercase();
+    let version = version.strip_prefix('v').unwrap_or(&version);
+    let (version, local) = match version.split_once('+') {
+        Some((version, local)) => (version, Some(local)),
+        None => (version, None),
+    };
+
+    let release_end = version
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(version.len());
+    let (release, mut rest) = version.split_at(release_end);
+    let release = release.trim_end_matches('.');
+    let mut parts = release
+        .split('.')
+        .map(|part| part.parse::<u64>().ok())
+        .collect::<Option<Vec<_>>>()?;
+    if parts.is_empty() || parts.len() > 3 {
+        return None;
+    }
+    parts.resize(3, 0);
+
+    let mut pre = vec![];
+    let mut build = vec![];
+    // Each suffix is an optional separator, a label, and an optional number
+    while !rest.is_empty() {
+        rest = rest.trim_start_matches(['.', '-', '_']);
+        let label_end = rest
+            .find(|c: char| !c.is_ascii_alphabetic())
+            .unwrap_or(rest.len());
+        let (label, after) = rest.split_at(label_end);
+        let after = after.trim_start_matches(['.', '-', '_']);
+        let num_end = after
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(after.len());
+        let (num, after) = after.split_at(num_end);
+        // An implicit number is 0, and SemVer doesn't allow leading zeros
+        let num = if num.is_empty() {
+            0
+        } else {
+            num.parse::<u64>().ok()?
+        };
+        let (is_pre, label) = match label {
+            "a" | "alpha" => (true, "alpha"),
+            "b" | "beta" => (true, "beta"),
+            "c" | "rc" | "pre" | "preview" => (true, "rc"),
+            "dev" => (true, "dev"),
+            "post" | "rev" | "r" => (false, "post"),
+            _ => return None,
+        };
+        let list = if is_pre { &mut pre } else { &mut build };
+        list.push(label.to_owned());
+        list.push(num.to_string());
+        rest = after;
+    }
+    build.extend(
+        local
+            .into_iter()
+            .flat_map(|local| local.split(['.', '-', '_']))
+            .map(ToOwned::to_owned),
+    );
+
+    let mut out = semver::Version::new(parts[0], parts[1], parts[2]);
+    if !pre.is_empty() {
+        out.pre = semver::Prerelease::new(&pre.join(".")).ok()?;
+    }
+    if !build.is_empty() {
+        out.build = semver::BuildMetadata::new(&build.join(".")).ok()?;
+    }
+    Some(out)
+}
@@ -216,6 +216,15 @@ fn package_info(
         }
     }
 
+    // Local packages that end up in our builds (dev-dependencies don't)
+    let local_dependencies = pkg_graph
+        .query_forward(std::iter::once(package.id()))?
+        .resolve_with_fn(|_, link| !link.dev_only())
+        .packages(DependencyDirection::Forward)
+        .filter(|p| p.id() != package.id() && p.source().is_local())
+        .map(|p| p.manifest_path().to_owned())
+        .collect();
+
     let version = Some(Version::Cargo(package.version().clone()));
     let mut info = PackageInfo {
         true_name: package.name().to_owned(),
@@ -246,8 +255,10 @@ fn package_info(
         cargo_metadata_table,
         cargo_package_id,
         npm_scope: None,
+        python_build_backend: None,
         build_command: None,
         axoupdater_versions,
+        local_dependencies,
     };
 
     // Find files we might want to auto-include
@@ -299,12 +310,18 @@ fn get_profiles(manifest_path: &Utf8Path) -> Result<BTreeMap<String, CargoProfil
         let split_debuginfo = profile.get("split-debuginfo");
         let inherits = profile.get("inherits");
 
-        // clean up the true/false sugar for "debug"
+        // clean up the true/false and named-level sugar for "debug"
         let debug = debug.and_then(|debug| {
             debug
                 .as_bool()
                 .map(|val| if val { 2 } else { 0 })
                 .or_else(|| debug.as_integer())
+                .or_else(|| match debug.as_str()? {
+                    "none" => Some(0),
+                    "line-directives-only" | "line-tables-only" | "limited" => Some(1),
+                    "full" => Some(2),
+                    _ => None,
+                })
         });
 
         // Just capture these directly
@@ -331,7 +348,8 @@ pub struct CargoProfile {
     pub inherits: Option<String>,
     /// Whether debuginfo is enabled.
     ///
-    /// can be 0, 1, 2, true (=2), false (=0).
+    /// can be 0, 1, 2, true (=2), false (=0), or a named level
+    /// ("none" = 0, "line-tables-only"/"limited" = 1, "full" = 2).
     pub debug: Option<i64>,
     /// Whether split-debuginfo is enabled.
     ///
@@ -120,6 +120,10 @@ fn test_cargo_virtual() {
         let package = packages[0].1;
         assert_eq!(package.name, "virtual");
         assert_eq!(&package.binaries[..], &["virtual"]);
+        assert_eq!(
+            package.local_dependencies,
+            vec![packages[1].1.manifest_path.clone()]
+        );
     }
 
     {
@@ -132,6 +136,7 @@ fn test_cargo_virtual() {
         let package = packages[2].1;
         assert_eq!(package.name, "virtual-gui");
         assert_eq!(&package.binaries[..], &["virtual-gui"]);
+        assert!(package.local_dependencies.is_empty());
     }
 }
 
@@ -584,6 +589,240 @@ fn test_changelog_errors() {
     ));
 }
 
+fn keep_a_changelog() -> &'static str {
+    r##"# Changelog
+
+## [Unreleased]
+
+## [1.1.0] - 2024-03-01
+
+### Added
+
+- Installers for [everyone][installers] ([#12])
+
+### Fixed
+
+- A [Bug] that [didn't have a definition][nowhere]
+
+## [1.0.0] - 2024-01-01
+
+- First release ([#3])
+
+[unreleased]: https://github.com/axodotdev/app/compare/v1.1.0...HEAD
+[1.1.0]: https://github.com/axodotdev/app/compare/v1.0.0...v1.1.0
+[1.0.0]: https://github.com/axodotdev/app/releases/tag/v1.0.0
+[#3]: https://github.com/axodotdev/app/pull/3
+[#12]: https://github.com/axodotdev/app/pull/12
+[installers]: https://opensource.axo.dev/cargo-dist/book/installers/
+[bug]: https://github.com/axodotdev/app/issues/7
+"##
+}
+
+#[test]
+fn test_changelog_reference_links() {
+    use crate::changelog::changelog_for_version_inner as test;
+    let changelog = keep_a_changelog();
+    let path = Utf8PathBuf::from("CHANGELOG.md");
+
+    assert_eq!(
+        test(&path, changelog, &ver("1.1.0")).unwrap().unwrap(),
+        ChangelogInfo {
+            title: "1.1.0 - 2024-03-01".to_owned(),
+            body: r"### Added
+
+- Installers for [everyone][installers] ([#12])
+
+### Fixed
+
+- A [Bug] that [didn't have a definition][nowhere]
+
+[#12]: https://github.com/axodotdev/app/pull/12
+[installers]: https://opensource.axo.dev/cargo-dist/book/installers/
+[bug]: https://github.com/axodotdev/app/issues/7"
+                .to_owned()
+        }
+    );
+
+    // Definitions are never duplicated, even if the entry already has them
+    let info = test(&path, changelog, &ver("1.0.0")).unwrap().unwrap();
+    assert_eq!(info.body.matches("[#3]:").count(), 1);
+}
+
+#[test]
+fn test_combine_changelogs() {
+    use crate::changelog::combine_changelogs;
+
+    let app = ChangelogInfo {
+        title: "Version 1.0.0".to_owned(),
+        body: "app notes\n".to_owned(),
+    };
+    let lib = ChangelogInfo {
+        title: "1.0.0".to_owned(),
+        body: "lib notes".to_owned(),
+    };
+
+    assert_eq!(combine_changelogs("v1.0.0".to_owned(), vec![]), None);
+    // A single entry is used as-is
+    assert_eq!(
+        combine_changelogs(
+            "v1.0.0".to_owned(),
+            vec![("app 1.0.0".to_owned(), app.clone())]
+        ),
+        Some(app.clone())
+    );
+    assert_eq!(
+        combine_changelogs(
+            "v1.0.0".to_owned(),
+            vec![("app 1.0.0".to_owned(), app), ("lib 1.0.0".to_owned(), lib)]
+        ),
+        Some(ChangelogInfo {
+            title: "v1.0.0".to_owned(),
+            body: "## app 1.0.0\n\napp notes\n\n## lib 1.0.0\n\nlib notes".to_owned(),
+        })
+    );
+}
+
+#[test]
+fn test_changelog_from_commits() {
+    use crate::changelog::changelog_from_commits;
+    use crate::local_repo::CommitInfo;
+
+    let commit = |sha: &str, summary: &str, body: &str| CommitInfo {
+        sha: sha.to_owned(),
+        summary: summary.to_owned(),
+        body: body.to_owned(),
+    };
+    let commits = [
+        commit("aaaaaaa111", "feat(installer): add a fish installer", ""),
+        commit("bbbbbbb222", "fix: don't crash on empty tags", ""),
+        commit("ccccccc333", "chore: bump deps", ""),
+        commit(
+            "ddddddd444",
+            "Merge pull request #5 from someone/branch",
+            "",
+        ),
+        commit("eeeeeee555", "feat!: drop support for old configs", ""),
+        commit(
+            "fffffff666",
+            "refactor(config): rename some fields",
+            "BREAKING CHANGE: the fields are renamed",
+        ),
+        commit("0000000777", "perf: build faster", ""),
+        commit("1111111888", "feat: another feature", ""),
+    ];
+
+    let info = changelog_from_commits("v2.0.0".to_owned(), &commits, None).unwrap();
+    assert_eq!(info.title, "v2.0.0");
+    assert_eq!(
+        info.body,
+        r"### Breaking Changes
+
+- drop support for old configs (eeeeeee)
+- **config:** rename some fields (fffffff)
+
+### Features
+
+- **installer:** add a fish installer (aaaaaaa)
+- another feature (1111111)
+
+### Bug Fixes
+
+- don't crash on empty tags (bbbbbbb)
+
+### Performance
+
+- build faster (0000000)"
+    );
+
+    // Nothing notable, no notes
+    assert_eq!(
+        changelog_from_commits(
+            "v2.0.1".to_owned(),
+            &[commit("aaaaaaa111", "chore: bump deps", "")],
+            None
+        ),
+        None
+    );
+}
+
+#[test]
+fn test_changelog_from_history() {
+    use crate::changelog::changelog_from_history;
+    use crate::local_repo::CommitInfo;
+
+    let commit = |sha: &str, summary: &str, body: &str| CommitInfo {
+        sha: sha.to_owned(),
+        summary: summary.to_owned(),
+        body: body.to_owned(),
+    };
+    let commits = [
+        commit("aaaaaaa111", "feat: add a fish installer (#3)", ""),
+        commit(
+            "bbbbbbb222",
+            "Merge pull request #5 from someone/branch",
+            "fix: don't crash on empty tags\n\nIt was bad.",
+        ),
+        commit("ccccccc333", "Merge branch 'main' into someone/branch", ""),
+        commit("ddddddd444", "chore: bump deps", ""),
+        commit("eeeeeee555", "Tweak the docs (#not-a-pr)", ""),
+    ];
+
+    let info = changelog_from_history(
+        "v2.0.0".to_owned(),
+        &commits,
+        Some("https://github.com/owner/repo/pull"),
+    )
+    .unwrap();
+    assert_eq!(info.title, "v2.0.0");
+    assert_eq!(
+        info.body,
+        r"### Features
+
+- add a fish installer ([#3](https://github.com/owner/repo/pull/3)) (aaaaaaa)
+
+### Bug Fixes
+
+- don't crash on empty tags ([#5](https://github.com/owner/repo/pull/5)) (bbbbbbb)
+
+### Other Changes
+
+- bump deps (ddddddd)
+- Tweak the docs (#not-a-pr) (eeeeeee)"
+    );
+
+    // Without a repo to link to, PR numbers are left alone
+    let info = changelog_from_history("v2.0.0".to_owned(), &commits[..1], None).unwrap();
+    assert_eq!(
+        info.body,
+        "### Features\n\n- add a fish installer (#3) (aaaaaaa)"
+    );
+}
+
+#[test]
+fn test_stamp_unreleased() {
+    use crate::changelog::stamp_unreleased;
+
+    let stamped = stamp_unreleased(keep_a_changelog(), "1.2.0").unwrap();
+    assert!(stamped.contains("\n## [1.2.0]\n"));
+    assert!(!stamped.contains("## [Unreleased]"));
+    // The stamped entry is now the one for the version
+    let info = crate::changelog::changelog_for_version_inner(
+        "CHANGELOG.md".into(),
+        &stamped,
+        &ver("1.2.0"),
+    )
+    .unwrap()
+    .unwrap();
+    assert_eq!(info.title, "1.2.0");
+
+    // Windows newlines survive, and only the first heading is touched
+    assert_eq!(
+        stamp_unreleased("# Changelog\r\n\n# Unreleased\r\n\n# Unreleased\n", "2.0.0").unwrap(),
+        "# Changelog\r\n\n# 2.0.0\r\n\n# Unreleased\n"
+    );
+    assert_eq!(stamp_unreleased(no_unreleased_changelog(), "2.0.0"), None);
+}
+
 #[test]
 fn test_generic_c() {
     let workspaces = WorkspaceGraph::find("tests/projects/generic-c/".into(), None).unwrap();
@@ -819,6 +1058,249 @@ fn shared_workspace_check<'a>(path: impl Into<&'a Utf8Path>) {
     }
 }
 
+#[cfg(feature = "python-projects")]
+#[test]
+fn test_python_workspace() {
+    let workspaces = WorkspaceGraph::find("tests/projects/python-workspace".into(), None).unwrap();
+    let project = workspaces.root_workspace();
+    assert_eq!(project.kind, WorkspaceKind::Generic);
+    let packages = workspaces.all_packages().collect::<Vec<_>>();
+    assert_eq!(packages.len(), 2);
+
+    {
+        let package = get_package(&packages, "maturin-app");
+        let workspace = python_workspace_of(&workspaces, &package);
+        assert_eq!(workspace.kind, WorkspaceKind::Python);
+        assert!(workspace.target_dir.ends_with("maturin-app/target"));
+        assert_eq!(package.python_build_backend.as_deref(), Some("maturin"));
+        // dynamic version, so it comes from Cargo.toml
+        assert_eq!(package.version.as_ref().unwrap().to_string(), "0.3.1");
+        assert_eq!(package.license.as_deref(), Some("MIT OR Apache-2.0"));
+        assert_eq!(package.binaries, vec!["maturin-app".to_owned()]);
+        assert_eq!(
+            package.repository_url.as_deref(),
+            Some("https://github.com/axodotdev/maturin-app")
+        );
+    }
+
+    {
+        let package = get_package(&packages, "plain-app");
+        let workspace = python_workspace_of(&workspaces, &package);
+        assert_eq!(workspace.kind, WorkspaceKind::Python);
+        assert_eq!(
+            package.python_build_backend.as_deref(),
+            Some("setuptools.build_meta")
+        );
+        let version = package.version.as_ref().unwrap();
+        assert_eq!(version.to_string(), "1.2.0-rc.1");
+        assert!(!version.is_stable());
+        assert_eq!(package.license, None);
+        check_file(&package.license_files[0], "plain license!");
+        check_file(package.readme_file.as_deref().unwrap(), "plain readme!");
+        assert_eq!(
+            package.authors,
+            vec![
+                "Ferris <ferris@example.com>".to_owned(),
+                "Just A Name".to_owned()
+            ]
+        );
+        assert_eq!(
+            package.binaries,
+            vec!["plain-app".to_owned(), "plain-app-helper".to_owned()]
+        );
+        assert_eq!(
+            package.homepage_url.as_deref(),
+            Some("https://example.com/plain-app")
+        );
+        assert_eq!(
+            package.repository_url.as_deref(),
+            Some("https://github.com/axodotdev/plain-app")
+        );
+        assert_eq!(
+            package.documentation_url.as_deref(),
+            Some("https://docs.example.com/plain-app")
+        );
+    }
+}
+
+#[cfg(feature = "go-projects")]
+#[test]
+fn test_go_workspace() {
+    let workspaces = WorkspaceGraph::find("tests/projects/go-workspace".into(), None).unwrap();
+    let project = workspaces.root_workspace();
+    assert_eq!(project.kind, WorkspaceKind::Generic);
+    let packages = workspaces.all_packages().collect::<Vec<_>>();
+    assert_eq!(packages.len(), 1);
+
+    let (idx, package) = packages[0];
+    let workspace = workspaces.workspace(workspaces.workspace_for_package(idx));
+    assert_eq!(workspace.kind, WorkspaceKind::Go);
+    assert_eq!(package.name, "go-tool");
+    assert_eq!(package.true_name, "github.com/axodotdev/go-tool/v2");
+    // go.mod has no version, so this comes from the dist.toml next to it
+    assert_eq!(package.version.as_ref().unwrap().to_string(), "2.1.0");
+    assert_eq!(
+        package.repository_url.as_deref(),
+        Some("https://github.com/axodotdev/go-tool")
+    );
+    check_file(package.readme_file.as_deref().unwrap(), "go readme!");
+    // The root package, and only the cmd/ dirs that are main packages
+    assert_eq!(
+        package.binaries,
+        vec!["go-tool".to_owned(), "go-tool-helper".to_owned()]
+    );
+    assert_eq!(
+        crate::go::main_packages(&package.package_root, &package.true_name).unwrap(),
+        vec![
+            ("go-tool".to_owned(), ".".to_owned()),
+            (
+                "go-tool-helper".to_owned(),
+                "./cmd/go-tool-helper".to_owned()
+            ),
+        ]
+    );
+}
+
+#[cfg(feature = "cmake-projects")]
+#[test]
+fn test_cmake_workspace() {
+    let workspaces = WorkspaceGraph::find("tests/projects/cmake-workspace".into(), None).unwrap();
+    let project = workspaces.root_workspace();
+    assert_eq!(project.kind, WorkspaceKind::Generic);
+    let packages = workspaces.all_packages().collect::<Vec<_>>();
+    assert_eq!(packages.len(), 1);
+
+    let (idx, package) = packages[0];
+    let workspace = workspaces.workspace(workspaces.workspace_for_package(idx));
+    assert_eq!(workspace.kind, WorkspaceKind::Cmake);
+    assert_eq!(package.name, "cmake-app");
+    assert_eq!(package.version.as_ref().unwrap().to_string(), "0.4.0");
+    assert_eq!(package.description.as_deref(), Some("a cmake app"));
+    assert_eq!(
+        package.homepage_url.as_deref(),
+        Some("https://example.com/cmake-app")
+    );
+    check_file(package.readme_file.as_deref().unwrap(), "cmake readme!");
+    // Only installed targets, found through add_subdirectory and variables
+    assert_eq!(
+        package.binaries,
+        vec!["cmake-app".to_owned(), "cmake-app-tool".to_owned()]
+    );
+    assert_eq!(package.cdylibs, vec!["cmakeapp".to_owned()]);
+    assert_eq!(package.cstaticlibs, vec!["cmakeapp_static".to_owned()]);
+}
+
+#[cfg(feature = "cmake-projects")]
+#[test]
+fn test_cmake_version_to_semver() {
+    use crate::cmake::cmake_version_to_semver;
+
+    let cases = [
+        ("1", Some("1.0.0")),
+        ("1.2", Some("1.2.0")),
+        ("1.2.3", Some("1.2.3")),
+        ("1.2.3.4", None),
+        ("1.x", None),
+        ("", None),
+    ];
+    for (version, expected) in cases {
+        assert_eq!(
+            cmake_version_to_semver(version).map(|v| v.to_string()),
+            expected.map(|v| v.to_owned()),
+            "converting {version:?}"
+        );
+    }
+}
+
+#[cfg(feature = "go-projects")]
+#[test]
+fn test_go_module_path() {
+    use crate::go::module_path;
+
+    let cases = [
+        ("module example.com/tool\n", Some("example.com/tool")),
+        (
+            "// hi\nmodule \"example.com/tool\" // comment\n",
+            Some("example.com/tool"),
+        ),
+        (
+            "go 1.22\nmodule example.com/tool/v3\n",
+            Some("example.com/tool/v3"),
+        ),
+        ("modules example.com/tool\n", None),
+        ("go 1.22\n", None),
+    ];
+    for (go_mod, expected) in cases {
+        assert_eq!(
+            module_path(go_mod).as_deref(),
+            expected,
+            "parsing {go_mod:?}"
+        );
+    }
+}
+
+#[cfg(feature = "python-projects")]
+fn python_workspace_of<'a>(
+    workspaces: &'a WorkspaceGraph,
+    package: &crate::PackageInfo,
+) -> &'a crate::WorkspaceInfo {
+    let (idx, _) = workspaces
+        .all_packages()
+        .find(|(_, p)| p.manifest_path == package.manifest_path)
+        .unwrap();
+    workspaces.workspace(workspaces.workspace_for_package(idx))
+}
+
+#[cfg(feature = "python-projects")]
+#[test]
+fn test_pep440_to_semver() {
+    use crate::python::pep440_to_semver;
+
+    let cases = [
+        ("1", Some("1.0.0")),
+        ("1.2", Some("1.2.0")),
+        ("v1.2.3", Some("1.2.3")),
+        ("1.0.0a1", Some("1.0.0-alpha.1")),
+        ("1.0.0.beta2", Some("1.0.0-beta.2")),
+        ("1.0.0rc1.dev2", Some("1.0.0-rc.1.dev.2")),
+        ("1.0.0.dev", Some("1.0.0-dev.0")),
+        ("1.0.0.post1", Some("1.0.0+post.1")),
+        ("1.0.0.post1+ubuntu.1", Some("1.0.0+post.1.ubuntu.1")),
+        ("1.2.3.4", None),
+        ("1!1.0.0", None),
+        ("banana", None),
+    ];
+    for (input, expected) in cases {
+        let actual = pep440_to_semver(input).map(|v| v.to_string());
+        assert_eq!(actual.as_deref(), expected, "converting {input}");
+    }
+}
+
+#[cfg(all(
+    feature = "cargo-projects",
+    feature = "npm-projects",
+    feature = "python-projects",
+    feature = "go-projects",
+    feature = "cmake-projects"
+))]
+#[test]
+fn test_workspace_member_round_trip() {
+    use crate::generic::WorkspaceMember;
+
+    let members = [
+        "dist:path/to/generic",
+        "cargo:rust",
+        "npm:js",
+        "python:py/project",
+        "go:cmd/app",
+        "cmake:native",
+    ];
+    for member in members {
+        let parsed: WorkspaceMember = member.parse().unwrap();
+        assert_eq!(parsed.to_string(), member);
+    }
+}
+
 #[track_caller]
 fn check_file(file: &Utf8Path, val: &str) {
     assert!(axoasset::LocalAsset::load_string(file).unwrap().trim() == val)
@@ -0,0 +1 @@
+int placeholder(void) { return 0; }
@@ -0,0 +1,5 @@
+// Copyright 2024 Axo Developer Co.
+
+package main // the helper
+
+func main() {}
@@ -0,0 +1,4 @@
+// A tool with a major version suffix
+module github.com/axodotdev/go-tool/v2
+
+go 1.22
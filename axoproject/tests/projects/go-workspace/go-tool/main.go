@@ -0,0 +1,10 @@
+/*
+The main tool.
+*/
+package main
+
+import "fmt"
+
+func main() {
+	fmt.Println("hello")
+}
@@ -0,0 +1,361 @@
+//! Checking two versions of the dist-manifest.json schema for breaking changes.
+//!
+//! A change is considered breaking if a manifest that was valid under the old schema
+//! may no longer deserialize under the new one, or if something a consumer could
+//! have relied on has gone away. Concretely we flag:
+//!
+//! * fields that were removed
+//! * fields whose type changed
+//! * fields that became required
+//! * enum variants that were removed
+//!
+//! Additive changes (new optional fields, new variants, new definitions) are fine.
+//!
+//! This works on the JSON schemas themselves (as produced by [`DistManifest::json_schema`][])
+//! rather than the Rust types, so it can compare against any historical schema.
+//!
+//! [`DistManifest::json_schema`]: crate::DistManifest::json_schema
+
+use serde_json::{Map, Value};
+
+/// A breaking change between two schemas
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BreakingChange {
+    /// Where the change happened (e.g. `Artifact.name`)
+    pub path: String,
+    /// What changed
+    pub kind: BreakingChangeKind,
+}
+
+/// The kinds of breaking change we can detect
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BreakingChangeKind {
+    /// A field was removed
+    RemovedField,
+    /// A field that was optional (or didn't exist) is now required
+    NewRequiredField,
+    /// The type of a field changed
+    ChangedType {
+        /// The old type
+        old: String,
+        /// The new type
+        new: String,
+    },
+    /// A variant of an enum was removed
+    RemovedVariant {
+        /// The name of the variant
+        variant: String,
+    },
+}
+
+impl std::fmt::Display for BreakingChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let path = &self.path;
+        match &self.kind {
+            BreakingChangeKind::RemovedField => write!(f, "{path}: field was removed"),
+            BreakingChangeKind::NewRequiredField => write!(f, "{path}: field is now required"),
+            BreakingChangeKind::ChangedType { old, new } => {
+                write!(f, "{path}: type changed from {old} to {new}")
+            }
+            BreakingChangeKind::RemovedVariant { variant } => {
+                write!(f, "{path}: variant \"{variant}\" was removed")
+            }
+        }
+    }
+}
+
+/// Check whether the `new` schema is backwards-compatible with the `old` one
+///
+/// Returns every breaking change found, so an empty list means it's compatible.
+pub fn check_compatibility(old: &Value, new: &Value) -> Vec<BreakingChange> {
+    let mut changes = vec![];
+    let root = old
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or("DistManifest");
+    compare(root, old, new, &mut changes);
+
+    // Definitions are referenced by name, so compare the ones both sides have.
+    // A definition that was removed or renamed is caught where it was referenced.
+    let empty = Map::new();
+    let old_defs = definitions(old).unwrap_or(&empty);
+    let new_defs = definitions(new).unwrap_or(&empty);
+    for (name, old_def) in old_defs {
+        if let Some(new_def) = new_defs.get(name) {
+            compare(name, old_def, new_def, &mut changes);
+        }
+    }
+    changes
+}
+
+fn definitions(schema: &Value) -> Option<&Map<String, Value>> {
+    schema.get("definitions")?.as_object()
+}
+
+/// Compare the schema of a single value
+fn compare(path: &str, old: &Value, new: &Value, changes: &mut Vec<BreakingChange>) {
+    let (old_desc, new_desc) = (describe(old), describe(new));
+    if old_desc != new_desc && !old_desc.is_empty() && !new_desc.is_empty() {
+        changes.push(BreakingChange {
+            path: path.to_owned(),
+            kind: BreakingChangeKind::ChangedType {
+                old: old_desc,
+                new: new_desc,
+            },
+        });
+        // No point drilling down into something that changed entirely
+        return;
+    }
+
+    // Fields
+    if let Some(old_props) = old.get("properties").and_then(Value::as_object) {
+        let empty = Map::new();
+        let new_props = new
+            .get("properties")
+            .and_then(Value::as_object)
+            .unwrap_or(&empty);
+        for (name, old_prop) in old_props {
+            let field_path = format!("{path}.{name}");
+            if let Some(new_prop) = new_props.get(name) {
+                compare(&field_path, old_prop, new_prop, changes);
+            } else {
+                changes.push(BreakingChange {
+                    path: field_path,
+                    kind: BreakingChangeKind::RemovedField,
+                });
+            }
+        }
+    }
+    let old_required = required(old);
+    for name in required(new) {
+        if !old_required.contains(&name) {
+            changes.push(BreakingChange {
+                path: format!("{path}.{name}"),
+                kind: BreakingChangeKind::NewRequiredField,
+            });
+        }
+    }
+
+    // Contents of arrays and maps
+    for key in ["items", "additionalProperties"] {
+        if let (Some(old_inner), Some(new_inner)) = (old.get(key), new.get(key)) {
+            if old_inner.is_object() && new_inner.is_object() {
+                compare(&format!("{path}[]"), old_inner, new_inner, changes);
+            }
+        }
+    }
+
+    // Enum values
+    if let Some(old_values) = old.get("enum").and_then(Value::as_array) {
+        let new_values = new.get("enum").and_then(Value::as_array);
+        for value in old_values {
+            if !new_values.is_some_and(|values| values.contains(value)) {
+                changes.push(BreakingChange {
+                    path: path.to_owned(),
+                    kind: BreakingChangeKind::RemovedVariant {
+                        variant: variant_name(value),
+                    },
+                });
+            }
+        }
+    }
+
+    // Alternatives (Options, tagged enums, flattened structs...)
+    //
+    // Ordering isn't meaningful here, so for every old alternative we just want
+    // *some* new alternative that's compatible with it.
+    for key in ["anyOf", "oneOf", "allOf"] {
+        let Some(old_alts) = old.get(key).and_then(Value::as_array) else {
+            continue;
+        };
+        let new_alts = new
+            .get(key)
+            .and_then(Value::as_array)
+            .map(|alts| &alts[..])
+            .unwrap_or(&[]);
+        for old_alt in old_alts {
+            let mut best: Option<Vec<BreakingChange>> = None;
+            for new_alt in new_alts {
+                let mut alt_changes = vec![];
+                compare(path, old_alt, new_alt, &mut alt_changes);
+                if best.as_ref().map_or(true, |b| alt_changes.len() < b.len()) {
+                    best = Some(alt_changes);
+                }
+            }
+            match best {
+                Some(alt_changes) if alt_changes.is_empty() => {}
+                // Something similar is still there, so report how it changed
+                Some(alt_changes) if old_alt.get("enum").is_none() && !new_alts.is_empty() => {
+                    changes.extend(alt_changes)
+                }
+                _ => changes.push(BreakingChange {
+                    path: path.to_owned(),
+                    kind: BreakingChangeKind::RemovedVariant {
+                        variant: alternative_name(old_alt),
+                    },
+                }),
+            }
+        }
+    }
+}
+
+/// The names of the required fields of an object
+fn required(schema: &Value) -> Vec<&str> {
+    schema
+        .get("required")
+        .and_then(Value::as_array)
+        .map(|names| names.iter().filter_map(Value::as_str).collect())
+        .unwrap_or_default()
+}
+
+/// A short description of the type of a schema, ignoring any nested fields
+///
+/// This is empty for schemas that don't say anything about the type on their own
+/// (e.g. a bare `anyOf`).
+fn describe(schema: &Value) -> String {
+    if let Some(reference) = schema.get("$ref").and_then(Value::as_str) {
+        return reference.rsplit('/').next().unwrap_or(reference).to_owned();
+    }
+    let mut types = match schema.get("type") {
+        Some(Value::String(ty)) => vec![ty.clone()],
+        Some(Value::Array(tys)) => tys
+            .iter()
+            .filter_map(Value::as_str)
+            .map(str::to_owned)
+            .collect(),
+        _ => vec![],
+    };
+    types.sort();
+    let mut desc = types.join(" | ");
+    if let Some(format) = schema.get("format").and_then(Value::as_str) {
+        desc = format!("{desc} ({format})");
+    }
+    desc
+}
+
+fn variant_name(value: &Value) -> String {
+    value
+        .as_str()
+        .map(str::to_owned)
+        .unwrap_or_else(|| value.to_string())
+}
+
+/// Try to find a human-friendly name for one alternative of an enum
+fn alternative_name(schema: &Value) -> String {
+    if let Some(values) = schema.get("enum").and_then(Value::as_array) {
+        return values
+            .iter()
+            .map(variant_name)
+            .collect::<Vec<_>>()
+            .join(", ");
+    }
+    // Tagged enums have a single-valued tag field
+    if let Some(props) = schema.get("properties").and_then(Value::as_object) {
+        for prop in props.values() {
+            if let Some([value]) = prop.get("enum").and_then(Value::as_array).map(|v| &v[..]) {
+                return variant_name(value);
+            }
+        }
+        // Externally tagged enums are an object with a single field
+        if props.len() == 1 {
+            return props.keys().next().cloned().unwrap_or_default();
+        }
+    }
+    let desc = describe(schema);
+    if desc.is_empty() {
+        schema.to_string()
+    } else {
+        desc
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+    use crate::{historical_schema, DistManifest, SCHEMA_VERSION};
+
+    fn schema(props: Value, required: Value) -> Value {
+        json!({
+            "title": "Thing",
+            "type": "object",
+            "required": required,
+            "properties": props,
+            "definitions": {
+                "Kind": {
+                    "oneOf": [
+                        { "type": "string", "enum": ["a"] },
+                        { "type": "string", "enum": ["b"] }
+                    ]
+                }
+            }
+        })
+    }
+
+    #[test]
+    fn current_schema_is_compatible_with_committed_schema() {
+        let committed = historical_schema(SCHEMA_VERSION).unwrap_or_else(|| {
+            panic!("schemas/dist-manifest-schema-v{SCHEMA_VERSION}.json is missing")
+        });
+        let committed: Value = serde_json::from_str(committed).unwrap();
+        let current = serde_json::to_value(DistManifest::json_schema()).unwrap();
+        let changes = check_compatibility(&committed, &current);
+        assert!(
+            changes.is_empty(),
+            "breaking changes to the dist-manifest schema, bump SCHEMA_VERSION and commit the new schema:\n{}",
+            changes
+                .iter()
+                .map(|c| c.to_string())
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+    }
+
+    #[test]
+    fn additive_changes_are_compatible() {
+        let old = schema(json!({ "name": { "type": "string" } }), json!(["name"]));
+        let new = schema(
+            json!({ "name": { "type": "string" }, "extra": { "type": ["string", "null"] } }),
+            json!(["name"]),
+        );
+        assert_eq!(check_compatibility(&old, &new), vec![]);
+    }
+
+    #[test]
+    fn breaking_changes_are_flagged() {
+        let old = schema(
+            json!({
+                "name": { "type": "string" },
+                "count": { "type": "integer", "format": "uint32" },
+                "kind": { "$ref": "#/definitions/Kind" }
+            }),
+            json!(["name"]),
+        );
+        let mut new = schema(
+            json!({
+                "count": { "type": "string" },
+                "kind": { "$ref": "#/definitions/Kind" }
+            }),
+            json!(["kind"]),
+        );
+        new["definitions"]["Kind"]["oneOf"]
+            .as_array_mut()
+            .unwrap()
+            .pop();
+
+        let changes = check_compatibility(&old, &new)
+            .iter()
+            .map(|c| c.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            vec![
+                "Thing.count: type changed from integer (uint32) to string",
+                "Thing.name: field was removed",
+                "Thing.kind: field is now required",
+                "Kind: variant \"b\" was removed",
+            ]
+        );
+    }
+}
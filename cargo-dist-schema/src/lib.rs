@@ -7,6 +7,16 @@
 //! with different versions of this format.
 //!
 //! The root type of the schema is [`DistManifest`][].
+//!
+//! The [`query`][] module provides higher-level queries over a manifest
+//! (target matching, best-archive selection, checksum lookup).
+//!
+//! The schema is explicitly versioned by [`SCHEMA_VERSION`][], and every
+//! historical version of it is available from [`historical_schema`][].
+//! The [`compat`][] module can check two schemas for breaking changes.
+
+pub mod compat;
+pub mod query;
 
 use std::collections::BTreeMap;
 
@@ -40,6 +50,12 @@ pub struct DistManifest {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub dist_version: Option<String>,
+    /// The version of the dist-manifest.json schema this follows
+    ///
+    /// This is None for manifests produced before the schema was explicitly versioned.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
     /// The (git) tag associated with this announcement
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -86,6 +102,10 @@ pub struct DistManifest {
     #[serde(default)]
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub assets: BTreeMap<AssetId, AssetInfo>,
+    /// Logs captured from the build commands that were run
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub build_logs: Vec<BuildLog>,
     /// Whether to publish prereleases to package managers
     #[serde(default)]
     pub publish_prereleases: bool,
@@ -179,6 +199,74 @@ pub struct AssetInfo {
     pub target_triples: Vec<String>,
     /// the linkage of this Asset
     pub linkage: Option<Linkage>,
+    /// post-build processors that were run on this Asset, in order
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub processors: Vec<String>,
+    /// the identifier debuggers and symbol servers use to match this Asset to its symbols
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<BuildId>,
+}
+
+/// An identifier embedded in a binary, which uniquely identifies the build it came from
+///
+/// Crash reports include these, so they can be used to find the exact release
+/// (and symbols) a crashing binary came from.
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+pub struct BuildId {
+    /// The kind of build id this is
+    pub kind: BuildIdKind,
+    /// The build id, as a hex string in the format symbol servers expect
+    ///
+    /// * gnu-build-id: lowercase hex
+    /// * macho-uuid: lowercase hex, without hyphens
+    /// * pdb: the uppercase GUID followed by the age (the SymStore "signature")
+    pub id: String,
+    /// The name of the file the binary expects its symbols to be in (PDBs only)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub debug_file: Option<String>,
+}
+
+/// A kind of build id
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, JsonSchema, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum BuildIdKind {
+    /// The `NT_GNU_BUILD_ID` note of an ELF binary
+    #[serde(rename = "gnu-build-id")]
+    GnuBuildId,
+    /// The `LC_UUID` of a Mach-O binary
+    #[serde(rename = "macho-uuid")]
+    MachoUuid,
+    /// The GUID and age of the PDB a PE binary was linked with
+    #[serde(rename = "pdb")]
+    Pdb,
+    /// Unknown to this version of cargo-dist-schema
+    ///
+    /// This is a fallback for forward/backward-compat
+    #[serde(other)]
+    #[serde(rename = "unknown")]
+    Unknown,
+}
+
+/// The captured output of a build command
+#[derive(Debug, Clone, Serialize, Deserialize, JsonSchema)]
+pub struct BuildLog {
+    /// The command that was run
+    pub command: Vec<String>,
+    /// the system it was run on
+    pub system: SystemId,
+    /// rust-style target triple the command was building for, if any
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    /// The file the command's stdout and stderr were written to, relative to the dist dir
+    pub path: String,
+    /// The exit code of the command (missing if it was killed by a signal)
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i32>,
 }
 
 /// CI backend info
@@ -419,6 +507,9 @@ pub enum AssetKind {
     /// A CHANGELOG or RELEASES file
     #[serde(rename = "changelog")]
     Changelog,
+    /// A software bill of materials (SBOM)
+    #[serde(rename = "sbom")]
+    Sbom,
     /// Unknown to this version of cargo-dist-schema
     ///
     /// This is a fallback for forward/backward-compat
@@ -453,6 +544,12 @@ pub enum ArtifactKind {
     /// An updater executable
     #[serde(rename = "updater")]
     Updater,
+    /// A software bill of materials (SBOM) for a build
+    #[serde(rename = "sbom")]
+    Sbom,
+    /// A Python wheel
+    #[serde(rename = "wheel")]
+    Wheel,
     /// Unknown to this version of cargo-dist-schema
     ///
     /// This is a fallback for forward/backward-compat
@@ -496,6 +593,24 @@ pub struct VersionInfo {
     pub format: Format,
 }
 
+/// The current version of the dist-manifest.json schema
+///
+/// This must be bumped whenever a breaking change is made to the schema (see [`compat`][]),
+/// and the new schema committed to `schemas/` and added to [`HISTORICAL_SCHEMAS`][].
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Every version of the dist-manifest.json schema, oldest first
+pub const HISTORICAL_SCHEMAS: &[(u32, &str)] =
+    &[(1, include_str!("../schemas/dist-manifest-schema-v1.json"))];
+
+/// Get the JSON schema for the given version of dist-manifest.json
+pub fn historical_schema(version: u32) -> Option<&'static str> {
+    HISTORICAL_SCHEMAS
+        .iter()
+        .find(|(v, _)| *v == version)
+        .map(|(_, schema)| *schema)
+}
+
 /// The current version of cargo-dist-schema
 pub const SELF_VERSION: &str = env!("CARGO_PKG_VERSION");
 /// The first epoch of cargo-dist, after this version a bunch of things changed
@@ -536,6 +651,7 @@ impl DistManifest {
     pub fn new(releases: Vec<Release>, artifacts: BTreeMap<String, Artifact>) -> Self {
         Self {
             dist_version: None,
+            schema_version: Some(SCHEMA_VERSION),
             announcement_tag: None,
             announcement_tag_is_implicit: false,
             announcement_is_prerelease: false,
@@ -548,6 +664,7 @@ impl DistManifest {
             artifacts,
             systems: Default::default(),
             assets: Default::default(),
+            build_logs: vec![],
             publish_prereleases: false,
             force_latest: false,
             ci: None,
@@ -0,0 +1,632 @@
+//! Higher-level queries over a [`DistManifest`][].
+//!
+//! The types in the root of this crate are a faithful mirror of dist-manifest.json,
+//! which makes them a bit tedious to consume. This module provides the questions
+//! that installers and updaters typically want answered:
+//!
+//! * which artifacts natively target a given platform ([`DistManifest::artifacts_for_target`][])
+//! * which installer of a given kind supports a platform ([`DistManifest::installer_for_target`][])
+//! * which archive is the best option for a system ([`DistManifest::best_archive_for`][])
+//! * what the checksum of a given file is ([`DistManifest::checksum_for`][])
+//!
+//! The platform fallback rules (musl-static running on linux-gnu, x64 macOS running
+//! on arm64 under Rosetta 2, ...) are defined once by [`supported_targets`][], and are
+//! the same rules cargo-dist itself uses when generating installers.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Artifact, ArtifactId, ArtifactKind, AssetId, BuildEnvironment, DistManifest, GlibcVersion,
+    Linkage, Release, SystemInfo,
+};
+
+/// Suffixes of target triples that refer to statically linked linux libcs.
+///
+/// Currently rust takes "linux-musl" to mean "statically linked musl", but
+/// in the future it will mean "dynamically linked musl":
+///
+/// https://github.com/rust-lang/compiler-team/issues/422
+///
+/// To avoid this ambiguity, we prefer "musl-static" and "musl-dynamic" aliases.
+/// [`normalize_target`][] renames bare "musl" to "musl-static", so in the following
+/// listings we don't need to deal with bare "musl".
+const LINUX_STATIC_LIBCS: &[&str] = &["linux-musl-static"];
+/// Dynamically linked linux libcs that static libcs can replace
+const LINUX_STATIC_REPLACEABLE_LIBCS: &[&str] = &["linux-gnu", "linux-musl-dynamic"];
+/// A fake target triple for apple's universal2 format (staples x64 and arm64 together)
+const TARGET_MACOS_UNIVERSAL2: &str = "universal2-apple-darwin";
+const TARGET_X64_MAC: &str = "x86_64-apple-darwin";
+const TARGET_ARM64_MAC: &str = "aarch64-apple-darwin";
+const TARGET_X86_WINDOWS: &str = "i686-pc-windows-msvc";
+const TARGET_X64_WINDOWS: &str = "x86_64-pc-windows-msvc";
+const TARGET_ARM64_WINDOWS: &str = "aarch64-pc-windows-msvc";
+
+/// The quality of support an archive provides for a given platform
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum SupportQuality {
+    /// The archive natively supports this platform, there's no beating it
+    HostNative,
+    /// The archive natively supports this platform, but it's a Universal binary that contains
+    /// multiple platforms stapled together, so if there are also more precise archives, prefer those.
+    BulkyNative,
+    /// The archive is still technically native to this platform, but it's in some sense
+    /// imperfect. This can happen for things like "running a 32-bit binary on 64-bit" or
+    /// "using a statically linked linux libc". This solution is acceptable, but a HostNative
+    /// (or BulkyNative) solution should always be preferred.
+    ImperfectNative,
+    /// The archive is only running by the grace of pretty heavyweight emulation like Rosetta2.
+    /// This should be treated as a last resort, but hey, it works!
+    Emulated,
+    /// The layers of emulation are out of control.
+    Hellmulated,
+    /// STOP
+    HighwayToHellmulated,
+}
+
+/// A platform that binaries built for some target can run on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TargetSupport {
+    /// The target triple of the platform (already run through [`normalize_target`][])
+    pub target: String,
+    /// How well the binaries run there
+    pub quality: SupportQuality,
+    /// Whether Rosetta 2 must be installed for the binaries to run there
+    pub rosetta2: bool,
+}
+
+/// Normalize a target triple for comparisons
+///
+/// Currently this just renames the ambiguous "musl" targets to "musl-static",
+/// which is what they mean today.
+pub fn normalize_target(target: &str) -> String {
+    let (degunked_target, abigunk) = if let Some(inner_target) = target.strip_suffix("eabihf") {
+        (inner_target, "eabihf")
+    } else {
+        (target, "")
+    };
+    if let Some(system) = degunked_target.strip_suffix("musl") {
+        format!("{system}musl-static{abigunk}")
+    } else {
+        target.to_owned()
+    }
+}
+
+/// Given a target that some binaries were natively built for, compute every
+/// platform they can run on, and to what level of quality.
+///
+/// The first entry is always the native target itself. It's fine for this to be
+/// generous as long as the [`SupportQuality`][] is honest, since callers are expected
+/// to prefer better options when they have them.
+pub fn supported_targets(native_target: &str) -> Vec<TargetSupport> {
+    let target = normalize_target(native_target);
+    // For the following linux checks we want to pull off any "eabihf" suffix while
+    // comparing/parsing libc types.
+    let (degunked_target, abigunk) = if let Some(inner_target) = target.strip_suffix("eabihf") {
+        (inner_target, "eabihf")
+    } else {
+        (target.as_str(), "")
+    };
+    let support = |target: String, quality: SupportQuality| TargetSupport {
+        target,
+        quality,
+        rosetta2: false,
+    };
+
+    // First, the target itself is a HostNative entry
+    let mut res = vec![support(target.clone(), SupportQuality::HostNative)];
+
+    // If this is a static linux libc, say it can support any linux at ImperfectNative quality
+    for &static_libc in LINUX_STATIC_LIBCS {
+        let Some(system) = degunked_target.strip_suffix(static_libc) else {
+            continue;
+        };
+        for &libc in LINUX_STATIC_REPLACEABLE_LIBCS {
+            res.push(support(
+                format!("{system}{libc}{abigunk}"),
+                SupportQuality::ImperfectNative,
+            ));
+        }
+        break;
+    }
+
+    // universal2 macos binaries are totally native for both arches, but bulkier than
+    // necessary if we have builds for the individual platforms too.
+    if target == TARGET_MACOS_UNIVERSAL2 {
+        res.push(support(
+            TARGET_X64_MAC.to_owned(),
+            SupportQuality::BulkyNative,
+        ));
+        res.push(support(
+            TARGET_ARM64_MAC.to_owned(),
+            SupportQuality::BulkyNative,
+        ));
+    }
+
+    // FIXME?: technically we could add "run 32-bit intel macos on 64-bit intel"
+    // BUT this is unlikely to succeed as you increasingly need an EOL macOS,
+    // as support was dropped in macOS Catalina (macOS 10.15, October 2019).
+    // So this is unlikely to be helpful and DEFINITELY shouldn't be suggested
+    // unless all installers enforce the check for OS version.
+
+    // If this is x64 macos, say it can run on arm64 macos using Rosetta2
+    // Note that Rosetta2 is not *actually* installed by default on Apple Silicon,
+    // and the auto-installer for it only applies to GUI apps, not CLI apps, so ideally
+    // any installer that uses this fallback should check if Rosetta2 is installed!
+    if target == TARGET_X64_MAC {
+        res.push(TargetSupport {
+            target: TARGET_ARM64_MAC.to_owned(),
+            quality: SupportQuality::Emulated,
+            rosetta2: true,
+        });
+    }
+
+    // x86_32 windows binaries run fine on x86_64, but it's Imperfect compared to actual x86_64 binaries
+    if target == TARGET_X86_WINDOWS {
+        res.push(support(
+            TARGET_X64_WINDOWS.to_owned(),
+            SupportQuality::ImperfectNative,
+        ));
+    }
+
+    // Windows' equivalent to Rosetta2 (CHPE) is in fact installed-by-default so no need to detect!
+    if target == TARGET_X64_WINDOWS || target == TARGET_X86_WINDOWS {
+        // prefer x64 over x86 if we have the option
+        let quality = if target == TARGET_X86_WINDOWS {
+            SupportQuality::Hellmulated
+        } else {
+            SupportQuality::Emulated
+        };
+        res.push(support(TARGET_ARM64_WINDOWS.to_owned(), quality));
+    }
+
+    // windows-msvc binaries should always be acceptable on windows-gnu (mingw)
+    //
+    // FIXME: in theory x64-pc-windows-msvc and i686-pc-windows-msvc can run on
+    // aarch64-pc-windows-gnu, as a hybrid of this rules and the CHPE rule above.
+    // I don't want to think about computing the transitive closure of platform
+    // support and how to do all the tie breaking ("HighwayToHellmulated"?), so
+    // for now all 5 arm64 mingw users can be a little sad.
+    if let Some(system) = target.strip_suffix("windows-msvc") {
+        res.push(support(
+            format!("{system}windows-gnu"),
+            SupportQuality::ImperfectNative,
+        ));
+    }
+
+    res
+}
+
+/// Get the glibc version binaries built on this system with this linkage require, if any
+///
+/// If the binaries link glibc but the system's glibc version is unknown,
+/// we assume the glibc of the oldest supported github linux runner
+/// ([`GlibcVersion::default`][]).
+pub fn native_glibc_version(system: &SystemInfo, linkage: &Linkage) -> Option<GlibcVersion> {
+    if !linkage.system.iter().any(|lib| lib.is_glibc()) {
+        return None;
+    }
+    if let BuildEnvironment::Linux {
+        glibc_version: Some(system_glibc),
+    } = &system.build_environment
+    {
+        // If there's a system libc, assume that's what it was built against
+        Some(system_glibc.clone())
+    } else {
+        Some(GlibcVersion::default())
+    }
+}
+
+/// The kinds of installer cargo-dist can produce
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum InstallerKind {
+    /// A curl-sh script (`-installer.sh`)
+    Shell,
+    /// An irm-iex script (`-installer.ps1`)
+    Powershell,
+    /// An npm package (`-npm-package.tar.gz`)
+    Npm,
+    /// A homebrew formula (`.rb`)
+    Homebrew,
+    /// A windows msi (`.msi`)
+    Msi,
+    /// A macOS pkg (`.pkg`)
+    Pkg,
+}
+
+impl InstallerKind {
+    /// Guess the kind of an installer from its artifact name
+    pub fn from_artifact_name(name: &str) -> Option<Self> {
+        let kind = if name.ends_with("-installer.sh") {
+            Self::Shell
+        } else if name.ends_with("-installer.ps1") {
+            Self::Powershell
+        } else if name.ends_with("-npm-package.tar.gz") {
+            Self::Npm
+        } else if name.ends_with(".rb") {
+            Self::Homebrew
+        } else if name.ends_with(".msi") {
+            Self::Msi
+        } else if name.ends_with(".pkg") {
+            Self::Pkg
+        } else {
+            return None;
+        };
+        Some(kind)
+    }
+}
+
+/// The system an app is being installed onto
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostInfo {
+    /// The target triple of the system
+    pub target_triple: String,
+    /// The version of glibc on the system, if known
+    ///
+    /// If this is None, glibc requirements are assumed to be satisfied.
+    pub glibc_version: Option<GlibcVersion>,
+    /// Whether Rosetta 2 is installed (only relevant on arm64 macOS)
+    pub rosetta2: bool,
+}
+
+/// An archive that can be used to install an app on some platform
+#[derive(Debug, Clone)]
+pub struct ArchiveOption<'a> {
+    /// The id of the archive's Artifact
+    pub id: &'a str,
+    /// The archive's Artifact
+    pub artifact: &'a Artifact,
+    /// How well the archive supports the platform
+    pub quality: SupportQuality,
+    /// The minimum glibc version the archive's binaries require
+    pub min_glibc_version: Option<GlibcVersion>,
+    /// Whether Rosetta 2 must be installed to run the archive's binaries
+    pub rosetta2: bool,
+}
+
+impl ArchiveOption<'_> {
+    /// Check whether the given system satisfies this archive's runtime conditions
+    pub fn runs_on(&self, host: &HostInfo) -> bool {
+        if self.rosetta2 && !host.rosetta2 {
+            return false;
+        }
+        match (&self.min_glibc_version, &host.glibc_version) {
+            (Some(min), Some(have)) => have >= min,
+            _ => true,
+        }
+    }
+}
+
+impl DistManifest {
+    /// Iterate the artifacts of a release that natively target the given platform
+    ///
+    /// This includes installers that claim to support the platform.
+    pub fn artifacts_for_target<'a>(
+        &'a self,
+        release: &'a Release,
+        target: &str,
+    ) -> impl Iterator<Item = (&'a str, &'a Artifact)> {
+        let target = normalize_target(target);
+        self.artifacts_for_release(release)
+            .filter(move |(_, artifact)| {
+                artifact
+                    .target_triples
+                    .iter()
+                    .any(|t| normalize_target(t) == target)
+            })
+    }
+
+    /// Get the installer of the given kind that can install the release on the given platform
+    ///
+    /// Installers that don't list any target triples (like homebrew formulas on
+    /// older manifests) are assumed to support every platform.
+    pub fn installer_for_target<'a>(
+        &'a self,
+        release: &'a Release,
+        target: &str,
+        kind: InstallerKind,
+    ) -> Option<(&'a str, &'a Artifact)> {
+        let target = normalize_target(target);
+        self.artifacts_for_release(release).find(|(_, artifact)| {
+            matches!(artifact.kind, ArtifactKind::Installer)
+                && artifact
+                    .name
+                    .as_deref()
+                    .and_then(InstallerKind::from_artifact_name)
+                    == Some(kind)
+                && (artifact.target_triples.is_empty()
+                    || artifact
+                        .target_triples
+                        .iter()
+                        .any(|t| normalize_target(t) == target))
+        })
+    }
+
+    /// Get every archive of a release that can be used on the given platform
+    ///
+    /// The options are sorted from best to worst, so you should use the first
+    /// one whose runtime conditions are satisfied (see [`DistManifest::best_archive_for`][]).
+    pub fn archives_for_target<'a>(
+        &'a self,
+        release: &'a Release,
+        target: &str,
+    ) -> Vec<ArchiveOption<'a>> {
+        let target = normalize_target(target);
+        let mut options = vec![];
+        for (id, artifact) in self.artifacts_for_release(release) {
+            if !matches!(artifact.kind, ArtifactKind::ExecutableZip) {
+                continue;
+            }
+            // A universal binary may list several native targets, so just take the best
+            let best = artifact
+                .target_triples
+                .iter()
+                .flat_map(|t| supported_targets(t))
+                .filter(|support| support.target == target)
+                .min_by_key(|support| support.quality);
+            let Some(support) = best else {
+                continue;
+            };
+            options.push(ArchiveOption {
+                id,
+                artifact,
+                quality: support.quality,
+                min_glibc_version: self.min_glibc_version_for_artifact(id),
+                rosetta2: support.rosetta2,
+            });
+        }
+        // Sort by SupportQuality, tie break by artifact id (for stability)
+        options.sort_by(|a, b| a.quality.cmp(&b.quality).then_with(|| a.id.cmp(b.id)));
+        options
+    }
+
+    /// Pick the best archive of a release for the given system
+    ///
+    /// This is the first option of [`DistManifest::archives_for_target`][] whose runtime
+    /// conditions the system satisfies. For instance a linux-gnu archive built
+    /// against a newer glibc than the system has will be skipped in favour of
+    /// a linux-musl-static one.
+    pub fn best_archive_for<'a>(
+        &'a self,
+        release: &'a Release,
+        host: &HostInfo,
+    ) -> Option<ArchiveOption<'a>> {
+        self.archives_for_target(release, &host.target_triple)
+            .into_iter()
+            .find(|option| option.runs_on(host))
+    }
+
+    /// Get the minimum glibc version required by the binaries in an artifact, if any
+    ///
+    /// This requires linkage info, so it will be None for manifests that weren't
+    /// produced by a full build.
+    pub fn min_glibc_version_for_artifact(&self, artifact_id: &str) -> Option<GlibcVersion> {
+        let artifact = self.artifacts.get(artifact_id)?;
+        artifact
+            .assets
+            .iter()
+            .filter_map(|asset| self.min_glibc_version_for_asset(asset.id.as_ref()?))
+            .max()
+    }
+
+    /// Get the minimum glibc version required by an asset (binary), if any
+    pub fn min_glibc_version_for_asset(&self, asset_id: &AssetId) -> Option<GlibcVersion> {
+        let asset = self.assets.get(asset_id)?;
+        let linkage = asset.linkage.as_ref()?;
+        let system = self.systems.get(&asset.system)?;
+        native_glibc_version(system, linkage)
+    }
+
+    /// Look up an artifact by its file name
+    pub fn artifact_by_name(&self, name: &str) -> Option<(&ArtifactId, &Artifact)> {
+        self.artifacts
+            .iter()
+            .find(|(_, artifact)| artifact.name.as_deref() == Some(name))
+    }
+
+    /// Get the checksum of a file using the given algorithm (e.g. "sha256")
+    ///
+    /// This only consults checksums recorded in the manifest. If the checksum
+    /// was only written to a sidecar file, see [`DistManifest::checksum_artifact_for`][].
+    pub fn checksum_for(&self, name: &str, algorithm: &str) -> Option<&str> {
+        let (_, artifact) = self.artifact_by_name(name)?;
+        artifact.checksums.get(algorithm).map(|s| &**s)
+    }
+
+    /// Get the artifact containing the checksum of a file (e.g. `myapp.tar.gz.sha256`)
+    pub fn checksum_artifact_for(&self, name: &str) -> Option<&Artifact> {
+        let (_, artifact) = self.artifact_by_name(name)?;
+        self.artifacts.get(artifact.checksum.as_ref()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::{Asset, AssetInfo, AssetKind, ExecutableAsset, Library};
+
+    const GNU: &str = "x86_64-unknown-linux-gnu";
+    const MUSL: &str = "x86_64-unknown-linux-musl";
+
+    fn artifact(name: &str, kind: ArtifactKind, targets: &[&str]) -> Artifact {
+        Artifact {
+            name: Some(name.to_owned()),
+            kind,
+            target_triples: targets.iter().map(|t| t.to_string()).collect(),
+            path: None,
+            assets: vec![],
+            install_hint: None,
+            description: None,
+            checksum: None,
+            checksums: BTreeMap::new(),
+        }
+    }
+
+    /// A manifest with gnu (built against glibc 2.35) and musl archives and a shell installer
+    fn manifest() -> DistManifest {
+        let mut gnu = artifact("app-gnu.tar.xz", ArtifactKind::ExecutableZip, &[GNU]);
+        gnu.assets.push(Asset {
+            id: Some("app-gnu-exe".to_owned()),
+            name: Some("app".to_owned()),
+            path: Some("app".to_owned()),
+            kind: AssetKind::Executable(ExecutableAsset {
+                symbols_artifact: None,
+            }),
+        });
+        gnu.checksum = Some("app-gnu.tar.xz.sha256".to_owned());
+        gnu.checksums
+            .insert("sha256".to_owned(), "abc123".to_owned());
+        let artifacts = [
+            gnu,
+            artifact("app-musl.tar.xz", ArtifactKind::ExecutableZip, &[MUSL]),
+            artifact("app-gnu.tar.xz.sha256", ArtifactKind::Checksum, &[]),
+            artifact("app-installer.sh", ArtifactKind::Installer, &[GNU]),
+        ];
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        let release = manifest.ensure_release("app".to_owned(), "1.0.0".to_owned());
+        for artifact in &artifacts {
+            release.artifacts.push(artifact.name.clone().unwrap());
+        }
+        for artifact in artifacts {
+            manifest
+                .artifacts
+                .insert(artifact.name.clone().unwrap(), artifact);
+        }
+        manifest.systems.insert(
+            "builder".to_owned(),
+            SystemInfo {
+                id: "builder".to_owned(),
+                cargo_version_line: None,
+                build_environment: BuildEnvironment::Linux {
+                    glibc_version: Some(GlibcVersion {
+                        major: 2,
+                        series: 35,
+                    }),
+                },
+            },
+        );
+        let linkage = Linkage {
+            system: [Library::new("/lib/x86_64-linux-gnu/libc.so.6".to_owned())]
+                .into_iter()
+                .collect(),
+            ..Default::default()
+        };
+        manifest.assets.insert(
+            "app-gnu-exe".to_owned(),
+            AssetInfo {
+                id: "app-gnu-exe".to_owned(),
+                name: "app".to_owned(),
+                system: "builder".to_owned(),
+                target_triples: vec![GNU.to_owned()],
+                linkage: Some(linkage),
+                processors: vec![],
+                build_id: None,
+            },
+        );
+        manifest
+    }
+
+    fn host(target: &str, glibc: Option<(u64, u64)>) -> HostInfo {
+        HostInfo {
+            target_triple: target.to_owned(),
+            glibc_version: glibc.map(|(major, series)| GlibcVersion { major, series }),
+            rosetta2: false,
+        }
+    }
+
+    #[test]
+    fn static_musl_supports_all_linux() {
+        let targets = supported_targets(MUSL)
+            .into_iter()
+            .map(|s| (s.target, s.quality))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            targets,
+            vec![
+                (
+                    "x86_64-unknown-linux-musl-static".to_owned(),
+                    SupportQuality::HostNative
+                ),
+                (GNU.to_owned(), SupportQuality::ImperfectNative),
+                (
+                    "x86_64-unknown-linux-musl-dynamic".to_owned(),
+                    SupportQuality::ImperfectNative
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn x64_mac_needs_rosetta_on_arm64() {
+        let arm = supported_targets(TARGET_X64_MAC)
+            .into_iter()
+            .find(|s| s.target == TARGET_ARM64_MAC)
+            .unwrap();
+        assert_eq!(arm.quality, SupportQuality::Emulated);
+        assert!(arm.rosetta2);
+    }
+
+    #[test]
+    fn best_archive_prefers_native_gnu() {
+        let manifest = manifest();
+        let release = &manifest.releases[0];
+        let options = manifest.archives_for_target(release, GNU);
+        let ids = options.iter().map(|o| o.id).collect::<Vec<_>>();
+        assert_eq!(ids, vec!["app-gnu.tar.xz", "app-musl.tar.xz"]);
+        assert_eq!(
+            options[0].min_glibc_version,
+            Some(GlibcVersion {
+                major: 2,
+                series: 35
+            })
+        );
+
+        let best = manifest
+            .best_archive_for(release, &host(GNU, Some((2, 39))))
+            .unwrap();
+        assert_eq!(best.id, "app-gnu.tar.xz");
+    }
+
+    #[test]
+    fn best_archive_falls_back_to_musl_on_old_glibc() {
+        let manifest = manifest();
+        let release = &manifest.releases[0];
+        let best = manifest
+            .best_archive_for(release, &host(GNU, Some((2, 31))))
+            .unwrap();
+        assert_eq!(best.id, "app-musl.tar.xz");
+        assert_eq!(best.quality, SupportQuality::ImperfectNative);
+
+        // bare "musl" hosts are normalized to musl-static
+        let best = manifest
+            .best_archive_for(release, &host(MUSL, None))
+            .unwrap();
+        assert_eq!(best.id, "app-musl.tar.xz");
+        assert_eq!(best.quality, SupportQuality::HostNative);
+    }
+
+    #[test]
+    fn installer_and_checksum_lookup() {
+        let manifest = manifest();
+        let release = &manifest.releases[0];
+        let (id, _) = manifest
+            .installer_for_target(release, GNU, InstallerKind::Shell)
+            .unwrap();
+        assert_eq!(id, "app-installer.sh");
+        assert!(manifest
+            .installer_for_target(release, GNU, InstallerKind::Powershell)
+            .is_none());
+        assert!(manifest
+            .installer_for_target(release, TARGET_X64_MAC, InstallerKind::Shell)
+            .is_none());
+
+        assert_eq!(
+            manifest.checksum_for("app-gnu.tar.xz", "sha256"),
+            Some("abc123")
+        );
+        assert_eq!(manifest.checksum_for("app-musl.tar.xz", "sha256"), None);
+        let sidecar = manifest.checksum_artifact_for("app-gnu.tar.xz").unwrap();
+        assert_eq!(sidecar.name.as_deref(), Some("app-gnu.tar.xz.sha256"));
+    }
+}
@@ -88,6 +88,8 @@
 
 use std::fmt::Display;
 
+use axoproject::changelog::ChangelogInfo;
+use axoproject::local_repo::LocalRepo;
 use axoproject::platforms::triple_to_display_name;
 use axoproject::PackageIdx;
 use axotag::{parse_tag, Package, PartialAnnouncementTag, ReleaseType};
@@ -97,7 +99,7 @@ use semver::Version;
 use tracing::info;
 
 use crate::{
-    config::LibraryStyle,
+    config::{ChangelogFormat, LibraryStyle},
     errors::{DistError, DistResult},
     DistGraphBuilder, SortedMap, TargetTriple,
 };
@@ -192,20 +194,57 @@ impl<'a> DistGraphBuilder<'a> {
 
     /// Try to compute changelogs for the announcement
     pub fn compute_announcement_changelog(&mut self, announcing: &AnnouncementTag) {
-        let info = if let Some(announcing_version) = &announcing.version {
+        let info = match self.inner.config.hosts.changelog_format {
+            ChangelogFormat::File => self
+                .changelog_from_files(announcing)
+                .or_else(|| self.changelog_from_git(announcing, false)),
+            ChangelogFormat::ConventionalCommits => self.changelog_from_git(announcing, true),
+        };
+        let Some(info) = info else {
+            return;
+        };
+
+        info!("successfully parsed changelog!");
+        self.manifest.announcement_title = Some(info.title);
+        // Those windows newlines get everywhere...
+        let clean_notes = newline_converter::dos2unix(&info.body);
+        self.manifest.announcement_changelog = Some(clean_notes.into_owned());
+    }
+
+    /// Get the announcement's changelog from the CHANGELOG/RELEASES files
+    fn changelog_from_files(&self, announcing: &AnnouncementTag) -> Option<ChangelogInfo> {
+        if let Some(announcing_version) = &announcing.version {
             // Try to find the version we're announcing in the top level CHANGELOG/RELEASES
             let version = axoproject::Version::Cargo(announcing_version.clone());
-            let Ok(Some(info)) = self
-                .workspaces
-                .root_workspace()
-                .changelog_for_version(&version)
-            else {
+            let root_workspace = self.workspaces.root_workspace();
+            if let Ok(Some(info)) = root_workspace.changelog_for_version(&version) {
+                return Some(info);
+            }
+
+            // Otherwise, merge the entries of every package with its own changelog
+            let root_changelog = root_workspace.root_auto_includes.changelog.as_deref();
+            let mut entries = vec![];
+            for release in &announcing.rust_releases {
+                let package = self.workspaces.package(release.package_idx);
+                let Some(changelog_path) = package.changelog_file.as_deref() else {
+                    continue;
+                };
+                if Some(changelog_path) == root_changelog {
+                    continue;
+                }
+                match package.changelog_for_version(&version) {
+                    Ok(Some(info)) => {
+                        entries.push((format!("{} {announcing_version}", package.name), info))
+                    }
+                    _ => info!("failed to find {version} in {changelog_path}, skipping it"),
+                }
+            }
+            let info = axoproject::changelog::combine_changelogs(announcing.tag.clone(), entries);
+            if info.is_none() {
                 info!(
                     "failed to find {version} in workspace changelogs, skipping changelog generation"
                 );
-                return;
-            };
-
+            }
             info
         } else if let Some(announcing_package) = announcing.package {
             // Try to find the package's specific CHANGELOG/RELEASES
@@ -215,27 +254,172 @@ impl<'a> DistGraphBuilder<'a> {
                 .version
                 .as_ref()
                 .expect("cargo package without a version!?");
-            let Ok(Some(info)) = self
-                .workspaces
-                .package(announcing_package)
-                .changelog_for_version(version)
-            else {
+            let Ok(Some(info)) = package.changelog_for_version(version) else {
                 info!(
                     "failed to find {version} in {package_name} changelogs, skipping changelog generation"
                 );
-                return;
+                return None;
             };
 
-            info
+            Some(info)
         } else {
             unreachable!("you're neither announcing a version or a package!?");
+        }
+    }
+
+    /// Get the announcement's changelog from the commits since the previous release
+    ///
+    /// If `conventional_only` is set, only conventional commits are included. Otherwise
+    /// this is a fallback for projects without a changelog, and everything goes in, but
+    /// only if we can find a previous release to start from.
+    fn changelog_from_git(
+        &self,
+        announcing: &AnnouncementTag,
+        conventional_only: bool,
+    ) -> Option<ChangelogInfo> {
+        let (Some(repo), Some(git)) = (&self.workspaces.repo, &self.inner.tools.git) else {
+            info!("not in a git repo, skipping changelog generation");
+            return None;
         };
+        let git = &git.cmd;
 
-        info!("successfully parsed changelog!");
-        self.manifest.announcement_title = Some(info.title);
-        // Those windows newlines get everywhere...
-        let clean_notes = newline_converter::dos2unix(&info.body);
-        self.manifest.announcement_changelog = Some(clean_notes.into_owned());
+        // If the tag doesn't exist yet, we're previewing a release of HEAD
+        let until = if repo.has_revision(git, &announcing.tag) {
+            announcing.tag.as_str()
+        } else {
+            "HEAD"
+        };
+        let previous_tag = self.previous_release_tag(repo, git, announcing, until);
+        if previous_tag.is_none() && !conventional_only {
+            info!("no previous release tag found, skipping changelog generation");
+            return None;
+        }
+        let commits = match repo.commits(git, previous_tag.as_deref(), until) {
+            Ok(commits) => commits,
+            Err(e) => {
+                info!("failed to read git history, skipping changelog generation: {e}");
+                return None;
+            }
+        };
+
+        // If we know where the repo is hosted, link up any pull requests
+        let package_list = announcing
+            .rust_releases
+            .iter()
+            .map(|release| release.package_idx)
+            .collect::<Vec<_>>();
+        let pull_request_url = self
+            .workspaces
+            .repository_url(Some(&package_list))
+            .ok()
+            .flatten()
+            .and_then(|url| url.github_repo().ok())
+            .map(|repo| format!("{}/pull", repo.web_url()));
+
+        let title = announcing.tag.clone();
+        let info = if conventional_only {
+            axoproject::changelog::changelog_from_commits(
+                title,
+                &commits,
+                pull_request_url.as_deref(),
+            )
+        } else {
+            axoproject::changelog::changelog_from_history(
+                title,
+                &commits,
+                pull_request_url.as_deref(),
+            )
+        };
+        if info.is_none() {
+            info!("no notable commits since the previous release, skipping changelog generation");
+        }
+        info
+    }
+
+    /// Find the most recent tag before `until` that released any of the packages we're announcing
+    fn previous_release_tag(
+        &self,
+        repo: &LocalRepo,
+        git: &str,
+        announcing: &AnnouncementTag,
+        until: &str,
+    ) -> Option<String> {
+        let current_version = match (&announcing.version, announcing.package) {
+            (Some(version), _) => version.clone(),
+            (None, Some(pkg_idx)) => self.workspaces.package(pkg_idx).version.as_ref()?.semver(),
+            (None, None) => return None,
+        };
+        let packages = announcing
+            .rust_releases
+            .iter()
+            .map(|release| release.package_idx)
+            .collect::<Vec<_>>();
+        self.release_tags(repo, git, until, &packages)
+            .into_iter()
+            .filter(|(version, tag)| *tag != announcing.tag && *version < current_version)
+            .max_by(|(a, _), (b, _)| a.cmp(b))
+            .map(|(_, tag)| tag)
+    }
+
+    /// Find every tag reachable from `until` that released any of the given packages
+    ///
+    /// Tags are parsed with axotag just like `--tag` is, so tags for other packages (or
+    /// other workflows, if tag-namespace is set) don't count. A unified version tag
+    /// counts as a release of every package.
+    pub(crate) fn release_tags(
+        &self,
+        repo: &LocalRepo,
+        git: &str,
+        until: &str,
+        packages: &[PackageIdx],
+    ) -> Vec<(Version, String)> {
+        let tags = match repo.tags(git, until) {
+            Ok(tags) => tags,
+            Err(e) => {
+                info!("failed to list git tags: {e}");
+                return vec![];
+            }
+        };
+        let tag_namespace = self
+            .inner
+            .config
+            .ci
+            .github
+            .as_ref()
+            .and_then(|ci| ci.tag_namespace.as_deref());
+        // Old tags have old versions, so don't ask axotag to check them
+        let all_packages = self
+            .workspaces
+            .all_packages()
+            .map(|(_, info)| Package {
+                name: info.name.clone(),
+                version: None,
+            })
+            .collect::<Vec<_>>();
+
+        let mut releases = vec![];
+        for tag in tags {
+            if let Some(namespace) = tag_namespace {
+                if !tag.starts_with(namespace) {
+                    continue;
+                }
+            }
+            let Ok(parsed) = parse_tag(&all_packages, &tag) else {
+                continue;
+            };
+            let version = match parsed.release {
+                ReleaseType::Version(version) => version,
+                ReleaseType::Package { idx, version } => {
+                    if !packages.contains(&PackageIdx(idx)) {
+                        continue;
+                    }
+                    version
+                }
+                ReleaseType::None => continue,
+            };
+            releases.push((version, tag));
+        }
+        releases
     }
 
     /// If we're publishing to Github, generate some Github notes
@@ -247,6 +431,7 @@ impl<'a> DistGraphBuilder<'a> {
 enum DisabledReason {
     DistFalse,
     NoArtifacts { kinds: Vec<String> },
+    NoVersion,
     PublishFalse,
     TagNotMatched { tag: String },
 }
@@ -257,6 +442,7 @@ impl Display for DisabledReason {
             Self::DistFalse => write!(f, "dist = false"),
             Self::PublishFalse => write!(f, "publish = false"),
             Self::NoArtifacts { kinds } => write!(f, "no {}", kinds.join(" ")),
+            Self::NoVersion => write!(f, "no version"),
             Self::TagNotMatched { tag } => write!(f, "didn't match tag {}", tag),
         }
     }
@@ -281,7 +467,9 @@ fn check_dist_package(
 ) -> Option<DisabledReason> {
     let config = graph.package_config(pkg_id).clone();
 
-    let mut package_empty = pkg.binaries.is_empty();
+    // maturin packages always have wheels, even without any scripts
+    let mut package_empty =
+        pkg.binaries.is_empty() && !crate::build::maturin::is_maturin_package(pkg);
     let mut missing_categories = vec!["binaries".to_owned()];
     if config
         .artifacts
@@ -325,6 +513,12 @@ fn check_dist_package(
         return Some(DisabledReason::PublishFalse);
     }
 
+    // Some package formats (like go.mod) don't have versions, so one needs to
+    // be set in a dist.toml
+    if pkg.version.is_none() {
+        return Some(DisabledReason::NoVersion);
+    }
+
     // If we're announcing a package, reject every other package
     match &announcing.release {
         ReleaseType::Package { idx, version: _ } => {
@@ -475,7 +669,7 @@ fn require_axotag_consistency(
 ///
 /// See `check_dist_package` for the actual selection logic and some notes on inference
 /// when `--tag` is absent.
-fn select_packages(
+pub(crate) fn select_packages(
     graph: &DistGraphBuilder,
     announcing: &PartialAnnouncementTag,
 ) -> Vec<ReleaseArtifacts> {
@@ -528,8 +722,12 @@ fn select_packages(
             }
         }
 
+        // maturin packages are releases even without binaries, for their wheels
+        let has_wheels =
+            disabled_reason.is_none() && crate::build::maturin::is_maturin_package(pkg);
+
         // If any binaries were accepted for this package, it's a Release!
-        if !binaries.is_empty() || !cdylibs.is_empty() || !cstaticlibs.is_empty() {
+        if !binaries.is_empty() || !cdylibs.is_empty() || !cstaticlibs.is_empty() || has_wheels {
             let release = ReleaseArtifacts {
                 package_idx: pkg_id,
                 executables: binaries,
@@ -14,9 +14,13 @@ use tracing::warn;
 use crate::{
     backend::{diff_files, templates::TEMPLATE_CI_GITHUB},
     config::{
-        v1::{ci::github::GithubCiConfig, publishers::PublisherConfig},
-        DependencyKind, GithubPermission, GithubPermissionMap, GithubReleasePhase, HostingStyle,
-        JinjaGithubRepoPair, JobStyle, ProductionMode, PublishStyle, SystemDependencies,
+        v1::{
+            builds::cargo::WorkspaceCargoBuildConfig, ci::github::GithubCiConfig,
+            publishers::PublisherConfig,
+        },
+        CargoBuilder, DependencyKind, GithubPermission, GithubPermissionMap, GithubReleasePhase,
+        HostingStyle, JinjaGithubRepoPair, JobStyle, ProductionMode, PublishStyle,
+        SystemDependencies,
     },
     errors::DistResult,
     DistError, DistGraph, SortedMap, SortedSet, TargetTriple,
@@ -102,6 +106,10 @@ pub struct GithubReleaseInfo {
     pub github_attestations: bool,
     /// `gh` command to run to create the release
     pub release_command: String,
+    /// `gh` command that succeeds if the release already exists
+    pub release_exists_command: String,
+    /// `gh` command to run instead of creating the release, if an earlier run already did
+    pub update_release_command: String,
     /// Which phase to create the release at
     pub release_phase: GithubReleasePhase,
 }
@@ -289,28 +297,15 @@ impl GithubCiInfo {
         let root_permissions = (!root_permissions.is_empty()).then_some(root_permissions);
 
         // Figure out what Local Artifact tasks we need
-        let local_runs = if ci_config.merge_tasks {
-            distribute_targets_to_runners_merged(local_targets, &ci_config.runners)
-        } else {
-            distribute_targets_to_runners_split(local_targets, &ci_config.runners)
-        };
-        for (runner, targets) in local_runs {
-            use std::fmt::Write;
-            let install_dist =
-                install_dist_for_targets(&targets, &install_dist_sh, &install_dist_ps1);
-            let mut dist_args = String::from("--artifacts=local");
-            for target in &targets {
-                write!(dist_args, " --target={target}").unwrap();
-            }
-            tasks.push(GithubMatrixEntry {
-                targets: Some(targets.iter().map(|s| s.to_string()).collect()),
-                cache_provider: cache_provider_for_runner(&runner),
-                runner: Some(runner),
-                dist_args: Some(dist_args),
-                install_dist: Some(install_dist.to_owned()),
-                packages_install: package_install_for_targets(&targets, &dependencies),
-            });
-        }
+        tasks.extend(local_artifacts_tasks(
+            local_targets,
+            &ci_config.runners,
+            ci_config.merge_tasks,
+            &dist.config.builds.cargo,
+            &dependencies,
+            &install_dist_sh,
+            &install_dist_ps1,
+        ));
 
         let github_ci_workflow_dir = dist.repo_dir.join(GITHUB_CI_DIR);
         let github_build_setup = ci_config
@@ -436,6 +431,7 @@ impl GithubReleaseInfo {
 
         let mut release_args = vec![];
         let action;
+        let update_release_command;
         // Always need to use the tag flag
         release_args.push("\"${{ needs.plan.outputs.tag }}\"");
 
@@ -444,6 +440,7 @@ impl GithubReleaseInfo {
             release_args.push("--repo");
             release_args.push("\"$REPO\"")
         }
+        let release_exists_command = format!("gh release view {}", release_args.join(" "));
         release_args.push("--target");
         release_args.push("\"$RELEASE_COMMIT\"");
         release_args.push("$PRERELEASE_FLAG");
@@ -453,11 +450,14 @@ impl GithubReleaseInfo {
             release_args.push("\"$ANNOUNCEMENT_TITLE\"");
             release_args.push("--notes-file");
             release_args.push("\"$RUNNER_TEMP/notes.txt\"");
+            // If an earlier run already created the release, refresh it instead
+            update_release_command = format!("gh release edit {}", release_args.join(" "));
             // When creating release, upload artifacts transactionally
             release_args.push("artifacts/*");
         } else {
             action = "edit";
             release_args.push("--draft=false");
+            update_release_command = String::new();
         }
         let release_command = format!("gh release {action} {}", release_args.join(" "));
 
@@ -467,6 +467,8 @@ impl GithubReleaseInfo {
             external_repo_commit,
             github_attestations,
             release_command,
+            release_exists_command,
+            update_release_command,
             release_phase,
         }))
     }
@@ -531,6 +533,49 @@ fn cache_provider_for_runner(runner: &str) -> Option<String> {
     }
 }
 
+/// Plan the Local Artifacts tasks: which runners build which targets, and what they need installed
+fn local_artifacts_tasks(
+    local_targets: SortedSet<&TargetTriple>,
+    custom_runners: &BTreeMap<String, String>,
+    merge_tasks: bool,
+    cargo: &WorkspaceCargoBuildConfig,
+    dependencies: &SystemDependencies,
+    install_dist_sh: &str,
+    install_dist_ps1: &str,
+) -> Vec<GithubMatrixEntry> {
+    let local_runs = if merge_tasks {
+        distribute_targets_to_runners_merged(local_targets, custom_runners, &cargo.builders)
+    } else {
+        distribute_targets_to_runners_split(local_targets, custom_runners, &cargo.builders)
+    };
+    let mut tasks = vec![];
+    for (runner, targets) in local_runs {
+        use std::fmt::Write;
+        let install_dist =
+            install_dist_for_targets(&targets, &cargo.builders, install_dist_sh, install_dist_ps1);
+        let mut dist_args = String::from("--artifacts=local");
+        for target in &targets {
+            write!(dist_args, " --target={target}").unwrap();
+        }
+        let packages_install = [
+            package_install_for_targets(&targets, dependencies),
+            cargo_builder_install_for_targets(&targets, cargo),
+        ]
+        .into_iter()
+        .flatten()
+        .collect::<Vec<_>>();
+        tasks.push(GithubMatrixEntry {
+            targets: Some(targets.iter().map(|s| s.to_string()).collect()),
+            cache_provider: cache_provider_for_runner(&runner),
+            runner: Some(runner),
+            dist_args: Some(dist_args),
+            install_dist: Some(install_dist.to_owned()),
+            packages_install: (!packages_install.is_empty()).then(|| packages_install.join("\n")),
+        });
+    }
+    tasks
+}
+
 /// Given a set of targets we want to build local artifacts for, map them to Github Runners
 /// while preferring to merge builds that can happen on the same machine.
 ///
@@ -545,10 +590,11 @@ fn cache_provider_for_runner(runner: &str) -> Option<String> {
 fn distribute_targets_to_runners_merged<'a>(
     targets: SortedSet<&'a TargetTriple>,
     custom_runners: &BTreeMap<String, String>,
+    builders: &SortedMap<TargetTriple, CargoBuilder>,
 ) -> std::vec::IntoIter<(GithubRunner, Vec<&'a TargetTriple>)> {
     let mut groups = SortedMap::<GithubRunner, Vec<&TargetTriple>>::new();
     for target in targets {
-        let runner = github_runner_for_target(target, custom_runners);
+        let runner = github_runner_for_target(target, custom_runners, builders);
         let runner = runner.unwrap_or_else(|| {
             let default = GITHUB_LINUX_RUNNER;
             warn!("not sure which github runner should be used for {target}, assuming {default}");
@@ -566,10 +612,11 @@ fn distribute_targets_to_runners_merged<'a>(
 fn distribute_targets_to_runners_split<'a>(
     targets: SortedSet<&'a TargetTriple>,
     custom_runners: &BTreeMap<String, String>,
+    builders: &SortedMap<TargetTriple, CargoBuilder>,
 ) -> std::vec::IntoIter<(GithubRunner, Vec<&'a TargetTriple>)> {
     let mut groups = vec![];
     for target in targets {
-        let runner = github_runner_for_target(target, custom_runners);
+        let runner = github_runner_for_target(target, custom_runners, builders);
         let runner = runner.unwrap_or_else(|| {
             let default = GITHUB_LINUX_RUNNER;
             warn!("not sure which github runner should be used for {target}, assuming {default}");
@@ -595,10 +642,14 @@ const GITHUB_WINDOWS_RUNNER: &str = "windows-2019";
 fn github_runner_for_target(
     target: &TargetTriple,
     custom_runners: &BTreeMap<String, String>,
+    builders: &SortedMap<TargetTriple, CargoBuilder>,
 ) -> Option<GithubRunner> {
     if let Some(runner) = custom_runners.get(target) {
         return Some(runner.to_owned());
     }
+    if cross_compiles_from_linux(target, builders) {
+        return Some(GITHUB_LINUX_RUNNER.to_owned());
+    }
 
     // We want to default to older runners to minimize the places
     // where random system dependencies can creep in and be very
@@ -607,7 +658,7 @@ fn github_runner_for_target(
         Some(GITHUB_LINUX_RUNNER.to_owned())
     } else if target.contains("x86_64-apple") {
         Some(GITHUB_MACOS_INTEL_RUNNER.to_owned())
-    } else if target.contains("aarch64-apple") {
+    } else if target.contains("aarch64-apple") || target.contains("universal2-apple") {
         Some(GITHUB_MACOS_ARM64_RUNNER.to_owned())
     } else if target.contains("windows") {
         Some(GITHUB_WINDOWS_RUNNER.to_owned())
@@ -616,21 +667,42 @@ fn github_runner_for_target(
     }
 }
 
+/// Whether a target's cargo builder lets it be built on a linux runner
+///
+/// cargo-zigbuild and cross can both cross-compile to linux, the BSDs, android, and
+/// windows-gnu from any linux machine, so those targets don't need a native runner.
+/// Apple and MSVC targets still need their SDKs, so they stay on their own runners.
+fn cross_compiles_from_linux(
+    target: &TargetTriple,
+    builders: &SortedMap<TargetTriple, CargoBuilder>,
+) -> bool {
+    let cross_builder = matches!(
+        builders.get(target),
+        Some(CargoBuilder::Zigbuild { .. } | CargoBuilder::Cross)
+    );
+    cross_builder && !target.contains("apple") && !target.contains("msvc")
+}
+
 /// Select the cargo-dist installer approach for a given Github Runner
 fn install_dist_for_targets<'a>(
     targets: &'a [&'a TargetTriple],
+    builders: &SortedMap<TargetTriple, CargoBuilder>,
     install_sh: &'a str,
     install_ps1: &'a str,
 ) -> &'a str {
     for target in targets {
-        if target.contains("linux") || target.contains("apple") {
+        if target.contains("linux")
+            || target.contains("apple")
+            || cross_compiles_from_linux(target, builders)
+        {
             return install_sh;
         } else if target.contains("windows") {
             return install_ps1;
         }
     }
 
-    unreachable!("internal error: unknown target triple!?")
+    // Targets we don't know a runner for get a linux one
+    install_sh
 }
 
 fn brewfile_from(packages: &[String]) -> String {
@@ -670,7 +742,10 @@ fn package_install_for_targets(
     // FIXME?: handle mixed-OS targets
     for target in targets {
         match target.as_str() {
-            "i686-apple-darwin" | "x86_64-apple-darwin" | "aarch64-apple-darwin" => {
+            "i686-apple-darwin"
+            | "x86_64-apple-darwin"
+            | "aarch64-apple-darwin"
+            | "universal2-apple-darwin" => {
                 let packages: Vec<String> = packages
                     .homebrew
                     .clone()
@@ -751,6 +826,43 @@ fn package_install_for_targets(
     None
 }
 
+/// Get the commands to install the tools needed to cargo build the given targets
+///
+/// Targets built with cargo-zigbuild or cross can be built on any linux runner,
+/// so these are what let us avoid needing a native runner for every linux arch.
+/// cargo-auditable wraps whichever builder is used, and pgo needs llvm-profdata,
+/// so those are needed everywhere.
+fn cargo_builder_install_for_targets(
+    targets: &[&TargetTriple],
+    cargo: &WorkspaceCargoBuildConfig,
+) -> Option<String> {
+    let mut zigbuild = false;
+    let mut cross = false;
+    for target in targets {
+        match cargo.builders.get(*target) {
+            Some(CargoBuilder::Zigbuild { .. }) => zigbuild = true,
+            Some(CargoBuilder::Cross) => cross = true,
+            Some(CargoBuilder::Cargo) | None => {}
+        }
+    }
+
+    let mut commands = vec![];
+    if zigbuild {
+        commands.push("pip3 install ziglang");
+        commands.push("cargo install --locked cargo-zigbuild");
+    }
+    if cross {
+        commands.push("cargo install --locked cross");
+    }
+    if cargo.auditable && !targets.is_empty() {
+        commands.push("cargo install --locked cargo-auditable");
+    }
+    if cargo.pgo.is_some() && !targets.is_empty() {
+        commands.push("rustup component add llvm-tools-preview");
+    }
+    (!commands.is_empty()).then(|| commands.join("\n"))
+}
+
 /// Builder for looking up and reporting errors in the steps provided by the
 /// `github-build-setup` configuration
 pub struct GithubJobStepsBuilder {
@@ -900,6 +1012,104 @@ mod tests {
     use serde_json::Value;
 
     use super::*;
+    use crate::config::PgoConfig;
+
+    fn cross_builders_config() -> WorkspaceCargoBuildConfig {
+        WorkspaceCargoBuildConfig {
+            msvc_crt_static: true,
+            rust_toolchain_version: None,
+            precise_builds: None,
+            builders: SortedMap::from_iter([
+                (
+                    "aarch64-unknown-linux-gnu".to_owned(),
+                    "zigbuild.2.17".parse::<CargoBuilder>().unwrap(),
+                ),
+                ("x86_64-unknown-freebsd".to_owned(), CargoBuilder::Cross),
+                ("x86_64-pc-windows-gnu".to_owned(), CargoBuilder::Cross),
+                // Still needs a mac for the SDK
+                (
+                    "aarch64-apple-darwin".to_owned(),
+                    "zigbuild".parse::<CargoBuilder>().unwrap(),
+                ),
+                ("x86_64-pc-windows-msvc".to_owned(), CargoBuilder::Cargo),
+            ]),
+            auditable: false,
+            pgo: None,
+        }
+    }
+
+    fn cross_builders_tasks(merge_tasks: bool) -> String {
+        let targets = [
+            "aarch64-apple-darwin",
+            "aarch64-unknown-linux-gnu",
+            "x86_64-pc-windows-gnu",
+            "x86_64-pc-windows-msvc",
+            "x86_64-unknown-freebsd",
+            "x86_64-unknown-linux-gnu",
+        ]
+        .map(|target| target.to_owned());
+        let tasks = local_artifacts_tasks(
+            targets.iter().collect(),
+            &BTreeMap::new(),
+            merge_tasks,
+            &cross_builders_config(),
+            &SystemDependencies::default(),
+            "install-dist.sh",
+            "install-dist.ps1",
+        );
+        serde_yml::to_string(&tasks).unwrap()
+    }
+
+    #[test]
+    fn cross_builders_runners() {
+        insta::assert_snapshot!(cross_builders_tasks(false));
+    }
+
+    #[test]
+    fn cross_builders_runners_merged() {
+        insta::assert_snapshot!(cross_builders_tasks(true));
+    }
+
+    #[test]
+    fn cargo_builders_are_installed() {
+        let gnu = "aarch64-unknown-linux-gnu".to_owned();
+        let musl = "aarch64-unknown-linux-musl".to_owned();
+        let mac = "aarch64-apple-darwin".to_owned();
+        let mut cargo = WorkspaceCargoBuildConfig {
+            msvc_crt_static: true,
+            rust_toolchain_version: None,
+            precise_builds: None,
+            builders: SortedMap::from_iter([
+                (
+                    gnu.clone(),
+                    "zigbuild.2.17".parse::<CargoBuilder>().unwrap(),
+                ),
+                (musl.clone(), CargoBuilder::Cross),
+                (mac.clone(), CargoBuilder::Cargo),
+            ]),
+            auditable: false,
+            pgo: None,
+        };
+
+        assert_eq!(cargo_builder_install_for_targets(&[&mac], &cargo), None);
+        assert_eq!(
+            cargo_builder_install_for_targets(&[&gnu], &cargo).unwrap(),
+            "pip3 install ziglang\ncargo install --locked cargo-zigbuild"
+        );
+        assert_eq!(
+            cargo_builder_install_for_targets(&[&musl, &mac], &cargo).unwrap(),
+            "cargo install --locked cross"
+        );
+
+        cargo.auditable = true;
+        cargo.pgo = Some(PgoConfig {
+            training_command: vec!["./train.sh".to_owned()],
+        });
+        assert_eq!(
+            cargo_builder_install_for_targets(&[&gnu], &cargo).unwrap(),
+            "pip3 install ziglang\ncargo install --locked cargo-zigbuild\ncargo install --locked cargo-auditable\nrustup component add llvm-tools-preview"
+        );
+    }
 
     #[test]
     fn validator_works() {
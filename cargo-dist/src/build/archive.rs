@@ -0,0 +1,330 @@
+//! Reproducible archives
+//!
+//! Archiving a directory naively records whatever the filesystem tells us: real mtimes,
+//! the owner of the files, and the order readdir happened to return entries in. None of
+//! that is stable between two builds of the same tag, so neither are the checksums.
+//!
+//! Instead we write archives ourselves, with:
+//!
+//! * entries sorted by name
+//! * uid/gid 0 and no owner names
+//! * permissions normalized to 0755 (dirs and executables) or 0644 (everything else)
+//! * mtimes clamped to [`source_date_epoch`][]
+
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    time::UNIX_EPOCH,
+};
+
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use flate2::{Compression, GzBuilder};
+use tracing::{info, warn};
+use xz2::write::XzEncoder;
+use zip::{write::FileOptions, CompressionMethod, ZipWriter};
+
+use crate::{
+    build::cache::CacheKey,
+    config::{CompressionImpl, ZipStyle},
+    DistError, DistResult, Tools,
+};
+
+/// The timestamp all archive mtimes get clamped to, in seconds since the unix epoch
+///
+/// This is `SOURCE_DATE_EPOCH` if it's set (see <https://reproducible-builds.org/specs/source-date-epoch/>),
+/// and otherwise the commit time of the tag we're releasing (or HEAD, if the tag doesn't
+/// exist yet, as is the case for local builds and PRs).
+///
+/// If neither is available we return None and archives will use real mtimes.
+pub fn source_date_epoch(
+    tools: &Tools,
+    working_dir: &Utf8Path,
+    tag: &str,
+) -> DistResult<Option<u64>> {
+    if let Ok(value) = std::env::var("SOURCE_DATE_EPOCH") {
+        let epoch = value
+            .trim()
+            .parse()
+            .map_err(|_| DistError::InvalidSourceDateEpoch { value })?;
+        info!("using SOURCE_DATE_EPOCH={epoch} for archive timestamps");
+        return Ok(Some(epoch));
+    }
+
+    let Some(git) = &tools.git else {
+        warn!("git isn't installed, so archive timestamps won't be reproducible (set SOURCE_DATE_EPOCH to fix this)");
+        return Ok(None);
+    };
+    for committish in [format!("{tag}^{{commit}}"), "HEAD".to_owned()] {
+        let output = Cmd::new(&git.cmd, "get the commit time for reproducible archives")
+            .arg("log")
+            .arg("-1")
+            .arg("--format=%ct")
+            .arg(&committish)
+            .arg("--")
+            .current_dir(working_dir)
+            .check(false)
+            .log(None)
+            .output();
+        let Ok(output) = output else {
+            continue;
+        };
+        if !output.status.success() {
+            continue;
+        }
+        if let Ok(epoch) = String::from_utf8_lossy(&output.stdout).trim().parse() {
+            info!("using the commit time of {committish} ({epoch}) for archive timestamps");
+            return Ok(Some(epoch));
+        }
+    }
+
+    warn!("couldn't find a commit time, so archive timestamps won't be reproducible (set SOURCE_DATE_EPOCH to fix this)");
+    Ok(None)
+}
+
+/// A file or directory we want to put in an archive
+#[derive(Debug)]
+struct ArchiveEntry {
+    /// Where the file is on disk
+    src_path: Utf8PathBuf,
+    /// The (unix-style) path it will have in the archive
+    name: String,
+    /// Whether this is a directory
+    is_dir: bool,
+    /// The normalized permissions
+    mode: u32,
+    /// The clamped mtime
+    mtime: u64,
+}
+
+/// Archive the contents of a directory, reproducibly
+///
+/// `with_root` is a directory that everything gets nested under in the archive.
+pub fn archive_dir(
+    src_path: &Utf8Path,
+    dest_path: &Utf8Path,
+    zip_style: &ZipStyle,
+    with_root: Option<&Utf8Path>,
+    source_date_epoch: Option<u64>,
+) -> DistResult<()> {
+    let entries = archive_entries(src_path, with_root, source_date_epoch)?;
+    let file = File::create(dest_path)?;
+    match zip_style {
+        ZipStyle::Zip => write_zip(file, &entries)?,
+        ZipStyle::Tar(CompressionImpl::Gzip) => {
+            // The gzip header has an mtime and a filename too; 0 means "no mtime"
+            let zip_contents_name = format!("{}.tar", dest_path.file_name().unwrap_or_default());
+            let out = GzBuilder::new()
+                .filename(zip_contents_name)
+                .mtime(0)
+                .write(file, Compression::default());
+            write_tar(out, &entries)?.finish()?;
+        }
+        ZipStyle::Tar(CompressionImpl::Xzip) => {
+            let out = XzEncoder::new(file, 9);
+            write_tar(out, &entries)?.finish()?;
+        }
+        ZipStyle::Tar(CompressionImpl::Zstd) => {
+            let out = zstd::stream::Encoder::new(file, 0)?;
+            write_tar(out, &entries)?.finish()?;
+        }
+        ZipStyle::TempDir => {
+            // no-op
+        }
+    }
+    Ok(())
+}
+
+/// Compute the [`BuildCache`][crate::build::cache::BuildCache] key for an archive
+///
+/// This covers everything [`archive_dir`][] looks at: the name, mode, (clamped) mtime
+/// and contents of every entry, along with the format and the archive's own file name
+/// (which gzip records).
+pub fn archive_cache_key(
+    src_path: &Utf8Path,
+    dest_path: &Utf8Path,
+    zip_style: &ZipStyle,
+    with_root: Option<&Utf8Path>,
+    source_date_epoch: Option<u64>,
+) -> DistResult<String> {
+    let mut key = CacheKey::new("archive");
+    key.field(dest_path.file_name().unwrap_or_default())
+        .field(format!("{zip_style:?}"));
+    for entry in archive_entries(src_path, with_root, source_date_epoch)? {
+        key.field(&entry.name).field(entry.mode).field(entry.mtime);
+        if !entry.is_dir {
+            key.file(&entry.src_path)?;
+        }
+    }
+    Ok(key.finish())
+}
+
+/// Find everything that should go in the archive, in a stable order
+fn archive_entries(
+    src_path: &Utf8Path,
+    with_root: Option<&Utf8Path>,
+    source_date_epoch: Option<u64>,
+) -> DistResult<Vec<ArchiveEntry>> {
+    let mut entries = vec![];
+
+    // If there's a root prefix, add entries for all of its components
+    let mut prefix = String::new();
+    if let Some(root) = with_root {
+        for component in root.components() {
+            prefix.push_str(component.as_str());
+            prefix.push('/');
+            entries.push(ArchiveEntry {
+                src_path: src_path.to_owned(),
+                name: prefix.clone(),
+                is_dir: true,
+                mode: 0o755,
+                mtime: clamp_mtime(src_path, source_date_epoch)?,
+            });
+        }
+    }
+
+    walk_dir(src_path, &prefix, source_date_epoch, &mut entries)?;
+    Ok(entries)
+}
+
+fn walk_dir(
+    dir: &Utf8Path,
+    prefix: &str,
+    source_date_epoch: Option<u64>,
+    entries: &mut Vec<ArchiveEntry>,
+) -> DistResult<()> {
+    let mut children = dir
+        .read_dir_utf8()?
+        .map(|entry| entry.map(|e| e.path().to_owned()))
+        .collect::<Result<Vec<_>, _>>()?;
+    // Don't trust the filesystem to give us a consistent order
+    children.sort();
+
+    for child in children {
+        let file_name = child.file_name().unwrap_or_default().to_owned();
+        let mtime = clamp_mtime(&child, source_date_epoch)?;
+        if child.is_dir() {
+            let name = format!("{prefix}{file_name}/");
+            entries.push(ArchiveEntry {
+                src_path: child.clone(),
+                name: name.clone(),
+                is_dir: true,
+                mode: 0o755,
+                mtime,
+            });
+            walk_dir(&child, &name, source_date_epoch, entries)?;
+        } else {
+            entries.push(ArchiveEntry {
+                mode: if is_executable(&child)? { 0o755 } else { 0o644 },
+                src_path: child,
+                name: format!("{prefix}{file_name}"),
+                is_dir: false,
+                mtime,
+            });
+        }
+    }
+    Ok(())
+}
+
+fn clamp_mtime(path: &Utf8Path, source_date_epoch: Option<u64>) -> DistResult<u64> {
+    let mtime = path
+        .metadata()?
+        .modified()?
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    Ok(source_date_epoch.map_or(mtime, |epoch| mtime.min(epoch)))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Utf8Path) -> DistResult<bool> {
+    use std::os::unix::fs::PermissionsExt;
+    Ok(path.metadata()?.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Utf8Path) -> DistResult<bool> {
+    // Windows doesn't have an executable bit, so the best we can do is guess
+    Ok(path.extension() == Some("exe"))
+}
+
+fn write_tar<W: Write>(out: W, entries: &[ArchiveEntry]) -> DistResult<W> {
+    let mut tar = tar::Builder::new(out);
+    for entry in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_mode(entry.mode);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_username("")?;
+        header.set_groupname("")?;
+        header.set_mtime(entry.mtime);
+        if entry.is_dir {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            tar.append_data(&mut header, &entry.name, io::empty())?;
+        } else {
+            let file = File::open(&entry.src_path)?;
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_size(file.metadata()?.len());
+            tar.append_data(&mut header, &entry.name, file)?;
+        }
+    }
+    Ok(tar.into_inner()?)
+}
+
+fn write_zip(out: File, entries: &[ArchiveEntry]) -> DistResult<()> {
+    let mut zip = ZipWriter::new(out);
+    let mut buffer = vec![];
+    for entry in entries {
+        let options = FileOptions::default()
+            .compression_method(CompressionMethod::STORE)
+            .last_modified_time(zip_datetime(entry.mtime))
+            .unix_permissions(entry.mode);
+        if entry.is_dir {
+            zip.add_directory(&entry.name, options)
+                .map_err(io::Error::from)?;
+        } else {
+            zip.start_file(&entry.name, options)
+                .map_err(io::Error::from)?;
+            File::open(&entry.src_path)?.read_to_end(&mut buffer)?;
+            zip.write_all(&buffer)?;
+            buffer.clear();
+        }
+    }
+    zip.finish().map_err(io::Error::from)?;
+    Ok(())
+}
+
+/// Convert a unix timestamp to a zip (MS-DOS) timestamp
+///
+/// These can't represent anything before 1980, so those get rounded up.
+fn zip_datetime(secs: u64) -> zip::DateTime {
+    let days = (secs / 86400) as i64;
+    let time = secs % 86400;
+    let (year, month, day) = civil_from_days(days);
+    zip::DateTime::from_date_and_time(
+        u16::try_from(year).unwrap_or(u16::MAX),
+        month,
+        day,
+        (time / 3600) as u8,
+        (time % 3600 / 60) as u8,
+        (time % 60) as u8,
+    )
+    .unwrap_or_default()
+}
+
+/// Days since 1970-01-01 to a (year, month, day) in the proleptic gregorian calendar
+///
+/// See <http://howardhinnant.github.io/date_algorithms.html#civil_from_days>
+pub(crate) fn civil_from_days(days: i64) -> (i64, u8, u8) {
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u8;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u8;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
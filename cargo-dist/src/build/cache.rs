@@ -0,0 +1,112 @@
+//! A content-addressed cache for build outputs
+//!
+//! Archiving is slow, but (since archives are reproducible) a pure function of its
+//! inputs. So we compute a key by hashing everything that can affect an archive,
+//! and keep a copy of the archive under that key. If a later build computes the
+//! same key, the archive can be copied back out instead of being rebuilt.
+//!
+//! Only archives are cached. A checksum's key would have to hash the same file the
+//! checksum does, so there's nothing to save, and installers are either quick to
+//! render from templates or (msi, pkg) built by external tools whose inputs we
+//! can't fully see.
+//!
+//! Keys are sha256 digests, so the cache is just a flat directory of files named
+//! by their key. Nothing ever gets evicted, but it lives in the target dir, so
+//! `cargo clean` (or deleting the directory) clears it.
+
+use std::fmt::Write;
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use sha2::Digest;
+use tracing::info;
+
+use crate::DistResult;
+
+/// The name of the cache directory in the target dir
+pub const TARGET_DIST_CACHE: &str = "dist-cache";
+
+/// A content-addressed cache of build outputs
+#[derive(Debug, Clone)]
+pub struct BuildCache {
+    /// The directory entries are stored in
+    dir: Utf8PathBuf,
+}
+
+/// Incrementally computes a key for a [`BuildCache`][]
+pub struct CacheKey {
+    hasher: sha2::Sha256,
+}
+
+impl BuildCache {
+    /// Use the given directory as a cache (it will be created on demand)
+    pub fn new(dir: Utf8PathBuf) -> Self {
+        Self { dir }
+    }
+
+    /// If there's an entry for this key, copy it to `dest_path`
+    ///
+    /// Returns whether the entry existed.
+    pub fn restore(&self, key: &str, dest_path: &Utf8Path) -> DistResult<bool> {
+        let entry = self.dir.join(key);
+        if !entry.is_file() {
+            return Ok(false);
+        }
+        info!("reusing cached {dest_path} ({key})");
+        LocalAsset::copy_file_to_file(&entry, dest_path)?;
+        Ok(true)
+    }
+
+    /// Save a copy of `src_path` as the entry for this key
+    pub fn store(&self, key: &str, src_path: &Utf8Path) -> DistResult<()> {
+        LocalAsset::create_dir_all(&self.dir)?;
+        // Write to a temporary name and then rename, so an interrupted build
+        // can't leave a truncated entry behind
+        let entry = self.dir.join(key);
+        let temp = self.dir.join(format!("{key}.tmp"));
+        LocalAsset::copy_file_to_file(src_path, &temp)?;
+        std::fs::rename(&temp, entry)?;
+        Ok(())
+    }
+}
+
+impl CacheKey {
+    /// Start a key for the given kind of output
+    ///
+    /// Our version is always included, since it can change how things get built.
+    pub fn new(kind: &str) -> Self {
+        let mut key = Self {
+            hasher: sha2::Sha256::new(),
+        };
+        key.field(env!("CARGO_PKG_VERSION")).field(kind);
+        key
+    }
+
+    /// Add a value that affects the output
+    pub fn field(&mut self, value: impl std::fmt::Display) -> &mut Self {
+        let value = value.to_string();
+        // Length-prefix values so that ("ab", "c") and ("a", "bc") differ
+        self.hasher.update((value.len() as u64).to_le_bytes());
+        self.hasher.update(value.as_bytes());
+        self
+    }
+
+    /// Add the contents of a file that affects the output
+    pub fn file(&mut self, path: &Utf8Path) -> DistResult<&mut Self> {
+        // Inputs can be huge (symbols!), so stream them instead of loading them
+        let mut file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        self.hasher.update(len.to_le_bytes());
+        std::io::copy(&mut file, &mut self.hasher)?;
+        Ok(self)
+    }
+
+    /// Get the final key
+    pub fn finish(self) -> String {
+        let mut output = String::new();
+        for byte in self.hasher.finalize() {
+            write!(&mut output, "{:02x}", byte).unwrap();
+        }
+        output
+    }
+}
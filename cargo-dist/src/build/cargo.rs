@@ -4,18 +4,21 @@ use std::env;
 
 use axoprocess::Cmd;
 use axoproject::WorkspaceIdx;
-use cargo_dist_schema::DistManifest;
+use cargo_dist_schema::{BuildEnvironment, DistManifest, GlibcVersion, SystemInfo};
 use miette::{Context, IntoDiagnostic};
 use tracing::warn;
 
+use crate::build::pgo::{generate_pgo_profile, profile_use_rustflags};
 use crate::build::BuildExpectations;
+use crate::config::CargoBuilder;
 use crate::env::{calculate_ldflags, fetch_brew_env, parse_env, select_brew_env};
 use crate::{
     errors::*, BinaryIdx, BuildStep, DistGraphBuilder, TargetTriple, AXOUPDATER_MINIMUM_VERSION,
     PROFILE_DIST,
 };
 use crate::{
-    CargoBuildStep, CargoTargetFeatureList, CargoTargetPackages, DistGraph, RustupStep, SortedMap,
+    CargoBuildStep, CargoPgoStep, CargoTargetFeatureList, CargoTargetPackages, DistGraph,
+    RustupStep, SortedMap,
 };
 
 impl<'a> DistGraphBuilder<'a> {
@@ -57,6 +60,10 @@ impl<'a> DistGraphBuilder<'a> {
             if self.workspaces.workspace_for_package(binary.pkg_idx) != workspace_idx {
                 continue;
             }
+            // Universal binaries are merged from other builds, not built directly
+            if !binary.lipo_inputs.is_empty() {
+                continue;
+            }
             if !binary.copy_exe_to.is_empty() || !binary.copy_symbols_to.is_empty() {
                 targets
                     .entry(binary.target.clone())
@@ -99,9 +106,34 @@ impl<'a> DistGraphBuilder<'a> {
                 rustflags.push_str(" -Ctarget-feature=+crt-static -Clink-self-contained=yes");
             }
 
+            let builder = self
+                .inner
+                .config
+                .builds
+                .cargo
+                .builders
+                .get(&target)
+                .cloned()
+                .unwrap_or_default();
+
+            // cargo-auditable wraps cargo, so it can't see inside cross's docker images
+            let auditable = self.inner.config.builds.cargo.auditable;
+            if auditable && builder == CargoBuilder::Cross {
+                return Err(DistError::AuditableWithCross { target });
+            }
+
+            let pgo = self.inner.config.builds.cargo.pgo.as_ref();
+            if pgo.is_some_and(|pgo| pgo.training_command.is_empty()) {
+                return Err(DistError::PgoEmptyTrainingCommand {});
+            }
+            let pgo = pgo.map(|pgo| CargoPgoStep {
+                training_command: pgo.training_command.clone(),
+                profile_dir: self.inner.target_dir.join("pgo-profiles").join(&target),
+            });
+
             // If we're trying to cross-compile, ensure the rustup toolchain
-            // is setup!
-            if target != self.inner.tools.cargo.host_target {
+            // is setup! (unless the builder brings its own)
+            if target != self.inner.tools.cargo.host_target && builder.needs_rustup_target() {
                 if let Some(rustup) = self.inner.tools.rustup.clone() {
                     builds.push(BuildStep::Rustup(RustupStep {
                         rustup,
@@ -113,42 +145,74 @@ impl<'a> DistGraphBuilder<'a> {
             }
 
             if self.inner.precise_cargo_builds {
-                // `(target, package, features)` uniquely identifies a build we need to do,
+                // `(target, package, settings)` uniquely identifies a build we need to do,
                 // so group all the binaries under those buckets and add a build for each one
                 // (targets is handled by the loop we're in)
                 let mut builds_by_pkg_spec = SortedMap::new();
                 for bin_idx in binaries {
                     let bin = self.binary(bin_idx);
                     builds_by_pkg_spec
-                        .entry((bin.pkg_spec.clone(), bin.features.clone()))
+                        .entry((
+                            bin.pkg_spec.clone(),
+                            bin.features.clone(),
+                            bin.rustflags.clone(),
+                            bin.env.clone(),
+                            bin.profile.clone(),
+                        ))
                         .or_insert(vec![])
                         .push(bin_idx);
                 }
-                for ((pkg_spec, features), expected_binaries) in builds_by_pkg_spec {
+                for ((pkg_spec, features, extra_rustflags, env, profile), expected_binaries) in
+                    builds_by_pkg_spec
+                {
                     builds.push(BuildStep::Cargo(CargoBuildStep {
                         target_triple: target.clone(),
                         package: CargoTargetPackages::Package(pkg_spec),
                         features,
-                        rustflags: rustflags.clone(),
-                        profile: String::from(PROFILE_DIST),
+                        rustflags: format!("{rustflags}{extra_rustflags}"),
+                        env,
+                        profile,
                         expected_binaries,
                         working_dir: working_dir.clone(),
+                        builder: builder.clone(),
+                        auditable,
+                        pgo: pgo.clone(),
                     }));
                 }
             } else {
-                // If we think a workspace build is possible, every binary agrees on the features, so take an arbitrary one
-                let features = binaries
+                // If we think a workspace build is possible, every binary agrees on the features
+                // (and target overrides), so take an arbitrary one
+                let (features, extra_rustflags, env, profile) = binaries
                     .first()
-                    .map(|&idx| self.binary(idx).features.clone())
-                    .unwrap_or_default();
+                    .map(|&idx| {
+                        let bin = self.binary(idx);
+                        (
+                            bin.features.clone(),
+                            bin.rustflags.clone(),
+                            bin.env.clone(),
+                            bin.profile.clone(),
+                        )
+                    })
+                    .unwrap_or_else(|| {
+                        (
+                            Default::default(),
+                            String::new(),
+                            SortedMap::new(),
+                            String::from(PROFILE_DIST),
+                        )
+                    });
                 builds.push(BuildStep::Cargo(CargoBuildStep {
                     target_triple: target.clone(),
                     package: CargoTargetPackages::Workspace,
                     features,
-                    rustflags,
-                    profile: String::from(PROFILE_DIST),
+                    rustflags: format!("{rustflags}{extra_rustflags}"),
+                    env,
+                    profile,
                     expected_binaries: binaries,
                     working_dir: working_dir.clone(),
+                    builder,
+                    auditable,
+                    pgo,
                 }));
             }
         }
@@ -162,12 +226,62 @@ pub fn build_cargo_target(
     manifest: &mut DistManifest,
     target: &CargoBuildStep,
 ) -> DistResult<()> {
+    let mut rustflags = target.rustflags.clone();
+    if let Some(pgo) = &target.pgo {
+        // Training has to run the binaries, so we can only do this for the host
+        if target.target_triple == dist_graph.tools.cargo.host_target {
+            let profile = generate_pgo_profile(dist_graph, manifest, target, pgo)?;
+            rustflags = profile_use_rustflags(&rustflags, &profile);
+        } else {
+            warn!(
+                "pgo is enabled, but {} binaries can't run on this machine, building them without it",
+                target.target_triple
+            );
+        }
+    }
+
+    // Process all the resulting binaries
+    let mut expected = run_cargo_build(dist_graph, target, &rustflags, "cargo target")?;
+    expected.process_bins(dist_graph, manifest)?;
+
+    // If we linked against an older glibc than the system has, make sure that's
+    // what the binaries report, instead of the glibc of the system we built on
+    if let CargoBuilder::Zigbuild {
+        glibc_version: Some(version),
+    } = &target.builder
+    {
+        if target.target_triple.contains("linux-gnu") {
+            record_glibc_version(dist_graph, manifest, target, version);
+        }
+    }
+
+    Ok(())
+}
+
+/// Run `cargo build` for a target with the given RUSTFLAGS, and find the binaries it produced
+///
+/// `what` describes the build for the progress message.
+pub(crate) fn run_cargo_build(
+    dist_graph: &DistGraph,
+    target: &CargoBuildStep,
+    rustflags: &str,
+    what: &str,
+) -> DistResult<BuildExpectations> {
+    let builder_desc = match &target.builder {
+        CargoBuilder::Cargo => String::new(),
+        builder => format!(" with {builder}"),
+    };
+    let auditable_desc = if target.auditable {
+        " with cargo-auditable"
+    } else {
+        ""
+    };
     eprint!(
-        "building cargo target ({}/{}",
+        "building {what}{builder_desc}{auditable_desc} ({}/{}",
         target.target_triple, target.profile
     );
 
-    let mut rustflags = target.rustflags.clone();
+    let mut rustflags = rustflags.to_owned();
     let mut desired_extra_env = vec![];
     let skip_brewfile = env::var("DO_NOT_USE_BREWFILE").is_ok();
     if !skip_brewfile {
@@ -178,15 +292,62 @@ pub fn build_cargo_target(
         }
     }
 
-    let mut command = Cmd::new(&dist_graph.tools.cargo.cmd, "build your app with Cargo");
+    // cargo-zigbuild lets you pick a glibc version by suffixing the target with it
+    let mut target_arg = target.target_triple.clone();
+    let mut command = match &target.builder {
+        CargoBuilder::Cargo => {
+            let mut command = Cmd::new(&dist_graph.tools.cargo.cmd, "build your app with Cargo");
+            if target.auditable {
+                command.arg("auditable");
+            }
+            command.arg("build");
+            command
+        }
+        CargoBuilder::Zigbuild { glibc_version } => {
+            if dist_graph.tools.cargo_zigbuild.is_none() {
+                return Err(DistError::ToolMissing {
+                    tool: "cargo-zigbuild".to_owned(),
+                });
+            }
+            if let Some(version) = glibc_version {
+                if target.target_triple.contains("linux-gnu") {
+                    target_arg = format!("{target_arg}.{}.{}", version.major, version.series);
+                } else {
+                    warn!(
+                        "a glibc version was given for building {}, but it doesn't use glibc, ignoring it",
+                        target.target_triple
+                    );
+                }
+            }
+            let mut command = Cmd::new(
+                &dist_graph.tools.cargo.cmd,
+                "build your app with cargo-zigbuild",
+            );
+            if target.auditable {
+                command.arg("auditable");
+            }
+            command.arg("zigbuild");
+            command
+        }
+        CargoBuilder::Cross => {
+            let Some(cross) = &dist_graph.tools.cross else {
+                return Err(DistError::ToolMissing {
+                    tool: "cross".to_owned(),
+                });
+            };
+            let mut command = Cmd::new(&cross.cmd, "build your app with cross");
+            command.arg("build");
+            command
+        }
+    };
     command
-        .arg("build")
         .arg("--profile")
         .arg(&target.profile)
         .arg("--message-format=json-render-diagnostics")
         .arg("--target")
-        .arg(&target.target_triple)
+        .arg(&target_arg)
         .env("RUSTFLAGS", &rustflags)
+        .envs(&target.env)
         .current_dir(&target.working_dir)
         .stdout(std::process::Stdio::piped());
     if !target.features.default_features {
@@ -247,10 +408,40 @@ pub fn build_cargo_target(
         }
     }
 
-    // Process all the resulting binaries
-    expected.process_bins(dist_graph, manifest)?;
+    Ok(expected)
+}
 
-    Ok(())
+/// Record that the binaries of a build were linked against a specific glibc version
+///
+/// Linkage info refers to the system the binaries were built on for their glibc version,
+/// so we add a copy of our system that claims that version.
+fn record_glibc_version(
+    dist_graph: &DistGraph,
+    manifest: &mut DistManifest,
+    target: &CargoBuildStep,
+    version: &GlibcVersion,
+) {
+    let Some(system) = manifest.systems.get(&dist_graph.system_id) else {
+        return;
+    };
+    let system_id = format!(
+        "{}:glibc-{}.{}",
+        dist_graph.system_id, version.major, version.series
+    );
+    let system = SystemInfo {
+        id: system_id.clone(),
+        cargo_version_line: system.cargo_version_line.clone(),
+        build_environment: BuildEnvironment::Linux {
+            glibc_version: Some(version.clone()),
+        },
+    };
+    manifest.systems.insert(system_id.clone(), system);
+    for &bin_idx in &target.expected_binaries {
+        let bin = dist_graph.binary(bin_idx);
+        if let Some(asset) = manifest.assets.get_mut(&bin.id) {
+            asset.system.clone_from(&system_id);
+        }
+    }
 }
 
 /// Run rustup to setup a cargo target
@@ -0,0 +1,265 @@
+//! Building CMake projects
+//!
+//! We configure with a toolchain file for the target, then build and install
+//! to a staging prefix. Whatever the project installs is where we find the
+//! binaries and libraries, so there's no `out-dir` to keep in sync.
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use axoproject::WorkspaceIdx;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::DistManifest;
+
+use crate::{
+    build::{
+        generic::{
+            check_build, native_build_env, platform_appropriate_cc, platform_appropriate_cxx,
+            run_logged, NativeBuildEnv,
+        },
+        package_id_string, target_os, BuildExpectations,
+    },
+    BuildStep, CmakeBuildStep, DistGraph, DistGraphBuilder, DistResult, SortedMap,
+};
+
+impl<'a> DistGraphBuilder<'a> {
+    /// Compute a CMake build for each package and target we need binaries for
+    pub(crate) fn compute_cmake_builds(&mut self, workspace_idx: WorkspaceIdx) -> Vec<BuildStep> {
+        let mut builds = vec![];
+        for ((target, pkg_idx), expected_binaries) in self.binaries_to_build(workspace_idx) {
+            let package = self.workspaces.package(pkg_idx);
+            let staging_dir = self
+                .inner
+                .dist_dir
+                .join(format!("cmake-{}-{target}", package.name));
+            let log_path = self
+                .inner
+                .dist_dir
+                .join(format!("build-{}-{target}.log", package.name));
+            builds.push(BuildStep::Cmake(CmakeBuildStep {
+                target_triple: target,
+                expected_binaries,
+                source_dir: package.package_root.clone(),
+                build_dir: staging_dir.join("build"),
+                install_dir: staging_dir.join("install"),
+                toolchain_file: staging_dir.join("toolchain.cmake"),
+                log_path,
+            }));
+        }
+        builds
+    }
+}
+
+/// Write the contents of a CMake toolchain file for building `target` on `host`
+pub(crate) fn toolchain_file(target: &str, host: &str, native_env: &NativeBuildEnv) -> String {
+    let mut lines = vec![format!("# Generated by cargo-dist for {target}")];
+    let target_arch = target.split('-').next().unwrap_or_default();
+    let host_arch = host.split('-').next().unwrap_or_default();
+    let target_os = cmake_system_name(target);
+
+    // macOS and Windows builds can change arch without cross-compiling,
+    // everything else needs to be told it's cross-compiling
+    if target.contains("darwin") {
+        let arch = if target_arch == "aarch64" {
+            "arm64"
+        } else {
+            target_arch
+        };
+        lines.push(cmake_set("CMAKE_OSX_ARCHITECTURES", arch));
+    } else if !target.contains("windows")
+        && (target_os != cmake_system_name(host) || target_arch != host_arch)
+    {
+        if let Some(os) = target_os {
+            lines.push(cmake_set("CMAKE_SYSTEM_NAME", os));
+        }
+        lines.push(cmake_set("CMAKE_SYSTEM_PROCESSOR", target_arch));
+    }
+
+    if target.contains("windows") {
+        // Visual Studio picks the arch with a "platform" (and ignores the compilers),
+        // other generators will find cl.exe for the right arch in the environment
+        let platform = match target_arch {
+            "aarch64" => "ARM64",
+            "i686" => "Win32",
+            _ => "x64",
+        };
+        lines.push("if(CMAKE_GENERATOR MATCHES \"Visual Studio\")".to_owned());
+        lines.push(format!(
+            "  {}",
+            cmake_set("CMAKE_GENERATOR_PLATFORM", platform)
+        ));
+        lines.push("endif()".to_owned());
+    } else {
+        let cc = std::env::var("CC").unwrap_or(platform_appropriate_cc(target).to_owned());
+        let cxx = std::env::var("CXX").unwrap_or(platform_appropriate_cxx(target).to_owned());
+        lines.push(cmake_set("CMAKE_C_COMPILER", &cc));
+        lines.push(cmake_set("CMAKE_CXX_COMPILER", &cxx));
+    }
+
+    if let Some(cflags) = &native_env.cflags {
+        lines.push(cmake_set("CMAKE_C_FLAGS_INIT", cflags));
+        lines.push(cmake_set("CMAKE_CXX_FLAGS_INIT", cflags));
+    }
+    if let Some(ldflags) = &native_env.ldflags {
+        lines.push(cmake_set("CMAKE_EXE_LINKER_FLAGS_INIT", ldflags));
+        lines.push(cmake_set("CMAKE_SHARED_LINKER_FLAGS_INIT", ldflags));
+    }
+
+    lines.push(String::new());
+    lines.join("\n")
+}
+
+/// Get CMake's name for the OS of a target triple
+fn cmake_system_name(target: &str) -> Option<&'static str> {
+    match target_os(target)? {
+        "android" => Some("Android"),
+        "linux" => Some("Linux"),
+        "macos" => Some("Darwin"),
+        "windows" => Some("Windows"),
+        "freebsd" => Some("FreeBSD"),
+        _ => None,
+    }
+}
+
+/// Write a `set(VAR "value")` command, escaping the value
+fn cmake_set(var: &str, value: &str) -> String {
+    let value = value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('$', "\\$");
+    format!("set({var} \"{value}\")")
+}
+
+/// Build and install a CMake project for a target
+pub fn build_cmake_target(
+    dist_graph: &DistGraph,
+    manifest: &mut DistManifest,
+    target: &CmakeBuildStep,
+) -> DistResult<()> {
+    eprintln!(
+        "building cmake target ({} in {})",
+        target.target_triple, target.source_dir
+    );
+
+    let native_env = native_build_env(dist_graph, &target.source_dir)?;
+    LocalAsset::write_new_all(
+        &toolchain_file(
+            &target.target_triple,
+            &dist_graph.tools.cargo.host_target,
+            &native_env,
+        ),
+        &target.toolchain_file,
+    )?;
+    // Don't let a previous build's install tree satisfy this one
+    if target.install_dir.exists() {
+        LocalAsset::remove_dir_all(&target.install_dir)?;
+    }
+
+    let phases = [
+        (
+            "configure",
+            vec![
+                "-S".to_owned(),
+                target.source_dir.to_string(),
+                "-B".to_owned(),
+                target.build_dir.to_string(),
+                format!("-DCMAKE_TOOLCHAIN_FILE={}", target.toolchain_file),
+                "-DCMAKE_BUILD_TYPE=Release".to_owned(),
+                format!("-DCMAKE_INSTALL_PREFIX={}", target.install_dir),
+            ],
+        ),
+        (
+            "build",
+            vec![
+                "--build".to_owned(),
+                target.build_dir.to_string(),
+                "--config".to_owned(),
+                "Release".to_owned(),
+                "--parallel".to_owned(),
+            ],
+        ),
+        (
+            "install",
+            vec![
+                "--install".to_owned(),
+                target.build_dir.to_string(),
+                "--config".to_owned(),
+                "Release".to_owned(),
+                "--prefix".to_owned(),
+                target.install_dir.to_string(),
+            ],
+        ),
+    ];
+    for (phase, args) in phases {
+        let log_path = phase_log_path(&target.log_path, phase);
+        let mut command = Cmd::new("cmake", format!("cmake {phase}"));
+        command.current_dir(&target.source_dir);
+        for arg in &args {
+            command.arg(arg);
+        }
+        command.envs(native_env.extra_env.clone());
+        command.env("CARGO_DIST_TARGET", &target.target_triple);
+
+        let status = run_logged(command, &log_path)?;
+        let mut build_command = vec!["cmake".to_owned()];
+        build_command.extend(args);
+        check_build(
+            dist_graph,
+            manifest,
+            &build_command,
+            Some(&target.target_triple),
+            &log_path,
+            status,
+        )?;
+    }
+
+    // Find everything we expect in the install tree, wherever the project put it
+    let installed = find_installed_files(&target.install_dir)?;
+    let mut expected = BuildExpectations::new(dist_graph, &target.expected_binaries);
+    for binary_idx in &target.expected_binaries {
+        let binary = dist_graph.binary(*binary_idx);
+        let found = installed.get(&binary.file_name).cloned();
+        expected.found_bins(
+            package_id_string(binary.pkg_id.as_ref()),
+            found.into_iter().collect(),
+        );
+    }
+    expected.process_bins(dist_graph, manifest)?;
+
+    Ok(())
+}
+
+/// Get the log for one cmake invocation, like `build-app-x86_64-unknown-linux-gnu-configure.log`
+fn phase_log_path(log_path: &Utf8Path, phase: &str) -> Utf8PathBuf {
+    let stem = log_path.file_stem().unwrap_or_default();
+    log_path.with_file_name(format!("{stem}-{phase}.log"))
+}
+
+/// Find every file in an install tree, by file name
+///
+/// If a name shows up more than once, the first one (in sorted order) wins,
+/// which prefers `bin/` over `lib/`.
+pub fn find_installed_files(install_dir: &Utf8Path) -> DistResult<SortedMap<String, Utf8PathBuf>> {
+    let mut files = SortedMap::new();
+    let mut dirs = vec![install_dir.to_owned()];
+    while let Some(dir) = dirs.pop() {
+        let mut entries = dir
+            .read_dir_utf8()?
+            .map(|entry| Ok(entry?.into_path()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        entries.sort();
+        // Pushing subdirs in reverse means we visit them in order
+        for path in entries.iter().rev() {
+            if path.is_dir() {
+                dirs.push(path.clone());
+            }
+        }
+        for path in entries {
+            if path.is_file() {
+                if let Some(name) = path.file_name() {
+                    files.entry(name.to_owned()).or_insert(path.clone());
+                }
+            }
+        }
+    }
+    Ok(files)
+}
@@ -7,7 +7,10 @@ use axoasset::LocalAsset;
 use camino::Utf8PathBuf;
 use cargo_dist_schema::DistManifest;
 
-use crate::{BinaryIdx, CargoBuildStep, DistGraph, DistResult, GenericBuildStep};
+use crate::{
+    BinaryIdx, CargoBuildStep, CmakeBuildStep, DistGraph, DistResult, GenericBuildStep,
+    GoBuildStep, LipoStep, SymbolKind,
+};
 
 use super::BuildExpectations;
 
@@ -33,6 +36,39 @@ pub fn build_fake_generic_target(
     build_fake_binaries(dist, manifest, &target.expected_binaries)
 }
 
+/// build a fake go target
+///
+/// This produces empty binaries but otherwise emulates the build process as much as possible.
+pub fn build_fake_go_target(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    target: &GoBuildStep,
+) -> DistResult<()> {
+    build_fake_binaries(dist, manifest, &target.expected_binaries)
+}
+
+/// build a fake cmake target
+///
+/// This produces empty binaries but otherwise emulates the build process as much as possible.
+pub fn build_fake_cmake_target(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    target: &CmakeBuildStep,
+) -> DistResult<()> {
+    build_fake_binaries(dist, manifest, &target.expected_binaries)
+}
+
+/// pretend to merge a universal binary
+///
+/// This produces an empty binary but otherwise emulates the build process as much as possible.
+pub fn build_fake_lipo_target(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    target: &LipoStep,
+) -> DistResult<()> {
+    build_fake_binaries(dist, manifest, &[target.expected_binary])
+}
+
 /// build fake binaries, and emulate the build process as much as possible
 fn build_fake_binaries(
     dist: &DistGraph,
@@ -50,7 +86,24 @@ fn build_fake_binaries(
         let real_fake_bin = tempdir.join(&binary.file_name);
         let package_id = super::package_id_string(binary.pkg_id.as_ref());
         LocalAsset::write_new_all("", &real_fake_bin)?;
-        expectations.found_bins(package_id, vec![real_fake_bin]);
+        let mut outputs = vec![real_fake_bin];
+        if let Some(symbol_kind) = super::expected_symbol_kind(dist, binary) {
+            let real_fake_symbols =
+                tempdir.join(format!("{}.{}", binary.file_name, symbol_kind.ext()));
+            if symbol_kind == SymbolKind::Dsym {
+                // dSYMs are bundles (directories), so make something that looks like one
+                LocalAsset::write_new_all(
+                    "",
+                    real_fake_symbols
+                        .join("Contents/Resources/DWARF")
+                        .join(&binary.file_name),
+                )?;
+            } else {
+                LocalAsset::write_new_all("", &real_fake_symbols)?;
+            }
+            outputs.push(real_fake_symbols);
+        }
+        expectations.found_bins(package_id, outputs);
     }
 
     expectations.process_bins(dist, manifest)?;
@@ -1,64 +1,46 @@
 //! Functionality required to invoke a generic build's `build-command`
 
-use std::{env, process::ExitStatus};
+use std::{
+    env,
+    fs::File,
+    io::{BufRead, BufReader, Read, Write},
+    process::{ExitStatus, Stdio},
+    sync::Mutex,
+};
 
 use axoprocess::Cmd;
 use axoproject::WorkspaceIdx;
 use camino::{Utf8Path, Utf8PathBuf};
-use cargo_dist_schema::DistManifest;
+use cargo_dist_schema::{BuildLog, DistManifest};
 
 use crate::{
     build::{package_id_string, BuildExpectations},
     copy_file,
     env::{calculate_cflags, calculate_ldflags, fetch_brew_env, parse_env, select_brew_env},
-    ArtifactKind, BinaryIdx, BuildStep, DistError, DistGraph, DistGraphBuilder, DistResult,
-    ExtraBuildStep, GenericBuildStep, SortedMap, TargetTriple,
+    ArtifactKind, BuildStep, DistError, DistGraph, DistGraphBuilder, DistResult, ExtraBuildStep,
+    GenericBuildStep, SortedMap, TargetTriple,
 };
 
 impl<'a> DistGraphBuilder<'a> {
     pub(crate) fn compute_generic_builds(&mut self, workspace_idx: WorkspaceIdx) -> Vec<BuildStep> {
-        // For now we can be really simplistic and just do a workspace build for every
-        // target-triple we have a binary-that-needs-a-real-build for.
-        let mut targets = SortedMap::<TargetTriple, Vec<BinaryIdx>>::new();
-        for (binary_idx, binary) in self.inner.binaries.iter().enumerate() {
-            // Only bother with binaries owned by this workspace
-            if self.workspaces.workspace_for_package(binary.pkg_idx) != workspace_idx {
-                continue;
-            }
-            if !binary.copy_exe_to.is_empty() || !binary.copy_symbols_to.is_empty() {
-                targets
-                    .entry(binary.target.clone())
-                    .or_default()
-                    .push(BinaryIdx(binary_idx));
-            }
-        }
-
         let mut builds = vec![];
-        for (target, binaries) in targets {
-            // `(target, pkg_idx)` uniquely identifies a build we need to do,
-            // so group all the binaries under those buckets and add a build for each one
-            // (targets is handled by the loop we're in)
-            let mut builds_by_pkg_idx = SortedMap::new();
-            for bin_idx in binaries {
-                let bin = self.binary(bin_idx);
-                builds_by_pkg_idx
-                    .entry(bin.pkg_idx)
-                    .or_insert(vec![])
-                    .push(bin_idx);
-            }
-            for (pkg_idx, expected_binaries) in builds_by_pkg_idx {
-                let package = self.workspaces.package(pkg_idx);
-                builds.push(BuildStep::Generic(GenericBuildStep {
-                    target_triple: target.clone(),
-                    expected_binaries,
-                    working_dir: package.package_root.clone(),
-                    out_dir: package.package_root.clone(),
-                    build_command: package
-                        .build_command
-                        .clone()
-                        .expect("A build command is mandatory for non-cargo builds"),
-                }));
-            }
+        for ((target, pkg_idx), expected_binaries) in self.binaries_to_build(workspace_idx) {
+            let package = self.workspaces.package(pkg_idx);
+            let log_path = self
+                .inner
+                .dist_dir
+                .join(format!("build-{}-{target}.log", package.name));
+            builds.push(BuildStep::Generic(GenericBuildStep {
+                target_triple: target,
+                expected_binaries,
+                working_dir: package.package_root.clone(),
+                out_dir: package.package_root.clone(),
+                build_command: package
+                    .build_command
+                    .clone()
+                    .expect("A build command is mandatory for non-cargo builds"),
+                log_path,
+            }));
         }
         builds
     }
@@ -84,18 +66,22 @@ impl<'a> DistGraphBuilder<'a> {
 
         by_command
             .into_iter()
-            .map(|((working_dir, build_command), expected_artifacts)| {
-                BuildStep::Extra(ExtraBuildStep {
-                    working_dir,
-                    build_command,
-                    artifact_relpaths: expected_artifacts,
-                })
-            })
+            .enumerate()
+            .map(
+                |(idx, ((working_dir, build_command), expected_artifacts))| {
+                    BuildStep::Extra(ExtraBuildStep {
+                        working_dir,
+                        build_command,
+                        artifact_relpaths: expected_artifacts,
+                        log_path: self.inner.dist_dir.join(format!("build-extra-{idx}.log")),
+                    })
+                },
+            )
             .collect()
     }
 }
 
-fn platform_appropriate_cc(target: &str) -> &str {
+pub(crate) fn platform_appropriate_cc(target: &str) -> &str {
     if target.contains("darwin") {
         "clang"
     } else if target.contains("linux") {
@@ -107,7 +93,7 @@ fn platform_appropriate_cc(target: &str) -> &str {
     }
 }
 
-fn platform_appropriate_cxx(target: &str) -> &str {
+pub(crate) fn platform_appropriate_cxx(target: &str) -> &str {
     if target.contains("darwin") {
         "clang++"
     } else if target.contains("linux") {
@@ -119,26 +105,49 @@ fn platform_appropriate_cxx(target: &str) -> &str {
     }
 }
 
-fn run_build(
+/// Extra environment for building C/C++ code, from a Brewfile's dependencies
+#[derive(Debug, Default)]
+pub(crate) struct NativeBuildEnv {
+    /// Homebrew's environment variables that are safe for us to use
+    pub extra_env: Vec<(String, String)>,
+    /// Flags for finding the dependencies' headers
+    pub cflags: Option<String>,
+    /// Flags for finding the dependencies' libraries
+    pub ldflags: Option<String>,
+}
+
+/// Compute the environment for building C/C++ code in working_dir
+pub(crate) fn native_build_env(
     dist_graph: &DistGraph,
-    build_command: &[String],
     working_dir: &Utf8Path,
-    target: Option<&TargetTriple>,
-) -> DistResult<ExitStatus> {
-    let mut command_string = build_command.to_owned();
-
-    let mut desired_extra_env = vec![];
-    let mut cflags = None;
-    let mut ldflags = None;
+) -> DistResult<NativeBuildEnv> {
+    let mut native_env = NativeBuildEnv::default();
     let skip_brewfile = env::var("DO_NOT_USE_BREWFILE").is_ok();
     if !skip_brewfile {
         if let Some(env_output) = fetch_brew_env(dist_graph, working_dir)? {
             let brew_env = parse_env(&env_output)?;
-            desired_extra_env = select_brew_env(&brew_env);
-            cflags = Some(calculate_cflags(&brew_env));
-            ldflags = Some(calculate_ldflags(&brew_env));
+            native_env.extra_env = select_brew_env(&brew_env);
+            native_env.cflags = Some(calculate_cflags(&brew_env));
+            native_env.ldflags = Some(calculate_ldflags(&brew_env));
         }
     }
+    Ok(native_env)
+}
+
+fn run_build(
+    dist_graph: &DistGraph,
+    build_command: &[String],
+    working_dir: &Utf8Path,
+    target: Option<&TargetTriple>,
+    log_path: &Utf8Path,
+) -> DistResult<ExitStatus> {
+    let mut command_string = build_command.to_owned();
+
+    let NativeBuildEnv {
+        extra_env: desired_extra_env,
+        cflags,
+        ldflags,
+    } = native_build_env(dist_graph, working_dir)?;
 
     let args = command_string.split_off(1);
     let command_name = command_string
@@ -146,7 +155,6 @@ fn run_build(
         .expect("The build command must contain at least one entry");
     let mut command = Cmd::new(command_name, format!("exec build: {command_name}"));
     command.current_dir(working_dir);
-    command.stdout_to_stderr();
     for arg in args {
         command.arg(arg);
     }
@@ -177,7 +185,67 @@ fn run_build(
         command.env("LDFLAGS", &ldflags);
     }
 
-    Ok(command.status()?)
+    run_logged(command, log_path)
+}
+
+/// Run a command, streaming its stdout and stderr to our stderr and to a log file
+///
+/// The exit status is returned unchecked, so the caller can report it.
+pub(crate) fn run_logged(mut command: Cmd, log_path: &Utf8Path) -> DistResult<ExitStatus> {
+    let log = Mutex::new(File::create(log_path)?);
+    command
+        .check(false)
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped());
+    let mut child = command.spawn()?;
+    let stdout = child.stdout.take().expect("child stdout should be piped");
+    let stderr = child.stderr.take().expect("child stderr should be piped");
+    std::thread::scope(|scope| {
+        scope.spawn(|| tee_to_log(stdout, &log));
+        tee_to_log(stderr, &log);
+    });
+    Ok(child.wait()?)
+}
+
+/// Copy lines from a child's output stream to our stderr and the log
+///
+/// Failing to write either is ignored, we'd rather the build finish.
+fn tee_to_log(stream: impl Read, log: &Mutex<File>) {
+    let mut reader = BufReader::new(stream);
+    let mut line = vec![];
+    while reader.read_until(b'\n', &mut line).unwrap_or(0) > 0 {
+        let _ = std::io::stderr().write_all(&line);
+        if let Ok(mut log) = log.lock() {
+            let _ = log.write_all(&line);
+        }
+        line.clear();
+    }
+}
+
+/// Record a build command's log in the manifest, and fail if the command did
+pub(crate) fn check_build(
+    dist_graph: &DistGraph,
+    manifest: &mut DistManifest,
+    build_command: &[String],
+    target: Option<&TargetTriple>,
+    log_path: &Utf8Path,
+    status: ExitStatus,
+) -> DistResult<()> {
+    manifest.build_logs.push(BuildLog {
+        command: build_command.to_owned(),
+        system: dist_graph.system_id.clone(),
+        target: target.cloned(),
+        path: log_path.file_name().unwrap_or_default().to_owned(),
+        exit_code: status.code(),
+    });
+    if !status.success() {
+        return Err(DistError::BuildCommandFailed {
+            command: build_command.join(" "),
+            status,
+            log_path: log_path.to_owned(),
+        });
+    }
+    Ok(())
 }
 
 /// Build a generic targets
@@ -197,11 +265,16 @@ pub fn build_generic_target(
         &target.build_command,
         &target.working_dir,
         Some(&target.target_triple),
+        &target.log_path,
+    )?;
+    check_build(
+        dist_graph,
+        manifest,
+        &target.build_command,
+        Some(&target.target_triple),
+        &target.log_path,
+        result,
     )?;
-
-    if !result.success() {
-        eprintln!("Build exited non-zero: {}", result);
-    }
 
     let mut expected = BuildExpectations::new(dist_graph, &target.expected_binaries);
 
@@ -221,17 +294,31 @@ pub fn build_generic_target(
 
 /// Similar to the above, but with slightly different signatures since
 /// it's not based around axoproject-identified binaries
-pub fn run_extra_artifacts_build(dist: &DistGraph, build: &ExtraBuildStep) -> DistResult<()> {
+pub fn run_extra_artifacts_build(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    build: &ExtraBuildStep,
+) -> DistResult<()> {
     eprintln!(
         "building extra artifacts target (via {})",
         build.build_command.join(" ")
     );
 
-    let result = run_build(dist, &build.build_command, &build.working_dir, None)?;
-
-    if !result.success() {
-        eprintln!("Build exited non-zero: {}", result);
-    }
+    let result = run_build(
+        dist,
+        &build.build_command,
+        &build.working_dir,
+        None,
+        &build.log_path,
+    )?;
+    check_build(
+        dist,
+        manifest,
+        &build.build_command,
+        None,
+        &build.log_path,
+        result,
+    )?;
 
     // Check that we got everything we expected, and copy into the distribution path
     for artifact_relpath in &build.artifact_relpaths {
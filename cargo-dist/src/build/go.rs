@@ -0,0 +1,154 @@
+//! Building Go modules
+//!
+//! Go can cross-compile to anything it supports without any extra toolchains, so
+//! all we need to do is translate our target triples to `GOOS`/`GOARCH`.
+
+use axoprocess::Cmd;
+use axoproject::WorkspaceIdx;
+use cargo_dist_schema::DistManifest;
+
+use crate::{
+    build::{
+        generic::{check_build, run_logged},
+        package_id_string, target_os, BuildExpectations,
+    },
+    BuildStep, DistError, DistGraph, DistGraphBuilder, DistResult, GoBuildStep, GoTarget,
+    SortedMap,
+};
+
+impl<'a> DistGraphBuilder<'a> {
+    /// Compute a `go build` for each package and target we need binaries for
+    pub(crate) fn compute_go_builds(
+        &mut self,
+        workspace_idx: WorkspaceIdx,
+    ) -> DistResult<Vec<BuildStep>> {
+        let mut builds = vec![];
+        for ((target, pkg_idx), expected_binaries) in self.binaries_to_build(workspace_idx) {
+            let go_target = go_target(&target).ok_or_else(|| DistError::GoUnsupportedTarget {
+                target: target.clone(),
+            })?;
+            let package = self.workspaces.package(pkg_idx);
+            let packages_by_binary =
+                axoproject::go::main_packages(&package.package_root, &package.true_name)
+                    .map_err(|inner| DistError::GoMainPackages {
+                        manifest_path: package.manifest_path.clone(),
+                        inner,
+                    })?
+                    .into_iter()
+                    .collect::<SortedMap<_, _>>();
+            let main_packages = expected_binaries
+                .iter()
+                .filter_map(|&idx| packages_by_binary.get(&self.binary(idx).name).cloned())
+                .collect();
+            let out_dir = self
+                .inner
+                .dist_dir
+                .join(format!("go-{}-{target}", package.name));
+            let log_path = self
+                .inner
+                .dist_dir
+                .join(format!("build-{}-{target}.log", package.name));
+            builds.push(BuildStep::Go(GoBuildStep {
+                target_triple: target,
+                go_target,
+                expected_binaries,
+                main_packages,
+                working_dir: package.package_root.clone(),
+                out_dir,
+                log_path,
+            }));
+        }
+        Ok(builds)
+    }
+}
+
+/// Get the GOOS/GOARCH for a target triple, if Go supports it
+pub fn go_target(target: &str) -> Option<GoTarget> {
+    let arch = target.split('-').next()?;
+    let goos = match target_os(target)? {
+        "macos" => "darwin",
+        os => os,
+    };
+
+    let (goarch, goarm) = match arch {
+        "x86_64" => ("amd64", None),
+        "aarch64" | "arm64" => ("arm64", None),
+        "i686" | "i586" | "i386" => ("386", None),
+        "armv7" | "thumbv7neon" => ("arm", Some("7")),
+        "arm" | "armv6" => ("arm", Some("6")),
+        "riscv64gc" | "riscv64" => ("riscv64", None),
+        "powerpc64le" => ("ppc64le", None),
+        "powerpc64" => ("ppc64", None),
+        "s390x" => ("s390x", None),
+        "loongarch64" => ("loong64", None),
+        _ => return None,
+    };
+
+    Some(GoTarget {
+        goos,
+        goarch,
+        goarm,
+    })
+}
+
+/// Build the binaries of a Go module
+pub fn build_go_target(
+    dist_graph: &DistGraph,
+    manifest: &mut DistManifest,
+    target: &GoBuildStep,
+) -> DistResult<()> {
+    let GoTarget {
+        goos,
+        goarch,
+        goarm,
+    } = &target.go_target;
+    eprintln!(
+        "building go target ({} via GOOS={goos} GOARCH={goarch})",
+        target.target_triple
+    );
+
+    // Passing a directory to -o makes go name each binary after its package
+    let mut build_command = vec![
+        "go".to_owned(),
+        "build".to_owned(),
+        "-trimpath".to_owned(),
+        "-o".to_owned(),
+        format!("{}/", target.out_dir),
+    ];
+    build_command.extend(target.main_packages.iter().cloned());
+
+    let mut command = Cmd::new("go", "go build");
+    command.current_dir(&target.working_dir);
+    for arg in &build_command[1..] {
+        command.arg(arg);
+    }
+    command.env("GOOS", goos).env("GOARCH", goarch);
+    if let Some(goarm) = goarm {
+        command.env("GOARM", goarm);
+    }
+    // musl binaries are supposed to be static, which means no cgo
+    if target.target_triple.contains("musl") {
+        command.env("CGO_ENABLED", "0");
+    }
+    command.env("CARGO_DIST_TARGET", &target.target_triple);
+
+    let status = run_logged(command, &target.log_path)?;
+    check_build(
+        dist_graph,
+        manifest,
+        &build_command,
+        Some(&target.target_triple),
+        &target.log_path,
+        status,
+    )?;
+
+    let mut expected = BuildExpectations::new(dist_graph, &target.expected_binaries);
+    for binary_idx in &target.expected_binaries {
+        let binary = dist_graph.binary(*binary_idx);
+        let src_path = target.out_dir.join(&binary.file_name);
+        expected.found_bins(package_id_string(binary.pkg_id.as_ref()), vec![src_path]);
+    }
+    expected.process_bins(dist_graph, manifest)?;
+
+    Ok(())
+}
@@ -0,0 +1,158 @@
+//! Merging single-arch macOS binaries into universal ("fat") ones
+//!
+//! This is what Apple's `lipo` tool does, but that only exists on macOS, and the
+//! fat Mach-O format is simple enough that we'd rather do it ourselves so that
+//! `universal2-apple-darwin` works anywhere.
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::DistManifest;
+use goblin::mach::{
+    constants::cputype::{
+        CpuSubType, CpuType, CPU_SUBTYPE_ARM64_ALL, CPU_SUBTYPE_X86_64_ALL, CPU_TYPE_ARM64,
+        CPU_TYPE_X86_64,
+    },
+    fat::FAT_MAGIC,
+    Mach,
+};
+
+use crate::{
+    build::{package_id_string, BuildExpectations},
+    copy_file, BinaryIdx, BuildStep, DistError, DistGraph, DistGraphBuilder, DistResult, LipoStep,
+    TargetTriple,
+};
+
+impl<'a> DistGraphBuilder<'a> {
+    /// Compute the steps for merging universal binaries
+    ///
+    /// This also registers that the single-arch binaries need to be built, so it needs
+    /// to run before the cargo/generic builds are computed.
+    pub(crate) fn compute_lipo_builds(&mut self) -> Vec<BuildStep> {
+        let dist_dir = self.inner.dist_dir.clone();
+        let mut builds = vec![];
+        for binary_idx in (0..self.inner.binaries.len()).map(BinaryIdx) {
+            let binary = self.binary(binary_idx);
+            if binary.lipo_inputs.is_empty() || binary.copy_exe_to.is_empty() {
+                continue;
+            }
+
+            let mut src_paths = vec![];
+            for input_idx in binary.lipo_inputs.clone() {
+                let input = self.binary_mut(input_idx);
+                let src_path = dist_dir.join(&input.id);
+                input.copy_exe_to.push(src_path.clone());
+                src_paths.push((input.target.clone(), src_path));
+            }
+            builds.push(BuildStep::Lipo(LipoStep {
+                expected_binary: binary_idx,
+                src_paths,
+            }));
+        }
+        builds
+    }
+}
+
+/// Merge single-arch binaries into a universal one (and copy it to its final homes)
+pub fn build_lipo_target(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    step: &LipoStep,
+) -> DistResult<()> {
+    let binary = dist.binary(step.expected_binary);
+    // This needs the right file name for BuildExpectations to recognize it
+    let dest_dir = dist.dist_dir.join(&binary.id);
+    let dest_path = dest_dir.join(&binary.file_name);
+    LocalAsset::create_dir_all(&dest_dir)?;
+
+    let mut inputs = vec![];
+    for (target, src_path) in &step.src_paths {
+        let bytes = LocalAsset::load_bytes(src_path)?;
+        inputs.push((src_path.clone(), target.clone(), bytes));
+    }
+    let fat = merge_macho(&inputs, &dest_path)?;
+
+    // Copying one of the inputs first is just an easy way to get the same permissions
+    // (i.e. the executable bit) on the output.
+    if let Some((_, src_path)) = step.src_paths.first() {
+        copy_file(src_path, &dest_path)?;
+    }
+    std::fs::write(&dest_path, fat)?;
+
+    let mut expectations = BuildExpectations::new(dist, &[step.expected_binary]);
+    expectations.found_bins(package_id_string(binary.pkg_id.as_ref()), vec![dest_path]);
+    expectations.process_bins(dist, manifest)?;
+
+    Ok(())
+}
+
+/// Merge the contents of single-arch Mach-O files into a fat Mach-O file
+///
+/// Each input is `(path, target, contents)`, where path is only used for errors.
+/// Static libraries (ar archives) are also accepted, in which case we trust
+/// the target to tell us the architecture.
+pub fn merge_macho(
+    inputs: &[(Utf8PathBuf, TargetTriple, Vec<u8>)],
+    dest_path: &Utf8Path,
+) -> DistResult<Vec<u8>> {
+    // The fat header, and the table of arches after it, are always big-endian
+    const SIZEOF_FAT_HEADER: usize = 8;
+    const SIZEOF_FAT_ARCH: usize = 20;
+
+    let mut arches = vec![];
+    for (path, target, bytes) in inputs {
+        let (cputype, cpusubtype) = match goblin::Object::parse(bytes)? {
+            goblin::Object::Mach(Mach::Binary(macho)) => {
+                (macho.header.cputype, macho.header.cpusubtype)
+            }
+            goblin::Object::Archive(_) => match cputype_for_target(target) {
+                Some(cputype) => cputype,
+                None => return Err(DistError::LipoInputNotMachO { path: path.clone() }),
+            },
+            _ => return Err(DistError::LipoInputNotMachO { path: path.clone() }),
+        };
+        if arches.iter().any(|&(other, _, _, _)| other == cputype) {
+            return Err(DistError::LipoDuplicateArch { path: path.clone() });
+        }
+        // Page-align each slice like lipo does (arm64 has 16KiB pages)
+        let align: u32 = if cputype == CPU_TYPE_ARM64 { 14 } else { 12 };
+        arches.push((cputype, cpusubtype, align, &bytes[..]));
+    }
+
+    let mut header = vec![];
+    let mut body = vec![];
+    let mut offset = SIZEOF_FAT_HEADER + SIZEOF_FAT_ARCH * arches.len();
+    header.extend(FAT_MAGIC.to_be_bytes());
+    header.extend((arches.len() as u32).to_be_bytes());
+    for (cputype, cpusubtype, align, bytes) in arches {
+        let padded = offset.next_multiple_of(1 << align);
+        body.resize(body.len() + padded - offset, 0);
+        offset = padded;
+
+        let too_large = || DistError::LipoTooLarge {
+            path: dest_path.to_owned(),
+        };
+        let slice_offset = u32::try_from(offset).map_err(|_| too_large())?;
+        let slice_size = u32::try_from(bytes.len()).map_err(|_| too_large())?;
+        header.extend(cputype.to_be_bytes());
+        header.extend(cpusubtype.to_be_bytes());
+        header.extend(slice_offset.to_be_bytes());
+        header.extend(slice_size.to_be_bytes());
+        header.extend(align.to_be_bytes());
+
+        body.extend(bytes);
+        offset += bytes.len();
+    }
+    header.extend(body);
+    Ok(header)
+}
+
+/// The Mach-O cputype for a target, for when we can't read it out of the file
+fn cputype_for_target(target: &str) -> Option<(CpuType, CpuSubType)> {
+    if target.starts_with("x86_64-apple") {
+        Some((CPU_TYPE_X86_64, CPU_SUBTYPE_X86_64_ALL))
+    } else if target.starts_with("aarch64-apple") {
+        Some((CPU_TYPE_ARM64, CPU_SUBTYPE_ARM64_ALL))
+    } else {
+        None
+    }
+}
@@ -0,0 +1,217 @@
+//! Building Python wheels with maturin, and publishing them to a local package index
+//!
+//! Wheel filenames include tags for the Python versions and platforms they support,
+//! which only maturin knows, so unlike our other artifacts we can't name them up front.
+//! Instead each wheel maturin produces is added to the manifest as its own artifact.
+//!
+//! The wheels are also published to a PEP 503 "simple" index in the dist dir, so
+//! they can be tested with `pip install --index-url file://.../simple`.
+
+use std::fmt::Write;
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use axoproject::PackageInfo;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::DistManifest;
+
+use crate::{
+    build::generic::{check_build, run_logged},
+    config::ChecksumStyle,
+    generate_checksum, DistError, DistGraph, DistResult, WheelImpl,
+};
+
+/// The directory in the dist dir we publish wheels to
+pub const PYTHON_INDEX_DIR: &str = "python-index";
+
+/// Whether this is a Python package whose wheels are built by maturin
+pub fn is_maturin_package(package: &PackageInfo) -> bool {
+    package.python_build_backend.as_deref() == Some(axoproject::python::BUILD_BACKEND_MATURIN)
+}
+
+/// Build wheels with maturin, and publish them
+pub fn build_wheels(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    step: &WheelImpl,
+) -> DistResult<()> {
+    eprintln!(
+        "building wheels for {} ({})",
+        step.app_name, step.target_triple
+    );
+    LocalAsset::create_dir_all(&step.out_dir)?;
+
+    let build_command = vec![
+        "maturin".to_owned(),
+        "build".to_owned(),
+        "--release".to_owned(),
+        "--target".to_owned(),
+        step.target_triple.clone(),
+        "--out".to_owned(),
+        step.out_dir.to_string(),
+    ];
+    let mut command = Cmd::new("maturin", "build wheels");
+    command.current_dir(&step.working_dir);
+    for arg in &build_command[1..] {
+        command.arg(arg);
+    }
+    let status = run_logged(command, &step.log_path)?;
+    check_build(
+        dist,
+        manifest,
+        &build_command,
+        Some(&step.target_triple),
+        &step.log_path,
+        status,
+    )?;
+
+    let mut wheels = vec![];
+    for entry in step.out_dir.read_dir_utf8()? {
+        let path = entry?.into_path();
+        if path.extension() == Some("whl") {
+            wheels.push(path);
+        }
+    }
+    if wheels.is_empty() {
+        return Err(DistError::NoWheels {
+            out_dir: step.out_dir.clone(),
+            log_path: step.log_path.clone(),
+        });
+    }
+    wheels.sort();
+    publish_wheels(dist, manifest, step, &wheels)
+}
+
+/// Pretend to build wheels, for --artifacts=lies
+pub fn build_fake_wheels(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    step: &WheelImpl,
+) -> DistResult<()> {
+    let distribution = step.app_name.replace(['-', '.'], "_");
+    let platform = step.target_triple.replace(['-', '.'], "_");
+    let wheel = step.out_dir.join(format!(
+        "{distribution}-{}-py3-none-{platform}.whl",
+        step.app_version
+    ));
+    LocalAsset::write_new_all("", &wheel)?;
+    publish_wheels(dist, manifest, step, &[wheel])
+}
+
+/// Copy wheels to the dist dir, record them in the manifest, and add them to the index
+fn publish_wheels(
+    dist: &DistGraph,
+    manifest: &mut DistManifest,
+    step: &WheelImpl,
+    wheels: &[Utf8PathBuf],
+) -> DistResult<()> {
+    for wheel in wheels {
+        let file_name = wheel
+            .file_name()
+            .expect("wheels have file names")
+            .to_owned();
+        let dest_path = dist.dist_dir.join(&file_name);
+        LocalAsset::copy_file_to_file(wheel, &dest_path)?;
+        let sha256 = generate_checksum(&ChecksumStyle::Sha256, &dest_path)?;
+
+        add_wheel_to_manifest(manifest, step, &dest_path, &sha256);
+        add_wheel_to_index(&step.index_dir, &dest_path)?;
+    }
+    Ok(())
+}
+
+fn add_wheel_to_manifest(
+    manifest: &mut DistManifest,
+    step: &WheelImpl,
+    path: &Utf8Path,
+    sha256: &str,
+) {
+    let name = path.file_name().expect("wheels have file names").to_owned();
+    let mut checksums = std::collections::BTreeMap::new();
+    checksums.insert(ChecksumStyle::Sha256.ext().to_owned(), sha256.to_owned());
+    let artifact = cargo_dist_schema::Artifact {
+        name: Some(name.clone()),
+        kind: cargo_dist_schema::ArtifactKind::Wheel,
+        target_triples: vec![step.target_triple.clone()],
+        path: Some(path.to_string()),
+        assets: vec![],
+        install_hint: None,
+        description: None,
+        checksum: None,
+        checksums,
+    };
+    manifest.artifacts.insert(name.clone(), artifact);
+    manifest.upload_files.push(path.to_string());
+
+    let release = manifest.ensure_release(step.app_name.clone(), step.app_version.clone());
+    if !release.artifacts.contains(&name) {
+        release.artifacts.push(name);
+    }
+}
+
+/// Add a wheel to a PEP 503 "simple" package index
+///
+/// The index pages are regenerated from the files on disk, so any number of
+/// builds can publish to the same index.
+pub fn add_wheel_to_index(index_dir: &Utf8Path, wheel: &Utf8Path) -> DistResult<()> {
+    let file_name = wheel.file_name().expect("wheels have file names");
+    // The distribution name is everything up to the first `-`
+    let distribution = file_name.split('-').next().unwrap_or(file_name);
+    let simple_dir = index_dir.join("simple");
+    let project_dir = simple_dir.join(normalize_project_name(distribution));
+    LocalAsset::create_dir_all(&project_dir)?;
+    LocalAsset::copy_file_to_file(wheel, project_dir.join(file_name))?;
+
+    let mut files = vec![];
+    for entry in project_dir.read_dir_utf8()? {
+        let path = entry?.into_path();
+        if path.extension() == Some("whl") {
+            let sha256 = generate_checksum(&ChecksumStyle::Sha256, &path)?;
+            let file_name = path.file_name().expect("wheels have file names").to_owned();
+            let href = format!("{file_name}#sha256={sha256}");
+            files.push((file_name, href));
+        }
+    }
+    write_index_page(&project_dir, files)?;
+
+    let mut projects = vec![];
+    for entry in simple_dir.read_dir_utf8()? {
+        let entry = entry?;
+        if entry.file_type()?.is_dir() {
+            let project = entry.file_name().to_owned();
+            let href = format!("{project}/");
+            projects.push((project, href));
+        }
+    }
+    write_index_page(&simple_dir, projects)
+}
+
+/// Write an index.html with a link for each `(text, href)`
+fn write_index_page(dir: &Utf8Path, mut links: Vec<(String, String)>) -> DistResult<()> {
+    links.sort();
+    let mut page = "<!DOCTYPE html>\n<html>\n  <body>\n".to_owned();
+    for (text, href) in links {
+        writeln!(page, "    <a href=\"{href}\">{text}</a><br/>")
+            .expect("writing to a String can't fail");
+    }
+    page.push_str("  </body>\n</html>\n");
+    LocalAsset::write_new_all(&page, dir.join("index.html"))?;
+    Ok(())
+}
+
+/// Normalize a project name the way PEP 503 says indexes should
+///
+/// That's lowercase, with runs of `-`, `_` and `.` replaced by a single `-`.
+pub fn normalize_project_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    for c in name.chars() {
+        if matches!(c, '-' | '_' | '.') {
+            if !out.ends_with('-') {
+                out.push('-');
+            }
+        } else {
+            out.push(c.to_ascii_lowercase());
+        }
+    }
+    out
+}
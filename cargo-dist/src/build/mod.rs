@@ -1,18 +1,28 @@
 //! Compiling Things
 
+use axoasset::LocalAsset;
 use axoproject::PackageId;
-use camino::Utf8PathBuf;
-use cargo_dist_schema::{AssetInfo, DistManifest};
-use tracing::info;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{AssetInfo, BuildId, DistManifest};
+use tracing::{info, warn};
 
 use crate::{
-    copy_file, linkage::determine_linkage, Binary, BinaryIdx, BinaryKind, DistError, DistGraph,
-    DistResult, SortedMap, TargetTriple,
+    copy_file, copy_file_or_dir, linkage::determine_linkage, ArtifactKind, Binary, BinaryIdx,
+    BinaryKind, DistError, DistGraph, DistResult, SortedMap, SymbolKind, TargetTriple,
 };
 
+pub mod archive;
+pub mod cache;
 pub mod cargo;
+pub mod cmake;
 pub mod fake;
 pub mod generic;
+pub mod go;
+pub mod lipo;
+pub mod maturin;
+pub mod pgo;
+pub mod process;
+pub mod symbols;
 
 /// Output expectations for builds, and computed facts (all packages)
 pub struct BuildExpectations {
@@ -43,6 +53,8 @@ pub struct ExpectedBinary {
     pub sym_paths: Vec<Utf8PathBuf>,
     /// What kind of binary this is (executable, dynamic library, etc.)
     pub kind: BinaryKind,
+    /// The post-build processors that have been run on this binary
+    pub processors: Vec<String>,
 }
 
 impl BuildExpectations {
@@ -63,6 +75,7 @@ impl BuildExpectations {
                     src_path: None,
                     sym_paths: vec![],
                     kind: binary.kind,
+                    processors: vec![],
                 },
             );
         }
@@ -96,8 +109,7 @@ impl BuildExpectations {
         // and probably exes/libs
         let (maybe_symbols, maybe_bins): (Vec<_>, Vec<_>) = filenames
             .into_iter()
-            // FIXME: unhardcode this when we add support for other symbol kinds!
-            .partition(|f| f.extension().map(|e| e == "pdb").unwrap_or(false));
+            .partition(|f| f.extension().and_then(SymbolKind::from_ext).is_some());
 
         // lookup the package
         let Some(pkg) = self.packages.get_mut(&pkg_id) else {
@@ -121,27 +133,19 @@ impl BuildExpectations {
             let Some(bin_result) = pkg.binaries.get_mut(&bin_name) else {
                 continue;
             };
-            // used to compare to the symbol stem further down
-            let Some(bin_stem) = src_path.file_stem().map(String::from) else {
-                continue;
-            };
-
-            // Cool, we expected this binary, register its location!
-            bin_result.src_path = Some(src_path);
 
             // Also register symbols
             for sym_path in &maybe_symbols {
-                let is_for_this_bin = sym_path
-                    .file_stem()
-                    .map(|stem| stem == bin_stem)
-                    .unwrap_or(false);
-                if !is_for_this_bin {
+                if !symbols_are_for_binary(&src_path, sym_path) {
                     continue;
                 }
 
                 // These are symbols we expected! Save the path.
                 bin_result.sym_paths.push(sym_path.to_owned());
             }
+
+            // Cool, we expected this binary, register its location!
+            bin_result.src_path = Some(src_path);
         }
     }
 
@@ -150,14 +154,40 @@ impl BuildExpectations {
     /// Currently this is:
     ///
     /// * checking src_path was set by found_bin
+    /// * running any configured post-build processors (see [`process`][])
     /// * computing linkage for the binary
+    /// * signing the binary
     /// * copying the binary and symbols to their final homes
-    ///
-    /// In the future this may also include:
-    ///
-    /// * code signing / hashing
-    /// * stripping
-    pub fn process_bins(&self, dist: &DistGraph, manifest: &mut DistManifest) -> DistResult<()> {
+    pub fn process_bins(
+        &mut self,
+        dist: &DistGraph,
+        manifest: &mut DistManifest,
+    ) -> DistResult<()> {
+        // Processors modify the binary, so they need to run before anything else
+        // looks at it (and there's nothing to process if we're faking it)
+        if !self.fake {
+            for pkg in self.packages.values_mut() {
+                for result_bin in pkg.binaries.values_mut() {
+                    let Some(src_path) = result_bin.src_path.clone() else {
+                        continue;
+                    };
+                    if !src_path.exists() {
+                        continue;
+                    }
+                    // cargo doesn't always uplift dwps, so go looking for them
+                    let symbol_kind = expected_symbol_kind(dist, dist.binary(result_bin.idx));
+                    let has_dwp = result_bin
+                        .sym_paths
+                        .iter()
+                        .any(|path| is_symbol_kind(path, SymbolKind::Dwp));
+                    if symbol_kind == Some(SymbolKind::Dwp) && !has_dwp {
+                        result_bin.sym_paths.extend(find_dwp_in_deps(&src_path));
+                    }
+                    process::run_processors(dist, result_bin)?;
+                }
+            }
+        }
+
         let mut missing = vec![];
         for (pkg_id, pkg) in &self.packages {
             for (bin_name, result_bin) in &pkg.binaries {
@@ -173,10 +203,11 @@ impl BuildExpectations {
                 let bin = dist.binary(result_bin.idx);
 
                 // compute linkage for the binary
-                self.compute_linkage_and_sign(dist, manifest, result_bin, &bin.target)?;
+                let build_id =
+                    self.compute_linkage_and_sign(dist, manifest, result_bin, &bin.target)?;
 
                 // copy files to their final homes
-                self.copy_assets(result_bin, bin)?;
+                self.copy_assets(dist, result_bin, bin, build_id.as_ref())?;
             }
         }
 
@@ -189,14 +220,14 @@ impl BuildExpectations {
         Ok(())
     }
 
-    // Compute the linkage info for this binary and sign it
+    // Compute the linkage info for this binary and sign it, returning its build id
     fn compute_linkage_and_sign(
         &self,
         dist: &DistGraph,
         manifest: &mut DistManifest,
         src: &ExpectedBinary,
         target: &TargetTriple,
-    ) -> DistResult<()> {
+    ) -> DistResult<Option<BuildId>> {
         let src_path = src
             .src_path
             .as_ref()
@@ -219,6 +250,11 @@ impl BuildExpectations {
         };
 
         let bin = dist.binary(src.idx);
+        let build_id = if self.fake {
+            symbols::fake_build_id(bin)
+        } else {
+            symbols::read_build_id(src_path, bin.kind)
+        };
         manifest.assets.insert(
             bin.id.clone(),
             AssetInfo {
@@ -227,13 +263,21 @@ impl BuildExpectations {
                 system: dist.system_id.clone(),
                 linkage: Some(linkage),
                 target_triples: vec![target.clone()],
+                processors: src.processors.clone(),
+                build_id: build_id.clone(),
             },
         );
-        Ok(())
+        Ok(build_id)
     }
 
     // Copy the assets for this binary
-    fn copy_assets(&self, src: &ExpectedBinary, dests: &Binary) -> DistResult<()> {
+    fn copy_assets(
+        &self,
+        dist: &DistGraph,
+        src: &ExpectedBinary,
+        dests: &Binary,
+        build_id: Option<&BuildId>,
+    ) -> DistResult<()> {
         // Copy the main binary
         let src_path = src
             .src_path
@@ -243,11 +287,50 @@ impl BuildExpectations {
             copy_file(src_path, dest_path)?;
         }
 
-        // Copy the symbols
-        for sym_path in &src.sym_paths {
-            for dest_path in &dests.copy_symbols_to {
-                copy_file(sym_path, dest_path)?;
+        // Copy the symbols (only the kind we planned an artifact for)
+        let Some(symbol_kind) = expected_symbol_kind(dist, dests) else {
+            return Ok(());
+        };
+        let Some(sym_path) = src
+            .sym_paths
+            .iter()
+            .find(|path| is_symbol_kind(path, symbol_kind))
+        else {
+            return Err(DistError::MissingSymbols {
+                bin_name: dests.file_name.clone(),
+                kind: symbol_kind.ext().to_owned(),
+            });
+        };
+        for dest_path in &dests.copy_symbols_to {
+            // dSYMs are directories
+            copy_file_or_dir(sym_path, dest_path)?;
+        }
+
+        // Add the symbols to any symbol server layouts, if we know where they go
+        if dests.copy_symbols_to_server.is_empty() {
+            return Ok(());
+        }
+        let Some(server_path) = build_id.and_then(symbols::symbol_server_path) else {
+            warn!(
+                "{} has no build id, so its symbols can't be added to a symbol server",
+                dests.file_name
+            );
+            return Ok(());
+        };
+        // Symbol servers want the DWARF itself, not the bundle around it
+        let server_sym_path = if symbol_kind == SymbolKind::Dsym {
+            sym_path
+                .join("Contents/Resources/DWARF")
+                .join(&dests.file_name)
+        } else {
+            sym_path.clone()
+        };
+        for server_dir in &dests.copy_symbols_to_server {
+            let dest_path = server_dir.join(&server_path);
+            if let Some(parent) = dest_path.parent() {
+                LocalAsset::create_dir_all(parent)?;
             }
+            copy_file_or_dir(&server_sym_path, &dest_path)?;
         }
 
         Ok(())
@@ -257,3 +340,80 @@ impl BuildExpectations {
 fn package_id_string(id: Option<&PackageId>) -> String {
     id.map(ToString::to_string).unwrap_or_default()
 }
+
+/// Get rust's name (`target_os`) for the OS of a target triple, if it's one we know
+pub(crate) fn target_os(target: &str) -> Option<&'static str> {
+    let components = target.split('-').skip(1).collect::<Vec<_>>();
+    let has = |os: &str| components.iter().any(|c| c.starts_with(os));
+
+    // android is also linux, so it has to come first
+    if has("android") {
+        Some("android")
+    } else if has("linux") {
+        Some("linux")
+    } else if has("darwin") {
+        Some("macos")
+    } else if has("windows") {
+        Some("windows")
+    } else if has("freebsd") {
+        Some("freebsd")
+    } else if has("netbsd") {
+        Some("netbsd")
+    } else if has("openbsd") {
+        Some("openbsd")
+    } else if has("illumos") {
+        Some("illumos")
+    } else {
+        None
+    }
+}
+
+/// Get the kind of symbols we planned a symbols artifact for, if any
+pub(crate) fn expected_symbol_kind(dist: &DistGraph, binary: &Binary) -> Option<SymbolKind> {
+    let artifact = dist.artifact(binary.symbols_artifact?);
+    if let ArtifactKind::Symbols(symbols) = &artifact.kind {
+        Some(symbols.kind)
+    } else {
+        None
+    }
+}
+
+fn is_symbol_kind(path: &Utf8Path, kind: SymbolKind) -> bool {
+    path.extension() == Some(kind.ext())
+}
+
+/// Check if a symbol file belongs to a binary
+///
+/// Symbols are named after either the binary's file name (`libfoo.so.debug`, `foo.dSYM`)
+/// or its stem (`foo.pdb`), and rustc names pdbs and dwps after the crate, which means
+/// hyphens become underscores (`my_app.pdb` for `my-app.exe`).
+pub(crate) fn symbols_are_for_binary(bin_path: &Utf8Path, sym_path: &Utf8Path) -> bool {
+    let normalize = |name: &str| name.replace('-', "_");
+    let Some(sym_stem) = sym_path.file_stem().map(normalize) else {
+        return false;
+    };
+    let names = [bin_path.file_name(), bin_path.file_stem()];
+    names
+        .into_iter()
+        .flatten()
+        .map(normalize)
+        .any(|name| name == sym_stem)
+}
+
+/// Find the dwp for a binary in cargo's `deps` dir, for when cargo didn't uplift it
+///
+/// The files there have a hash in their name (`my_app-0123abcd.dwp`), so if there's
+/// more than one (from older builds) we take the newest.
+fn find_dwp_in_deps(bin_path: &Utf8Path) -> Option<Utf8PathBuf> {
+    let deps_dir = bin_path.parent()?.join("deps");
+    let prefix = format!("{}-", bin_path.file_stem()?.replace('-', "_"));
+    deps_dir
+        .read_dir_utf8()
+        .ok()?
+        .filter_map(Result::ok)
+        .filter(|entry| {
+            entry.file_name().starts_with(&prefix) && is_symbol_kind(entry.path(), SymbolKind::Dwp)
+        })
+        .max_by_key(|entry| entry.metadata().and_then(|meta| meta.modified()).ok())
+        .map(|entry| entry.path().to_owned())
+}
@@ -0,0 +1,171 @@
+//! Profile-guided optimization of cargo builds
+//!
+//! A PGO build is a normal cargo build with three extra phases in front of it:
+//!
+//! 1. build the binaries instrumented with `-Cprofile-generate`
+//! 2. run the user's training command against each of them, which makes them
+//!    write out `.profraw` profiles of how they were used
+//! 3. merge those profiles into one `.profdata` file with `llvm-profdata`
+//!
+//! The real build then gets `-Cprofile-use` pointed at the merged profile.
+//!
+//! See <https://doc.rust-lang.org/rustc/profile-guided-optimization.html>
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::DistManifest;
+use tracing::warn;
+
+use crate::{
+    build::{
+        cargo::run_cargo_build,
+        generic::{check_build, run_logged},
+    },
+    BinaryKind, CargoBuildStep, CargoPgoStep, DistError, DistGraph, DistResult,
+};
+
+/// Build instrumented binaries, train them, and merge the resulting profiles
+///
+/// Returns the path to the merged profile.
+pub fn generate_pgo_profile(
+    dist_graph: &DistGraph,
+    manifest: &mut DistManifest,
+    target: &CargoBuildStep,
+    pgo: &CargoPgoStep,
+) -> DistResult<Utf8PathBuf> {
+    // Start from a clean slate, leftover profiles from an old build would skew things
+    if pgo.profile_dir.exists() {
+        LocalAsset::remove_dir_all(&pgo.profile_dir)?;
+    }
+    LocalAsset::create_dir_all(&pgo.profile_dir)?;
+
+    let rustflags = profile_generate_rustflags(&target.rustflags, &pgo.profile_dir);
+    let expected = run_cargo_build(dist_graph, target, &rustflags, "instrumented cargo target")?;
+
+    for (pkg_id, pkg) in &expected.packages {
+        for (bin_name, bin) in &pkg.binaries {
+            // Libraries can't be run, so there's nothing to train
+            if bin.kind != BinaryKind::Executable {
+                continue;
+            }
+            let Some(src_path) = &bin.src_path else {
+                return Err(DistError::MissingBinaries {
+                    pkg_name: pkg_id.to_owned(),
+                    bin_name: bin_name.to_owned(),
+                });
+            };
+            train(dist_graph, manifest, target, pgo, bin_name, src_path)?;
+        }
+    }
+
+    merge_profiles(dist_graph, &pgo.profile_dir)
+}
+
+/// RUSTFLAGS for the instrumented build, which makes the binaries write profiles to `profile_dir`
+pub(crate) fn profile_generate_rustflags(rustflags: &str, profile_dir: &Utf8Path) -> String {
+    format!("{rustflags} -Cprofile-generate={profile_dir}")
+}
+
+/// RUSTFLAGS for the real build, optimized with the merged `profile`
+pub(crate) fn profile_use_rustflags(rustflags: &str, profile: &Utf8Path) -> String {
+    format!("{rustflags} -Cprofile-use={profile}")
+}
+
+/// Run the training command against one instrumented binary
+fn train(
+    dist_graph: &DistGraph,
+    manifest: &mut DistManifest,
+    target: &CargoBuildStep,
+    pgo: &CargoPgoStep,
+    bin_name: &str,
+    bin_path: &Utf8Path,
+) -> DistResult<()> {
+    eprintln!(
+        "training {bin_name} for pgo (via {})",
+        pgo.training_command.join(" ")
+    );
+    let Some((command_name, args)) = pgo.training_command.split_first() else {
+        return Err(DistError::PgoEmptyTrainingCommand {});
+    };
+    let mut command = Cmd::new(command_name, format!("train {bin_name} for pgo"));
+    for arg in args {
+        command.arg(arg);
+    }
+    command
+        .current_dir(&target.working_dir)
+        .env("CARGO_DIST_BINARY", bin_path)
+        .env("CARGO_DIST_BINARY_NAME", bin_name)
+        .env("CARGO_DIST_TARGET", &target.target_triple);
+
+    let log_path = dist_graph
+        .dist_dir
+        .join(format!("pgo-train-{bin_name}-{}.log", target.target_triple));
+    let status = run_logged(command, &log_path)?;
+    check_build(
+        dist_graph,
+        manifest,
+        &pgo.training_command,
+        Some(&target.target_triple),
+        &log_path,
+        status,
+    )
+}
+
+/// Merge all the `.profraw` files in a directory into one `.profdata`
+fn merge_profiles(dist_graph: &DistGraph, profile_dir: &Utf8Path) -> DistResult<Utf8PathBuf> {
+    let has_profiles = profile_dir
+        .read_dir_utf8()?
+        .filter_map(Result::ok)
+        .any(|entry| entry.path().extension() == Some("profraw"));
+    if !has_profiles {
+        return Err(DistError::PgoNoProfiles {
+            profile_dir: profile_dir.to_owned(),
+        });
+    }
+
+    let merged = profile_dir.join("merged.profdata");
+    let llvm_profdata = find_llvm_profdata(&dist_graph.tools.cargo.host_target)?;
+    Cmd::new(llvm_profdata, "merge pgo profiles")
+        .arg("merge")
+        .arg("-o")
+        .arg(&merged)
+        .arg(profile_dir)
+        .run()?;
+    Ok(merged)
+}
+
+/// Find llvm-profdata, which needs to match the LLVM version rustc uses
+fn find_llvm_profdata(host_target: &str) -> DistResult<String> {
+    // rustup's llvm-tools component puts it in the sysroot, rather than on PATH
+    let rustc = std::env::var("RUSTC").unwrap_or_else(|_| "rustc".to_owned());
+    let sysroot = Cmd::new(rustc, "get rustc's sysroot")
+        .arg("--print")
+        .arg("sysroot")
+        .log(None)
+        .output();
+    if let Ok(output) = sysroot {
+        let sysroot = String::from_utf8(output.stdout)?;
+        let path = Utf8PathBuf::from(sysroot.trim())
+            .join("lib/rustlib")
+            .join(host_target)
+            .join("bin")
+            .join(format!("llvm-profdata{}", std::env::consts::EXE_SUFFIX));
+        if path.exists() {
+            return Ok(path.into_string());
+        }
+    }
+
+    // Otherwise hope there's a suitable one on PATH
+    let on_path = Cmd::new("llvm-profdata", "detect llvm-profdata")
+        .arg("--version")
+        .log(None)
+        .check(false)
+        .output();
+    if on_path.is_ok() {
+        warn!("using llvm-profdata from PATH, if it fails to read the profiles it may not match rustc's LLVM version (try `rustup component add llvm-tools-preview`)");
+        return Ok("llvm-profdata".to_owned());
+    }
+
+    Err(DistError::PgoProfdataMissing {})
+}
@@ -0,0 +1,191 @@
+//! Post-build processing of binaries
+//!
+//! After a build, but before a binary gets signed or copied anywhere, it can be run
+//! through the pipeline of built-in processors configured with `binary-processors`.
+//! Each processor is a thin wrapper around a standard tool (objcopy, strip, upx...)
+//! which modifies the binary in place. Processors that don't make sense for a
+//! binary (e.g. setting the RPATH of an executable) are skipped for it.
+//!
+//! Cargo may reuse its outputs for later builds, so rather than mangling them we
+//! first copy the binary to `target/dist-processed/` and process it there.
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::{
+    build::ExpectedBinary,
+    config::{BinaryProcessorComplex, BinaryProcessorKind},
+    copy_file, BinaryKind, DistGraph, DistResult,
+};
+
+/// The name of the directory in the target dir that binaries are processed in
+pub const TARGET_DIST_PROCESSED: &str = "dist-processed";
+
+/// The kind of object file a target uses, which decides which tools apply
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ObjectFormat {
+    Elf,
+    MachO,
+    Pe,
+}
+
+impl ObjectFormat {
+    pub(crate) fn for_target(target: &str) -> Self {
+        if target.contains("windows") {
+            ObjectFormat::Pe
+        } else if target.contains("apple") {
+            ObjectFormat::MachO
+        } else {
+            ObjectFormat::Elf
+        }
+    }
+}
+
+/// Run the configured processors on a binary, updating its paths to the processed files
+pub fn run_processors(dist: &DistGraph, expected: &mut ExpectedBinary) -> DistResult<()> {
+    let bin = dist.binary(expected.idx);
+    // Universal binaries are merged from binaries that were already processed
+    if !bin.lipo_inputs.is_empty() {
+        return Ok(());
+    }
+    let processors = dist
+        .config
+        .builds
+        .processors
+        .iter()
+        .map(|processor| &processor.0)
+        .filter(|processor| processor.wanted_for_target(&bin.target))
+        .collect::<Vec<_>>();
+    if processors.is_empty() {
+        return Ok(());
+    }
+
+    let src_path = expected
+        .src_path
+        .as_deref()
+        .expect("bin src_path should have been checked by caller");
+    let work_dir = dist.target_dir.join(TARGET_DIST_PROCESSED).join(&bin.id);
+    LocalAsset::create_dir_all(&work_dir)?;
+    let path = work_dir.join(&bin.file_name);
+    copy_file(src_path, &path)?;
+
+    let format = ObjectFormat::for_target(&bin.target);
+    for processor in processors {
+        let ran = match processor.processor {
+            BinaryProcessorKind::SplitDebuginfo => {
+                let debug_path = split_debuginfo(format, expected.kind, &path)?;
+                // The debuginfo is now this binary's symbols
+                expected.sym_paths.extend(debug_path.clone());
+                debug_path.is_some()
+            }
+            BinaryProcessorKind::Strip => strip(format, expected.kind, &path)?,
+            BinaryProcessorKind::Upx => upx(expected.kind, &path)?,
+            BinaryProcessorKind::Rpath => rpath(format, expected.kind, processor, &path)?,
+        };
+        if ran {
+            expected.processors.push(processor.processor.to_string());
+        }
+    }
+
+    expected.src_path = Some(path);
+    Ok(())
+}
+
+/// Get a binutils-style tool, respecting the usual env var for overriding it
+/// (so that cross-compiles can use e.g. `aarch64-linux-gnu-objcopy`)
+fn tool(env_var: &str, default: &str) -> String {
+    std::env::var(env_var).unwrap_or_else(|_| default.to_owned())
+}
+
+/// Move ELF debuginfo into a `.debug` file next to the binary
+///
+/// Returns the path of the `.debug` file, if this applied.
+fn split_debuginfo(
+    format: ObjectFormat,
+    kind: BinaryKind,
+    path: &Utf8Path,
+) -> DistResult<Option<Utf8PathBuf>> {
+    // Mach-O and PE debuginfo already lives in dSYMs and PDBs
+    if format != ObjectFormat::Elf || kind == BinaryKind::StaticLibrary {
+        return Ok(None);
+    }
+    let debug_path = Utf8PathBuf::from(format!("{path}.debug"));
+    let objcopy = tool("OBJCOPY", "objcopy");
+    Cmd::new(&objcopy, "split debuginfo out of binary")
+        .arg("--only-keep-debug")
+        .arg(path)
+        .arg(&debug_path)
+        .run()?;
+    // The debuglink records the .debug file's name and checksum, so debuggers can find it
+    Cmd::new(&objcopy, "link binary to split debuginfo")
+        .arg("--strip-debug")
+        .arg(format!("--add-gnu-debuglink={debug_path}"))
+        .arg(path)
+        .run()?;
+    Ok(Some(debug_path))
+}
+
+/// Strip symbols from a binary
+fn strip(format: ObjectFormat, kind: BinaryKind, path: &Utf8Path) -> DistResult<bool> {
+    // PE symbols already live in PDBs, and stripping static libraries breaks them
+    if format == ObjectFormat::Pe || kind == BinaryKind::StaticLibrary {
+        return Ok(false);
+    }
+    let mut command = Cmd::new(tool("STRIP", "strip"), "strip binary");
+    // Dynamic libraries need to keep the symbols they export
+    match (format, kind) {
+        (ObjectFormat::Elf, BinaryKind::Executable) => command.arg("--strip-all"),
+        (ObjectFormat::Elf, _) => command.arg("--strip-unneeded"),
+        (_, BinaryKind::Executable) => &mut command,
+        (_, _) => command.arg("-x"),
+    };
+    command.arg(path).run()?;
+    Ok(true)
+}
+
+/// Compress an executable with UPX
+fn upx(kind: BinaryKind, path: &Utf8Path) -> DistResult<bool> {
+    if kind != BinaryKind::Executable {
+        return Ok(false);
+    }
+    Cmd::new("upx", "compress binary with upx")
+        .arg("--best")
+        .arg("-q")
+        .arg(path)
+        .run()?;
+    Ok(true)
+}
+
+/// Set the RPATH of a dynamic library
+fn rpath(
+    format: ObjectFormat,
+    kind: BinaryKind,
+    processor: &BinaryProcessorComplex,
+    path: &Utf8Path,
+) -> DistResult<bool> {
+    if kind != BinaryKind::DynamicLibrary {
+        return Ok(false);
+    }
+    match format {
+        ObjectFormat::Elf => {
+            let rpath = processor.rpath.as_deref().unwrap_or("$ORIGIN");
+            Cmd::new("patchelf", "set rpath of library")
+                .arg("--set-rpath")
+                .arg(rpath)
+                .arg(path)
+                .run()?;
+        }
+        ObjectFormat::MachO => {
+            let rpath = processor.rpath.as_deref().unwrap_or("@loader_path");
+            Cmd::new("install_name_tool", "add rpath to library")
+                .arg("-add_rpath")
+                .arg(rpath)
+                .arg(path)
+                .run()?;
+        }
+        // Windows just searches the directory the dll is in
+        ObjectFormat::Pe => return Ok(false),
+    }
+    Ok(true)
+}
@@ -0,0 +1,171 @@
+//! Build ids, and laying out symbols for symbol servers
+//!
+//! Every binary we care about embeds some kind of unique id for the build it came from:
+//!
+//! * ELF binaries have an `NT_GNU_BUILD_ID` note
+//! * Mach-O binaries have an `LC_UUID` load command
+//! * PE binaries have a CodeView record with the GUID and age of their PDB
+//!
+//! Debuggers and crash reporters use these to look up the matching symbols, so we
+//! record them in the dist-manifest, and (if `symbol-server` is enabled) use them to
+//! lay the symbols out the way symbol servers expect:
+//!
+//! * ELF/Mach-O: `<id[0:2]>/<id[2:]>.debug` (the `.build-id` layout)
+//! * PDB: `<name.pdb>/<signature>/<name.pdb>` (the SymStore layout)
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{BuildId, BuildIdKind};
+use goblin::{elf::note::NT_GNU_BUILD_ID, mach::Mach, Object};
+use tracing::warn;
+
+use crate::{build::process::ObjectFormat, Binary, BinaryKind};
+
+/// Read the build id of a binary, if it has one we understand
+pub fn read_build_id(path: &Utf8Path, kind: BinaryKind) -> Option<BuildId> {
+    // Static libraries are never linked, so they don't get ids
+    if kind == BinaryKind::StaticLibrary {
+        return None;
+    }
+    let bytes = match LocalAsset::load_bytes(path) {
+        Ok(bytes) => bytes,
+        Err(e) => {
+            warn!("couldn't read {path} to find its build id: {e}");
+            return None;
+        }
+    };
+    match Object::parse(&bytes) {
+        Ok(object) => build_id_of_object(&object, &bytes),
+        Err(e) => {
+            warn!("couldn't parse {path} to find its build id: {e}");
+            None
+        }
+    }
+}
+
+fn build_id_of_object(object: &Object, bytes: &[u8]) -> Option<BuildId> {
+    match object {
+        Object::Elf(elf) => {
+            // The section headers can be stripped, but the program headers can't
+            let mut notes = elf
+                .iter_note_sections(bytes, Some(".note.gnu.build-id"))
+                .into_iter()
+                .chain(elf.iter_note_headers(bytes))
+                .flatten();
+            let note = notes.find_map(|note| {
+                note.ok()
+                    .filter(|note| note.n_type == NT_GNU_BUILD_ID && note.name == "GNU")
+            })?;
+            Some(BuildId {
+                kind: BuildIdKind::GnuBuildId,
+                id: hex_lower(note.desc),
+                debug_file: None,
+            })
+        }
+        Object::Mach(Mach::Binary(macho)) => {
+            let uuid = macho
+                .load_commands
+                .iter()
+                .find_map(|lc| match &lc.command {
+                    goblin::mach::load_command::CommandVariant::Uuid(uuid) => Some(uuid.uuid),
+                    _ => None,
+                })?;
+            Some(BuildId {
+                kind: BuildIdKind::MachoUuid,
+                id: hex_lower(&uuid),
+                debug_file: None,
+            })
+        }
+        Object::PE(pe) => {
+            let codeview = pe.debug_data.as_ref()?.codeview_pdb70_debug_info.as_ref()?;
+            // The filename is whatever path the linker wrote the pdb to, NUL-terminated
+            let filename = String::from_utf8_lossy(codeview.filename);
+            let filename = filename.trim_end_matches('\0');
+            let debug_file = filename.rsplit(['/', '\\']).next().unwrap_or(filename);
+            Some(BuildId {
+                kind: BuildIdKind::Pdb,
+                id: pdb_signature(&codeview.signature, codeview.age),
+                debug_file: Some(debug_file.to_owned()),
+            })
+        }
+        // Universal binaries have one id per architecture, and the single-arch
+        // binaries they were made from already reported them
+        _ => None,
+    }
+}
+
+/// Make up a plausible build id for a binary, for --artifacts=lies
+pub fn fake_build_id(binary: &Binary) -> Option<BuildId> {
+    if binary.kind == BinaryKind::StaticLibrary || !binary.lipo_inputs.is_empty() {
+        return None;
+    }
+    use sha2::Digest;
+    let hash = sha2::Sha256::digest(binary.id.as_bytes());
+    let build_id = match ObjectFormat::for_target(&binary.target) {
+        ObjectFormat::Elf => BuildId {
+            kind: BuildIdKind::GnuBuildId,
+            id: hex_lower(&hash[..20]),
+            debug_file: None,
+        },
+        ObjectFormat::MachO => BuildId {
+            kind: BuildIdKind::MachoUuid,
+            id: hex_lower(&hash[..16]),
+            debug_file: None,
+        },
+        ObjectFormat::Pe => {
+            let signature: [u8; 16] = hash[..16].try_into().expect("sha256 is 32 bytes");
+            let stem = Utf8Path::new(&binary.file_name)
+                .file_stem()
+                .unwrap_or(&binary.file_name);
+            BuildId {
+                kind: BuildIdKind::Pdb,
+                id: pdb_signature(&signature, 1),
+                debug_file: Some(format!("{}.pdb", stem.replace('-', "_"))),
+            }
+        }
+    };
+    Some(build_id)
+}
+
+/// Get the path symbols should have in a symbol server, relative to its root
+pub fn symbol_server_path(build_id: &BuildId) -> Option<Utf8PathBuf> {
+    match build_id.kind {
+        BuildIdKind::GnuBuildId | BuildIdKind::MachoUuid => {
+            // Too short to be split into a directory and a file
+            if build_id.id.len() <= 2 || !build_id.id.is_ascii() {
+                return None;
+            }
+            let (dir, file) = build_id.id.split_at(2);
+            // Whatever kind of file the symbols started out as, debuginfod and
+            // gdb only look for `.debug`
+            Some(Utf8PathBuf::from(format!("{dir}/{file}.debug")))
+        }
+        BuildIdKind::Pdb => {
+            let debug_file = build_id.debug_file.as_deref()?;
+            Some(Utf8PathBuf::from(format!(
+                "{debug_file}/{}/{debug_file}",
+                build_id.id
+            )))
+        }
+        _ => None,
+    }
+}
+
+/// Format a PDB's GUID and age the way SymStore does
+///
+/// The GUID is stored as a little-endian u32, two little-endian u16s, and 8 bytes.
+fn pdb_signature(guid: &[u8; 16], age: u32) -> String {
+    let data1 = u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]);
+    let data2 = u16::from_le_bytes([guid[4], guid[5]]);
+    let data3 = u16::from_le_bytes([guid[6], guid[7]]);
+    let data4 = hex_lower(&guid[8..]).to_ascii_uppercase();
+    format!("{data1:08X}{data2:04X}{data3:04X}{data4}{age:X}")
+}
+
+fn hex_lower(bytes: &[u8]) -> String {
+    use std::fmt::Write;
+    bytes.iter().fold(String::new(), |mut out, byte| {
+        write!(out, "{byte:02x}").expect("writing to a String can't fail");
+        out
+    })
+}
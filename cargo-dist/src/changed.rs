@@ -0,0 +1,296 @@
+//! Finding which packages changed since they were last released
+//!
+//! This is the machinery behind `cargo dist changed`, for workspaces that release
+//! their apps separately with singular tags like `my-app-v1.2.3`. For each app we
+//! find the tag of its last release, diff the repo against it, and attribute every
+//! changed file to the package whose directory it's in. An app has changed if it,
+//! or one of the local packages it's built from, owns one of those files.
+
+use std::fmt::Write;
+
+use axoproject::{local_repo::LocalRepo, PackageIdx};
+use axotag::PartialAnnouncementTag;
+use camino::{Utf8Path, Utf8PathBuf};
+use serde::Serialize;
+
+use crate::{
+    announce::select_packages,
+    config::{get_project, Config},
+    errors::{DistError, DistResult},
+    release::release_tag,
+    tasks::tool_info,
+    DistGraphBuilder, SortedMap, SortedSet,
+};
+
+/// Which packages changed since they were last released
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ChangedReport {
+    /// Every package we'd release, in workspace order
+    pub packages: Vec<PackageChanges>,
+}
+
+/// The changes to one package since it was last released
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct PackageChanges {
+    /// Name of the package
+    pub name: String,
+    /// Current version of the package
+    pub version: String,
+    /// The tag of the package's last release, if it's been released before
+    pub last_release: Option<String>,
+    /// Whether the package needs a new release
+    ///
+    /// This is true if any of `changed_files` changed, or if it's never been released.
+    pub changed: bool,
+    /// Changed files the package is built from (relative to the repo root)
+    pub changed_files: Vec<String>,
+    /// Local dependencies of the package that have changed files
+    pub changed_dependencies: Vec<String>,
+    /// The tag that would release just this package at its current version
+    pub tag: String,
+    /// Whether the current version was already released, so it needs a version bump first
+    pub needs_bump: bool,
+}
+
+impl ChangedReport {
+    /// The tags that would release each changed package
+    pub fn tags(&self) -> Vec<&str> {
+        self.packages
+            .iter()
+            .filter(|package| package.changed && !package.needs_bump)
+            .map(|package| package.tag.as_str())
+            .collect()
+    }
+}
+
+/// Run `cargo dist changed`
+pub fn do_changed(cfg: &Config) -> DistResult<ChangedReport> {
+    let tools = tool_info()?;
+    let git = tools
+        .git
+        .as_ref()
+        .map(|git| git.cmd.clone())
+        .ok_or_else(|| DistError::ToolMissing {
+            tool: "git".to_owned(),
+        })?;
+    let mut workspaces = get_project()?;
+    let graph = DistGraphBuilder::new(
+        cfg.root_cmd.clone(),
+        tools,
+        &mut workspaces,
+        cfg.artifact_mode,
+        cfg.allow_all_dirty,
+        true,
+    )?;
+    let Some(repo) = graph.workspaces.repo.clone() else {
+        return Err(DistError::ChangedNoRepo);
+    };
+    compute_changes(&graph, &repo, &git)
+}
+
+/// Find which of the packages we'd release have changed since their last release
+pub(crate) fn compute_changes(
+    graph: &DistGraphBuilder,
+    repo: &LocalRepo,
+    git: &str,
+) -> DistResult<ChangedReport> {
+    let tag_namespace = graph
+        .inner
+        .config
+        .ci
+        .github
+        .as_ref()
+        .and_then(|ci| ci.tag_namespace.clone());
+    let owners = PackageOwners::new(graph);
+
+    // Packages released together share a tag, so only diff against each tag once
+    let mut diffs = SortedMap::<String, Vec<Utf8PathBuf>>::new();
+    let mut packages = vec![];
+    for release in select_packages(graph, &PartialAnnouncementTag::default()) {
+        let pkg_idx = release.package_idx;
+        let package = graph.workspaces.package(pkg_idx);
+        let version = package.version.as_ref().unwrap().semver();
+        let tag = release_tag(Some(&package.name), &version, tag_namespace.as_deref());
+
+        // Tags for versions after this one must be from some other branch
+        let last_release = graph
+            .release_tags(repo, git, "HEAD", &[pkg_idx])
+            .into_iter()
+            .filter(|(tag_version, _)| *tag_version <= version)
+            .max_by(|(a, _), (b, _)| a.cmp(b));
+        let Some((last_version, last_tag)) = last_release else {
+            packages.push(PackageChanges {
+                name: package.name.clone(),
+                version: version.to_string(),
+                last_release: None,
+                changed: true,
+                changed_files: vec![],
+                changed_dependencies: vec![],
+                tag,
+                needs_bump: false,
+            });
+            continue;
+        };
+
+        if !diffs.contains_key(&last_tag) {
+            let files = repo
+                .changed_files(git, &last_tag, "HEAD")
+                .map_err(|inner| DistError::ChangedDiffFailed {
+                    tag: last_tag.clone(),
+                    inner,
+                })?;
+            diffs.insert(last_tag.clone(), files);
+        }
+        let workspace_files = workspace_files(graph, pkg_idx);
+        let mut changed_files = vec![];
+        let mut changed_dependencies = SortedSet::new();
+        for file in &diffs[&last_tag] {
+            let relevant = if workspace_files.contains(file) {
+                true
+            } else if let Some(owner) = owners.owner(file) {
+                let owner_package = graph.workspaces.package(owner);
+                if owner == pkg_idx {
+                    true
+                } else if package
+                    .local_dependencies
+                    .contains(&owner_package.manifest_path)
+                {
+                    changed_dependencies.insert(owner_package.name.clone());
+                    true
+                } else {
+                    false
+                }
+            } else {
+                false
+            };
+            if relevant {
+                let relative = file.strip_prefix(&repo.path).unwrap_or(file);
+                changed_files.push(relative.to_string());
+            }
+        }
+
+        let changed = !changed_files.is_empty();
+        packages.push(PackageChanges {
+            name: package.name.clone(),
+            version: version.to_string(),
+            last_release: Some(last_tag),
+            changed,
+            changed_files,
+            changed_dependencies: changed_dependencies.into_iter().collect(),
+            tag,
+            needs_bump: changed && last_version == version,
+        });
+    }
+
+    Ok(ChangedReport { packages })
+}
+
+/// Files that affect every package in a workspace
+///
+/// This is the workspace's manifest and the dist config. Lockfiles aren't included,
+/// because releasing any package in the workspace touches them.
+fn workspace_files(graph: &DistGraphBuilder, pkg_idx: PackageIdx) -> SortedSet<Utf8PathBuf> {
+    let workspace = graph
+        .workspaces
+        .workspace(graph.workspaces.workspace_for_package(pkg_idx));
+    let root_workspace = graph.workspaces.root_workspace();
+    [workspace, root_workspace]
+        .into_iter()
+        .flat_map(|workspace| {
+            std::iter::once(&workspace.manifest_path).chain(&workspace.dist_manifest_path)
+        })
+        .map(|path| canonicalize(path))
+        .collect()
+}
+
+/// Which package each file in the repo belongs to
+struct PackageOwners {
+    /// The (canonicalized) root directory of each package
+    roots: Vec<(Utf8PathBuf, PackageIdx)>,
+}
+
+impl PackageOwners {
+    fn new(graph: &DistGraphBuilder) -> Self {
+        let roots = graph
+            .workspaces
+            .all_packages()
+            .map(|(pkg_idx, package)| (canonicalize(&package.package_root), pkg_idx))
+            .collect();
+        Self { roots }
+    }
+
+    /// Get the package a file belongs to
+    ///
+    /// Packages can be nested inside each other (like the root package of a
+    /// workspace), so the most deeply nested one wins.
+    fn owner(&self, file: &Utf8Path) -> Option<PackageIdx> {
+        self.roots
+            .iter()
+            .filter(|(root, _)| file.starts_with(root))
+            .max_by_key(|(root, _)| root.components().count())
+            .map(|(_, pkg_idx)| *pkg_idx)
+    }
+}
+
+/// Canonicalize a path so it can be compared to the paths git gives us
+fn canonicalize(path: &Utf8Path) -> Utf8PathBuf {
+    path.canonicalize_utf8().unwrap_or_else(|_| path.to_owned())
+}
+
+/// Render a report for humans
+pub fn render_human(report: &ChangedReport) -> String {
+    let mut out = String::new();
+    let (changed, unchanged): (Vec<_>, Vec<_>) =
+        report.packages.iter().partition(|package| package.changed);
+
+    if changed.is_empty() {
+        out.push_str("nothing has changed since the last releases\n");
+    } else {
+        out.push_str("changed since their last release:\n");
+    }
+    for package in &changed {
+        match &package.last_release {
+            Some(last_release) => writeln!(
+                out,
+                "  {} {} (since {last_release})",
+                package.name, package.version
+            )
+            .unwrap(),
+            None => writeln!(
+                out,
+                "  {} {} (never released)",
+                package.name, package.version
+            )
+            .unwrap(),
+        }
+        if !package.changed_dependencies.is_empty() {
+            writeln!(out, "    via {}", package.changed_dependencies.join(", ")).unwrap();
+        }
+        if package.needs_bump {
+            writeln!(
+                out,
+                "    needs a version bump, try: cargo dist release patch --package {}",
+                package.name
+            )
+            .unwrap();
+        } else {
+            writeln!(out, "    tag: {}", package.tag).unwrap();
+        }
+    }
+
+    if !unchanged.is_empty() {
+        out.push_str("unchanged:\n");
+    }
+    for package in &unchanged {
+        writeln!(out, "  {} {}", package.name, package.version).unwrap();
+    }
+
+    let tags = report.tags();
+    if !tags.is_empty() {
+        out.push_str("\nto check what releasing them would do, run:\n");
+        for tag in tags {
+            writeln!(out, "  cargo dist plan --tag={tag}").unwrap();
+        }
+    }
+    out
+}
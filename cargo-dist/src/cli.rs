@@ -161,6 +161,13 @@ pub enum Commands {
     #[clap(disable_version_flag = true)]
     #[clap(hide = true)]
     ManifestSchema(ManifestSchemaArgs),
+    /// Compare two dist-manifest.jsons
+    ///
+    /// Reports added or removed releases, targets, artifacts and installers,
+    /// as well as renamed binaries and changes to what the binaries need
+    /// from the system (dynamic libraries and minimum glibc version).
+    #[clap(disable_version_flag = true)]
+    ManifestDiff(ManifestDiffArgs),
     /// Get a plan of what to build (and check project status)
     ///
     /// If you want to know what running your cargo-dist CI will produce,
@@ -180,6 +187,37 @@ pub enum Commands {
     #[clap(disable_version_flag = true)]
     Host(HostArgs),
 
+    /// Check that a published release is intact
+    ///
+    /// Downloads every artifact of the release, checks their checksums (and
+    /// detached signatures and GitHub attestations, if present), unpacks archives
+    /// to check they contain what the manifest says, and runs installers in a
+    /// temporary directory.
+    ///
+    /// Releases given by tag are looked up from hosting. With no arguments, the
+    /// release for the current tag (see --tag) is verified.
+    #[clap(disable_version_flag = true)]
+    Verify(VerifyArgs),
+
+    /// Bump versions, commit and tag a release
+    ///
+    /// Computes the next version for the packages 'cargo dist plan' would announce,
+    /// updates it in their manifests (Cargo.toml, package.json, pyproject.toml, dist.toml),
+    /// renames the "Unreleased" section of their changelogs to the new version,
+    /// and commits and tags the result. Nothing is pushed.
+    #[clap(disable_version_flag = true)]
+    Release(ReleaseArgs),
+
+    /// List the packages that changed since they were last released
+    ///
+    /// For workspaces that release each app with its own tag (like my-app-v1.2.3).
+    /// Each app's files are compared to its last release tag, and an app counts as
+    /// changed if its own files, the local packages it depends on, or the workspace's
+    /// manifest or dist config changed. The suggested tags can be passed to
+    /// 'cargo dist plan --tag=...' to release just those apps.
+    #[clap(disable_version_flag = true)]
+    Changed(ChangedArgs),
+
     /// Performs a self-update, if a new version is available, and then 'init'
     #[clap(disable_version_flag = true)]
     Selfupdate(UpdateArgs),
@@ -214,6 +252,13 @@ pub struct BuildArgs {
     /// * linkage: prints information on dynamic libraries used by build artifacts
     #[clap(long, short, value_delimiter(','))]
     pub print: Vec<String>,
+
+    /// Build everything twice and check that the artifacts are byte-for-byte identical
+    ///
+    /// The second build reuses cargo's build cache, so this mostly checks that packaging
+    /// (archives, installers...) is reproducible.
+    #[clap(long)]
+    pub check_reproducible: bool,
 }
 
 /// How we should select the artifacts to build
@@ -439,11 +484,28 @@ pub enum OutputFormat {
     Json,
 }
 
+#[derive(Args, Clone, Debug)]
+pub struct ManifestDiffArgs {
+    /// The old dist-manifest.json
+    pub old: Utf8PathBuf,
+    /// The new dist-manifest.json
+    pub new: Utf8PathBuf,
+}
+
 #[derive(Args, Clone, Debug)]
 pub struct ManifestSchemaArgs {
     /// Write the manifest schema to the named file instead of stdout
     #[clap(long)]
     pub output: Option<String>,
+    /// Emit a historical version of the schema instead of the current one
+    #[clap(long)]
+    pub schema_version: Option<u32>,
+    /// Instead of emitting the schema, check it for breaking changes against the given schema
+    ///
+    /// Removed fields, changed types, newly required fields and removed enum variants
+    /// are all reported as errors.
+    #[clap(long, value_name = "SCHEMA", conflicts_with_all = ["output", "schema_version"])]
+    pub check: Option<Utf8PathBuf>,
 }
 
 #[derive(Args, Clone, Debug)]
@@ -451,6 +513,45 @@ pub struct HostArgs {
     /// The hosting steps to perform
     #[clap(long, value_delimiter(','))]
     pub steps: Vec<HostStyle>,
+    /// Replace files the host already has if they don't match the ones we built
+    ///
+    /// By default uploading skips files the host already has with matching checksums,
+    /// and errors out on files whose checksums don't match.
+    #[clap(long)]
+    pub replace_mismatched: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct VerifyArgs {
+    /// The release's tag (like v1.2.3), or the path or URL of its dist-manifest.json
+    /// (or the directory containing it)
+    ///
+    /// file:// URLs are also accepted. Anything that isn't a URL, a .json file, or
+    /// an existing directory is taken to be a tag.
+    #[clap(value_name = "RELEASE")]
+    pub release: Option<String>,
+    /// Don't run installers
+    #[clap(long)]
+    pub skip_installers: bool,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct ChangedArgs {}
+
+#[derive(Args, Clone, Debug)]
+pub struct ReleaseArgs {
+    /// How to bump the version: major, minor, patch, or an exact version like 1.2.3
+    #[clap(value_name = "BUMP")]
+    pub bump: cargo_dist::config::VersionBump,
+    /// Only release this package, with a tag like my-app-v1.2.3
+    ///
+    /// By default every package that would be announced is released together
+    /// with a tag like v1.2.3, which requires them to all have the same version.
+    #[clap(long)]
+    pub package: Option<String>,
+    /// Show what would change without changing anything
+    #[clap(long)]
+    pub dry_run: bool,
 }
 
 impl HostStyle {
@@ -576,6 +576,189 @@ impl<'de> serde::Deserialize<'de> for GithubRepoPair {
     }
 }
 
+/// Settings for profile-guided optimization of cargo builds
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct PgoConfig {
+    /// The command to run against each instrumented binary to train it
+    ///
+    /// It's run once per binary, with the path to the binary in `CARGO_DIST_BINARY`.
+    pub training_command: Vec<String>,
+}
+
+/// A built-in tool that transforms binaries after they're built
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum BinaryProcessorKind {
+    /// Move ELF debuginfo into a separate `.debug` file, leaving a `.gnu_debuglink` behind
+    SplitDebuginfo,
+    /// Strip symbols
+    Strip,
+    /// Compress executables with UPX
+    Upx,
+    /// Set the RPATH of dynamic libraries
+    Rpath,
+}
+
+impl std::fmt::Display for BinaryProcessorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            BinaryProcessorKind::SplitDebuginfo => "split-debuginfo",
+            BinaryProcessorKind::Strip => "strip",
+            BinaryProcessorKind::Upx => "upx",
+            BinaryProcessorKind::Rpath => "rpath",
+        };
+        name.fmt(f)
+    }
+}
+
+/// A post-build binary processor, and when to run it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BinaryProcessor(pub BinaryProcessorComplex);
+
+/// Backing type for BinaryProcessor
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct BinaryProcessorComplex {
+    /// The processor to run
+    pub processor: BinaryProcessorKind,
+    /// Targets to run it for; defaults to all targets if not specified
+    #[serde(default)]
+    pub targets: Vec<String>,
+    /// The RPATH to set (only used by `rpath`)
+    ///
+    /// Defaults to `$ORIGIN` on Linux and `@loader_path` on macOS.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rpath: Option<String>,
+}
+
+impl BinaryProcessorComplex {
+    /// Checks if this processor should run for the specified target.
+    pub fn wanted_for_target(&self, target: &String) -> bool {
+        self.targets.is_empty() || self.targets.contains(target)
+    }
+}
+
+/// Definition for a single binary processor
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum BinaryProcessorSpec {
+    /// Simple specification format, just the name of the processor
+    Untagged(BinaryProcessorKind),
+    /// Complex specification format
+    Tagged(BinaryProcessorComplex),
+}
+
+impl<'de> Deserialize<'de> for BinaryProcessor {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let res = match BinaryProcessorSpec::deserialize(deserializer)? {
+            BinaryProcessorSpec::Untagged(processor) => BinaryProcessorComplex {
+                processor,
+                targets: vec![],
+                rpath: None,
+            },
+            BinaryProcessorSpec::Tagged(processor) => processor,
+        };
+        Ok(BinaryProcessor(res))
+    }
+}
+
+/// The tool used to run a cargo build for a target
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum CargoBuilder {
+    /// Plain `cargo build`
+    ///
+    /// syntax: `cargo`
+    #[default]
+    Cargo,
+    /// `cargo zigbuild`, using zig as the linker
+    ///
+    /// syntax: `zigbuild`, or `zigbuild.2.17` to target a specific glibc version
+    Zigbuild {
+        /// The glibc version to link against (linux-gnu targets only)
+        glibc_version: Option<cargo_dist_schema::GlibcVersion>,
+    },
+    /// `cross build`, building in a docker image with the target's toolchain
+    ///
+    /// syntax: `cross`
+    Cross,
+}
+
+impl CargoBuilder {
+    /// Whether this builder needs `rustup target add` to have been run for the target
+    pub fn needs_rustup_target(&self) -> bool {
+        // cross brings its own toolchains in its images
+        !matches!(self, CargoBuilder::Cross)
+    }
+}
+
+impl std::str::FromStr for CargoBuilder {
+    type Err = DistError;
+    fn from_str(builder: &str) -> DistResult<Self> {
+        let invalid = || DistError::CargoBuilderInvalid {
+            builder: builder.to_owned(),
+        };
+        match builder {
+            "cargo" => Ok(CargoBuilder::Cargo),
+            "cross" => Ok(CargoBuilder::Cross),
+            "zigbuild" => Ok(CargoBuilder::Zigbuild {
+                glibc_version: None,
+            }),
+            _ => {
+                let version = builder.strip_prefix("zigbuild.").ok_or_else(invalid)?;
+                let (major, series) = version.split_once('.').ok_or_else(invalid)?;
+                Ok(CargoBuilder::Zigbuild {
+                    glibc_version: Some(cargo_dist_schema::GlibcVersion {
+                        major: major.parse().map_err(|_| invalid())?,
+                        series: series.parse().map_err(|_| invalid())?,
+                    }),
+                })
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for CargoBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CargoBuilder::Cargo => write!(f, "cargo"),
+            CargoBuilder::Cross => write!(f, "cross"),
+            CargoBuilder::Zigbuild {
+                glibc_version: None,
+            } => write!(f, "zigbuild"),
+            CargoBuilder::Zigbuild {
+                glibc_version: Some(version),
+            } => write!(f, "zigbuild.{}.{}", version.major, version.series),
+        }
+    }
+}
+
+impl serde::Serialize for CargoBuilder {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> serde::Deserialize<'de> for CargoBuilder {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        use serde::de::Error;
+
+        let builder = String::deserialize(deserializer)?;
+        builder
+            .parse()
+            .map_err(|e| D::Error::custom(format!("{e}")))
+    }
+}
+
 impl GithubRepoPair {
     /// Convert this into a jinja-friendly form
     pub fn into_jinja(self) -> JinjaGithubRepoPair {
@@ -639,6 +822,48 @@ impl InstallPathStrategy {
     }
 }
 
+/// Where to get the release notes for an announcement from
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ChangelogFormat {
+    /// The CHANGELOG/RELEASES file of the workspace, or of each released package
+    #[default]
+    File,
+    /// The git history since the previous tag, read as conventional commits
+    ConventionalCommits,
+}
+
+impl std::fmt::Display for ChangelogFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            ChangelogFormat::File => "file",
+            ChangelogFormat::ConventionalCommits => "conventional-commits",
+        };
+        string.fmt(f)
+    }
+}
+
+/// A format for software bills of materials (SBOMs)
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SbomFormat {
+    /// CycloneDX 1.5 (JSON)
+    #[serde(rename = "cyclonedx")]
+    CycloneDx,
+    /// SPDX 2.3 (JSON)
+    Spdx,
+}
+
+impl SbomFormat {
+    /// Get the extension of an SBOM in this format
+    pub fn ext(self) -> &'static str {
+        match self {
+            SbomFormat::CycloneDx => ".cdx.json",
+            SbomFormat::Spdx => ".spdx.json",
+        }
+    }
+}
+
 /// A checksumming algorithm
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case")]
@@ -672,6 +897,20 @@ impl ChecksumStyle {
             ChecksumStyle::False => "false",
         }
     }
+
+    /// Get the checksum with the given extension (the inverse of [`ChecksumStyle::ext`][])
+    pub fn from_ext(ext: &str) -> Option<Self> {
+        let style = match ext {
+            "sha256" => ChecksumStyle::Sha256,
+            "sha512" => ChecksumStyle::Sha512,
+            "sha3-256" => ChecksumStyle::Sha3_256,
+            "sha3-512" => ChecksumStyle::Sha3_512,
+            "blake2s" => ChecksumStyle::Blake2s,
+            "blake2b" => ChecksumStyle::Blake2b,
+            _ => return None,
+        };
+        Some(style)
+    }
 }
 
 /// Which style(s) of configuration to generate
@@ -699,6 +938,97 @@ impl std::fmt::Display for GenerateMode {
 pub struct HostArgs {
     /// Which hosting steps to run
     pub steps: Vec<HostStyle>,
+    /// Whether files that already exist on the host with different contents should be replaced
+    /// (otherwise this is an error)
+    pub replace_mismatched: bool,
+}
+
+/// Arguments to `cargo dist verify`
+#[derive(Clone, Debug)]
+pub struct VerifyArgs {
+    /// Path or URL of the dist-manifest.json (or the directory containing it)
+    ///
+    /// If None, the release for `tag` is looked up from hosting.
+    pub source: Option<String>,
+    /// The tag of the release to look up, if not the current one
+    pub tag: Option<String>,
+    /// Whether to skip running installers
+    pub skip_installers: bool,
+}
+
+/// Arguments to `cargo dist release`
+#[derive(Clone, Debug)]
+pub struct ReleaseArgs {
+    /// How to compute the new version
+    pub bump: VersionBump,
+    /// Only release this package (with a package-specific tag)
+    pub package: Option<String>,
+    /// Report what would happen without changing anything
+    pub dry_run: bool,
+}
+
+/// How `cargo dist release` should pick the next version
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionBump {
+    /// 1.2.3 => 2.0.0
+    Major,
+    /// 1.2.3 => 1.3.0
+    Minor,
+    /// 1.2.3 => 1.2.4
+    Patch,
+    /// Use exactly this version
+    Exact(semver::Version),
+}
+
+impl VersionBump {
+    /// Get the version that comes after `version`
+    ///
+    /// Bumping a prerelease to the release it was a prerelease of just drops
+    /// the prerelease, so a patch bump of 1.2.0-alpha.1 is 1.2.0.
+    pub fn apply(&self, version: &semver::Version) -> semver::Version {
+        let is_pre = !version.pre.is_empty();
+        let (major, minor, patch) = (version.major, version.minor, version.patch);
+        let (major, minor, patch) = match self {
+            VersionBump::Exact(version) => return version.clone(),
+            VersionBump::Major if is_pre && minor == 0 && patch == 0 => (major, 0, 0),
+            VersionBump::Major => (major + 1, 0, 0),
+            VersionBump::Minor if is_pre && patch == 0 => (major, minor, 0),
+            VersionBump::Minor => (major, minor + 1, 0),
+            VersionBump::Patch if is_pre => (major, minor, patch),
+            VersionBump::Patch => (major, minor, patch + 1),
+        };
+        semver::Version::new(major, minor, patch)
+    }
+}
+
+impl std::str::FromStr for VersionBump {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "major" => Ok(VersionBump::Major),
+            "minor" => Ok(VersionBump::Minor),
+            "patch" => Ok(VersionBump::Patch),
+            _ => s
+                .strip_prefix('v')
+                .unwrap_or(s)
+                .parse()
+                .map(VersionBump::Exact)
+                .map_err(|_| {
+                    format!("expected major, minor, patch or a version like 1.2.3, got {s}")
+                }),
+        }
+    }
+}
+
+impl std::fmt::Display for VersionBump {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionBump::Major => "major".fmt(f),
+            VersionBump::Minor => "minor".fmt(f),
+            VersionBump::Patch => "patch".fmt(f),
+            VersionBump::Exact(version) => version.fmt(f),
+        }
+    }
 }
 
 /// What parts of hosting to perform
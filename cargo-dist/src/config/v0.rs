@@ -110,9 +110,9 @@ pub struct DistMetadata {
     /// The inputs should be valid rustc target triples (see `rustc --print target-list`) such
     /// as `x86_64-pc-windows-msvc`, `aarch64-apple-darwin`, or `x86_64-unknown-linux-gnu`.
     ///
-    /// FIXME: We should also accept one magic target: `universal2-apple-darwin`. This will induce
+    /// There's also one magic target: `universal2-apple-darwin`. This will induce
     /// us to build `x86_64-apple-darwin` and `aarch64-apple-darwin` (arm64) and then combine
-    /// them into a "universal" binary that can run on either arch (using apple's `lipo` tool).
+    /// them into a "universal" binary that can run on either arch (like apple's `lipo` tool).
     ///
     /// FIXME: Allow higher level requests like "[macos, windows, linux] x [x86_64, aarch64]"?
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -139,6 +139,30 @@ pub struct DistMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub msvc_crt_static: Option<bool>,
 
+    /// The tool to use for cargo builds of specific targets, mapped by target triple
+    ///
+    /// For instance `{ aarch64-unknown-linux-gnu = "zigbuild.2.17" }` cross-compiles
+    /// that target with cargo-zigbuild against glibc 2.17. Targets that aren't listed
+    /// use plain cargo.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cargo_builders: Option<SortedMap<String, CargoBuilder>>,
+
+    /// Whether to build with `cargo auditable`, embedding dependency info in the binaries
+    ///
+    /// Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cargo_auditable: Option<bool>,
+
+    /// Build with profile-guided optimization, trained by the given command
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pgo: Option<PgoConfig>,
+
+    /// Built-in processors to run on binaries after they're built, in order
+    ///
+    /// For instance `["split-debuginfo", "strip"]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub binary_processors: Option<Vec<BinaryProcessor>>,
+
     /// The archive format to use for windows builds (defaults .zip)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub windows_archive: Option<ZipStyle>,
@@ -147,6 +171,21 @@ pub struct DistMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub unix_archive: Option<ZipStyle>,
 
+    /// Whether to include a THIRD-PARTY-LICENSES file with the licenses of the crates
+    /// built into the binaries in archives
+    ///
+    /// Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub third_party_licenses: Option<bool>,
+
+    /// Generate a software bill of materials (SBOM) in this format for each build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sbom: Option<SbomFormat>,
+
+    /// Whether to publish each build's symbols laid out for a symbol server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_server: Option<bool>,
+
     /// Replace the app's name with this value for the npm package's name
     #[serde(skip_serializing_if = "Option::is_none")]
     pub npm_package: Option<String>,
@@ -276,6 +315,12 @@ pub struct DistMetadata {
     /// (defaults to false)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub all_features: Option<bool>,
+    /// Overrides of cargo build settings for specific targets
+    ///
+    /// Keys are either target triples or `cfg(...)` expressions, like the keys of
+    /// `[target]` in Cargo.toml.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<SortedMap<String, v1::builds::cargo::CargoTargetBuildLayer>>,
 
     /// Plan jobs to run in CI
     ///
@@ -342,6 +387,12 @@ pub struct DistMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub force_latest: Option<bool>,
 
+    /// Where to get the release notes for an announcement from
+    ///
+    /// (defaults to "file", the CHANGELOG/RELEASES of the workspace or packages)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog_format: Option<ChangelogFormat>,
+
     /// Whether we should create the Github Release for you when you push a tag.
     ///
     /// If true (default), cargo-dist will create a new Github Release and generate
@@ -459,6 +510,9 @@ impl DistMetadata {
             auto_includes: _,
             windows_archive: _,
             unix_archive: _,
+            third_party_licenses: _,
+            sbom: _,
+            symbol_server: _,
             npm_package: _,
             npm_scope: _,
             checksum: _,
@@ -473,6 +527,7 @@ impl DistMetadata {
             features: _,
             default_features: _,
             all_features: _,
+            target: _,
             plan_jobs: _,
             local_artifacts_jobs: _,
             global_artifacts_jobs: _,
@@ -482,6 +537,7 @@ impl DistMetadata {
             post_announce_jobs: _,
             publish_prereleases: _,
             force_latest: _,
+            changelog_format: _,
             create_release: _,
             pr_run_mode: _,
             allow_dirty: _,
@@ -490,6 +546,10 @@ impl DistMetadata {
             macos_sign: _,
             github_attestations: _,
             msvc_crt_static: _,
+            cargo_builders: _,
+            cargo_auditable: _,
+            pgo: _,
+            binary_processors: _,
             hosting: _,
             github_custom_runners: _,
             github_custom_job_permissions: _,
@@ -554,6 +614,9 @@ impl DistMetadata {
             auto_includes,
             windows_archive,
             unix_archive,
+            third_party_licenses,
+            sbom,
+            symbol_server,
             npm_package,
             npm_scope,
             checksum,
@@ -568,6 +631,7 @@ impl DistMetadata {
             features,
             default_features,
             all_features,
+            target,
             plan_jobs,
             local_artifacts_jobs,
             global_artifacts_jobs,
@@ -577,6 +641,7 @@ impl DistMetadata {
             post_announce_jobs,
             publish_prereleases,
             force_latest,
+            changelog_format,
             create_release,
             pr_run_mode,
             allow_dirty,
@@ -585,6 +650,10 @@ impl DistMetadata {
             macos_sign,
             github_attestations,
             msvc_crt_static,
+            cargo_builders,
+            cargo_auditable,
+            pgo,
+            binary_processors,
             hosting,
             extra_artifacts,
             github_custom_runners,
@@ -653,6 +722,9 @@ impl DistMetadata {
         if force_latest.is_some() {
             warn!("package.metadata.dist.force-stable is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
+        if changelog_format.is_some() {
+            warn!("package.metadata.dist.changelog-format is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
         if pr_run_mode.is_some() {
             warn!("package.metadata.dist.pr-run-mode is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
@@ -668,6 +740,18 @@ impl DistMetadata {
         if msvc_crt_static.is_some() {
             warn!("package.metadata.dist.msvc-crt-static is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
+        if cargo_builders.is_some() {
+            warn!("package.metadata.dist.cargo-builders is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if cargo_auditable.is_some() {
+            warn!("package.metadata.dist.cargo-auditable is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if pgo.is_some() {
+            warn!("package.metadata.dist.pgo is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if binary_processors.is_some() {
+            warn!("package.metadata.dist.binary-processors is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
         if hosting.is_some() {
             warn!("package.metadata.dist.hosting is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
@@ -727,6 +811,15 @@ impl DistMetadata {
         if unix_archive.is_none() {
             *unix_archive = workspace_config.unix_archive;
         }
+        if third_party_licenses.is_none() {
+            *third_party_licenses = workspace_config.third_party_licenses;
+        }
+        if sbom.is_none() {
+            *sbom = workspace_config.sbom;
+        }
+        if symbol_server.is_none() {
+            *symbol_server = workspace_config.symbol_server;
+        }
         if npm_package.is_none() {
             npm_package.clone_from(&workspace_config.npm_package);
         }
@@ -751,6 +844,9 @@ impl DistMetadata {
         if all_features.is_none() {
             *all_features = workspace_config.all_features;
         }
+        if target.is_none() {
+            target.clone_from(&workspace_config.target);
+        }
         if tap.is_none() {
             tap.clone_from(&workspace_config.tap);
         }
@@ -37,8 +37,15 @@ impl DistMetadata {
             include,
             auto_includes,
             msvc_crt_static,
+            cargo_builders,
+            cargo_auditable,
+            pgo,
+            binary_processors,
             windows_archive,
             unix_archive,
+            third_party_licenses,
+            sbom,
+            symbol_server,
             npm_package,
             npm_scope,
             checksum,
@@ -53,6 +60,7 @@ impl DistMetadata {
             features,
             default_features,
             all_features,
+            target,
             plan_jobs,
             local_artifacts_jobs,
             global_artifacts_jobs,
@@ -62,6 +70,7 @@ impl DistMetadata {
             post_announce_jobs,
             publish_prereleases,
             force_latest,
+            changelog_format,
             create_release,
             github_releases_repo,
             github_releases_submodule_path,
@@ -89,23 +98,29 @@ impl DistMetadata {
             || auto_includes.is_some()
             || windows_archive.is_some()
             || unix_archive.is_some()
-            || package_libraries.is_some();
+            || package_libraries.is_some()
+            || third_party_licenses.is_some();
         let archive_layer = needs_archive_layer.then_some(ArchiveLayer {
             include,
             auto_includes,
             windows_archive,
             unix_archive,
             package_libraries,
+            third_party_licenses,
         });
         let needs_artifacts = archive_layer.is_some()
             || source_tarball.is_some()
             || extra_artifacts.is_some()
-            || checksum.is_some();
+            || checksum.is_some()
+            || sbom.is_some()
+            || symbol_server.is_some();
         let artifacts_layer = needs_artifacts.then_some(ArtifactLayer {
             archives: archive_layer,
             source_tarball,
             extra: extra_artifacts,
             checksum,
+            sbom,
+            symbol_server,
         });
 
         // Builds
@@ -113,7 +128,11 @@ impl DistMetadata {
             || precise_builds.is_some()
             || features.is_some()
             || default_features.is_some()
-            || all_features.is_some();
+            || all_features.is_some()
+            || target.is_some()
+            || cargo_builders.is_some()
+            || cargo_auditable.is_some()
+            || pgo.is_some();
         let cargo_layer = needs_cargo_build_layer.then_some(BoolOr::Val(CargoBuildLayer {
             common: CommonBuildLayer::default(),
             rust_toolchain_version,
@@ -121,16 +140,22 @@ impl DistMetadata {
             features,
             default_features,
             all_features,
+            target,
             msvc_crt_static,
+            builders: cargo_builders,
+            auditable: cargo_auditable,
+            pgo,
         }));
         let needs_build_layer = cargo_layer.is_some()
             || system_dependencies.is_some()
             || ssldotcom_windows_sign.is_some()
-            || msvc_crt_static.is_some();
+            || msvc_crt_static.is_some()
+            || binary_processors.is_some();
         let build_layer = needs_build_layer.then_some(BuildLayer {
             common: CommonBuildLayer {},
             ssldotcom_windows_sign,
             macos_sign,
+            processors: binary_processors,
             system_dependencies,
             cargo: cargo_layer,
             generic: None,
@@ -237,6 +262,7 @@ impl DistMetadata {
         let needs_host_layer = github_host_layer.is_some()
             || axodotdev_host_layer.is_some()
             || force_latest.is_some()
+            || changelog_format.is_some()
             || display.is_some()
             || display_name.is_some();
         let host_layer = needs_host_layer.then_some(HostLayer {
@@ -244,6 +270,7 @@ impl DistMetadata {
             github: github_host_layer,
             axodotdev: axodotdev_host_layer,
             force_latest,
+            changelog_format,
             display,
             display_name,
         });
@@ -15,6 +15,8 @@ pub struct ArchiveConfig {
     pub unix_archive: ZipStyle,
     /// Whether to include built libraries in the release archive
     pub package_libraries: Vec<LibraryStyle>,
+    /// Whether to include a THIRD-PARTY-LICENSES file for the crates built into the binaries
+    pub third_party_licenses: bool,
 }
 
 /// archive config (raw from config file)
@@ -48,6 +50,12 @@ pub struct ArchiveLayer {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default, with = "opt_string_or_vec")]
     pub package_libraries: Option<Vec<LibraryStyle>>,
+
+    /// Whether to include a THIRD-PARTY-LICENSES file for the crates built into the binaries
+    ///
+    /// Defaults to false.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub third_party_licenses: Option<bool>,
 }
 
 impl ArchiveConfig {
@@ -59,6 +67,7 @@ impl ArchiveConfig {
             windows_archive: ZipStyle::Zip,
             unix_archive: ZipStyle::Tar(CompressionImpl::Xzip),
             package_libraries: vec![],
+            third_party_licenses: false,
         }
     }
 }
@@ -73,6 +82,7 @@ impl ApplyLayer for ArchiveConfig {
             windows_archive,
             unix_archive,
             package_libraries,
+            third_party_licenses,
         }: Self::Layer,
     ) {
         self.include.apply_val(include);
@@ -80,6 +90,7 @@ impl ApplyLayer for ArchiveConfig {
         self.windows_archive.apply_val(windows_archive);
         self.unix_archive.apply_val(unix_archive);
         self.package_libraries.apply_val(package_libraries);
+        self.third_party_licenses.apply_val(third_party_licenses);
     }
 }
 impl ApplyLayer for ArchiveLayer {
@@ -92,6 +103,7 @@ impl ApplyLayer for ArchiveLayer {
             windows_archive,
             unix_archive,
             package_libraries,
+            third_party_licenses,
         }: Self::Layer,
     ) {
         self.include.apply_opt(include);
@@ -99,5 +111,6 @@ impl ApplyLayer for ArchiveLayer {
         self.windows_archive.apply_opt(windows_archive);
         self.unix_archive.apply_opt(unix_archive);
         self.package_libraries.apply_opt(package_libraries);
+        self.third_party_licenses.apply_opt(third_party_licenses);
     }
 }
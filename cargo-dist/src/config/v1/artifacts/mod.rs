@@ -12,6 +12,10 @@ pub struct AppArtifactConfig {
     pub archives: ArchiveConfig,
     /// Any extra artifacts and their buildscripts
     pub extra: Vec<ExtraArtifact>,
+    /// The format of the SBOM to generate for each build, if any
+    pub sbom: Option<SbomFormat>,
+    /// Whether to publish each build's symbols laid out for a symbol server
+    pub symbol_server: bool,
 }
 
 /// workspace artifact config (final)
@@ -43,6 +47,16 @@ pub struct ArtifactLayer {
     /// How to checksum
     #[serde(skip_serializing_if = "Option::is_none")]
     pub checksum: Option<ChecksumStyle>,
+
+    /// Generate a software bill of materials (SBOM) in this format for each build
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sbom: Option<SbomFormat>,
+
+    /// Whether to publish each build's symbols laid out for a symbol server
+    ///
+    /// (defaults to false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_server: Option<bool>,
 }
 impl AppArtifactConfig {
     /// get the defaults for a package
@@ -50,6 +64,8 @@ impl AppArtifactConfig {
         Self {
             archives: ArchiveConfig::defaults_for_package(workspaces, pkg_idx),
             extra: vec![],
+            sbom: None,
+            symbol_server: false,
         }
     }
 }
@@ -71,6 +87,8 @@ impl ApplyLayer for AppArtifactConfig {
         Self::Layer {
             archives,
             extra,
+            sbom,
+            symbol_server,
             // these are all workspace-only
             source_tarball: _,
             checksum: _,
@@ -78,6 +96,8 @@ impl ApplyLayer for AppArtifactConfig {
     ) {
         self.archives.apply_val_layer(archives);
         self.extra.apply_val(extra);
+        self.sbom.apply_opt(sbom);
+        self.symbol_server.apply_val(symbol_server);
     }
 }
 
@@ -91,6 +111,8 @@ impl ApplyLayer for WorkspaceArtifactConfig {
             // these are all app-only
             archives: _,
             extra: _,
+            sbom: _,
+            symbol_server: _,
         }: Self::Layer,
     ) {
         self.source_tarball.apply_val(source_tarball);
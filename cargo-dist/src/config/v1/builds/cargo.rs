@@ -1,6 +1,7 @@
 //! cargo build config
 
 use super::*;
+use crate::{errors::DistError, DistResult, PROFILE_DIST};
 
 /// cargo build config for the whole workspace
 #[derive(Debug, Clone)]
@@ -15,6 +16,15 @@ pub struct WorkspaceCargoBuildConfig {
 
     /// Build only the required packages, and individually
     pub precise_builds: Option<bool>,
+
+    /// The tool to use for cargo builds of specific targets (defaults to plain cargo)
+    pub builders: SortedMap<TargetTriple, CargoBuilder>,
+
+    /// Whether to build with `cargo auditable`
+    pub auditable: bool,
+
+    /// Settings for profile-guided optimization, if enabled
+    pub pgo: Option<PgoConfig>,
 }
 
 /// cargo build config for a specific app
@@ -33,6 +43,49 @@ pub struct AppCargoBuildConfig {
     ///
     /// (defaults to false)
     pub all_features: bool,
+    /// Overrides for specific targets, keyed by target triple or `cfg(...)` expression
+    pub target: SortedMap<String, CargoTargetBuildLayer>,
+}
+
+/// cargo build config for a specific app on a specific target
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CargoTargetBuildConfig {
+    /// A list of features to enable
+    pub features: Vec<String>,
+    /// Whether to enable default features
+    pub default_features: bool,
+    /// Whether to enable all features
+    pub all_features: bool,
+    /// Extra flags to add to RUSTFLAGS
+    pub rustflags: String,
+    /// Extra environment variables to set
+    pub env: SortedMap<String, String>,
+    /// The cargo profile to build with
+    pub profile: String,
+}
+
+/// cargo build config for a specific target (raw)
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct CargoTargetBuildLayer {
+    /// A list of features to enable (replaces the app's list)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub features: Option<Vec<String>>,
+    /// Whether to enable default features
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_features: Option<bool>,
+    /// Whether to enable all features
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub all_features: Option<bool>,
+    /// Extra flags to add to RUSTFLAGS (added to any earlier overrides' flags)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rustflags: Option<String>,
+    /// Extra environment variables to set (merged with any earlier overrides' variables)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub env: Option<SortedMap<String, String>>,
+    /// The cargo profile to build with (defaults to "dist")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub profile: Option<String>,
 }
 
 /// cargo build config (raw)
@@ -80,6 +133,41 @@ pub struct CargoBuildLayer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub precise_builds: Option<bool>,
 
+    /// The tool to use for cargo builds of specific targets, mapped by target triple
+    ///
+    /// The options are:
+    ///
+    /// * `"cargo"`: plain `cargo build` (the default)
+    /// * `"zigbuild"`: `cargo zigbuild`, which uses zig as a linker so that you
+    ///   can cross-compile without the target's toolchain installed
+    /// * `"zigbuild.2.17"`: the same, but linking against a specific glibc version
+    /// * `"cross"`: `cross build`, which builds inside a docker image for the target
+    ///
+    /// This lets you build e.g. aarch64 linux binaries on an x64 linux machine.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builders: Option<SortedMap<TargetTriple, CargoBuilder>>,
+
+    /// Whether to build with [cargo-auditable][], embedding the dependency tree in the binaries
+    ///
+    /// This lets tools like `cargo audit bin` check shipped binaries for vulnerable
+    /// dependencies. Requires cargo-auditable to be installed on the machine doing the builds.
+    ///
+    /// Defaults to false.
+    ///
+    /// [cargo-auditable]: https://github.com/rust-secure-code/cargo-auditable
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub auditable: Option<bool>,
+
+    /// Build with profile-guided optimization
+    ///
+    /// Each build becomes an instrumented build, followed by running `training-command`
+    /// against each instrumented binary (its path is in `CARGO_DIST_BINARY`), followed by
+    /// the real build optimized with the profiles that training produced.
+    ///
+    /// Training needs to run the binaries, so this only applies to builds for the host.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pgo: Option<PgoConfig>,
+
     /// A list of features to enable when building a package with cargo-dist
     ///
     /// (defaults to none)
@@ -95,6 +183,12 @@ pub struct CargoBuildLayer {
     /// (defaults to false)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub all_features: Option<bool>,
+    /// Overrides for specific targets, keyed by target triple or `cfg(...)` expression
+    ///
+    /// For instance `{ "cfg(target_os = \"linux\")" = { features = ["jemalloc"] } }`.
+    /// Matching `cfg(...)` overrides are applied first, then an exact triple match.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<SortedMap<String, CargoTargetBuildLayer>>,
 }
 
 impl WorkspaceCargoBuildConfig {
@@ -107,6 +201,9 @@ impl WorkspaceCargoBuildConfig {
             rust_toolchain_version: None,
             precise_builds: None,
             msvc_crt_static: true,
+            builders: SortedMap::new(),
+            auditable: false,
+            pgo: None,
         }
     }
 }
@@ -123,8 +220,51 @@ impl AppCargoBuildConfig {
             features: vec![],
             default_features: true,
             all_features: false,
+            target: SortedMap::new(),
         }
     }
+
+    /// Get the settings for building on a specific target, with any matching
+    /// `target` overrides applied
+    ///
+    /// `cfg(...)` overrides are applied first (in key order), and then an override
+    /// for the exact triple, so the most specific one wins.
+    pub fn for_target(&self, target: &TargetTriple) -> DistResult<CargoTargetBuildConfig> {
+        let mut config = CargoTargetBuildConfig {
+            features: self.features.clone(),
+            default_features: self.default_features,
+            all_features: self.all_features,
+            rustflags: String::new(),
+            env: SortedMap::new(),
+            profile: PROFILE_DIST.to_owned(),
+        };
+        for (spec, layer) in &self.target {
+            if spec.starts_with("cfg(") && cfg_matches(spec, target)? {
+                config.apply_layer(layer.clone());
+            }
+        }
+        if let Some(layer) = self.target.get(target) {
+            config.apply_layer(layer.clone());
+        }
+        Ok(config)
+    }
+}
+
+/// Check whether a `cfg(...)` expression is true for a target
+fn cfg_matches(spec: &str, target: &TargetTriple) -> DistResult<bool> {
+    let parsed = target_spec::TargetSpec::new(spec.to_owned()).map_err(|details| {
+        DistError::InvalidTargetCfg {
+            cfg: spec.to_owned(),
+            details,
+        }
+    })?;
+    // Targets rustc doesn't know about (like universal2-apple-darwin) don't match anything
+    let Ok(platform) =
+        target_spec::Platform::new(target.clone(), target_spec::TargetFeatures::Unknown)
+    else {
+        return Ok(false);
+    };
+    Ok(parsed.eval(&platform).unwrap_or(false))
 }
 
 impl ApplyLayer for WorkspaceCargoBuildConfig {
@@ -134,17 +274,24 @@ impl ApplyLayer for WorkspaceCargoBuildConfig {
         Self::Layer {
             rust_toolchain_version,
             precise_builds,
+            builders,
+            auditable,
+            pgo,
             // local-only
             common: _,
             msvc_crt_static: _,
             features: _,
             default_features: _,
             all_features: _,
+            target: _,
         }: Self::Layer,
     ) {
         self.rust_toolchain_version
             .apply_opt(rust_toolchain_version);
         self.precise_builds.apply_opt(precise_builds);
+        self.builders.apply_val(builders);
+        self.auditable.apply_val(auditable);
+        self.pgo.apply_opt(pgo);
     }
 }
 impl ApplyLayer for AppCargoBuildConfig {
@@ -156,17 +303,49 @@ impl ApplyLayer for AppCargoBuildConfig {
             features,
             default_features,
             all_features,
+            target,
 
             // global-only
             rust_toolchain_version: _,
             precise_builds: _,
             msvc_crt_static: _,
+            builders: _,
+            auditable: _,
+            pgo: _,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
         self.features.apply_val(features);
         self.default_features.apply_val(default_features);
         self.all_features.apply_val(all_features);
+        self.target.apply_val(target);
+    }
+}
+impl ApplyLayer for CargoTargetBuildConfig {
+    type Layer = CargoTargetBuildLayer;
+    fn apply_layer(
+        &mut self,
+        Self::Layer {
+            features,
+            default_features,
+            all_features,
+            rustflags,
+            env,
+            profile,
+        }: Self::Layer,
+    ) {
+        self.features.apply_val(features);
+        self.default_features.apply_val(default_features);
+        self.all_features.apply_val(all_features);
+        // These accumulate, so e.g. a cfg(unix) override and a triple override can both add flags
+        if let Some(rustflags) = rustflags {
+            self.rustflags.push(' ');
+            self.rustflags.push_str(&rustflags);
+        }
+        if let Some(env) = env {
+            self.env.extend(env);
+        }
+        self.profile.apply_val(profile);
     }
 }
 impl ApplyLayer for CargoBuildLayer {
@@ -178,9 +357,13 @@ impl ApplyLayer for CargoBuildLayer {
             rust_toolchain_version,
             precise_builds,
             msvc_crt_static,
+            builders,
+            auditable,
+            pgo,
             features,
             default_features,
             all_features,
+            target,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
@@ -188,9 +371,13 @@ impl ApplyLayer for CargoBuildLayer {
             .apply_opt(rust_toolchain_version);
         self.msvc_crt_static.apply_opt(msvc_crt_static);
         self.precise_builds.apply_opt(precise_builds);
+        self.builders.apply_opt(builders);
+        self.auditable.apply_opt(auditable);
+        self.pgo.apply_opt(pgo);
         self.features.apply_opt(features);
         self.default_features.apply_opt(default_features);
         self.all_features.apply_opt(all_features);
+        self.target.apply_opt(target);
     }
 }
 
@@ -16,6 +16,8 @@ pub struct WorkspaceBuildConfig {
     pub ssldotcom_windows_sign: Option<ProductionMode>,
     /// whether to sign macos binaries with apple
     pub macos_sign: bool,
+    /// post-build processors to run on binaries, in order
+    pub processors: Vec<BinaryProcessor>,
 }
 
 /// app-scoped build config
@@ -38,6 +40,8 @@ pub struct BuildConfigInheritable {
     pub ssldotcom_windows_sign: Option<ProductionMode>,
     /// whether to sign macos binaries with apple
     pub macos_sign: Option<bool>,
+    /// post-build processors to run on binaries, in order
+    pub processors: Option<Vec<BinaryProcessor>>,
     /// cargo builds
     pub cargo: Option<CargoBuildLayer>,
     /// generic builds
@@ -62,6 +66,10 @@ pub struct BuildLayer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub macos_sign: Option<bool>,
 
+    /// post-build processors to run on binaries, in order
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub processors: Option<Vec<BinaryProcessor>>,
+
     /// cargo builds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cargo: Option<BoolOr<CargoBuildLayer>>,
@@ -83,6 +91,7 @@ impl BuildConfigInheritable {
             system_dependencies: Default::default(),
             ssldotcom_windows_sign: None,
             macos_sign: None,
+            processors: None,
         }
     }
     /// get defaults for a workspace
@@ -94,6 +103,7 @@ impl BuildConfigInheritable {
             system_dependencies: Default::default(),
             ssldotcom_windows_sign: None,
             macos_sign: None,
+            processors: None,
         }
     }
     /// apply inheritance to get final workspace config
@@ -106,6 +116,7 @@ impl BuildConfigInheritable {
             cargo,
             ssldotcom_windows_sign,
             macos_sign,
+            processors,
             // local-only
             generic: _,
             system_dependencies: _,
@@ -118,6 +129,7 @@ impl BuildConfigInheritable {
             cargo: cargo_out,
             macos_sign: macos_sign.unwrap_or(false),
             ssldotcom_windows_sign,
+            processors: processors.unwrap_or_default(),
         }
     }
     /// apply inheritance to get final package config
@@ -134,6 +146,7 @@ impl BuildConfigInheritable {
             // local-only
             ssldotcom_windows_sign: _,
             macos_sign: _,
+            processors: _,
         } = self;
         let mut cargo_out = AppCargoBuildConfig::defaults_for_package(workspaces, pkg_idx, &common);
         if let Some(cargo) = cargo {
@@ -163,6 +176,7 @@ impl ApplyLayer for BuildConfigInheritable {
             system_dependencies,
             ssldotcom_windows_sign,
             macos_sign,
+            processors,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
@@ -172,6 +186,7 @@ impl ApplyLayer for BuildConfigInheritable {
         self.ssldotcom_windows_sign
             .apply_opt(ssldotcom_windows_sign);
         self.macos_sign.apply_opt(macos_sign);
+        self.processors.apply_opt(processors);
     }
 }
 
@@ -22,6 +22,8 @@ pub struct AppHostConfig {
 pub struct WorkspaceHostConfig {
     /// Always regard releases as stable
     pub force_latest: bool,
+    /// Where to get the release notes for an announcement from
+    pub changelog_format: ChangelogFormat,
     /// github host config (github releases)
     pub github: Option<GithubHostConfig>,
     /// axodotdev host config (axo releases)
@@ -34,6 +36,8 @@ pub struct HostConfigInheritable {
     pub common: CommonHostConfig,
     /// Always regard releases as stable
     pub force_latest: Option<bool>,
+    /// Where to get the release notes for an announcement from
+    pub changelog_format: Option<ChangelogFormat>,
     /// Whether artifacts/installers for this app should be displayed in release bodies
     pub display: Option<bool>,
     /// How to refer to the app in release bodies
@@ -64,6 +68,16 @@ pub struct HostLayer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub force_latest: Option<bool>,
 
+    /// Where to get the release notes for an announcement from
+    ///
+    /// (defaults to "file")
+    ///
+    /// "file" uses the entry for the version in the workspace's changelog,
+    /// or else each released package's changelog. "conventional-commits"
+    /// generates notes from the git history since the previous tag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog_format: Option<ChangelogFormat>,
+
     /// Whether artifacts/installers for this app should be displayed in release bodies
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display: Option<bool>,
@@ -87,6 +101,7 @@ impl HostConfigInheritable {
             github: None,
             axodotdev: None,
             force_latest: None,
+            changelog_format: None,
             display: None,
             display_name: None,
         }
@@ -98,6 +113,7 @@ impl HostConfigInheritable {
             github: None,
             axodotdev: None,
             force_latest: None,
+            changelog_format: None,
             display: None,
             display_name: None,
         }
@@ -113,6 +129,7 @@ impl HostConfigInheritable {
             github: _,
             axodotdev: _,
             force_latest: _,
+            changelog_format: _,
             display,
             display_name,
         } = self;
@@ -133,6 +150,7 @@ impl HostConfigInheritable {
             github,
             axodotdev,
             force_latest,
+            changelog_format,
             display: _,
             display_name: _,
         } = self;
@@ -150,6 +168,7 @@ impl HostConfigInheritable {
             github,
             axodotdev,
             force_latest: force_latest.unwrap_or(false),
+            changelog_format: changelog_format.unwrap_or_default(),
         }
     }
 }
@@ -163,6 +182,7 @@ impl ApplyLayer for HostConfigInheritable {
             github,
             axodotdev,
             force_latest,
+            changelog_format,
             display,
             display_name,
         }: Self::Layer,
@@ -171,6 +191,7 @@ impl ApplyLayer for HostConfigInheritable {
         self.github.apply_bool_layer(github);
         self.axodotdev.apply_bool_layer(axodotdev);
         self.force_latest.apply_opt(force_latest);
+        self.changelog_format.apply_opt(changelog_format);
         self.display.apply_opt(display);
         self.display_name.apply_opt(display_name);
     }
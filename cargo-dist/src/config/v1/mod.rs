@@ -398,9 +398,9 @@ pub struct TomlLayer {
     /// The inputs should be valid rustc target triples (see `rustc --print target-list`) such
     /// as `x86_64-pc-windows-msvc`, `aarch64-apple-darwin`, or `x86_64-unknown-linux-gnu`.
     ///
-    /// FIXME: We should also accept one magic target: `universal2-apple-darwin`. This will induce
+    /// There's also one magic target: `universal2-apple-darwin`. This will induce
     /// us to build `x86_64-apple-darwin` and `aarch64-apple-darwin` (arm64) and then combine
-    /// them into a "universal" binary that can run on either arch (using apple's `lipo` tool).
+    /// them into a "universal" binary that can run on either arch (like apple's `lipo` tool).
     ///
     /// FIXME: Allow higher level requests like "[macos, windows, linux] x [x86_64, aarch64]"?
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -173,6 +173,16 @@ pub enum DistError {
         path: String,
     },
 
+    /// Unknown value for a cargo builder
+    ///
+    /// NOTE: we can't use `diagnostic(help)` here because this will get crammed into
+    /// a serde_json error, reducing it to a String. So we inline the help!
+    #[error(r#"cargo builder "{builder}" is unknown (it can be "cargo", "cross", "zigbuild", or "zigbuild.2.17" to pick a glibc version)"#)]
+    CargoBuilderInvalid {
+        /// The value that was passed
+        builder: String,
+    },
+
     /// Being pedantic about the env-var mode of install-path to be consistent
     ///
     /// NOTE: we can't use `diagnostic(help)` here because this will get crammed into
@@ -351,6 +361,16 @@ pub enum DistError {
         path: String,
     },
 
+    /// cargo-auditable was requested for a target built with cross
+    #[error("cargo-auditable is enabled, but {target} is built with cross")]
+    #[diagnostic(help(
+        "cargo-auditable can't run inside cross's docker images, try building {target} with zigbuild instead"
+    ))]
+    AuditableWithCross {
+        /// the target built with cross
+        target: String,
+    },
+
     /// A required tool is missing
     #[error("{tool}, required to run this task, is missing")]
     #[diagnostic(help("Ensure {tool} is installed"))]
@@ -374,6 +394,89 @@ pub enum DistError {
         extension: String,
     },
 
+    /// Symbols we planned to ship for a binary weren't produced by the build
+    #[error("couldn't find the {kind} symbols for {bin_name}")]
+    #[diagnostic(help("these should have been produced by the build, based on your cargo profile and binary-processors settings"))]
+    MissingSymbols {
+        /// The binary
+        bin_name: String,
+        /// The kind of symbols
+        kind: String,
+    },
+
+    /// A `cfg(...)` expression in cargo target overrides couldn't be parsed
+    #[error("couldn't parse the target override {cfg}")]
+    #[diagnostic(help("this should look like cfg(target_os = \"linux\"), like in Cargo.toml"))]
+    InvalidTargetCfg {
+        /// The expression
+        cfg: String,
+        /// The error
+        #[source]
+        details: target_spec::Error,
+    },
+
+    /// A build command exited non-zero
+    #[error("build command `{command}` failed ({status})")]
+    #[diagnostic(help("its full output was saved to {log_path}"))]
+    BuildCommandFailed {
+        /// The command that was run
+        command: String,
+        /// How it exited
+        status: std::process::ExitStatus,
+        /// Where its output was logged
+        log_path: Utf8PathBuf,
+    },
+
+    /// We don't know what Go calls a target
+    #[error("couldn't build Go binaries for {target}, because we don't know its GOOS/GOARCH")]
+    #[diagnostic(help("you may need to remove it from this package's targets"))]
+    GoUnsupportedTarget {
+        /// The target triple
+        target: String,
+    },
+
+    /// We couldn't find the main packages of a Go module
+    #[error("couldn't find the Go main packages of {manifest_path}")]
+    GoMainPackages {
+        /// The go.mod
+        manifest_path: Utf8PathBuf,
+        /// What went wrong
+        #[diagnostic_source]
+        inner: AxoprojectError,
+    },
+
+    /// maturin succeeded, but we couldn't find any wheels
+    #[error("maturin didn't produce any wheels in {out_dir}")]
+    #[diagnostic(help("its full output was saved to {log_path}"))]
+    NoWheels {
+        /// Where maturin was told to put them
+        out_dir: Utf8PathBuf,
+        /// Where its output was logged
+        log_path: Utf8PathBuf,
+    },
+
+    /// The pgo training command was empty
+    #[error("pgo is enabled, but its training-command is empty")]
+    #[diagnostic(help(
+        "set training-command to the program (and arguments) that exercises your binary"
+    ))]
+    PgoEmptyTrainingCommand {},
+
+    /// A pgo training command didn't produce any profiles
+    #[error("pgo training didn't produce any profiles in {profile_dir}")]
+    #[diagnostic(help(
+        "make sure your training command runs the binary in $CARGO_DIST_BINARY, and that it exits normally"
+    ))]
+    PgoNoProfiles {
+        /// Where the profiles should have been written
+        profile_dir: Utf8PathBuf,
+    },
+
+    /// llvm-profdata is needed to merge pgo profiles
+    #[error("llvm-profdata, required to merge pgo profiles, is missing")]
+    #[diagnostic(help("run `rustup component add llvm-tools-preview` to install it"))]
+    PgoProfdataMissing {},
+
     /// Binaries were missing
     #[error("failed to find bin {bin_name} for {pkg_name}")]
     #[diagnostic(help("did the above build fail?"))]
@@ -571,6 +674,231 @@ pub enum DistError {
         /// Version the project uses
         your_version: semver::Version,
     },
+
+    /// A file we wanted to upload already exists on the host with different contents
+    #[error("{host} already has a copy of {name} that doesn't match the one we built")]
+    #[diagnostic(help(
+        "expected checksum {local}, but the host has {remote}\nIf the file you just built should replace it, rerun with --replace-mismatched"
+    ))]
+    UploadMismatch {
+        /// The hosting provider
+        host: String,
+        /// Name of the file
+        name: String,
+        /// Checksum of the file we built
+        local: String,
+        /// Checksum of the file on the host
+        remote: String,
+    },
+
+    /// A download failed with an unexpected HTTP status
+    #[error("failed to fetch {url}: the server responded with HTTP {status}")]
+    FetchFailed {
+        /// The URL we were fetching
+        url: String,
+        /// The HTTP status code
+        status: u16,
+    },
+
+    /// A `gh` command failed
+    #[error("failed to {desc}")]
+    #[diagnostic(help("gh said:\n{stderr}"))]
+    GithubCliFailed {
+        /// What we were trying to do
+        desc: String,
+        /// What gh printed to stderr
+        stderr: String,
+    },
+
+    /// `cargo dist verify` couldn't find a dist-manifest.json
+    #[error("couldn't find {manifest} in {location}")]
+    VerifyNoManifest {
+        /// Where we looked
+        location: String,
+        /// The name of the manifest
+        manifest: String,
+    },
+
+    /// `cargo dist verify` couldn't figure out where a tagged release is hosted
+    #[error("couldn't determine where the release for {tag} is hosted")]
+    #[diagnostic(help("pass the path or URL of the release's dist-manifest.json instead"))]
+    VerifyNoHosting {
+        /// The tag of the release
+        tag: String,
+    },
+
+    /// `cargo dist verify` found problems with a release
+    #[error("the release has {} problem(s):\n{}", problems.len(), problems.join("\n"))]
+    VerifyFailed {
+        /// Every problem found, prefixed with the file it was found in
+        problems: Vec<String>,
+    },
+
+    /// `cargo dist manifest-schema --schema-version` was passed a version that doesn't exist
+    #[error("there is no dist-manifest schema version {version}")]
+    #[diagnostic(help("the latest schema version is {latest}"))]
+    UnknownSchemaVersion {
+        /// The requested version
+        version: u32,
+        /// The latest version we know about
+        latest: u32,
+    },
+
+    /// SOURCE_DATE_EPOCH wasn't a number
+    #[error("SOURCE_DATE_EPOCH should be a unix timestamp, but it was \"{value}\"")]
+    #[diagnostic(help("see https://reproducible-builds.org/specs/source-date-epoch/"))]
+    InvalidSourceDateEpoch {
+        /// The value of the env var
+        value: String,
+    },
+
+    /// `cargo dist build --check-reproducible` got different results the second time
+    #[error("these artifacts were different when rebuilt:\n{}", artifacts.join("\n"))]
+    #[diagnostic(help(
+        "archives are reproducible, so this is probably the binaries or installers themselves"
+    ))]
+    NotReproducible {
+        /// The artifacts that differed
+        artifacts: Vec<String>,
+    },
+
+    /// We couldn't get the package graph for an SBOM or license notices
+    #[error("failed to read the dependencies of {manifest_path}")]
+    #[diagnostic(help(
+        "this runs `cargo metadata --offline`, so all dependencies need to have been downloaded already"
+    ))]
+    CargoDependencyMetadata {
+        /// The Cargo.toml of the workspace
+        manifest_path: Utf8PathBuf,
+        /// The underlying error
+        #[source]
+        details: guppy::Error,
+    },
+
+    /// We couldn't work out which crates go into a binary for an SBOM or license notices
+    #[error("failed to resolve the dependencies of {package} on {target}")]
+    CargoDependencyResolve {
+        /// The package the binary is from
+        package: String,
+        /// The target we were resolving for
+        target: String,
+        /// The underlying error
+        #[source]
+        details: guppy::Error,
+    },
+
+    /// An input to a universal macOS binary wasn't a single-arch Mach-O file
+    #[error("{path} isn't a single-architecture Mach-O file, so it can't be merged into a universal binary")]
+    #[diagnostic(help(
+        "universal2-apple-darwin is only supported for Rust binaries and libraries"
+    ))]
+    LipoInputNotMachO {
+        /// The file we tried to merge
+        path: Utf8PathBuf,
+    },
+
+    /// Two inputs to a universal macOS binary were for the same architecture
+    #[error("{path} has the same architecture as another input to its universal binary")]
+    #[diagnostic(help("This is probably not your fault, please file an issue!"))]
+    LipoDuplicateArch {
+        /// The file we tried to merge
+        path: Utf8PathBuf,
+    },
+
+    /// A universal macOS binary would be too big for the fat Mach-O format
+    #[error("the universal binary {path} would be over 4GiB, which the fat Mach-O format can't describe")]
+    LipoTooLarge {
+        /// The file we were trying to produce
+        path: Utf8PathBuf,
+    },
+
+    /// `cargo dist manifest-schema --check` found breaking changes
+    #[error("the dist-manifest schema has {} breaking change(s) compared to {path}:\n{}", changes.len(), changes.join("\n"))]
+    #[diagnostic(help(
+        "tools reading dist-manifest.json may no longer understand it, bump SCHEMA_VERSION in cargo-dist-schema"
+    ))]
+    SchemaIncompatible {
+        /// The schema we compared against
+        path: Utf8PathBuf,
+        /// Every breaking change found
+        changes: Vec<String>,
+    },
+
+    /// `cargo dist release` was asked to release a package that doesn't exist
+    #[error("there's no package named {package} in this workspace")]
+    ReleaseUnknownPackage {
+        /// The name we were given
+        package: String,
+    },
+
+    /// `cargo dist release` found packages with different versions
+    #[error("the packages being released don't all have the same version:\n{}", versions.join("\n"))]
+    #[diagnostic(help(
+        "pass --package to release one of them on its own, or give them all the same version"
+    ))]
+    ReleaseMixedVersions {
+        /// Each package and its version
+        versions: Vec<String>,
+    },
+
+    /// `cargo dist release` would go backwards (or nowhere)
+    #[error("can't release {new_version}, it's not newer than the current version {old_version}")]
+    ReleaseNotNewer {
+        /// The current version
+        old_version: semver::Version,
+        /// The version we were asked for
+        new_version: semver::Version,
+    },
+
+    /// `cargo dist release` needs a git repo to commit and tag in
+    #[error("cargo dist release needs to be run in a git repository")]
+    ReleaseNoRepo,
+
+    /// `cargo dist release` doesn't want to commit unrelated changes
+    #[error("your git working tree has uncommitted changes:\n{status}")]
+    #[diagnostic(help("commit or stash them before releasing"))]
+    ReleaseDirtyRepo {
+        /// The output of `git status --porcelain`
+        status: String,
+    },
+
+    /// `cargo dist release` computed a tag that already exists
+    #[error("the tag {tag} already exists")]
+    ReleaseTagExists {
+        /// The tag
+        tag: String,
+    },
+
+    /// `cargo dist release` doesn't know how to change this kind of package's version
+    #[error("don't know how to change the version in {manifest_path}")]
+    #[diagnostic(help("set the version in a dist.toml next to it instead"))]
+    ReleaseUnsupportedManifest {
+        /// The package's manifest
+        manifest_path: Utf8PathBuf,
+    },
+
+    /// `cargo dist release` couldn't find the version it was supposed to change
+    #[error("couldn't find version {version} in {manifest_path}")]
+    ReleaseVersionNotFound {
+        /// The manifest we looked in
+        manifest_path: Utf8PathBuf,
+        /// The version we expected to find
+        version: String,
+    },
+
+    /// `cargo dist changed` needs git history to compare against
+    #[error("cargo dist changed needs to be run in a git repository")]
+    ChangedNoRepo,
+
+    /// `cargo dist changed` couldn't diff against a previous release
+    #[error("couldn't find the files changed since {tag}")]
+    ChangedDiffFailed {
+        /// The tag we were diffing against
+        tag: String,
+        /// The error git gave us
+        #[diagnostic_source]
+        inner: AxoprojectError,
+    },
 }
 
 /// This error indicates we tried to deserialize some YAML with serde_yml
@@ -5,17 +5,21 @@ use crate::{
     check_integrity,
     config::{
         v1::{ci::CiConfig, hosts::WorkspaceHostConfig},
-        CiStyle, Config, HostArgs, HostStyle, HostingStyle,
+        ChecksumStyle, CiStyle, Config, HostArgs, HostStyle, HostingStyle,
     },
     errors::DistResult,
-    gather_work,
+    gather_work, generate_checksum,
     manifest::save_manifest,
-    net::create_gazenot_client,
-    DistError, DistGraph, DistGraphBuilder, HostingInfo,
+    net::{create_gazenot_client, fetch_file},
+    DistError, DistGraph, DistGraphBuilder, HostingInfo, SortedMap,
 };
+use axoasset::SourceFile;
+use axoprocess::Cmd;
 use axoproject::WorkspaceGraph;
-use cargo_dist_schema::{DistManifest, Hosting};
-use gazenot::{AnnouncementKey, Gazenot};
+use camino::Utf8Path;
+use cargo_dist_schema::{ArtifactKind, DistManifest, Hosting};
+use gazenot::{error::GazenotError, AnnouncementKey, ArtifactSet, Gazenot};
+use serde::Deserialize;
 
 /// Do hosting
 pub fn do_host(cfg: &Config, host_args: HostArgs) -> DistResult<DistManifest> {
@@ -48,7 +52,7 @@ pub fn do_host(cfg: &Config, host_args: HostArgs) -> DistResult<DistManifest> {
                         // The currently in-memory manifest has more information about stuff that
                         // the original 'plan' couldn't: system info, linkage, hashes, symbols, ...
                         save_manifest(&dist.dist_dir.join("dist-manifest.json"), &manifest)?;
-                        upload_to_hosting(&dist, &manifest, &abyss)?;
+                        upload_to_hosting(&dist, &manifest, &abyss, host_args.replace_mismatched)?;
                     }
                     if host_args.steps.contains(&HostStyle::Release) {
                         // note that this mutates the manifest with new Release URLs, to be written back!
@@ -61,7 +65,12 @@ pub fn do_host(cfg: &Config, host_args: HostArgs) -> DistResult<DistManifest> {
                     }
                 }
                 HostingStyle::Github => {
-                    // implemented in CI backend
+                    // Creating the Release is implemented in CI backend, but if it
+                    // already exists we can fill in whatever files it's missing
+                    if host_args.steps.contains(&HostStyle::Upload) {
+                        save_manifest(&dist.dist_dir.join("dist-manifest.json"), &manifest)?;
+                        upload_to_github(&dist, &manifest, hosting, host_args.replace_mismatched)?;
+                    }
                 }
             }
         }
@@ -93,6 +102,7 @@ impl<'a> DistGraphBuilder<'a> {
                 github,
                 axodotdev,
                 force_latest: _,
+                changelog_format: _,
             } = &self.inner.config.hosts;
             if github.is_some() {
                 hosting.push(HostingStyle::Github);
@@ -210,33 +220,472 @@ fn check_hosting(_dist: &DistGraph, _manifest: &DistManifest, _abyss: &Gazenot)
     Ok(())
 }
 
-fn upload_to_hosting(dist: &DistGraph, manifest: &DistManifest, abyss: &Gazenot) -> DistResult<()> {
-    // Gather up the files to upload for each release
-    let files = manifest.releases.iter().filter_map(|release| {
+/// The state of a file we want to upload, according to the host
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum RemoteFile {
+    /// The host doesn't have it
+    Missing,
+    /// The host has a broken copy from an interrupted upload
+    Partial,
+    /// The host has it, and maybe knows its checksum
+    Present {
+        /// The checksum of the hosted copy, if known
+        checksum: Option<String>,
+    },
+}
+
+/// What to do with a file we want to upload
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum UploadAction {
+    /// The host doesn't have it, upload it
+    Upload,
+    /// The host has a different copy, overwrite it
+    Replace,
+    /// The host already has this exact file, leave it be
+    Skip,
+}
+
+/// Decide what to do with a file we want to upload, given what the host has
+///
+/// `local_checksum` is only invoked if the host already has a copy with a known
+/// checksum, as computing it can be expensive for big archives. It should use the same algorithm as
+/// the checksum in `remote`.
+pub(crate) fn plan_upload(
+    host: &str,
+    name: &str,
+    remote: &RemoteFile,
+    replace_mismatched: bool,
+    local_checksum: impl FnOnce() -> DistResult<String>,
+) -> DistResult<UploadAction> {
+    let remote_checksum = match remote {
+        RemoteFile::Missing => return Ok(UploadAction::Upload),
+        // Whatever is there is garbage, so there's nothing to lose
+        RemoteFile::Partial => return Ok(UploadAction::Replace),
+        RemoteFile::Present { checksum } => checksum,
+    };
+    let Some(remote_checksum) = remote_checksum else {
+        // We can't tell if it's the same file, but nothing says it isn't, so just
+        // make sure the host ends up with ours
+        return Ok(UploadAction::Replace);
+    };
+    let local = local_checksum()?;
+    if remote_checksum.eq_ignore_ascii_case(&local) {
+        return Ok(UploadAction::Skip);
+    }
+    if replace_mismatched {
+        return Ok(UploadAction::Replace);
+    }
+    Err(DistError::UploadMismatch {
+        host: host.to_owned(),
+        name: name.to_owned(),
+        local,
+        remote: remote_checksum.clone(),
+    })
+}
+
+/// Get the checksum of a file we want to upload, preferring the one in the manifest
+fn local_checksum(
+    manifest: &DistManifest,
+    name: &str,
+    checksum: ChecksumStyle,
+    path: &Utf8Path,
+) -> DistResult<String> {
+    let known = manifest
+        .artifacts
+        .get(name)
+        .and_then(|artifact| artifact.checksums.get(checksum.ext()));
+    match known {
+        Some(known) => Ok(known.clone()),
+        None => generate_checksum(&checksum, path),
+    }
+}
+
+fn upload_to_hosting(
+    dist: &DistGraph,
+    manifest: &DistManifest,
+    abyss: &Gazenot,
+    replace_mismatched: bool,
+) -> DistResult<()> {
+    let runtime = tokio::runtime::Handle::current();
+    let retry = dist.client_settings.retry;
+    let checksum = dist.config.artifacts.checksum;
+
+    for release in &manifest.releases {
         // Github Releases only has semantics on Announce
         let Hosting {
             axodotdev,
             github: _,
         } = &release.hosting;
-        if let Some(set) = axodotdev {
-            // Upload all files associated with this Release, plus the dist-manifest.json
-            let files = manifest
-                .artifacts_for_release(release)
-                .filter_map(|(_id, artifact)| artifact.name.as_deref())
-                .chain(Some("dist-manifest.json"))
-                .map(|name| dist.dist_dir.join(name))
-                .collect::<Vec<_>>();
-            Some((set, files))
-        } else {
-            None
+        let Some(set) = axodotdev else {
+            continue;
+        };
+
+        // Upload all files associated with this Release, plus the dist-manifest.json.
+        //
+        // Checksum files are uploaded only after everything else, so finding one on the
+        // host means the file it checksums was fully uploaded. This is what lets us tell
+        // which files a previous run already took care of.
+        let (checksum_files, files): (Vec<_>, Vec<_>) = manifest
+            .artifacts_for_release(release)
+            .filter_map(|(_id, artifact)| Some((artifact.name.as_deref()?, artifact)))
+            .partition(|(_name, artifact)| matches!(artifact.kind, ArtifactKind::Checksum));
+
+        for (name, artifact) in files {
+            let path = dist.dist_dir.join(name);
+            let checksum_file = artifact
+                .checksum
+                .as_ref()
+                .and_then(|id| manifest.artifacts.get(id)?.name.as_deref());
+            let remote = match checksum_file {
+                Some(checksum_file) if !set.is_mock() => {
+                    let url = hosted_file_url(abyss, set, checksum_file)?;
+                    let desc = format!("check if {name} is already hosted");
+                    let hosted =
+                        runtime.block_on(retry.run(&desc, || fetch_file(&dist.axoclient, &url)))?;
+                    match hosted {
+                        Some(contents) => RemoteFile::Present {
+                            checksum: String::from_utf8_lossy(&contents)
+                                .split_whitespace()
+                                .next()
+                                .map(ToOwned::to_owned),
+                        },
+                        None => RemoteFile::Missing,
+                    }
+                }
+                // Without a checksum file we have no way of knowing, so always upload
+                _ => RemoteFile::Missing,
+            };
+            let action = plan_upload("axo Releases", name, &remote, replace_mismatched, || {
+                local_checksum(manifest, name, checksum, &path)
+            })?;
+            upload_to_artifact_set(dist, abyss, set, name, action)?;
+        }
+
+        for (name, _artifact) in checksum_files {
+            let path = dist.dist_dir.join(name);
+            // These are small enough to just compare the hosted copy directly
+            let remote = if set.is_mock() {
+                RemoteFile::Missing
+            } else {
+                let url = hosted_file_url(abyss, set, name)?;
+                let desc = format!("check if {name} is already hosted");
+                let hosted =
+                    runtime.block_on(retry.run(&desc, || fetch_file(&dist.axoclient, &url)))?;
+                match hosted {
+                    Some(contents) => RemoteFile::Present {
+                        checksum: Some(sha256_hex(&contents)),
+                    },
+                    None => RemoteFile::Missing,
+                }
+            };
+            let action = plan_upload("axo Releases", name, &remote, replace_mismatched, || {
+                generate_checksum(&ChecksumStyle::Sha256, &path)
+            })?;
+            upload_to_artifact_set(dist, abyss, set, name, action)?;
         }
-    });
 
-    tokio::runtime::Handle::current().block_on(abyss.upload_files(files))?;
+        // The dist-manifest.json is always refreshed, since it's ours to update
+        upload_to_artifact_set(
+            dist,
+            abyss,
+            set,
+            "dist-manifest.json",
+            UploadAction::Replace,
+        )?;
+    }
+
     eprintln!("all artifacts hosted!");
     Ok(())
 }
 
+/// Upload a single file to an ArtifactSet (if it needs to be), retrying on failure
+fn upload_to_artifact_set(
+    dist: &DistGraph,
+    abyss: &Gazenot,
+    set: &ArtifactSet,
+    name: &str,
+    action: UploadAction,
+) -> DistResult<()> {
+    if action == UploadAction::Skip {
+        eprintln!("{name} is already hosted, skipping");
+        return Ok(());
+    }
+    let path = dist.dist_dir.join(name);
+    let desc = format!("upload {name}");
+    tokio::runtime::Handle::current().block_on(dist.client_settings.retry.run(&desc, || {
+        abyss.upload_files(Some((set, vec![path.clone()])))
+    }))?;
+    Ok(())
+}
+
+/// Get the URL a file in an ArtifactSet can be downloaded from
+fn hosted_file_url(abyss: &Gazenot, set: &ArtifactSet, name: &str) -> DistResult<String> {
+    let url = abyss
+        .download_artifact_set_url(set, name)
+        .map_err(|e| GazenotError::new(format!("get download url for {name}"), e))?;
+    Ok(url.to_string())
+}
+
+fn sha256_hex(contents: &[u8]) -> String {
+    use sha2::Digest;
+    format!("{:x}", sha2::Sha256::digest(contents))
+}
+
+/// The parts of `gh release view --json assets` we care about
+#[derive(Deserialize)]
+struct GithubReleaseAssets {
+    assets: Vec<GithubReleaseAsset>,
+}
+
+#[derive(Deserialize)]
+struct GithubReleaseAsset {
+    name: String,
+    /// "uploaded" if the upload completed
+    state: String,
+    /// "sha256:<hex>", only reported for assets uploaded after GitHub started computing these
+    #[serde(default)]
+    digest: Option<String>,
+}
+
+/// Upload files to an existing Github Release
+///
+/// If the Github Release doesn't exist yet, this does nothing, as CI uploads everything
+/// when it creates it. Otherwise the Release's files are brought up to date, so that
+/// rerunning CI after an interrupted upload picks up where it left off.
+fn upload_to_github(
+    dist: &DistGraph,
+    manifest: &DistManifest,
+    hosting: &HostingInfo,
+    replace_mismatched: bool,
+) -> DistResult<()> {
+    let Some(tag) = manifest.announcement_tag.as_deref() else {
+        return Ok(());
+    };
+    let external_repo = dist
+        .config
+        .hosts
+        .github
+        .as_ref()
+        .and_then(|github| github.repo.as_ref());
+    let repo = match external_repo {
+        Some(external) => format!("{}/{}", external.owner, external.repo),
+        None => format!("{}/{}", hosting.owner, hosting.project),
+    };
+    if external_repo.is_some() && !can_push_to_github_repo(&repo) {
+        // Only the job creating the Release has a token that can write to the other repo
+        eprintln!(
+            "Github Release is in {repo}, which we can't write to here, files will be uploaded when publishing"
+        );
+        return Ok(());
+    }
+    let runtime = tokio::runtime::Handle::current();
+    let retry = dist.client_settings.retry;
+
+    let desc = format!("list files in Github Release {tag}");
+    let remote =
+        runtime.block_on(retry.run(&desc, || async { github_release_assets(&repo, tag) }))?;
+    let Some(remote) = remote else {
+        eprintln!(
+            "Github Release {tag} doesn't exist yet, files will be uploaded when it's created"
+        );
+        return Ok(());
+    };
+
+    // Upload all files associated with the Releases hosted on Github
+    let files = manifest
+        .releases
+        .iter()
+        .filter(|release| release.hosting.github.is_some())
+        .flat_map(|release| manifest.artifacts_for_release(release))
+        .filter_map(|(_id, artifact)| Some((artifact.name.as_deref()?, artifact)))
+        .collect::<SortedMap<_, _>>();
+    for (name, artifact) in files {
+        let path = dist.dist_dir.join(name);
+        let checksum_file = artifact
+            .checksum
+            .as_ref()
+            .and_then(|id| manifest.artifacts.get(id)?.name.as_deref());
+        let remote = github_remote_file(&remote, name, checksum_file, |checksum_file| {
+            let desc = format!("check if {name} is already uploaded");
+            runtime.block_on(retry.run(&desc, || async {
+                github_release_file(&repo, tag, checksum_file)
+            }))
+        })?;
+        let action = plan_upload("Github Releases", name, &remote, replace_mismatched, || {
+            local_checksum(manifest, name, ChecksumStyle::Sha256, &path)
+        })?;
+        upload_to_github_release(dist, &repo, tag, name, action)?;
+    }
+    // The dist-manifest.json is always refreshed, since it's ours to update
+    upload_to_github_release(
+        dist,
+        &repo,
+        tag,
+        "dist-manifest.json",
+        UploadAction::Replace,
+    )?;
+
+    eprintln!("all artifacts uploaded to Github Release {tag}!");
+    Ok(())
+}
+
+/// Whether the current Github token can write to a repo
+fn can_push_to_github_repo(repo: &str) -> bool {
+    let mut cmd = Cmd::new("gh", "check Github repo permissions");
+    cmd.arg("api")
+        .arg(format!("repos/{repo}"))
+        .arg("--jq")
+        .arg(".permissions.push")
+        .check(false);
+    cmd.output().is_ok_and(|output| {
+        output.status.success() && String::from_utf8_lossy(&output.stdout).trim() == "true"
+    })
+}
+
+/// Run a `gh` command, capturing its output so failures can be explained (and retried)
+fn run_gh(mut cmd: Cmd, desc: &str) -> DistResult<std::process::Output> {
+    let output = cmd.check(false).output()?;
+    if !output.status.success() {
+        return Err(DistError::GithubCliFailed {
+            desc: desc.to_owned(),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        });
+    }
+    Ok(output)
+}
+
+/// Get the files a Github Release has, or None if the Release doesn't exist
+fn github_release_assets(
+    repo: &str,
+    tag: &str,
+) -> DistResult<Option<SortedMap<String, RemoteFile>>> {
+    let mut cmd = Cmd::new("gh", "list Github Release files");
+    cmd.arg("release")
+        .arg("view")
+        .arg(tag)
+        .arg("--repo")
+        .arg(repo)
+        .arg("--json")
+        .arg("assets");
+    let output = match run_gh(cmd, &format!("list files in Github Release {tag}")) {
+        Ok(output) => output,
+        Err(DistError::GithubCliFailed { stderr, .. }) if stderr.contains("release not found") => {
+            return Ok(None);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let src = SourceFile::new("gh release view", String::from_utf8(output.stdout)?);
+    parse_github_release_assets(&src).map(Some)
+}
+
+/// Parse the output of `gh release view --json assets`
+pub(crate) fn parse_github_release_assets(
+    src: &SourceFile,
+) -> DistResult<SortedMap<String, RemoteFile>> {
+    let release: GithubReleaseAssets = src.deserialize_json()?;
+    let files = release
+        .assets
+        .into_iter()
+        .map(|asset| {
+            let remote = if asset.state == "uploaded" {
+                RemoteFile::Present {
+                    checksum: asset
+                        .digest
+                        .and_then(|digest| Some(digest.strip_prefix("sha256:")?.to_owned())),
+                }
+            } else {
+                RemoteFile::Partial
+            };
+            (asset.name, remote)
+        })
+        .collect();
+    Ok(files)
+}
+
+/// Work out what a Github Release has for a file we want to upload
+///
+/// Github only reports digests for assets uploaded after it started computing them,
+/// so for older ones we fall back to the checksum file we published alongside them,
+/// the same way we check files hosted on axo Releases. `fetch_checksum_file` is only
+/// invoked if the digest is missing and the Release has a complete checksum file.
+pub(crate) fn github_remote_file(
+    assets: &SortedMap<String, RemoteFile>,
+    name: &str,
+    checksum_file: Option<&str>,
+    fetch_checksum_file: impl FnOnce(&str) -> DistResult<String>,
+) -> DistResult<RemoteFile> {
+    let remote = assets.get(name).cloned().unwrap_or(RemoteFile::Missing);
+    if remote != (RemoteFile::Present { checksum: None }) {
+        return Ok(remote);
+    }
+    let Some(checksum_file) = checksum_file else {
+        return Ok(remote);
+    };
+    if !matches!(assets.get(checksum_file), Some(RemoteFile::Present { .. })) {
+        return Ok(remote);
+    }
+    let contents = fetch_checksum_file(checksum_file)?;
+    Ok(RemoteFile::Present {
+        checksum: contents.split_whitespace().next().map(ToOwned::to_owned),
+    })
+}
+
+/// Get the contents of a (small, text) file in a Github Release
+fn github_release_file(repo: &str, tag: &str, name: &str) -> DistResult<String> {
+    let mut cmd = Cmd::new("gh", "download Github Release file");
+    cmd.arg("release")
+        .arg("download")
+        .arg(tag)
+        .arg("--repo")
+        .arg(repo)
+        .arg("--pattern")
+        .arg(name)
+        .arg("--output")
+        .arg("-");
+    let output = run_gh(cmd, &format!("download {name} from Github Release {tag}"))?;
+    Ok(String::from_utf8(output.stdout)?)
+}
+
+/// Upload a single file to a Github Release (if it needs to be), retrying on failure
+fn upload_to_github_release(
+    dist: &DistGraph,
+    repo: &str,
+    tag: &str,
+    name: &str,
+    action: UploadAction,
+) -> DistResult<()> {
+    let clobber = match action {
+        UploadAction::Skip => {
+            eprintln!("{name} is already uploaded, skipping");
+            return Ok(());
+        }
+        UploadAction::Upload => false,
+        UploadAction::Replace => true,
+    };
+    let path = dist.dist_dir.join(name);
+    let desc = format!("upload {name}");
+    tokio::runtime::Handle::current().block_on(dist.client_settings.retry.run(
+        &desc,
+        || async {
+            let mut cmd = Cmd::new("gh", "upload file to Github Release");
+            cmd.arg("release")
+                .arg("upload")
+                .arg(tag)
+                .arg(&path)
+                .arg("--repo")
+                .arg(repo);
+            if clobber {
+                cmd.arg("--clobber");
+            }
+            run_gh(cmd, &format!("upload {name} to Github Release {tag}"))?;
+            eprintln!("uploaded {name}");
+            DistResult::Ok(())
+        },
+    ))?;
+    Ok(())
+}
+
 fn release_hosting(
     _dist: &DistGraph,
     manifest: &mut DistManifest,
@@ -194,7 +194,11 @@ pub fn do_init(cfg: &Config, args: &InitArgs) -> DistResult<()> {
     if is_migrating || newly_initted_generic {
         filename = match desired_workspace_kind {
             WorkspaceKind::Rust => "Cargo.toml",
-            WorkspaceKind::Generic | WorkspaceKind::Javascript => "dist-workspace.toml",
+            WorkspaceKind::Generic
+            | WorkspaceKind::Javascript
+            | WorkspaceKind::Python
+            | WorkspaceKind::Go
+            | WorkspaceKind::Cmake => "dist-workspace.toml",
         };
         destination = root_workspace.workspace_dir.join(filename);
     } else {
@@ -372,6 +376,9 @@ fn get_new_dist_metadata(
             auto_includes: None,
             windows_archive: None,
             unix_archive: None,
+            third_party_licenses: None,
+            sbom: None,
+            symbol_server: None,
             npm_scope: None,
             npm_package: None,
             checksum: None,
@@ -386,6 +393,7 @@ fn get_new_dist_metadata(
             features: None,
             default_features: None,
             all_features: None,
+            target: None,
             plan_jobs: None,
             local_artifacts_jobs: None,
             global_artifacts_jobs: None,
@@ -395,6 +403,7 @@ fn get_new_dist_metadata(
             post_announce_jobs: None,
             publish_prereleases: None,
             force_latest: None,
+            changelog_format: None,
             create_release: None,
             github_releases_repo: None,
             github_releases_submodule_path: None,
@@ -405,6 +414,10 @@ fn get_new_dist_metadata(
             macos_sign: None,
             github_attestations: None,
             msvc_crt_static: None,
+            cargo_builders: None,
+            cargo_auditable: None,
+            pgo: None,
+            binary_processors: None,
             hosting: None,
             extra_artifacts: None,
             github_custom_runners: None,
@@ -850,6 +863,9 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         auto_includes,
         windows_archive,
         unix_archive,
+        third_party_licenses: _,
+        sbom: _,
+        symbol_server: _,
         npm_scope,
         npm_package,
         checksum,
@@ -864,6 +880,7 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         features,
         all_features,
         default_features,
+        target: _,
         plan_jobs,
         local_artifacts_jobs,
         global_artifacts_jobs,
@@ -873,6 +890,7 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         post_announce_jobs,
         publish_prereleases,
         force_latest,
+        changelog_format,
         create_release,
         github_releases_repo,
         github_releases_submodule_path,
@@ -882,6 +900,10 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         macos_sign,
         github_attestations,
         msvc_crt_static,
+        cargo_builders: _,
+        cargo_auditable: _,
+        pgo: _,
+        binary_processors: _,
         hosting,
         tag_namespace,
         install_updater,
@@ -1200,6 +1222,13 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         *force_latest,
     );
 
+    apply_optional_value(
+        table,
+        "changelog-format",
+        "# Where to get release notes from\n",
+        changelog_format.as_ref().map(|f| f.to_string()),
+    );
+
     apply_optional_value(
         table,
         "pr-run-mode",
@@ -21,14 +21,17 @@ use backend::{
 };
 use build::generic::{build_generic_target, run_extra_artifacts_build};
 use build::{
+    cache::BuildCache,
     cargo::{build_cargo_target, rustup_toolchain},
-    fake::{build_fake_cargo_target, build_fake_generic_target},
+    fake::{
+        build_fake_cargo_target, build_fake_cmake_target, build_fake_generic_target,
+        build_fake_go_target, build_fake_lipo_target,
+    },
+    lipo::build_lipo_target,
 };
 use camino::{Utf8Path, Utf8PathBuf};
 use cargo_dist_schema::{ArtifactId, DistManifest};
-use config::{
-    ArtifactMode, ChecksumStyle, CompressionImpl, Config, DirtyMode, GenerateMode, ZipStyle,
-};
+use config::{ArtifactMode, ChecksumStyle, Config, DirtyMode, GenerateMode, ZipStyle};
 use console::Term;
 use semver::Version;
 use temp_dir::TempDir;
@@ -41,26 +44,100 @@ pub use tasks::*;
 pub mod announce;
 pub mod backend;
 pub mod build;
+pub mod changed;
 pub mod config;
 pub mod env;
 pub mod errors;
 pub mod host;
 mod init;
+pub mod licenses;
 pub mod linkage;
 pub mod manifest;
+pub mod manifest_diff;
 pub mod net;
 pub mod platform;
+pub mod release;
+pub mod sbom;
 pub mod sign;
 pub mod tasks;
 #[cfg(test)]
 mod tests;
+pub mod verify;
 
 /// cargo dist build -- actually build binaries and installers!
 pub fn do_build(cfg: &Config) -> DistResult<DistManifest> {
     check_integrity(cfg)?;
 
-    let (dist, mut manifest) = tasks::gather_work(cfg)?;
+    let (mut dist, mut manifest) = tasks::gather_work(cfg)?;
+    dist.source_date_epoch = build::archive::source_date_epoch(
+        &dist.tools,
+        &dist.repo_dir,
+        manifest.announcement_tag.as_deref().unwrap_or("HEAD"),
+    )?;
+    dist.build_cache = Some(BuildCache::new(
+        dist.target_dir.join(build::cache::TARGET_DIST_CACHE),
+    ));
+
+    run_build(&dist, &mut manifest)?;
+
+    Ok(manifest)
+}
+
+/// cargo dist build --check-reproducible -- build everything twice and check
+/// that all the artifacts came out the same
+///
+/// Note that the second build will reuse cargo's build cache, so this is mostly
+/// checking our own packaging (archives, installers...) rather than the compiler.
+/// Our own build cache is deliberately left off, so everything really is rebuilt.
+pub fn do_build_check_reproducible(cfg: &Config) -> DistResult<DistManifest> {
+    check_integrity(cfg)?;
+
+    let (mut dist, manifest) = tasks::gather_work(cfg)?;
+    dist.source_date_epoch = build::archive::source_date_epoch(
+        &dist.tools,
+        &dist.repo_dir,
+        manifest.announcement_tag.as_deref().unwrap_or("HEAD"),
+    )?;
+
+    let mut first_manifest = manifest.clone();
+    run_build(&dist, &mut first_manifest)?;
+    let first = artifact_digests(&dist)?;
+
+    eprintln!("rebuilding to check that artifacts are reproducible...");
+    eprintln!();
+    let mut manifest = manifest;
+    run_build(&dist, &mut manifest)?;
+    let second = artifact_digests(&dist)?;
+
+    let mut artifacts = vec![];
+    for (id, digest) in &first {
+        if second.get(id) != Some(digest) {
+            artifacts.push(id.clone());
+        }
+    }
+    if !artifacts.is_empty() {
+        return Err(DistError::NotReproducible { artifacts });
+    }
+    eprintln!("all {} artifacts were reproducible!", first.len());
+
+    Ok(manifest)
+}
+
+/// Get the sha256 of every artifact that got built
+fn artifact_digests(dist: &DistGraph) -> DistResult<SortedMap<String, String>> {
+    let mut digests = SortedMap::new();
+    for artifact in &dist.artifacts {
+        // Some "artifacts" (like dSYMs) are directories, skip those
+        if artifact.file_path.is_file() {
+            let digest = generate_checksum(&ChecksumStyle::Sha256, &artifact.file_path)?;
+            digests.insert(artifact.id.clone(), digest);
+        }
+    }
+    Ok(digests)
+}
 
+/// Run all the build steps of a DistGraph
+fn run_build(dist: &DistGraph, manifest: &mut DistManifest) -> DistResult<()> {
     // FIXME: parallelize this by working this like a dependency graph, so we can start
     // bundling up an executable the moment it's built! Note however that you shouldn't
     // parallelize Cargo invocations because it has global state that can get clobbered.
@@ -75,29 +152,29 @@ pub fn do_build(cfg: &Config) -> DistResult<DistManifest> {
     eprintln!("building artifacts:");
     for artifact in &dist.artifacts {
         eprintln!("  {}", artifact.id);
-        init_artifact_dir(&dist, artifact)?;
+        init_artifact_dir(dist, artifact)?;
     }
     eprintln!();
 
     // Run all the local build steps first
     for step in &dist.local_build_steps {
         if dist.local_builds_are_lies {
-            build_fake(&dist, step, &mut manifest)?;
+            build_fake(dist, step, manifest)?;
         } else {
-            run_build_step(&dist, step, &mut manifest)?;
+            run_build_step(dist, step, manifest)?;
         }
     }
 
     // Next the global steps
     for step in &dist.global_build_steps {
         if dist.local_builds_are_lies {
-            build_fake(&dist, step, &mut manifest)?;
+            build_fake(dist, step, manifest)?;
         } else {
-            run_build_step(&dist, step, &mut manifest)?;
+            run_build_step(dist, step, manifest)?;
         }
     }
 
-    Ok(manifest)
+    Ok(())
 }
 
 /// Just generate the manifest produced by `cargo dist build` without building
@@ -116,6 +193,8 @@ fn run_build_step(
 ) -> DistResult<()> {
     match target {
         BuildStep::Generic(target) => build_generic_target(dist_graph, manifest, target)?,
+        BuildStep::Go(target) => build::go::build_go_target(dist_graph, manifest, target)?,
+        BuildStep::Cmake(target) => build::cmake::build_cmake_target(dist_graph, manifest, target)?,
         BuildStep::Cargo(target) => build_cargo_target(dist_graph, manifest, target)?,
         BuildStep::Rustup(cmd) => rustup_toolchain(dist_graph, cmd)?,
         BuildStep::CopyFile(CopyStep {
@@ -135,7 +214,13 @@ fn run_build_step(
             dest_path,
             zip_style,
             with_root,
-        }) => zip_dir(src_path, dest_path, zip_style, with_root.as_deref())?,
+        }) => zip_dir(
+            dist_graph,
+            src_path,
+            dest_path,
+            zip_style,
+            with_root.as_deref(),
+        )?,
         BuildStep::GenerateInstaller(installer) => {
             generate_installer(dist_graph, installer, manifest)?
         }
@@ -157,8 +242,14 @@ fn run_build_step(
             target,
             working_dir,
         }) => generate_source_tarball(dist_graph, committish, prefix, target, working_dir)?,
-        BuildStep::Extra(target) => run_extra_artifacts_build(dist_graph, target)?,
+        BuildStep::Extra(target) => run_extra_artifacts_build(dist_graph, manifest, target)?,
         BuildStep::Updater(updater) => fetch_updater(dist_graph, updater)?,
+        BuildStep::Lipo(target) => build_lipo_target(dist_graph, manifest, target)?,
+        BuildStep::Sbom(sbom) => sbom::generate_sbom(dist_graph, manifest, sbom)?,
+        BuildStep::ThirdPartyLicenses(licenses) => {
+            licenses::generate_third_party_licenses(dist_graph, licenses)?
+        }
+        BuildStep::Maturin(wheel) => build::maturin::build_wheels(dist_graph, manifest, wheel)?,
     };
     Ok(())
 }
@@ -289,6 +380,8 @@ fn build_fake(
         // These two are the meat: don't actually run these at all, just
         // fake them out
         BuildStep::Generic(target) => build_fake_generic_target(dist_graph, manifest, target)?,
+        BuildStep::Go(target) => build_fake_go_target(dist_graph, manifest, target)?,
+        BuildStep::Cmake(target) => build_fake_cmake_target(dist_graph, manifest, target)?,
         BuildStep::Cargo(target) => build_fake_cargo_target(dist_graph, manifest, target)?,
         // Never run rustup
         BuildStep::Rustup(_) => {}
@@ -311,7 +404,13 @@ fn build_fake(
             dest_path,
             zip_style,
             with_root,
-        }) => zip_dir(src_path, dest_path, zip_style, with_root.as_deref())?,
+        }) => zip_dir(
+            dist_graph,
+            src_path,
+            dest_path,
+            zip_style,
+            with_root.as_deref(),
+        )?,
         BuildStep::GenerateInstaller(installer) => match installer {
             // MSI and pkg, unlike other installers, aren't safe to generate on any platform
             InstallerImpl::Msi(msi) => generate_fake_msi(dist_graph, msi, manifest)?,
@@ -330,6 +429,10 @@ fn build_fake(
             dest_path.as_deref(),
             for_artifact.as_ref(),
         )?,
+        BuildStep::Sbom(sbom) => sbom::generate_sbom(dist_graph, manifest, sbom)?,
+        BuildStep::ThirdPartyLicenses(licenses) => {
+            licenses::generate_third_party_licenses(dist_graph, licenses)?
+        }
         // Except source tarballs, which are definitely not okay
         // We mock these because it requires:
         // 1. git to be installed;
@@ -346,6 +449,10 @@ fn build_fake(
         // Or extra artifacts, which may involve real builds
         BuildStep::Extra(target) => run_fake_extra_artifacts_build(dist_graph, target)?,
         BuildStep::Updater(_) => unimplemented!(),
+        BuildStep::Lipo(target) => build_fake_lipo_target(dist_graph, manifest, target)?,
+        BuildStep::Maturin(wheel) => {
+            build::maturin::build_fake_wheels(dist_graph, manifest, wheel)?
+        }
     }
     Ok(())
 }
@@ -400,7 +507,10 @@ fn generate_and_write_checksum(
 }
 
 /// Generate a checksum for the src_path and return it as a string
-fn generate_checksum(checksum: &ChecksumStyle, src_path: &Utf8Path) -> DistResult<String> {
+pub(crate) fn generate_checksum(
+    checksum: &ChecksumStyle,
+    src_path: &Utf8Path,
+) -> DistResult<String> {
     info!("generating {checksum:?} for {src_path}");
     use sha2::Digest;
     use std::fmt::Write;
@@ -551,28 +661,47 @@ pub(crate) fn copy_file_or_dir(src_path: &Utf8Path, dest_path: &Utf8Path) -> Dis
     }
 }
 
+/// Archive a directory, reproducibly (see [`build::archive`][])
 fn zip_dir(
+    dist: &DistGraph,
     src_path: &Utf8Path,
     dest_path: &Utf8Path,
     zip_style: &ZipStyle,
     with_root: Option<&Utf8Path>,
 ) -> DistResult<()> {
-    match zip_style {
-        ZipStyle::Zip => LocalAsset::zip_dir(src_path, dest_path, with_root)?,
-        ZipStyle::Tar(CompressionImpl::Gzip) => {
-            LocalAsset::tar_gz_dir(src_path, dest_path, with_root)?
-        }
-        ZipStyle::Tar(CompressionImpl::Xzip) => {
-            LocalAsset::tar_xz_dir(src_path, dest_path, with_root)?
-        }
-        ZipStyle::Tar(CompressionImpl::Zstd) => {
-            LocalAsset::tar_zstd_dir(src_path, dest_path, with_root)?
-        }
-        ZipStyle::TempDir => {
-            // no-op
-        }
+    // Temp dirs aren't real archives, so there's nothing worth caching
+    let cache = dist
+        .build_cache
+        .as_ref()
+        .filter(|_| *zip_style != ZipStyle::TempDir);
+    let Some(cache) = cache else {
+        return build::archive::archive_dir(
+            src_path,
+            dest_path,
+            zip_style,
+            with_root,
+            dist.source_date_epoch,
+        );
+    };
+
+    let key = build::archive::archive_cache_key(
+        src_path,
+        dest_path,
+        zip_style,
+        with_root,
+        dist.source_date_epoch,
+    )?;
+    if cache.restore(&key, dest_path)? {
+        return Ok(());
     }
-    Ok(())
+    build::archive::archive_dir(
+        src_path,
+        dest_path,
+        zip_style,
+        with_root,
+        dist.source_date_epoch,
+    )?;
+    cache.store(&key, dest_path)
 }
 
 /// Arguments for `cargo dist generate` ([`do_generate`][])
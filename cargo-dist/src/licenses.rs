@@ -0,0 +1,246 @@
+//! Third-party license notices
+//!
+//! Most licenses require their text to be shipped along with any binaries built from
+//! the code, so for each variant we can write a THIRD-PARTY-LICENSES file covering the
+//! crates built into its binaries. The crates are resolved just like they are for
+//! [SBOMs][crate::sbom], and the license texts come from the crates' sources, which cargo
+//! has already downloaded to its local registry (so no network access is needed).
+
+use std::fmt::Write;
+
+use axoasset::LocalAsset;
+use camino::Utf8Path;
+use tracing::warn;
+
+use crate::{
+    sbom::{cargo_package_graph, spdx_license, variant_crates},
+    DistGraph, DistResult, SortedMap, ThirdPartyLicensesImpl,
+};
+
+/// The licenses of everything built into a variant's binaries
+#[derive(Debug, Clone)]
+pub struct ThirdPartyLicenses {
+    /// The app the binaries belong to
+    pub app_name: String,
+    /// The version of the app
+    pub app_version: String,
+    /// The target the binaries were built for
+    pub target: String,
+    /// The crates, grouped by license
+    pub groups: Vec<LicenseGroup>,
+}
+
+/// Crates that share a license
+#[derive(Debug, Clone)]
+pub struct LicenseGroup {
+    /// The license, as an SPDX expression if it is one
+    pub license: String,
+    /// The crates under this license, sorted by name
+    pub crates: Vec<LicensedCrate>,
+}
+
+/// A third-party crate and its license texts
+#[derive(Debug, Clone)]
+pub struct LicensedCrate {
+    /// The name of the crate
+    pub name: String,
+    /// The version of the crate
+    pub version: String,
+    /// The license of the crate, if it says
+    pub license: Option<String>,
+    /// The license files found in the crate's sources
+    pub texts: Vec<LicenseText>,
+}
+
+/// A license file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LicenseText {
+    /// The name of the file (like `LICENSE-MIT`)
+    pub file_name: String,
+    /// The contents of the file
+    pub text: String,
+}
+
+/// The label for crates that don't say what their license is
+const UNKNOWN_LICENSE: &str = "Unspecified license";
+
+/// Write the THIRD-PARTY-LICENSES file for a variant
+pub fn generate_third_party_licenses(
+    dist: &DistGraph,
+    step: &ThirdPartyLicensesImpl,
+) -> DistResult<()> {
+    let licenses = gather_third_party_licenses(dist, step)?;
+    LocalAsset::write_new_all(&render_third_party_licenses(&licenses), &step.dest_path)?;
+    eprintln!("  generated {}", step.dest_path);
+    Ok(())
+}
+
+/// Collect the licenses of the third-party crates built into a variant
+fn gather_third_party_licenses(
+    dist: &DistGraph,
+    step: &ThirdPartyLicensesImpl,
+) -> DistResult<ThirdPartyLicenses> {
+    let release = dist.release(step.release);
+    let variant = dist.variant(step.variant);
+    let graph = cargo_package_graph(&step.cargo_manifest_path)?;
+    let (_, packages) = variant_crates(dist, &graph, variant)?;
+
+    let mut crates = vec![];
+    for package in packages.values() {
+        // Workspace members and other path dependencies are part of the app
+        if package.source().is_local() {
+            continue;
+        }
+        let package_root = package
+            .manifest_path()
+            .parent()
+            .unwrap_or(Utf8Path::new(""));
+        let license_file = package.license_file().map(|path| package_root.join(path));
+        let texts = license_texts(package_root, license_file.as_deref())?;
+        if texts.is_empty() {
+            warn!(
+                "couldn't find any license files for {} {} in {package_root}",
+                package.name(),
+                package.version()
+            );
+        }
+        crates.push(LicensedCrate {
+            name: package.name().to_owned(),
+            version: package.version().to_string(),
+            license: package
+                .license()
+                .map(|license| spdx_license(license).unwrap_or_else(|| license.to_owned())),
+            texts,
+        });
+    }
+
+    Ok(ThirdPartyLicenses {
+        app_name: release.app_name.clone(),
+        app_version: release.version.to_string(),
+        target: variant.target.clone(),
+        groups: group_by_license(crates),
+    })
+}
+
+/// Find the license texts in a crate's sources
+///
+/// This is every `LICENSE*`, `LICENCE*`, `UNLICENSE*`, `COPYING*` and `NOTICE*` file in the
+/// root of the crate (Apache-2.0 requires NOTICE files to be passed along), as well as the
+/// crate's `license-file`, wherever that is.
+pub fn license_texts(
+    package_root: &Utf8Path,
+    license_file: Option<&Utf8Path>,
+) -> DistResult<Vec<LicenseText>> {
+    const PREFIXES: &[&str] = &["LICENSE", "LICENCE", "UNLICENSE", "COPYING", "NOTICE"];
+
+    let mut paths = vec![];
+    if package_root.is_dir() {
+        for entry in package_root.read_dir_utf8()? {
+            let entry = entry?;
+            let upper_name = entry.file_name().to_ascii_uppercase();
+            if entry.file_type()?.is_file()
+                && PREFIXES.iter().any(|prefix| upper_name.starts_with(prefix))
+            {
+                paths.push(entry.into_path());
+            }
+        }
+    }
+    paths.sort();
+    if let Some(license_file) = license_file {
+        if !paths.iter().any(|path| path == license_file) && license_file.is_file() {
+            paths.push(license_file.to_owned());
+        }
+    }
+
+    let mut texts = vec![];
+    for path in paths {
+        // Not every license file is valid UTF-8, but it's still better than nothing
+        let text = String::from_utf8_lossy(&LocalAsset::load_bytes(&path)?).into_owned();
+        texts.push(LicenseText {
+            file_name: path.file_name().unwrap_or(path.as_str()).to_owned(),
+            text,
+        });
+    }
+    Ok(texts)
+}
+
+/// Group crates by their license, sorting everything so the output is stable
+pub fn group_by_license(crates: Vec<LicensedCrate>) -> Vec<LicenseGroup> {
+    let mut groups = SortedMap::<String, Vec<LicensedCrate>>::new();
+    for krate in crates {
+        let license = krate
+            .license
+            .clone()
+            .unwrap_or_else(|| UNKNOWN_LICENSE.to_owned());
+        groups.entry(license).or_default().push(krate);
+    }
+    groups
+        .into_iter()
+        .map(|(license, mut crates)| {
+            crates.sort_by(|a, b| (&a.name, &a.version).cmp(&(&b.name, &b.version)));
+            LicenseGroup { license, crates }
+        })
+        .collect()
+}
+
+/// Render a THIRD-PARTY-LICENSES file
+///
+/// Each license gets a section listing the crates under it, followed by their license
+/// texts. Crates often share the exact same text (like the Apache-2.0 license itself),
+/// so each distinct text is only written once per section.
+pub fn render_third_party_licenses(licenses: &ThirdPartyLicenses) -> String {
+    let heavy_rule = "=".repeat(80);
+    let light_rule = "-".repeat(80);
+
+    let mut out = String::new();
+    writeln!(out, "THIRD-PARTY LICENSES").unwrap();
+    writeln!(out).unwrap();
+    writeln!(
+        out,
+        "{} {} ({}) includes the following third-party crates, grouped by license.",
+        licenses.app_name, licenses.app_version, licenses.target
+    )
+    .unwrap();
+    if licenses.groups.is_empty() {
+        writeln!(out).unwrap();
+        writeln!(out, "(none)").unwrap();
+    }
+
+    for group in &licenses.groups {
+        writeln!(out).unwrap();
+        writeln!(out, "{heavy_rule}").unwrap();
+        writeln!(out, "{}", group.license).unwrap();
+        writeln!(out, "{heavy_rule}").unwrap();
+        writeln!(out).unwrap();
+
+        // Dedupe the texts, remembering who they came from
+        let mut texts = Vec::<(&LicenseText, Vec<String>)>::new();
+        for krate in &group.crates {
+            let label = format!("{} {}", krate.name, krate.version);
+            if krate.texts.is_empty() {
+                writeln!(out, "* {label} (no license text found)").unwrap();
+            } else {
+                writeln!(out, "* {label}").unwrap();
+            }
+            for text in &krate.texts {
+                match texts
+                    .iter_mut()
+                    .find(|(existing, _)| existing.text.trim() == text.text.trim())
+                {
+                    Some((_, users)) => users.push(label.clone()),
+                    None => texts.push((text, vec![label.clone()])),
+                }
+            }
+        }
+
+        for (text, users) in texts {
+            writeln!(out).unwrap();
+            writeln!(out, "{light_rule}").unwrap();
+            writeln!(out, "{} ({})", text.file_name, users.join(", ")).unwrap();
+            writeln!(out, "{light_rule}").unwrap();
+            writeln!(out).unwrap();
+            writeln!(out, "{}", text.text.trim_end()).unwrap();
+        }
+    }
+    out
+}
@@ -91,6 +91,8 @@ fn compute_linkage_assuming_local_build(
                             system: dist.system_id.clone(),
                             linkage: Some(linkage),
                             target_triples: vec![target.clone()],
+                            processors: vec![],
+                            build_id: crate::build::symbols::read_build_id(&bin_path, bin.kind),
                         },
                     );
                 }
@@ -293,33 +295,50 @@ fn do_otool(path: &Utf8PathBuf) -> DistResult<Vec<String>> {
     let mut buf = vec![];
     let size = f.read_to_end(&mut buf).unwrap();
     let mut cur = Cursor::new(&buf[..size]);
-    if let Ok(OFile::MachFile {
-        header: _,
-        commands,
-    }) = OFile::parse(&mut cur)
-    {
-        let commands = commands
-            .iter()
-            .map(|load| load.command())
-            .cloned()
-            .collect::<Vec<LoadCommand>>();
-
-        for command in commands {
-            match command {
-                LoadCommand::IdDyLib(ref dylib)
-                | LoadCommand::LoadDyLib(ref dylib)
-                | LoadCommand::LoadWeakDyLib(ref dylib)
-                | LoadCommand::ReexportDyLib(ref dylib)
-                | LoadCommand::LoadUpwardDylib(ref dylib)
-                | LoadCommand::LazyLoadDylib(ref dylib) => {
-                    libraries.push(dylib.name.to_string());
+    if let Ok(file) = OFile::parse(&mut cur) {
+        otool_libraries(&file, &mut libraries);
+    }
+
+    Ok(libraries)
+}
+
+fn otool_libraries(file: &OFile, libraries: &mut Vec<String>) {
+    match file {
+        OFile::MachFile {
+            header: _,
+            commands,
+        } => {
+            let commands = commands
+                .iter()
+                .map(|load| load.command())
+                .cloned()
+                .collect::<Vec<LoadCommand>>();
+
+            for command in commands {
+                match command {
+                    LoadCommand::IdDyLib(ref dylib)
+                    | LoadCommand::LoadDyLib(ref dylib)
+                    | LoadCommand::LoadWeakDyLib(ref dylib)
+                    | LoadCommand::ReexportDyLib(ref dylib)
+                    | LoadCommand::LoadUpwardDylib(ref dylib)
+                    | LoadCommand::LazyLoadDylib(ref dylib) => {
+                        let name = dylib.name.to_string();
+                        if !libraries.contains(&name) {
+                            libraries.push(name);
+                        }
+                    }
+                    _ => {}
                 }
-                _ => {}
             }
         }
+        // Universal binaries: the union of what every arch links
+        OFile::FatFile { magic: _, files } => {
+            for (_, file) in files {
+                otool_libraries(file, libraries);
+            }
+        }
+        _ => {}
     }
-
-    Ok(libraries)
 }
 
 fn do_ldd(path: &Utf8PathBuf) -> DistResult<Vec<String>> {
@@ -394,7 +413,10 @@ pub fn determine_linkage(path: &Utf8PathBuf, target: &str) -> Linkage {
 fn try_determine_linkage(path: &Utf8PathBuf, target: &str) -> DistResult<Linkage> {
     let libraries = match target {
         // Can be run on any OS
-        "i686-apple-darwin" | "x86_64-apple-darwin" | "aarch64-apple-darwin" => do_otool(path)?,
+        "i686-apple-darwin"
+        | "x86_64-apple-darwin"
+        | "aarch64-apple-darwin"
+        | "universal2-apple-darwin" => do_otool(path)?,
         "i686-unknown-linux-gnu"
         | "x86_64-unknown-linux-gnu"
         | "aarch64-unknown-linux-gnu"
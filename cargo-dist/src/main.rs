@@ -13,8 +13,8 @@ use cargo_dist::{linkage::LinkageDisplay, *};
 use cargo_dist_schema::{AssetKind, DistManifest};
 use clap::Parser;
 use cli::{
-    Cli, Commands, FakeCli, GenerateMode, HelpMarkdownArgs, HostArgs, ManifestArgs, OutputFormat,
-    PlanArgs,
+    ChangedArgs, Cli, Commands, FakeCli, GenerateMode, HelpMarkdownArgs, HostArgs, ManifestArgs,
+    OutputFormat, PlanArgs, ReleaseArgs, VerifyArgs,
 };
 use console::Term;
 use miette::{miette, IntoDiagnostic};
@@ -51,8 +51,12 @@ fn real_main(cli: &axocli::CliApp<Cli>) -> Result<(), miette::Report> {
         Commands::Plan(args) => cmd_plan(config, args),
         Commands::HelpMarkdown(args) => cmd_help_md(config, args),
         Commands::ManifestSchema(args) => cmd_manifest_schema(config, args),
+        Commands::ManifestDiff(args) => cmd_manifest_diff(config, args),
         Commands::Build(args) => cmd_build(config, args),
         Commands::Host(args) => cmd_host(config, args),
+        Commands::Verify(args) => cmd_verify(config, args),
+        Commands::Release(args) => cmd_release(config, args),
+        Commands::Changed(args) => cmd_changed(config, args),
         Commands::Selfupdate(args) => runtime.block_on(cmd_update(config, args)),
     }
 }
@@ -219,7 +223,11 @@ fn cmd_build(cli: &Cli, args: &BuildArgs) -> Result<(), miette::Report> {
         installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
         root_cmd: "build".to_owned(),
     };
-    let report = do_build(&config)?;
+    let report = if args.check_reproducible {
+        cargo_dist::do_build_check_reproducible(&config)?
+    } else {
+        do_build(&config)?
+    };
     print(
         cli,
         &report,
@@ -231,6 +239,7 @@ fn cmd_build(cli: &Cli, args: &BuildArgs) -> Result<(), miette::Report> {
 fn cmd_host(cli: &Cli, args: &HostArgs) -> Result<(), miette::Report> {
     let args = cargo_dist::config::HostArgs {
         steps: args.steps.iter().map(|m| m.to_lib()).collect(),
+        replace_mismatched: args.replace_mismatched,
     };
     // host can be invoked on multiple machines, so use arg keys to disambiguate
     let arg_key = args
@@ -255,6 +264,76 @@ fn cmd_host(cli: &Cli, args: &HostArgs) -> Result<(), miette::Report> {
     print(cli, &report, false, Some("host"))
 }
 
+fn cmd_verify(cli: &Cli, args: &VerifyArgs) -> Result<(), miette::Report> {
+    let (source, tag) = match &args.release {
+        Some(release) if verify::ReleaseSource::is_source(release) => (Some(release.clone()), None),
+        Some(tag) => (None, Some(tag.clone())),
+        None => (None, None),
+    };
+    let args = cargo_dist::config::VerifyArgs {
+        source,
+        tag,
+        skip_installers: args.skip_installers,
+    };
+    let config = cargo_dist::config::Config {
+        tag_settings: cli.tag_settings(true),
+        create_hosting: false,
+        artifact_mode: config::ArtifactMode::All,
+        no_local_paths: true,
+        allow_all_dirty: cli.allow_dirty,
+        targets: cli.target.clone(),
+        ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
+        installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
+        root_cmd: "verify".to_owned(),
+    };
+    cargo_dist::verify::do_verify(&config, &args)?;
+    Ok(())
+}
+
+fn cmd_release(cli: &Cli, args: &ReleaseArgs) -> Result<(), miette::Report> {
+    let args = cargo_dist::config::ReleaseArgs {
+        bump: args.bump.clone(),
+        package: args.package.clone(),
+        dry_run: args.dry_run,
+    };
+    let config = cargo_dist::config::Config {
+        tag_settings: cli.tag_settings(false),
+        create_hosting: false,
+        artifact_mode: config::ArtifactMode::All,
+        no_local_paths: true,
+        allow_all_dirty: cli.allow_dirty,
+        targets: cli.target.clone(),
+        ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
+        installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
+        root_cmd: "release".to_owned(),
+    };
+    cargo_dist::release::do_release(&config, &args)?;
+    Ok(())
+}
+
+fn cmd_changed(cli: &Cli, _args: &ChangedArgs) -> Result<(), miette::Report> {
+    let config = cargo_dist::config::Config {
+        tag_settings: cli.tag_settings(false),
+        create_hosting: false,
+        artifact_mode: config::ArtifactMode::All,
+        no_local_paths: true,
+        allow_all_dirty: cli.allow_dirty,
+        targets: cli.target.clone(),
+        ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
+        installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
+        root_cmd: "changed".to_owned(),
+    };
+    let report = cargo_dist::changed::do_changed(&config)?;
+    match cli.output_format {
+        OutputFormat::Human => print!("{}", cargo_dist::changed::render_human(&report)),
+        OutputFormat::Json => {
+            let string = serde_json::to_string_pretty(&report).unwrap();
+            println!("{string}");
+        }
+    }
+    Ok(())
+}
+
 fn cmd_manifest(cli: &Cli, args: &ManifestArgs) -> Result<(), miette::Report> {
     let config = cargo_dist::config::Config {
         tag_settings: cli.tag_settings(true),
@@ -280,6 +359,7 @@ fn cmd_plan(cli: &Cli, _args: &PlanArgs) -> Result<(), miette::Report> {
         build_args: BuildArgs {
             artifacts: cli::ArtifactMode::All,
             print: vec![],
+            check_reproducible: false,
         },
     };
 
@@ -524,7 +604,33 @@ fn cmd_manifest_schema(
     args: &cli::ManifestSchemaArgs,
 ) -> Result<(), miette::ErrReport> {
     let schema = cargo_dist_schema::DistManifest::json_schema();
-    let json_schema = serde_json::to_string_pretty(&schema).expect("failed to stringify schema!?");
+
+    if let Some(path) = &args.check {
+        let old: serde_json::Value = axoasset::SourceFile::load_local(path)?.deserialize_json()?;
+        let new = serde_json::to_value(&schema).expect("failed to convert schema!?");
+        let changes = cargo_dist_schema::compat::check_compatibility(&old, &new);
+        if !changes.is_empty() {
+            return Err(errors::DistError::SchemaIncompatible {
+                path: path.clone(),
+                changes: changes.iter().map(|c| c.to_string()).collect(),
+            }
+            .into());
+        }
+        eprintln!("no breaking changes compared to {path}");
+        return Ok(());
+    }
+
+    let json_schema = if let Some(version) = args.schema_version {
+        let historical = cargo_dist_schema::historical_schema(version).ok_or(
+            errors::DistError::UnknownSchemaVersion {
+                version,
+                latest: cargo_dist_schema::SCHEMA_VERSION,
+            },
+        )?;
+        historical.trim_end().to_owned()
+    } else {
+        serde_json::to_string_pretty(&schema).expect("failed to stringify schema!?")
+    };
 
     if let Some(destination) = args.output.to_owned() {
         let contents = json_schema + "\n";
@@ -535,6 +641,18 @@ fn cmd_manifest_schema(
     Ok(())
 }
 
+fn cmd_manifest_diff(cli: &Cli, args: &cli::ManifestDiffArgs) -> Result<(), miette::Report> {
+    let diff = cargo_dist::manifest_diff::do_manifest_diff(&args.old, &args.new)?;
+    match cli.output_format {
+        OutputFormat::Human => print!("{}", cargo_dist::manifest_diff::render_human(&diff)),
+        OutputFormat::Json => {
+            let string = serde_json::to_string_pretty(&diff).unwrap();
+            println!("{string}");
+        }
+    }
+    Ok(())
+}
+
 fn this_cargo_dist_provided_by_brew() -> bool {
     if cfg!(target_family = "windows") {
         return false;
@@ -606,7 +724,7 @@ async fn cmd_update(_config: &Cli, args: &cli::UpdateArgs) -> Result<(), miette:
     updater.configure_version_specifier(specifier);
 
     // Want this code to get updated if we develop http client opinions
-    let ClientSettings {} = ClientSettings::new();
+    let ClientSettings { retry: _ } = ClientSettings::new();
 
     // This uses debug assertions because we want to avoid this
     // being compiled into the release build; this is purely for
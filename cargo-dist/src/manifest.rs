@@ -68,6 +68,8 @@ pub(crate) fn load_and_merge_manifests(
             // There's one value and N machines (redesign required for per-machine values)
             // although dist_version *really* should be stable across all machines
             dist_version: _,
+            // same as dist_version
+            schema_version: _,
             // one value N machines
             system_info: _,
             announcement_tag,
@@ -83,6 +85,7 @@ pub(crate) fn load_and_merge_manifests(
             releases,
             systems,
             assets,
+            build_logs,
             ci,
             linkage,
             github_attestations: _,
@@ -129,6 +132,7 @@ pub(crate) fn load_and_merge_manifests(
         // Just merge all the system-specific info
         output.systems.extend(systems);
         output.assets.extend(assets);
+        output.build_logs.extend(build_logs);
         output.linkage.extend(linkage);
     }
 
@@ -239,6 +243,10 @@ fn add_manifest_artifact(
     artifact_idx: ArtifactIdx,
 ) {
     let artifact = dist.artifact(artifact_idx);
+    // The build registers the wheels it actually produced
+    if let ArtifactKind::Wheel(_) = artifact.kind {
+        return;
+    }
     let mut assets = vec![];
 
     let built_assets = artifact
@@ -279,6 +287,7 @@ fn add_manifest_artifact(
                         StaticAssetKind::Changelog => AssetKind::Changelog,
                         StaticAssetKind::License => AssetKind::License,
                         StaticAssetKind::Readme => AssetKind::Readme,
+                        StaticAssetKind::Sbom => AssetKind::Sbom,
                         StaticAssetKind::Other => AssetKind::Unknown,
                     };
                     Asset {
@@ -381,6 +390,17 @@ fn add_manifest_artifact(
             description = None;
             kind = cargo_dist_schema::ArtifactKind::Updater;
         }
+        ArtifactKind::Sbom(_) => {
+            install_hint = None;
+            description = None;
+            kind = cargo_dist_schema::ArtifactKind::Sbom;
+        }
+        ArtifactKind::SymbolServer(_) => {
+            install_hint = None;
+            description = Some("symbols laid out for a symbol server".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Symbols;
+        }
+        ArtifactKind::Wheel(_) => unreachable!("wheels are added to the manifest by the build"),
     };
 
     let checksum = artifact.checksum.map(|idx| dist.artifact(idx).id.clone());
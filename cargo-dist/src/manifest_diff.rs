@@ -0,0 +1,412 @@
+//! Diffing two dist-manifest.jsons
+//!
+//! This is the machinery behind `cargo dist manifest-diff`, which summarizes how
+//! two releases (or two plans for the same release) differ in ways that matter
+//! to users: which targets and installers exist, what the binaries are called,
+//! and what they need from the system to run.
+
+use std::fmt::Write;
+
+use axoasset::SourceFile;
+use camino::Utf8Path;
+use cargo_dist_schema::{ArtifactKind, AssetKind, DistManifest, Linkage};
+use serde::Serialize;
+use similar::{Algorithm, ChangeTag};
+
+use crate::{
+    errors::DistResult,
+    platform::{native_runtime_conditions_for_artifact, LibcVersion},
+    SortedMap, SortedSet,
+};
+
+/// The differences between two dist-manifest.jsons
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ManifestDiff {
+    /// Apps which only appear in the new manifest
+    pub added_releases: Vec<String>,
+    /// Apps which only appear in the old manifest
+    pub removed_releases: Vec<String>,
+    /// Apps in both manifests which changed
+    pub releases: Vec<ReleaseDiff>,
+}
+
+/// The differences between two versions of a release
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ReleaseDiff {
+    /// Name of the app
+    pub app_name: String,
+    /// Version in the old manifest
+    pub old_version: String,
+    /// Version in the new manifest
+    pub new_version: String,
+    /// Targets we now build for
+    pub added_targets: Vec<String>,
+    /// Targets we no longer build for
+    pub removed_targets: Vec<String>,
+    /// New artifacts (other than installers)
+    pub added_artifacts: Vec<String>,
+    /// Removed artifacts (other than installers)
+    pub removed_artifacts: Vec<String>,
+    /// New installers
+    pub added_installers: Vec<String>,
+    /// Removed installers
+    pub removed_installers: Vec<String>,
+    /// Targets in both manifests which changed
+    pub targets: Vec<TargetDiff>,
+}
+
+/// The differences between the binaries for a target in two versions of a release
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct TargetDiff {
+    /// The target triple
+    pub target: String,
+    /// Binaries which only appear in the new release
+    pub added_binaries: Vec<String>,
+    /// Binaries which only appear in the old release
+    pub removed_binaries: Vec<String>,
+    /// Binaries which appear to have been renamed (old name, new name)
+    pub renamed_binaries: Vec<(String, String)>,
+    /// Dynamic libraries the binaries now link against ("kind: path")
+    pub added_linkage: Vec<String>,
+    /// Dynamic libraries the binaries no longer link against ("kind: path")
+    pub removed_linkage: Vec<String>,
+    /// The minimum glibc version needed, if it changed (old, new)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_glibc_version: Option<(Option<LibcVersion>, Option<LibcVersion>)>,
+}
+
+impl ManifestDiff {
+    /// Whether the manifests are equivalent
+    pub fn is_empty(&self) -> bool {
+        self.added_releases.is_empty()
+            && self.removed_releases.is_empty()
+            && self.releases.is_empty()
+    }
+}
+
+impl ReleaseDiff {
+    fn is_empty(&self) -> bool {
+        let Self {
+            app_name: _,
+            old_version,
+            new_version,
+            added_targets,
+            removed_targets,
+            added_artifacts,
+            removed_artifacts,
+            added_installers,
+            removed_installers,
+            targets,
+        } = self;
+        old_version == new_version
+            && added_targets.is_empty()
+            && removed_targets.is_empty()
+            && added_artifacts.is_empty()
+            && removed_artifacts.is_empty()
+            && added_installers.is_empty()
+            && removed_installers.is_empty()
+            && targets.is_empty()
+    }
+}
+
+impl TargetDiff {
+    fn is_empty(&self) -> bool {
+        let Self {
+            target: _,
+            added_binaries,
+            removed_binaries,
+            renamed_binaries,
+            added_linkage,
+            removed_linkage,
+            min_glibc_version,
+        } = self;
+        added_binaries.is_empty()
+            && removed_binaries.is_empty()
+            && renamed_binaries.is_empty()
+            && added_linkage.is_empty()
+            && removed_linkage.is_empty()
+            && min_glibc_version.is_none()
+    }
+}
+
+/// Load and diff two dist-manifest.jsons
+pub fn do_manifest_diff(old: &Utf8Path, new: &Utf8Path) -> DistResult<ManifestDiff> {
+    let old: DistManifest = SourceFile::load_local(old)?.deserialize_json()?;
+    let new: DistManifest = SourceFile::load_local(new)?.deserialize_json()?;
+    Ok(diff_manifests(&old, &new))
+}
+
+/// Diff two manifests
+pub fn diff_manifests(old: &DistManifest, new: &DistManifest) -> ManifestDiff {
+    let app_names = |manifest: &DistManifest| {
+        let mut names = manifest
+            .releases
+            .iter()
+            .map(|r| r.app_name.clone())
+            .collect::<Vec<_>>();
+        names.sort();
+        names
+    };
+    let old_apps = app_names(old);
+    let new_apps = app_names(new);
+    let (added_releases, removed_releases) = diff_lists(&old_apps, &new_apps);
+
+    let releases = old
+        .releases
+        .iter()
+        .filter_map(|old_release| {
+            let new_release = new.release_by_name(&old_release.app_name)?;
+            let old_summary = ReleaseSummary::new(old, old_release);
+            let new_summary = ReleaseSummary::new(new, new_release);
+            let diff = old_summary.diff(&new_summary, old_release, new_release);
+            (!diff.is_empty()).then_some(diff)
+        })
+        .collect();
+
+    ManifestDiff {
+        added_releases,
+        removed_releases,
+        releases,
+    }
+}
+
+/// The parts of a release we compare
+struct ReleaseSummary {
+    artifacts: Vec<String>,
+    installers: Vec<String>,
+    targets: SortedMap<String, TargetSummary>,
+}
+
+#[derive(Default)]
+struct TargetSummary {
+    binaries: Vec<String>,
+    linkage: Vec<String>,
+    min_glibc_version: Option<LibcVersion>,
+}
+
+impl ReleaseSummary {
+    fn new(manifest: &DistManifest, release: &cargo_dist_schema::Release) -> Self {
+        let mut artifacts = vec![];
+        let mut installers = vec![];
+        let mut targets = SortedMap::<String, TargetSummary>::new();
+        for (id, artifact) in manifest.artifacts_for_release(release) {
+            let name = artifact.name.clone().unwrap_or_else(|| id.to_owned());
+            match artifact.kind {
+                ArtifactKind::Installer => installers.push(name),
+                ArtifactKind::ExecutableZip => {
+                    artifacts.push(name);
+                    let glibc = native_runtime_conditions_for_artifact(manifest, &id.to_owned())
+                        .min_glibc_version;
+                    let mut binaries = SortedSet::new();
+                    let mut linkage = Linkage::default();
+                    for asset in &artifact.assets {
+                        if let AssetKind::Executable(_) = asset.kind {
+                            if let Some(bin) = asset.name.clone().or_else(|| asset.path.clone()) {
+                                binaries.insert(bin);
+                            }
+                        }
+                        let asset_linkage = asset
+                            .id
+                            .as_ref()
+                            .and_then(|id| manifest.assets.get(id)?.linkage.as_ref());
+                        if let Some(asset_linkage) = asset_linkage {
+                            linkage.extend(asset_linkage);
+                        }
+                    }
+                    for target in &artifact.target_triples {
+                        let summary = targets.entry(target.clone()).or_default();
+                        summary.binaries.extend(binaries.iter().cloned());
+                        summary.linkage.extend(linkage_entries(&linkage));
+                        summary.min_glibc_version = summary.min_glibc_version.max(glibc);
+                    }
+                }
+                _ => artifacts.push(name),
+            }
+        }
+        for summary in targets.values_mut() {
+            summary.binaries.sort();
+            summary.binaries.dedup();
+            summary.linkage.sort();
+            summary.linkage.dedup();
+        }
+        artifacts.sort();
+        installers.sort();
+        Self {
+            artifacts,
+            installers,
+            targets,
+        }
+    }
+
+    fn diff(
+        &self,
+        new: &ReleaseSummary,
+        old_release: &cargo_dist_schema::Release,
+        new_release: &cargo_dist_schema::Release,
+    ) -> ReleaseDiff {
+        let old_targets = self.targets.keys().cloned().collect::<Vec<_>>();
+        let new_targets = new.targets.keys().cloned().collect::<Vec<_>>();
+        let (added_targets, removed_targets) = diff_lists(&old_targets, &new_targets);
+        let (added_artifacts, removed_artifacts) = diff_lists(&self.artifacts, &new.artifacts);
+        let (added_installers, removed_installers) = diff_lists(&self.installers, &new.installers);
+
+        let targets = self
+            .targets
+            .iter()
+            .filter_map(|(target, old_target)| {
+                let new_target = new.targets.get(target)?;
+                let diff = old_target.diff(target, new_target);
+                (!diff.is_empty()).then_some(diff)
+            })
+            .collect();
+
+        ReleaseDiff {
+            app_name: old_release.app_name.clone(),
+            old_version: old_release.app_version.clone(),
+            new_version: new_release.app_version.clone(),
+            added_targets,
+            removed_targets,
+            added_artifacts,
+            removed_artifacts,
+            added_installers,
+            removed_installers,
+            targets,
+        }
+    }
+}
+
+impl TargetSummary {
+    fn diff(&self, target: &str, new: &TargetSummary) -> TargetDiff {
+        let (mut added_binaries, mut removed_binaries) = diff_lists(&self.binaries, &new.binaries);
+        // If exactly one binary went away and exactly one showed up, it's most likely a rename
+        let renamed_binaries = if added_binaries.len() == 1 && removed_binaries.len() == 1 {
+            vec![(removed_binaries.remove(0), added_binaries.remove(0))]
+        } else {
+            vec![]
+        };
+        let (added_linkage, removed_linkage) = diff_lists(&self.linkage, &new.linkage);
+        let min_glibc_version = (self.min_glibc_version != new.min_glibc_version)
+            .then_some((self.min_glibc_version, new.min_glibc_version));
+
+        TargetDiff {
+            target: target.to_owned(),
+            added_binaries,
+            removed_binaries,
+            renamed_binaries,
+            added_linkage,
+            removed_linkage,
+            min_glibc_version,
+        }
+    }
+}
+
+/// Flatten linkage into "kind: path" entries
+fn linkage_entries(linkage: &Linkage) -> Vec<String> {
+    let Linkage {
+        system,
+        homebrew,
+        public_unmanaged,
+        other,
+        frameworks,
+    } = linkage;
+    [
+        ("system", system),
+        ("homebrew", homebrew),
+        ("public (unmanaged)", public_unmanaged),
+        ("other", other),
+        ("frameworks", frameworks),
+    ]
+    .into_iter()
+    .flat_map(|(kind, libs)| libs.iter().map(move |lib| format!("{kind}: {}", lib.path)))
+    .collect()
+}
+
+/// Get the (added, removed) entries between two sorted lists
+fn diff_lists(old: &[String], new: &[String]) -> (Vec<String>, Vec<String>) {
+    let mut added = vec![];
+    let mut removed = vec![];
+    for op in similar::capture_diff_slices(Algorithm::Myers, old, new) {
+        for change in op.iter_changes(old, new) {
+            match change.tag() {
+                ChangeTag::Insert => added.push(change.value().clone()),
+                ChangeTag::Delete => removed.push(change.value().clone()),
+                ChangeTag::Equal => {}
+            }
+        }
+    }
+    (added, removed)
+}
+
+fn fmt_glibc(version: &Option<LibcVersion>) -> String {
+    match version {
+        Some(LibcVersion { major, series }) => format!("{major}.{series}"),
+        None => "none".to_owned(),
+    }
+}
+
+/// Render a diff for humans
+pub fn render_human(diff: &ManifestDiff) -> String {
+    let mut out = String::new();
+    if diff.is_empty() {
+        out.push_str("no differences\n");
+        return out;
+    }
+    for app in &diff.added_releases {
+        writeln!(out, "+ release {app}").unwrap();
+    }
+    for app in &diff.removed_releases {
+        writeln!(out, "- release {app}").unwrap();
+    }
+    for release in &diff.releases {
+        if release.old_version == release.new_version {
+            writeln!(out, "{} {}", release.app_name, release.new_version).unwrap();
+        } else {
+            writeln!(
+                out,
+                "{} {} -> {}",
+                release.app_name, release.old_version, release.new_version
+            )
+            .unwrap();
+        }
+        let lists = [
+            ("+ target", &release.added_targets),
+            ("- target", &release.removed_targets),
+            ("+ artifact", &release.added_artifacts),
+            ("- artifact", &release.removed_artifacts),
+            ("+ installer", &release.added_installers),
+            ("- installer", &release.removed_installers),
+        ];
+        for (label, entries) in lists {
+            for entry in entries {
+                writeln!(out, "  {label} {entry}").unwrap();
+            }
+        }
+        for target in &release.targets {
+            writeln!(out, "  {}:", target.target).unwrap();
+            for (old, new) in &target.renamed_binaries {
+                writeln!(out, "    binary {old} -> {new}").unwrap();
+            }
+            let lists = [
+                ("+ binary", &target.added_binaries),
+                ("- binary", &target.removed_binaries),
+                ("+ linkage", &target.added_linkage),
+                ("- linkage", &target.removed_linkage),
+            ];
+            for (label, entries) in lists {
+                for entry in entries {
+                    writeln!(out, "    {label} {entry}").unwrap();
+                }
+            }
+            if let Some((old, new)) = &target.min_glibc_version {
+                writeln!(
+                    out,
+                    "    min glibc {} -> {}",
+                    fmt_glibc(old),
+                    fmt_glibc(new)
+                )
+                .unwrap();
+            }
+        }
+    }
+    out
+}
@@ -1,15 +1,22 @@
 //! Centralized logic for initializing http clients to
 //! ensure uniform configuration.
 
-use crate::errors::DistResult;
+use std::future::Future;
+use std::time::Duration;
+
+use crate::errors::{DistError, DistResult};
 use axoasset::reqwest;
+use tracing::warn;
 
 /// Settings for http clients
 ///
 /// Any settings that should apply to all http requests should
 /// be stored here, to avoid different configurations.
 #[derive(Debug, Clone, Default)]
-pub struct ClientSettings {}
+pub struct ClientSettings {
+    /// How to retry operations that fail (uploads, listing remote files, ...)
+    pub retry: RetryPolicy,
+}
 
 impl ClientSettings {
     /// Create new ClientSettings using all necessary values
@@ -23,7 +30,9 @@ impl ClientSettings {
 /// As of this writing this shouldn't be used/exposed, as we'd prefer
 /// to avoid proliferating random http clients. For now AxoClient
 /// is sufficient.
-fn create_reqwest_client(ClientSettings {}: &ClientSettings) -> DistResult<reqwest::Client> {
+fn create_reqwest_client(
+    ClientSettings { retry: _ }: &ClientSettings,
+) -> DistResult<reqwest::Client> {
     let client = reqwest::Client::builder()
         .build()
         .expect("failed to initialize http client");
@@ -45,10 +54,152 @@ pub fn create_axoasset_client(settings: &ClientSettings) -> DistResult<axoasset:
 /// this should be called in a fairly lazy/latebound way -- only when we know
 /// for sure we HAVE to do gazenot http requests.
 pub fn create_gazenot_client(
-    ClientSettings {}: &ClientSettings,
+    ClientSettings { retry: _ }: &ClientSettings,
     source_host: &str,
     owner: &str,
 ) -> DistResult<gazenot::Gazenot> {
     let client = gazenot::Gazenot::into_the_abyss(source_host, owner)?;
     Ok(client)
 }
+
+/// Download a file, returning None if the server doesn't have it
+///
+/// Any other unsuccessful response is an error, so that a flaky server isn't
+/// mistaken for a missing file.
+pub async fn fetch_file(client: &axoasset::AxoClient, url: &str) -> DistResult<Option<Vec<u8>>> {
+    let response = client.get(url).await?;
+    let status = response.status();
+    if status == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    if !status.is_success() {
+        return Err(DistError::FetchFailed {
+            url: url.to_owned(),
+            status: status.as_u16(),
+        });
+    }
+    let contents = response.bytes().await.map_err(|details| {
+        axoasset::AxoassetError::RemoteAssetRequestFailed {
+            origin_path: url.to_owned(),
+            details,
+        }
+    })?;
+    Ok(Some(contents.to_vec()))
+}
+
+/// How to retry a flaky network operation
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// How many times to try the operation in total
+    pub attempts: u32,
+    /// How long to wait after the first failure (doubled after every subsequent one)
+    pub initial_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            attempts: 5,
+            initial_delay: Duration::from_secs(2),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Run `op` until it succeeds or we run out of attempts, backing off between failures
+    ///
+    /// Only [transient][Transient] failures are retried, anything else is returned
+    /// immediately. The error from the final attempt is returned if all of them fail.
+    pub async fn run<T, E, F, Fut>(&self, desc: &str, mut op: F) -> Result<T, E>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        E: std::fmt::Display + Transient,
+    {
+        let mut delay = self.initial_delay;
+        let mut attempt = 1;
+        loop {
+            match op().await {
+                Ok(val) => return Ok(val),
+                Err(e) if attempt < self.attempts && e.is_transient() => {
+                    warn!(
+                        "failed to {desc} (attempt {attempt}/{}), retrying in {}s: {e}",
+                        self.attempts,
+                        delay.as_secs_f32()
+                    );
+                    tokio::time::sleep(delay).await;
+                    delay *= 2;
+                    attempt += 1;
+                }
+                Err(e) => return Err(e),
+            }
+        }
+    }
+}
+
+/// Errors that might go away if we try again
+pub trait Transient {
+    /// Whether this is a transient failure (a dropped connection, a timeout,
+    /// an overloaded server, ...) and so is worth retrying
+    fn is_transient(&self) -> bool;
+}
+
+/// Whether an HTTP status means "try again later" (server errors and rate limits)
+pub fn is_transient_status(status: u16) -> bool {
+    status == 429 || (500..600).contains(&status)
+}
+
+impl Transient for reqwest::Error {
+    fn is_transient(&self) -> bool {
+        self.is_connect()
+            || self.is_timeout()
+            || self
+                .status()
+                .is_some_and(|status| is_transient_status(status.as_u16()))
+    }
+}
+
+impl Transient for axoasset::AxoassetError {
+    fn is_transient(&self) -> bool {
+        match self {
+            axoasset::AxoassetError::RemoteAssetRequestFailed { details, .. } => {
+                details.is_transient()
+            }
+            _ => false,
+        }
+    }
+}
+
+impl Transient for gazenot::error::GazenotError {
+    fn is_transient(&self) -> bool {
+        use gazenot::error::GazenotErrorInner;
+        match &self.cause {
+            GazenotErrorInner::Reqwest(e) => e.is_transient(),
+            GazenotErrorInner::Asset(e) => e.is_transient(),
+            GazenotErrorInner::ResponseError { status, .. } => is_transient_status(status.as_u16()),
+            _ => false,
+        }
+    }
+}
+
+impl Transient for DistError {
+    fn is_transient(&self) -> bool {
+        match self {
+            DistError::FetchFailed { status, .. } => is_transient_status(*status),
+            DistError::Asset(e) => e.is_transient(),
+            DistError::Gazenot(e) => e.is_transient(),
+            DistError::GithubCliFailed { stderr, .. } => {
+                // gh doesn't give us much to go on besides what it prints
+                const TRANSIENT: &[&str] = &[
+                    "HTTP 429",
+                    "HTTP 5",
+                    "timeout",
+                    "connection reset",
+                    "connection refused",
+                ];
+                TRANSIENT.iter().any(|needle| stderr.contains(needle))
+            }
+            _ => false,
+        }
+    }
+}
@@ -227,12 +227,10 @@
 
 #![allow(rustdoc::private_intra_doc_links)]
 
-use axoproject::platforms::{
-    TARGET_ARM64_MAC, TARGET_ARM64_WINDOWS, TARGET_X64_MAC, TARGET_X64_WINDOWS, TARGET_X86_WINDOWS,
-};
-
+pub use cargo_dist_schema::query::SupportQuality;
 use cargo_dist_schema::{
-    ArtifactId, AssetId, BuildEnvironment, DistManifest, GlibcVersion, Linkage, SystemInfo,
+    query::{self, supported_targets},
+    ArtifactId, AssetId, DistManifest, GlibcVersion, Linkage, SystemInfo,
 };
 use serde::Serialize;
 
@@ -242,54 +240,6 @@ use crate::{
     BinaryKind, DistGraphBuilder, ReleaseIdx, SortedMap, TargetTriple,
 };
 
-/// Suffixes of TargetTriples that refer to statically linked linux libcs.
-///
-/// On Linux it's preferred to dynamically link libc *but* because the One True ABI
-/// is actually the Linux kernel syscall interface, you *can* theoretically statically
-/// link libc. This comes with various tradeoffs but the big selling point is that the
-/// Linux kernel is a much more slowly moving target, so you can build a binary
-/// that's portable across way more systems by statically linking libc. As such,
-/// for any archive claiming to provide a static libc linux build, we can mark this
-/// archive as providing support for any linux distro (for that architecture)
-///
-/// Currently rust takes "linux-musl" to mean "statically linked musl", but
-/// in the future it will mean "dynamically linked musl":
-///
-/// https://github.com/rust-lang/compiler-team/issues/422
-///
-/// To avoid this ambiguity, we prefer "musl-static" and "musl-dynamic" aliases to
-/// disambiguate this situation. This module immediately rename "musl" to "musl-static",
-/// so in the following listings we don't need to deal with bare "musl".
-///
-/// Also note that known bonus ABI suffixes like "eabihf" are also already dealt with.
-const LINUX_STATIC_LIBCS: &[&str] = &["linux-musl-static"];
-/// Dynamically linked linux libcs that static libcs can replace
-const LINUX_STATIC_REPLACEABLE_LIBCS: &[&str] = &["linux-gnu", "linux-musl-dynamic"];
-/// A fake TargetTriple for apple's universal2 format (staples x64 and arm64 together)
-const TARGET_MACOS_UNIVERSAL2: &str = "universal2-apple-darwin";
-
-/// The quality of support an archive provides for a given platform
-#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
-pub enum SupportQuality {
-    /// The archive natively supports this platform, there's no beating it
-    HostNative,
-    /// The archive natively supports this platform, but it's a Universal binary that contains
-    /// multiple platforms stapled together, so if there are also more precise archives, prefer those.
-    BulkyNative,
-    /// The archive is still technically native to this platform, but it's in some sense
-    /// imperfect. This can happen for things like "running a 32-bit binary on 64-bit" or
-    /// "using a statically linked linux libc". This solution is acceptable, but a HostNative
-    /// (or BulkyNative) solution should always be preferred.
-    ImperfectNative,
-    /// The archive is only running by the grace of pretty heavyweight emulation like Rosetta2.
-    /// This should be treated as a last resort, but hey, it works!
-    Emulated,
-    /// The layers of emulation are out of control.
-    Hellmulated,
-    /// STOP
-    HighwayToHellmulated,
-}
-
 /// A unixy libc version
 #[derive(Debug, Copy, Clone, PartialEq, PartialOrd, Eq, Ord, Serialize)]
 pub struct LibcVersion {
@@ -438,7 +388,7 @@ impl PlatformSupport {
                 dist.make_executable_zip_for_variant(release_idx, variant_idx);
 
             let native_runtime_conditions =
-                native_runtime_conditions_for_artifact(dist, &artifact.id);
+                native_runtime_conditions_for_artifact(&dist.manifest, &artifact.id);
 
             let executables = binaries
                 .iter()
@@ -507,9 +457,29 @@ impl PlatformSupport {
 
     /// Convert to the old-style format so we can gradually migrate
     pub fn fragments(&self) -> Vec<ExecutableZipFragment> {
+        self.fragments_preferring(None)
+    }
+
+    /// Like [`PlatformSupport::fragments`][], but if an archive built for `preferred_target`
+    /// supports a platform, use it instead of the best option.
+    ///
+    /// This is used by installers that would rather ship one universal2 macOS archive
+    /// than a separate one for each arch.
+    pub fn fragments_preferring(
+        &self,
+        preferred_target: Option<&str>,
+    ) -> Vec<ExecutableZipFragment> {
         let mut fragments = vec![];
         for (target, options) in &self.platforms {
-            let Some(option) = options.first() else {
+            let preferred = preferred_target.and_then(|preferred| {
+                options.iter().find(|option| {
+                    self.archives[option.archive_idx]
+                        .target_triples
+                        .iter()
+                        .any(|t| t == preferred)
+                })
+            });
+            let Some(option) = preferred.or(options.first()) else {
                 continue;
             };
             let archive = &self.archives[option.archive_idx];
@@ -577,146 +547,22 @@ fn supports(
 ) -> Vec<(TargetTriple, PlatformEntry)> {
     let mut res = Vec::new();
     for target in &archive.target_triples {
-        // For the following linux checks we want to pull off any "eabihf" suffix while
-        // comparing/parsing libc types.
-        let (degunked_target, abigunk) = if let Some(inner_target) = target.strip_suffix("eabihf") {
-            (inner_target, "eabihf")
-        } else {
-            (target.as_str(), "")
-        };
-
-        // If this is the ambiguous-soon-to-be-changed "musl" target, rename it to musl-static,
-        // which is its current behaviour.
-        let (target, degunked_target) = if let Some(system) = degunked_target.strip_suffix("musl") {
-            (
-                format!("{system}musl-static{abigunk}"),
-                format!("{degunked_target}-static"),
-            )
-        } else {
-            (target.to_owned(), degunked_target.to_owned())
-        };
-
-        // First, add the target itself as a HostNative entry
-        res.push((
-            target.clone(),
-            PlatformEntry {
-                quality: SupportQuality::HostNative,
-                runtime_conditions: archive.native_runtime_conditions.clone(),
-                archive_idx,
-            },
-        ));
-
-        // If this is a static linux libc, say it can support any linux at ImperfectNative quality
-        for &static_libc in LINUX_STATIC_LIBCS {
-            let Some(system) = degunked_target.strip_suffix(static_libc) else {
-                continue;
-            };
-            for &libc in LINUX_STATIC_REPLACEABLE_LIBCS {
-                res.push((
-                    format!("{system}{libc}{abigunk}"),
-                    PlatformEntry {
-                        quality: SupportQuality::ImperfectNative,
-                        runtime_conditions: archive.native_runtime_conditions.clone(),
-                        archive_idx,
-                    },
-                ));
-            }
-            break;
-        }
-
-        // universal2 macos binaries are totally native for both arches, but bulkier than
-        // necessary if we have builds for the individual platforms too.
-        if target == TARGET_MACOS_UNIVERSAL2 {
-            res.push((
-                TARGET_X64_MAC.to_owned(),
-                PlatformEntry {
-                    quality: SupportQuality::BulkyNative,
-                    runtime_conditions: archive.native_runtime_conditions.clone(),
-                    archive_idx,
-                },
-            ));
-            res.push((
-                TARGET_ARM64_MAC.to_owned(),
-                PlatformEntry {
-                    quality: SupportQuality::BulkyNative,
-                    runtime_conditions: archive.native_runtime_conditions.clone(),
-                    archive_idx,
-                },
-            ));
-        }
-
-        // FIXME?: technically we could add "run 32-bit intel macos on 64-bit intel"
-        // BUT this is unlikely to succeed as you increasingly need an EOL macOS,
-        // as support was dropped in macOS Catalina (macOS 10.15, October 2019).
-        // So this is unlikely to be helpful and DEFINITELY shouldn't be suggested
-        // unless all installers enforce the check for OS version.
-
-        // If this is x64 macos, say it can run on arm64 macos using Rosetta2
-        // Note that Rosetta2 is not *actually* installed by default on Apple Silicon,
-        // and the auto-installer for it only applies to GUI apps, not CLI apps, so ideally
-        // any installer that uses this fallback should check if Rosetta2 is installed!
-        if target == TARGET_X64_MAC {
+        // The actual compatibility rules are shared with other manifest consumers
+        for support in supported_targets(target) {
+            // Emulation may impose conditions on top of the archive's native ones
             let runtime_conditions = RuntimeConditions {
-                rosetta2: true,
+                rosetta2: support.rosetta2,
                 ..archive.native_runtime_conditions.clone()
             };
             res.push((
-                TARGET_ARM64_MAC.to_owned(),
+                support.target,
                 PlatformEntry {
-                    quality: SupportQuality::Emulated,
+                    quality: support.quality,
                     runtime_conditions,
                     archive_idx,
                 },
             ));
         }
-
-        // x86_32 windows binaries run fine on x86_64, but it's Imperfect compared to actual x86_64 binaries
-        if target == TARGET_X86_WINDOWS {
-            res.push((
-                TARGET_X64_WINDOWS.to_owned(),
-                PlatformEntry {
-                    quality: SupportQuality::ImperfectNative,
-                    runtime_conditions: archive.native_runtime_conditions.clone(),
-                    archive_idx,
-                },
-            ));
-        }
-
-        // Windows' equivalent to Rosetta2 (CHPE) is in fact installed-by-default so no need to detect!
-        if target == TARGET_X64_WINDOWS || target == TARGET_X86_WINDOWS {
-            // prefer x64 over x86 if we have the option
-            let quality = if target == TARGET_X86_WINDOWS {
-                SupportQuality::Hellmulated
-            } else {
-                SupportQuality::Emulated
-            };
-            res.push((
-                TARGET_ARM64_WINDOWS.to_owned(),
-                PlatformEntry {
-                    quality,
-                    runtime_conditions: archive.native_runtime_conditions.clone(),
-                    archive_idx,
-                },
-            ));
-        }
-
-        // windows-msvc binaries should always be acceptable on windows-gnu (mingw)
-        //
-        // FIXME: in theory x64-pc-windows-msvc and i686-pc-windows-msvc can run on
-        // aarch64-pc-windows-gnu, as a hybrid of this rules and the CHPE rule above.
-        // I don't want to think about computing the transitive closure of platform
-        // support and how to do all the tie breaking ("HighwayToHellmulated"?), so
-        // for now all 5 arm64 mingw users can be a little sad.
-        if let Some(system) = target.strip_suffix("windows-msvc") {
-            res.push((
-                format!("{system}windows-gnu"),
-                PlatformEntry {
-                    quality: SupportQuality::ImperfectNative,
-                    runtime_conditions: archive.native_runtime_conditions.clone(),
-                    archive_idx,
-                },
-            ));
-        }
     }
     res
 }
@@ -749,11 +595,10 @@ fn max_of_min_libc_versions(
 }
 
 /// Compute the requirements for running the binaries of this release on its host platform
-fn native_runtime_conditions_for_artifact(
-    dist: &DistGraphBuilder,
+pub(crate) fn native_runtime_conditions_for_artifact(
+    manifest: &DistManifest,
     artifact_id: &ArtifactId,
 ) -> RuntimeConditions {
-    let manifest = &dist.manifest;
     let mut runtime_conditions = RuntimeConditions::default();
     if let Some(artifact) = manifest.artifacts.get(artifact_id) {
         for asset in &artifact.assets {
@@ -805,22 +650,7 @@ fn native_runtime_conditions_for_asset(
 
 /// Get the native glibc version this binary links against, to the best of our ability
 fn native_glibc_version(system: &SystemInfo, linkage: &Linkage) -> Option<LibcVersion> {
-    for lib in &linkage.system {
-        // If this links against glibc, then we need to require that
-        if lib.is_glibc() {
-            if let BuildEnvironment::Linux {
-                glibc_version: Some(system_glibc),
-            } = &system.build_environment
-            {
-                // If there's a system libc, assume that's what it was built against
-                return Some(LibcVersion::glibc_from_schema(system_glibc));
-            } else {
-                // If the system has no known libc version use Ubuntu 20.04's glibc as a guess
-                return Some(LibcVersion::default_glibc());
-            }
-        }
-    }
-    None
+    query::native_glibc_version(system, linkage).map(|v| LibcVersion::glibc_from_schema(&v))
 }
 
 /// Get the native musl libc version this binary links against, to the best of our ability
@@ -0,0 +1,578 @@
+//! Cutting releases
+//!
+//! This is the machinery behind `cargo dist release`, which bumps the versions
+//! of the packages we'd announce, stamps their changelogs, and commits and tags
+//! the result with a tag that `cargo dist plan` will agree with. Pushing is left
+//! to the user, so they get a chance to look at what we did first.
+
+use axoasset::{toml_edit, LocalAsset, SourceFile};
+use axoprocess::Cmd;
+use axoproject::{PackageIdx, WorkspaceGraph, WorkspaceKind};
+use axotag::{parse_tag, Package, PartialAnnouncementTag, ReleaseType};
+use camino::{Utf8Path, Utf8PathBuf};
+use semver::Version;
+use tracing::warn;
+
+use crate::{
+    announce::select_packages,
+    config::{get_project, Config, ReleaseArgs},
+    errors::{DistError, DistResult},
+    tasks::tool_info,
+    DistGraphBuilder, SortedMap, SortedSet, Tools,
+};
+
+/// Run `cargo dist release`
+pub fn do_release(cfg: &Config, args: &ReleaseArgs) -> DistResult<()> {
+    let tools = tool_info()?;
+    let mut workspaces = get_project()?;
+    release_project(cfg, args, tools, &mut workspaces)
+}
+
+/// Cut a release of the given project
+pub(crate) fn release_project(
+    cfg: &Config,
+    args: &ReleaseArgs,
+    tools: Tools,
+    workspaces: &mut WorkspaceGraph,
+) -> DistResult<()> {
+    let git = tools
+        .git
+        .as_ref()
+        .map(|git| git.cmd.clone())
+        .ok_or_else(|| DistError::ToolMissing {
+            tool: "git".to_owned(),
+        })?;
+    let cargo = tools.cargo.cmd.clone();
+    let graph = DistGraphBuilder::new(
+        cfg.root_cmd.clone(),
+        tools,
+        workspaces,
+        cfg.artifact_mode,
+        cfg.allow_all_dirty,
+        true,
+    )?;
+    let Some(repo) = graph.workspaces.repo.clone() else {
+        return Err(DistError::ReleaseNoRepo);
+    };
+
+    // Figure out what we're releasing, the same way `plan` would
+    let announcing = match &args.package {
+        Some(name) => {
+            let (pkg_idx, package) = graph
+                .workspaces
+                .all_packages()
+                .find(|(_, package)| &package.name == name)
+                .ok_or_else(|| DistError::ReleaseUnknownPackage {
+                    package: name.clone(),
+                })?;
+            PartialAnnouncementTag {
+                tag: name.clone(),
+                release: ReleaseType::Package {
+                    idx: pkg_idx.0,
+                    version: package
+                        .version
+                        .as_ref()
+                        .map(|v| v.semver())
+                        .unwrap_or_else(|| Version::new(0, 0, 0)),
+                },
+                prerelease: false,
+            }
+        }
+        None => PartialAnnouncementTag::default(),
+    };
+    let releases = select_packages(&graph, &announcing);
+    if releases.is_empty() {
+        return Err(DistError::NothingToRelease {
+            help: "make sure the packages you want to release have binaries and aren't publish = false".to_owned(),
+        });
+    }
+    let packages = releases
+        .iter()
+        .map(|release| release.package_idx)
+        .collect::<Vec<_>>();
+
+    // Everything has to agree on what version we're bumping from
+    let current_versions = packages
+        .iter()
+        .map(|&pkg_idx| {
+            let package = graph.workspaces.package(pkg_idx);
+            let version = package.version.as_ref().unwrap().semver();
+            (package.name.clone(), version)
+        })
+        .collect::<SortedMap<_, _>>();
+    let distinct_versions = current_versions.values().collect::<SortedSet<_>>();
+    if distinct_versions.len() > 1 {
+        return Err(DistError::ReleaseMixedVersions {
+            versions: current_versions
+                .iter()
+                .map(|(name, version)| format!("  {name} {version}"))
+                .collect(),
+        });
+    }
+    let old_version = distinct_versions.into_iter().next().unwrap().clone();
+    let new_version = args.bump.apply(&old_version);
+    if new_version <= old_version {
+        return Err(DistError::ReleaseNotNewer {
+            old_version,
+            new_version,
+        });
+    }
+
+    let tag_namespace = graph
+        .inner
+        .config
+        .ci
+        .github
+        .as_ref()
+        .and_then(|ci| ci.tag_namespace.clone());
+    let tag = release_tag(
+        args.package.as_deref(),
+        &new_version,
+        tag_namespace.as_deref(),
+    );
+    // Make sure axotag agrees that this tag means what we think it means
+    let tag_packages = graph
+        .workspaces
+        .all_packages()
+        .map(|(pkg_idx, package)| Package {
+            name: package.name.clone(),
+            version: if packages.contains(&pkg_idx) {
+                Some(new_version.clone())
+            } else {
+                package.version.as_ref().map(|v| v.semver())
+            },
+        })
+        .collect::<Vec<_>>();
+    parse_tag(&tag_packages, &tag)?;
+    if repo.has_revision(&git, &tag) {
+        return Err(DistError::ReleaseTagExists { tag });
+    }
+
+    // Work out all the changes before touching anything
+    let new_version_str = new_version.to_string();
+    let mut edits = Edits::default();
+    let mut bumped = SortedSet::new();
+    for &pkg_idx in &packages {
+        bumped.extend(bump_package_version(
+            graph.workspaces,
+            pkg_idx,
+            &new_version_str,
+            &mut edits,
+        )?);
+    }
+    bump_dependency_versions(graph.workspaces, &bumped, &new_version_str, &mut edits)?;
+    let mut changelogs = packages
+        .iter()
+        .filter_map(|&pkg_idx| graph.workspaces.package(pkg_idx).changelog_file.clone())
+        .collect::<SortedSet<_>>();
+    if args.package.is_none() {
+        let root_workspace = graph.workspaces.root_workspace();
+        changelogs.extend(root_workspace.root_auto_includes.changelog.clone());
+    }
+    for changelog in changelogs {
+        let contents = edits.contents(&changelog)?;
+        match axoproject::changelog::stamp_unreleased(&contents, &new_version_str) {
+            Some(stamped) => {
+                edits.files.insert(changelog, stamped);
+            }
+            None => warn!("{changelog} has no Unreleased section, leaving it alone"),
+        }
+    }
+
+    let check = console::style("✔".to_string()).for_stderr().green();
+    for &pkg_idx in &bumped {
+        let name = &graph.workspaces.package(pkg_idx).name;
+        eprintln!("{check} {name}: {old_version} => {new_version}");
+    }
+    if args.dry_run {
+        for path in edits.files.keys() {
+            eprintln!("  would update {path}");
+        }
+        eprintln!("  would commit and tag {tag}");
+        return Ok(());
+    }
+
+    // Check this late so dry runs can be done with uncommitted changes
+    let status = run_git(&git, &repo.path, "check for uncommitted changes", |cmd| {
+        cmd.arg("status").arg("--porcelain");
+    })?;
+    if !status.trim().is_empty() {
+        return Err(DistError::ReleaseDirtyRepo {
+            status: status.trim_end().to_owned(),
+        });
+    }
+
+    let mut changed_files = vec![];
+    for (path, contents) in &edits.files {
+        LocalAsset::write_new(contents, path)?;
+        changed_files.push(path.clone());
+    }
+    // Cargo.lock has the versions of workspace members in it too
+    let cargo_workspaces = packages
+        .iter()
+        .map(|&pkg_idx| graph.workspaces.workspace_for_package(pkg_idx))
+        .collect::<SortedSet<_>>();
+    for workspace_idx in cargo_workspaces {
+        let workspace = graph.workspaces.workspace(workspace_idx);
+        let lockfile = workspace.workspace_dir.join("Cargo.lock");
+        if workspace.kind != WorkspaceKind::Rust || !lockfile.exists() {
+            continue;
+        }
+        let mut cmd = Cmd::new(&cargo, "update Cargo.lock");
+        cmd.arg("update")
+            .arg("--workspace")
+            .current_dir(&workspace.workspace_dir);
+        cmd.run()?;
+        changed_files.push(lockfile);
+    }
+
+    run_git(&git, &repo.path, "stage the release", |cmd| {
+        cmd.arg("add").arg("--");
+        for path in &changed_files {
+            cmd.arg(path);
+        }
+    })?;
+    run_git(&git, &repo.path, "commit the release", |cmd| {
+        cmd.arg("commit")
+            .arg("-m")
+            .arg(format!("chore: release {tag}"));
+    })?;
+    run_git(&git, &repo.path, "tag the release", |cmd| {
+        cmd.arg("tag").arg("-a").arg(&tag).arg("-m").arg(&tag);
+    })?;
+    eprintln!("{check} committed and tagged {tag}");
+    eprintln!();
+    eprintln!("run 'git push --follow-tags' to publish it");
+
+    Ok(())
+}
+
+/// Get the tag for releasing a version (of just `package`, if set)
+pub fn release_tag(
+    package: Option<&str>,
+    version: &Version,
+    tag_namespace: Option<&str>,
+) -> String {
+    let tag = match package {
+        Some(package) => format!("{package}-v{version}"),
+        None => format!("v{version}"),
+    };
+    // axotag ignores anything before a `/`, and the release workflow only
+    // looks for tags that start with the namespace
+    match tag_namespace {
+        Some(namespace) => format!("{namespace}/{tag}"),
+        None => tag,
+    }
+}
+
+/// Run git in the repo and get its output
+fn run_git(
+    git: &str,
+    repo_dir: &Utf8Path,
+    desc: &str,
+    args: impl FnOnce(&mut Cmd),
+) -> DistResult<String> {
+    let mut cmd = Cmd::new(git, desc);
+    args(&mut cmd);
+    // Let the user see why git is upset, if it is
+    cmd.current_dir(repo_dir)
+        .stderr(std::process::Stdio::inherit());
+    let output = cmd.output()?;
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// The new contents of every file we're changing
+#[derive(Default)]
+struct Edits {
+    files: SortedMap<Utf8PathBuf, String>,
+}
+
+impl Edits {
+    /// Get the contents of a file, including any edits we've already made
+    fn contents(&self, path: &Utf8Path) -> DistResult<String> {
+        match self.files.get(path) {
+            Some(contents) => Ok(contents.clone()),
+            None => Ok(LocalAsset::load_string(path)?),
+        }
+    }
+
+    /// Edit a toml file, keeping the changes if `edit` says it changed something
+    fn edit_toml(
+        &mut self,
+        path: &Utf8Path,
+        edit: impl FnOnce(&mut toml_edit::DocumentMut) -> bool,
+    ) -> DistResult<bool> {
+        let src = SourceFile::new(path.as_str(), self.contents(path)?);
+        let mut toml = src.deserialize_toml_edit()?;
+        if !edit(&mut toml) {
+            return Ok(false);
+        }
+        self.files.insert(path.to_owned(), toml.to_string());
+        Ok(true)
+    }
+}
+
+/// Set the version of a package in whichever manifest it comes from
+///
+/// Returns every package whose version changed, which can be more than the one
+/// we asked for if it inherits its version from a cargo workspace.
+fn bump_package_version(
+    workspaces: &WorkspaceGraph,
+    pkg_idx: PackageIdx,
+    new_version: &str,
+    edits: &mut Edits,
+) -> DistResult<Vec<PackageIdx>> {
+    let package = workspaces.package(pkg_idx);
+    let manifest_path = &package.manifest_path;
+
+    // A version in dist.toml overrides whatever the package manager thinks
+    if let Some(dist_manifest_path) = &package.dist_manifest_path {
+        if edits.edit_toml(dist_manifest_path, |toml| {
+            set_toml_version(toml, &["package"], new_version)
+        })? {
+            return Ok(vec![pkg_idx]);
+        }
+    }
+
+    let changed = match manifest_path.file_name() {
+        Some("Cargo.toml") => {
+            if edits.edit_toml(manifest_path, |toml| {
+                set_toml_version(toml, &["package"], new_version)
+            })? {
+                true
+            } else {
+                // Must be `version.workspace = true`, which means bumping every
+                // package that does that
+                let workspace_idx = workspaces.workspace_for_package(pkg_idx);
+                let workspace = workspaces.workspace(workspace_idx);
+                let changed = edits.edit_toml(&workspace.manifest_path, |toml| {
+                    set_toml_version(toml, &["workspace", "package"], new_version)
+                })?;
+                if changed {
+                    let mut bumped = vec![];
+                    for (other_idx, other) in workspaces.direct_packages(workspace_idx) {
+                        let src = SourceFile::new(
+                            other.manifest_path.as_str(),
+                            edits.contents(&other.manifest_path)?,
+                        );
+                        if inherits_workspace_version(&src.deserialize_toml_edit()?) {
+                            bumped.push(other_idx);
+                        }
+                    }
+                    return Ok(bumped);
+                }
+                false
+            }
+        }
+        Some("pyproject.toml") => edits.edit_toml(manifest_path, |toml| {
+            set_toml_version(toml, &["project"], new_version)
+        })?,
+        Some("dist.toml") => edits.edit_toml(manifest_path, |toml| {
+            set_toml_version(toml, &["package"], new_version)
+        })?,
+        Some("package.json") => {
+            let contents = edits.contents(manifest_path)?;
+            match set_json_version(&contents, new_version) {
+                Some(contents) => {
+                    edits.files.insert(manifest_path.clone(), contents);
+                    true
+                }
+                None => false,
+            }
+        }
+        _ => {
+            return Err(DistError::ReleaseUnsupportedManifest {
+                manifest_path: manifest_path.clone(),
+            })
+        }
+    };
+    if !changed {
+        return Err(DistError::ReleaseVersionNotFound {
+            manifest_path: manifest_path.clone(),
+            version: package
+                .version
+                .as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_default(),
+        });
+    }
+    Ok(vec![pkg_idx])
+}
+
+/// Check for `version.workspace = true` in a Cargo.toml
+fn inherits_workspace_version(toml: &toml_edit::DocumentMut) -> bool {
+    toml.get("package")
+        .and_then(|package| package.get("version"))
+        .and_then(|version| version.get("workspace"))
+        .and_then(|workspace| workspace.as_bool())
+        .unwrap_or(false)
+}
+
+/// Update the `version` requirements of path dependencies on the cargo packages we're bumping
+fn bump_dependency_versions(
+    workspaces: &WorkspaceGraph,
+    packages: &SortedSet<PackageIdx>,
+    new_version: &str,
+    edits: &mut Edits,
+) -> DistResult<()> {
+    let crate_names = packages
+        .iter()
+        .map(|&pkg_idx| workspaces.package(pkg_idx))
+        .filter(|package| package.manifest_path.file_name() == Some("Cargo.toml"))
+        .map(|package| package.true_name.clone())
+        .collect::<SortedSet<_>>();
+    if crate_names.is_empty() {
+        return Ok(());
+    }
+
+    let mut manifests = SortedSet::new();
+    for workspace_idx in workspaces.all_workspace_indices() {
+        let workspace = workspaces.workspace(workspace_idx);
+        if workspace.kind == WorkspaceKind::Rust {
+            manifests.insert(workspace.manifest_path.clone());
+        }
+    }
+    for (_, package) in workspaces.all_packages() {
+        if package.manifest_path.file_name() == Some("Cargo.toml") {
+            manifests.insert(package.manifest_path.clone());
+        }
+    }
+    for manifest_path in manifests {
+        edits.edit_toml(&manifest_path, |toml| {
+            set_dependency_versions(toml, &crate_names, new_version)
+        })?;
+    }
+    Ok(())
+}
+
+/// Set the `version` key of the table at `path`, returning whether we did
+///
+/// Versions that aren't plain strings (like `version.workspace = true`) are left alone.
+pub fn set_toml_version(toml: &mut toml_edit::DocumentMut, path: &[&str], version: &str) -> bool {
+    let mut table = toml.as_table_mut() as &mut dyn toml_edit::TableLike;
+    for key in path {
+        let Some(next) = table.get_mut(key).and_then(|t| t.as_table_like_mut()) else {
+            return false;
+        };
+        table = next;
+    }
+    let Some(value) = table.get_mut("version").and_then(|v| v.as_value_mut()) else {
+        return false;
+    };
+    if !value.is_str() {
+        return false;
+    }
+    let decor = value.decor().clone();
+    *value = version.into();
+    *value.decor_mut() = decor;
+    true
+}
+
+/// Update the version requirements of path dependencies on `crate_names`
+///
+/// Any operator on the requirement (like `=`) is kept.
+pub fn set_dependency_versions(
+    toml: &mut toml_edit::DocumentMut,
+    crate_names: &SortedSet<String>,
+    version: &str,
+) -> bool {
+    const DEP_KINDS: &[&str] = &["dependencies", "dev-dependencies", "build-dependencies"];
+    let mut changed = false;
+    let mut update = |table: &mut dyn toml_edit::TableLike| {
+        for kind in DEP_KINDS {
+            let Some(deps) = table.get_mut(kind).and_then(|d| d.as_table_like_mut()) else {
+                continue;
+            };
+            for (key, dep) in deps.iter_mut() {
+                let Some(dep) = dep.as_table_like_mut() else {
+                    continue;
+                };
+                let name = dep
+                    .get("package")
+                    .and_then(|p| p.as_str())
+                    .unwrap_or(key.get())
+                    .to_owned();
+                if !crate_names.contains(&name) || !dep.contains_key("path") {
+                    continue;
+                }
+                let Some(req) = dep.get_mut("version").and_then(|v| v.as_value_mut()) else {
+                    continue;
+                };
+                let Some(old_req) = req.as_str() else {
+                    continue;
+                };
+                let op =
+                    &old_req[..old_req.len() - old_req.trim_start_matches(['=', '^', '~']).len()];
+                let decor = req.decor().clone();
+                *req = format!("{op}{version}").into();
+                *req.decor_mut() = decor;
+                changed = true;
+            }
+        }
+    };
+
+    update(toml.as_table_mut());
+    if let Some(workspace) = toml
+        .get_mut("workspace")
+        .and_then(|w| w.as_table_like_mut())
+    {
+        update(workspace);
+    }
+    if let Some(targets) = toml.get_mut("target").and_then(|t| t.as_table_like_mut()) {
+        for (_, target) in targets.iter_mut() {
+            if let Some(target) = target.as_table_like_mut() {
+                update(target);
+            }
+        }
+    }
+    changed
+}
+
+/// Set the top-level `"version"` of a package.json, keeping the rest of the file as-is
+pub fn set_json_version(json: &str, version: &str) -> Option<String> {
+    let mut depth = 0;
+    let mut chars = json.char_indices().peekable();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '{' | '[' => depth += 1,
+            '}' | ']' => depth -= 1,
+            '"' => {
+                let end = string_end(json, idx)?;
+                // Skip over the string
+                while chars.next_if(|&(i, _)| i < end).is_some() {}
+                if depth != 1 || &json[idx..end] != "\"version\"" {
+                    continue;
+                }
+                // Make sure this is the key and find the value
+                let rest = json[end..].trim_start();
+                let rest = rest.strip_prefix(':')?.trim_start();
+                let value_start = json.len() - rest.len();
+                if !rest.starts_with('"') {
+                    return None;
+                }
+                let value_end = string_end(json, value_start)?;
+                return Some(format!(
+                    "{}\"{version}\"{}",
+                    &json[..value_start],
+                    &json[value_end..]
+                ));
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Given the index of a `"`, get the index just past the closing `"`
+fn string_end(json: &str, start: usize) -> Option<usize> {
+    let mut escaped = false;
+    for (idx, c) in json[start + 1..].char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            '"' => return Some(start + 1 + idx + 1),
+            _ => {}
+        }
+    }
+    None
+}
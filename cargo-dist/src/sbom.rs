@@ -0,0 +1,593 @@
+//! Software bills of materials (SBOMs)
+//!
+//! For each variant we can write an SBOM describing what went into its binaries:
+//!
+//! * the crates cargo resolved for that target, with the features we built with
+//! * the dynamic libraries [`linkage`][crate::linkage] found them linking against
+//!
+//! Crates come from `cargo metadata --offline` and checksums come from Cargo.lock, so
+//! no network access is needed. Only crates that end up in the binaries are included,
+//! so build-dependencies and proc-macros (which only run on the build machine) aren't.
+//!
+//! We support [CycloneDX 1.5](https://cyclonedx.org/docs/1.5/json/) and
+//! [SPDX 2.3](https://spdx.github.io/spdx-spec/v2.3/), both as JSON.
+
+use std::{collections::BTreeSet, time::SystemTime};
+
+use axoasset::{LocalAsset, SourceFile};
+use axoproject::platforms::{MACOS_UNIVERSAL2_COMPONENTS, TARGET_MACOS_UNIVERSAL2};
+use camino::Utf8Path;
+use cargo_dist_schema::{DistManifest, Library, PackageManager};
+use guppy::{
+    graph::{
+        cargo::{CargoOptions, CargoResolverVersion, CargoSet},
+        feature::{named_feature_filter, StandardFeatures},
+        DependencyDirection, ExternalSource, PackageGraph, PackageMetadata, PackageSource,
+    },
+    platform::{Platform, TargetFeatures},
+    MetadataCommand, PackageId,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tracing::warn;
+
+use crate::{
+    build::archive::civil_from_days, config::SbomFormat, CargoTargetFeatureList, DistError,
+    DistGraph, DistResult, ReleaseVariant, SbomImpl, SortedMap,
+};
+
+/// Everything we know about a build, independent of the SBOM format
+#[derive(Debug, Clone)]
+pub struct Sbom {
+    /// The name of the document (the id of the variant)
+    pub name: String,
+    /// The target the binaries were built for
+    pub target: String,
+    /// When the SBOM was made, in seconds since the unix epoch
+    pub timestamp: u64,
+    /// The app the binaries belong to
+    pub app: SbomComponent,
+    /// The crates that went into the binaries (not including the app itself)
+    pub crates: Vec<SbomComponent>,
+    /// The dynamic libraries the binaries link against
+    pub libraries: Vec<Library>,
+}
+
+/// An app or crate in an SBOM
+#[derive(Debug, Clone)]
+pub struct SbomComponent {
+    /// The name of the package
+    pub name: String,
+    /// The version of the package
+    pub version: String,
+    /// The description of the package
+    pub description: Option<String>,
+    /// The license of the package, as an SPDX expression (if it is one)
+    pub license: Option<String>,
+    /// The sha256 of the .crate, as recorded in Cargo.lock
+    pub checksum: Option<String>,
+    /// Where the package can be downloaded from, if it's published
+    pub download_url: Option<String>,
+    /// The package URL (purl) of the package
+    pub purl: String,
+    /// The purls of the crates this one directly depends on
+    pub depends_on: Vec<String>,
+}
+
+/// Generate an SBOM for a variant
+pub fn generate_sbom(dist: &DistGraph, manifest: &DistManifest, step: &SbomImpl) -> DistResult<()> {
+    let sbom = gather_sbom(dist, manifest, step)?;
+    let contents = match step.format {
+        SbomFormat::CycloneDx => cyclonedx(&sbom),
+        SbomFormat::Spdx => spdx(&sbom),
+    };
+    let contents = serde_json::to_string_pretty(&contents).expect("failed to serialize SBOM");
+    LocalAsset::write_new_all(&contents, &step.dest_path)?;
+    eprintln!("  generated SBOM {}", step.dest_path);
+    Ok(())
+}
+
+/// Collect all the info that goes in an SBOM
+fn gather_sbom(dist: &DistGraph, manifest: &DistManifest, step: &SbomImpl) -> DistResult<Sbom> {
+    let release = dist.release(step.release);
+    let variant = dist.variant(step.variant);
+
+    let mut app = SbomComponent {
+        name: release.app_name.clone(),
+        version: release.version.to_string(),
+        description: release.app_desc.clone(),
+        license: release.app_license.as_deref().and_then(spdx_license),
+        checksum: None,
+        download_url: None,
+        purl: format!("pkg:generic/{}@{}", release.app_name, release.version),
+        depends_on: vec![],
+    };
+
+    let mut crates = vec![];
+    if let Some(manifest_path) = &step.cargo_manifest_path {
+        let graph = cargo_package_graph(manifest_path)?;
+        let checksums = lockfile_checksums(&graph.workspace().root().join("Cargo.lock"))?;
+        let (roots, packages) = variant_crates(dist, &graph, variant)?;
+
+        for (pkg_id, package) in &packages {
+            let depends_on = package
+                .direct_links()
+                .filter(|link| !link.dev_only() && packages.contains_key(link.to().id()))
+                .map(|link| purl(&link.to()))
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            let component = component(package, &checksums, depends_on);
+            if roots.contains(pkg_id) {
+                // There's (almost) always exactly one root, the app's package. If not,
+                // the app depends on the roots, and they get listed like any other crate.
+                if roots.len() == 1 {
+                    app = SbomComponent {
+                        description: app.description.or(component.description),
+                        license: app.license.or(component.license),
+                        ..component
+                    };
+                    continue;
+                }
+                app.depends_on.push(component.purl.clone());
+            }
+            crates.push(component);
+        }
+    }
+
+    let mut libraries = BTreeSet::new();
+    for &binary_idx in &variant.binaries {
+        let binary = dist.binary(binary_idx);
+        let Some(linkage) = manifest
+            .assets
+            .get(&binary.id)
+            .and_then(|asset| asset.linkage.as_ref())
+        else {
+            continue;
+        };
+        for libs in [
+            &linkage.system,
+            &linkage.homebrew,
+            &linkage.public_unmanaged,
+            &linkage.other,
+            &linkage.frameworks,
+        ] {
+            libraries.extend(libs.iter().cloned());
+        }
+    }
+
+    let timestamp = dist.source_date_epoch.unwrap_or_else(|| {
+        SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    });
+
+    Ok(Sbom {
+        name: variant.id.clone(),
+        target: variant.target.clone(),
+        timestamp,
+        app,
+        crates,
+        libraries: libraries.into_iter().collect(),
+    })
+}
+
+/// Read the package graph of a cargo workspace
+///
+/// This uses `cargo metadata --offline`, so it doesn't need network access.
+pub(crate) fn cargo_package_graph(manifest_path: &Utf8Path) -> DistResult<PackageGraph> {
+    MetadataCommand::new()
+        .manifest_path(manifest_path)
+        .other_options(["--offline".to_owned()])
+        .build_graph()
+        .map_err(|details| DistError::CargoDependencyMetadata {
+            manifest_path: manifest_path.to_owned(),
+            details,
+        })
+}
+
+/// The packages the binaries of a variant come from, and every package cargo builds into them
+pub(crate) fn variant_crates<'g>(
+    dist: &DistGraph,
+    graph: &'g PackageGraph,
+    variant: &ReleaseVariant,
+) -> DistResult<(
+    BTreeSet<PackageId>,
+    SortedMap<PackageId, PackageMetadata<'g>>,
+)> {
+    // universal2 binaries are just the binaries for each of these smooshed together
+    let targets = if variant.target == TARGET_MACOS_UNIVERSAL2 {
+        MACOS_UNIVERSAL2_COMPONENTS
+            .iter()
+            .map(|t| t.to_string())
+            .collect()
+    } else {
+        vec![variant.target.clone()]
+    };
+
+    // Resolve what cargo would build for every binary, on every target
+    let mut roots = BTreeSet::new();
+    let mut packages = SortedMap::new();
+    for &binary_idx in &variant.binaries {
+        let binary = dist.binary(binary_idx);
+        let Some(pkg_id) = &binary.pkg_id else {
+            continue;
+        };
+        roots.insert(pkg_id.clone());
+        for target in &targets {
+            for package in resolve_packages(graph, pkg_id, binary, target)? {
+                packages.insert(package.id().clone(), package);
+            }
+        }
+    }
+    Ok((roots, packages))
+}
+
+/// Get the packages that cargo would build into a binary for the given target
+fn resolve_packages<'g>(
+    graph: &'g PackageGraph,
+    pkg_id: &PackageId,
+    binary: &crate::Binary,
+    target: &str,
+) -> DistResult<Vec<PackageMetadata<'g>>> {
+    let err = |details| DistError::CargoDependencyResolve {
+        package: binary.pkg_spec.clone(),
+        target: target.to_owned(),
+        details,
+    };
+
+    let base = if binary.features.default_features {
+        StandardFeatures::Default
+    } else {
+        StandardFeatures::None
+    };
+    let (base, features) = match &binary.features.features {
+        CargoTargetFeatureList::All => (StandardFeatures::All, &[][..]),
+        CargoTargetFeatureList::List(features) => (base, &features[..]),
+    };
+    let filter = named_feature_filter(base, features.iter().map(String::as_str));
+    let initials = graph
+        .resolve_ids([pkg_id])
+        .map_err(err)?
+        .to_feature_set(filter);
+
+    let mut opts = CargoOptions::new();
+    // This is the default since the 2021 edition
+    opts.set_resolver(CargoResolverVersion::V2);
+    match Platform::new(target.to_owned(), TargetFeatures::Unknown) {
+        Ok(platform) => {
+            opts.set_target_platform(platform);
+        }
+        Err(e) => {
+            // Leave it as "any platform", which may include some extra crates
+            warn!("couldn't understand target {target} for the SBOM, including dependencies for all platforms: {e}");
+        }
+    }
+    let cargo_set =
+        CargoSet::new(initials, graph.feature_graph().resolve_none(), &opts).map_err(err)?;
+
+    Ok(cargo_set
+        .target_features()
+        .to_package_set()
+        .packages(DependencyDirection::Forward)
+        .collect())
+}
+
+/// A package in Cargo.lock
+#[derive(Deserialize)]
+struct LockedPackage {
+    name: String,
+    version: String,
+    checksum: Option<String>,
+}
+
+/// Cargo.lock (the parts we care about)
+#[derive(Deserialize)]
+struct Lockfile {
+    #[serde(default)]
+    package: Vec<LockedPackage>,
+}
+
+/// Get the checksums of all the crates in a Cargo.lock, keyed by (name, version)
+fn lockfile_checksums(path: &Utf8Path) -> DistResult<SortedMap<(String, String), String>> {
+    if !path.exists() {
+        warn!("{path} doesn't exist, so the SBOM won't include checksums");
+        return Ok(SortedMap::new());
+    }
+    let lockfile: Lockfile = SourceFile::load_local(path)?.deserialize_toml()?;
+    Ok(lockfile
+        .package
+        .into_iter()
+        .filter_map(|pkg| Some(((pkg.name, pkg.version), pkg.checksum?)))
+        .collect())
+}
+
+/// Describe a crate
+fn component(
+    package: &PackageMetadata,
+    checksums: &SortedMap<(String, String), String>,
+    depends_on: Vec<String>,
+) -> SbomComponent {
+    let name = package.name().to_owned();
+    let version = package.version().to_string();
+    let download_url = match package.source().parse_external() {
+        Some(ExternalSource::Registry(ExternalSource::CRATES_IO_URL)) => Some(format!(
+            "https://crates.io/api/v1/crates/{name}/{version}/download"
+        )),
+        Some(ExternalSource::Git {
+            repository,
+            resolved,
+            ..
+        }) => Some(format!("git+{repository}@{resolved}")),
+        _ => None,
+    };
+    SbomComponent {
+        checksum: checksums.get(&(name.clone(), version.clone())).cloned(),
+        description: package.description().map(str::to_owned),
+        license: package.license().and_then(spdx_license),
+        download_url,
+        purl: purl(package),
+        depends_on,
+        name,
+        version,
+    }
+}
+
+/// The package URL (purl) of a crate
+///
+/// See <https://github.com/package-url/purl-spec/blob/master/PURL-TYPES.rst#cargo>
+fn purl(package: &PackageMetadata) -> String {
+    let purl = format!("pkg:cargo/{}@{}", package.name(), package.version());
+    match package.source() {
+        PackageSource::External(source) if source.starts_with("registry+") => {
+            match package.source().parse_external() {
+                Some(ExternalSource::Registry(ExternalSource::CRATES_IO_URL)) => purl,
+                _ => format!("{purl}?repository_url={}", &source["registry+".len()..]),
+            }
+        }
+        PackageSource::External(source) => {
+            format!(
+                "{purl}?vcs_url={}",
+                source.split('?').next().unwrap_or(source)
+            )
+        }
+        _ => purl,
+    }
+}
+
+/// Normalize a Cargo.toml license to an SPDX expression
+///
+/// Old crates use `/` to mean OR, and some have licenses that aren't SPDX at all
+/// (which we can't say anything about).
+pub fn spdx_license(license: &str) -> Option<String> {
+    let license = license.replace('/', " OR ");
+    spdx::Expression::parse_mode(&license, spdx::ParseMode::LAX)
+        .ok()
+        .map(|_| license)
+}
+
+/// Format a unix timestamp the way SBOMs want (RFC 3339, UTC)
+fn timestamp(secs: u64) -> String {
+    let (year, month, day) = civil_from_days((secs / 86400) as i64);
+    let time = secs % 86400;
+    format!(
+        "{year:04}-{month:02}-{day:02}T{:02}:{:02}:{:02}Z",
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// The name we show for a library (the path is often much too long)
+fn library_name(library: &Library) -> &str {
+    Utf8Path::new(&library.path)
+        .file_name()
+        .unwrap_or(&library.path)
+}
+
+/// The properties we record for a library, in CycloneDX style
+fn library_properties(library: &Library) -> Vec<Value> {
+    let mut properties = vec![json!({ "name": "cargo-dist:path", "value": library.path })];
+    if let Some(source) = &library.source {
+        properties.push(json!({ "name": "cargo-dist:package", "value": source }));
+    }
+    if let Some(package_manager) = &library.package_manager {
+        let package_manager = match package_manager {
+            PackageManager::Homebrew => "homebrew",
+            PackageManager::Apt => "apt",
+        };
+        properties.push(json!({ "name": "cargo-dist:package-manager", "value": package_manager }));
+    }
+    properties
+}
+
+/// Render an SBOM as CycloneDX 1.5 JSON
+pub fn cyclonedx(sbom: &Sbom) -> Value {
+    fn component(component: &SbomComponent, kind: &str) -> Value {
+        let mut out = json!({
+            "type": kind,
+            "bom-ref": component.purl,
+            "name": component.name,
+            "version": component.version,
+            "purl": component.purl,
+        });
+        if let Some(description) = &component.description {
+            out["description"] = json!(description);
+        }
+        if let Some(license) = &component.license {
+            out["licenses"] = json!([{ "expression": license }]);
+        }
+        if let Some(checksum) = &component.checksum {
+            out["hashes"] = json!([{ "alg": "SHA-256", "content": checksum }]);
+        }
+        if let Some(url) = &component.download_url {
+            out["externalReferences"] = json!([{ "type": "distribution", "url": url }]);
+        }
+        out
+    }
+
+    let mut components = sbom
+        .crates
+        .iter()
+        .map(|c| component(c, "library"))
+        .collect::<Vec<_>>();
+    let mut dependencies = vec![];
+    let mut app_depends_on = sbom.app.depends_on.clone();
+    for library in &sbom.libraries {
+        let bom_ref = format!("library:{}", library.path);
+        components.push(json!({
+            "type": "library",
+            "bom-ref": bom_ref,
+            "name": library_name(library),
+            "properties": library_properties(library),
+        }));
+        app_depends_on.push(bom_ref);
+    }
+    dependencies.push(json!({ "ref": sbom.app.purl, "dependsOn": app_depends_on }));
+    for c in &sbom.crates {
+        dependencies.push(json!({ "ref": c.purl, "dependsOn": c.depends_on }));
+    }
+
+    json!({
+        "bomFormat": "CycloneDX",
+        "specVersion": "1.5",
+        "version": 1,
+        "metadata": {
+            "timestamp": timestamp(sbom.timestamp),
+            "tools": {
+                "components": [{
+                    "type": "application",
+                    "name": "cargo-dist",
+                    "version": env!("CARGO_PKG_VERSION"),
+                }],
+            },
+            "component": component(&sbom.app, "application"),
+            "properties": [{ "name": "cargo-dist:target", "value": sbom.target }],
+        },
+        "components": components,
+        "dependencies": dependencies,
+    })
+}
+
+/// Make an SPDX id (these can only contain letters, numbers, `.` and `-`)
+fn spdx_id(kind: &str, name: &str) -> String {
+    let name = name
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '.' || c == '-' {
+                c
+            } else {
+                '-'
+            }
+        })
+        .collect::<String>();
+    format!("SPDXRef-{kind}-{name}")
+}
+
+fn package_spdx_id(component: &SbomComponent) -> String {
+    spdx_id(
+        "Package",
+        &format!("{}-{}", component.name, component.version),
+    )
+}
+
+/// Render an SBOM as SPDX 2.3 JSON
+pub fn spdx(sbom: &Sbom) -> Value {
+    fn package(component: &SbomComponent, purpose: &str) -> Value {
+        let noassertion = || "NOASSERTION".to_owned();
+        let mut out = json!({
+            "SPDXID": package_spdx_id(component),
+            "name": component.name,
+            "versionInfo": component.version,
+            "primaryPackagePurpose": purpose,
+            "downloadLocation": component.download_url.clone().unwrap_or_else(noassertion),
+            "filesAnalyzed": false,
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": component.license.clone().unwrap_or_else(noassertion),
+            "copyrightText": "NOASSERTION",
+            "externalRefs": [{
+                "referenceCategory": "PACKAGE-MANAGER",
+                "referenceType": "purl",
+                "referenceLocator": component.purl,
+            }],
+        });
+        if let Some(description) = &component.description {
+            out["description"] = json!(description);
+        }
+        if let Some(checksum) = &component.checksum {
+            out["checksums"] = json!([{ "algorithm": "SHA256", "checksumValue": checksum }]);
+        }
+        out
+    }
+
+    let id_for_purl = sbom
+        .crates
+        .iter()
+        .chain([&sbom.app])
+        .map(|c| (c.purl.as_str(), package_spdx_id(c)))
+        .collect::<SortedMap<_, _>>();
+    let app_id = package_spdx_id(&sbom.app);
+
+    let mut packages = vec![package(&sbom.app, "APPLICATION")];
+    let mut relationships = vec![json!({
+        "spdxElementId": "SPDXRef-DOCUMENT",
+        "relationshipType": "DESCRIBES",
+        "relatedSpdxElement": app_id,
+    })];
+    for c in sbom.crates.iter().chain([&sbom.app]) {
+        if c.purl != sbom.app.purl {
+            packages.push(package(c, "LIBRARY"));
+        }
+        let id = package_spdx_id(c);
+        for dep in &c.depends_on {
+            // A relationship with a package that isn't in the document would be invalid
+            let Some(dep_id) = id_for_purl.get(dep.as_str()) else {
+                continue;
+            };
+            relationships.push(json!({
+                "spdxElementId": id,
+                "relationshipType": "DEPENDS_ON",
+                "relatedSpdxElement": dep_id,
+            }));
+        }
+    }
+    for library in &sbom.libraries {
+        let id = spdx_id("Library", &library.path);
+        packages.push(json!({
+            "SPDXID": id,
+            "name": library_name(library),
+            "primaryPackagePurpose": "LIBRARY",
+            "downloadLocation": "NOASSERTION",
+            "filesAnalyzed": false,
+            "licenseConcluded": "NOASSERTION",
+            "licenseDeclared": "NOASSERTION",
+            "copyrightText": "NOASSERTION",
+            "sourceInfo": library.source.as_ref().map_or_else(
+                || format!("linked from {}", library.path),
+                |source| format!("linked from {}, provided by the {source} package", library.path),
+            ),
+        }));
+        relationships.push(json!({
+            "spdxElementId": app_id,
+            "relationshipType": "DYNAMIC_LINK",
+            "relatedSpdxElement": id,
+        }));
+    }
+
+    json!({
+        "spdxVersion": "SPDX-2.3",
+        "dataLicense": "CC0-1.0",
+        "SPDXID": "SPDXRef-DOCUMENT",
+        "name": sbom.name,
+        // This just needs to be unique, and the variant id has the app, version and target
+        "documentNamespace": format!("https://spdx.org/spdxdocs/{}", sbom.name),
+        "creationInfo": {
+            "created": timestamp(sbom.timestamp),
+            "creators": [format!("Tool: cargo-dist-{}", env!("CARGO_PKG_VERSION"))],
+            "comment": format!("binaries built for {}", sbom.target),
+        },
+        "packages": packages,
+        "relationships": relationships,
+    })
+}
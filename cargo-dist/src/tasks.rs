@@ -53,9 +53,10 @@ use std::collections::BTreeMap;
 use axoasset::AxoClient;
 use axoprocess::Cmd;
 use axoproject::platforms::{
-    TARGET_ARM64_LINUX_GNU, TARGET_ARM64_MAC, TARGET_X64_LINUX_GNU, TARGET_X64_MAC,
+    MACOS_UNIVERSAL2_COMPONENTS, TARGET_ARM64_LINUX_GNU, TARGET_ARM64_MAC, TARGET_MACOS_UNIVERSAL2,
+    TARGET_X64_LINUX_GNU, TARGET_X64_MAC,
 };
-use axoproject::{PackageId, PackageIdx, WorkspaceGraph};
+use axoproject::{rust::CargoProfiles, PackageId, PackageIdx, WorkspaceGraph, WorkspaceIdx};
 use camino::Utf8PathBuf;
 use cargo_dist_schema::{ArtifactId, BuildEnvironment, DistManifest, SystemId, SystemInfo};
 use semver::Version;
@@ -67,12 +68,15 @@ use crate::backend::ci::github::GithubCiInfo;
 use crate::backend::ci::CiInfo;
 use crate::backend::installer::homebrew::to_homebrew_license_format;
 use crate::backend::installer::macpkg::PkgInstallerInfo;
+use crate::build::cache::BuildCache;
 use crate::config::v1::builds::cargo::AppCargoBuildConfig;
 use crate::config::v1::ci::CiConfig;
 use crate::config::v1::installers::CommonInstallerConfig;
 use crate::config::v1::publishers::PublisherConfig;
 use crate::config::v1::{app_config, workspace_config, AppConfig, WorkspaceConfig};
-use crate::config::{DependencyKind, DirtyMode, LibraryStyle};
+use crate::config::{
+    BinaryProcessorKind, CargoBuilder, DependencyKind, DirtyMode, LibraryStyle, SbomFormat,
+};
 use crate::linkage::determine_build_environment;
 use crate::net::ClientSettings;
 use crate::platform::{PlatformSupport, RuntimeConditions};
@@ -216,6 +220,14 @@ pub struct DistGraph {
     pub workspace_dir: Utf8PathBuf,
     /// cargo-dist's target dir (generally nested under `target_dir`).
     pub dist_dir: Utf8PathBuf,
+    /// The timestamp to clamp archive mtimes to
+    ///
+    /// This is only computed by `cargo dist build` (see [`crate::build::archive::source_date_epoch`][])
+    pub source_date_epoch: Option<u64>,
+    /// Where to cache build outputs that haven't changed between builds
+    ///
+    /// This is only set by `cargo dist build` (see [`crate::build::cache`][])
+    pub build_cache: Option<BuildCache>,
     /// misc workspace-global config
     pub config: WorkspaceConfig,
     /// Targets we need to build (local artifacts)
@@ -268,6 +280,10 @@ pub struct Tools {
     pub brew: Option<Tool>,
     /// git, used if the repository is a git repo
     pub git: Option<Tool>,
+    /// cargo-zigbuild, for cross-compiling with zig as the linker
+    pub cargo_zigbuild: Option<Tool>,
+    /// cross, for cross-compiling in docker images
+    pub cross: Option<Tool>,
     /// ssl.com's CodeSignTool, for Windows Code Signing
     ///
     /// <https://www.ssl.com/guide/esigner-codesigntool-command-guide/>
@@ -325,10 +341,23 @@ pub struct Binary {
     pub copy_exe_to: Vec<Utf8PathBuf>,
     /// Places the symbols need to be copied to
     pub copy_symbols_to: Vec<Utf8PathBuf>,
+    /// Symbol server layouts the symbols need to be added to (by build id)
+    pub copy_symbols_to_server: Vec<Utf8PathBuf>,
     /// feature flags!
     pub features: CargoTargetFeatures,
+    /// Extra RUSTFLAGS from target-specific overrides
+    pub rustflags: String,
+    /// Extra environment variables from target-specific overrides
+    pub env: SortedMap<String, String>,
+    /// The cargo profile to build with
+    pub profile: String,
     /// What kind of binary this is
     pub kind: BinaryKind,
+    /// The single-arch binaries this one is merged from
+    ///
+    /// This is only non-empty for `universal2-apple-darwin`, which cargo can't build
+    /// directly. Instead we build each of these and then merge them with a [`LipoStep`][].
+    pub lipo_inputs: Vec<BinaryIdx>,
 }
 
 /// Different kinds of binaries cargo-dist knows about
@@ -348,6 +377,10 @@ pub enum BinaryKind {
 pub enum BuildStep {
     /// Do a generic build (and copy the outputs to various locations)
     Generic(GenericBuildStep),
+    /// Do a go build (and copy the outputs to various locations)
+    Go(GoBuildStep),
+    /// Do a cmake build and install (and copy the outputs to various locations)
+    Cmake(CmakeBuildStep),
     /// Do a cargo build (and copy the outputs to various locations)
     Cargo(CargoBuildStep),
     /// Do an extra artifact build (and copy the outputs to various locations)
@@ -370,8 +403,14 @@ pub enum BuildStep {
     Checksum(ChecksumImpl),
     /// Fetch or build an updater binary
     Updater(UpdaterStep),
-    // FIXME: For macos universal builds we'll want
-    // Lipo(LipoStep)
+    /// Merge single-arch macOS binaries into a universal one
+    Lipo(LipoStep),
+    /// Generate a software bill of materials
+    Sbom(SbomImpl),
+    /// Collect the licenses of the crates built into a variant
+    ThirdPartyLicenses(ThirdPartyLicensesImpl),
+    /// Build Python wheels with maturin
+    Maturin(WheelImpl),
 }
 
 /// A cargo build (and copy the outputs to various locations)
@@ -387,10 +426,27 @@ pub struct CargoBuildStep {
     pub profile: String,
     /// The value to set for RUSTFLAGS
     pub rustflags: String,
+    /// Extra environment variables to set
+    pub env: SortedMap<String, String>,
     /// Binaries we expect from this build
     pub expected_binaries: Vec<BinaryIdx>,
     /// The working directory to run the build in
     pub working_dir: Utf8PathBuf,
+    /// The tool to run the build with
+    pub builder: CargoBuilder,
+    /// Whether to run the build through cargo-auditable
+    pub auditable: bool,
+    /// Settings for profile-guided optimization, if enabled
+    pub pgo: Option<CargoPgoStep>,
+}
+
+/// Profile-guided optimization settings for a cargo build
+#[derive(Debug, Clone)]
+pub struct CargoPgoStep {
+    /// The command to run against each instrumented binary
+    pub training_command: Vec<String>,
+    /// Where the instrumented binaries should write their profiles
+    pub profile_dir: Utf8PathBuf,
 }
 
 /// A cargo build (and copy the outputs to various locations)
@@ -406,6 +462,57 @@ pub struct GenericBuildStep {
     pub out_dir: Utf8PathBuf,
     /// The command to run to produce the expected binaries
     pub build_command: Vec<String>,
+    /// Where to write the output of the build command
+    pub log_path: Utf8PathBuf,
+}
+
+/// A go build (and copy the outputs to various locations)
+#[derive(Debug)]
+pub struct GoBuildStep {
+    /// The target triple we're building for
+    pub target_triple: TargetTriple,
+    /// The GOOS/GOARCH/GOARM to build with
+    pub go_target: GoTarget,
+    /// Binaries we expect from this build
+    pub expected_binaries: Vec<BinaryIdx>,
+    /// The main packages to build, like `./cmd/my-tool`
+    pub main_packages: Vec<String>,
+    /// The directory of the go.mod
+    pub working_dir: Utf8PathBuf,
+    /// The output directory to write the binaries to
+    pub out_dir: Utf8PathBuf,
+    /// Where to write the output of the build
+    pub log_path: Utf8PathBuf,
+}
+
+/// A cmake configure, build and install (and copy the outputs to various locations)
+#[derive(Debug)]
+pub struct CmakeBuildStep {
+    /// The target triple we're building for
+    pub target_triple: TargetTriple,
+    /// Binaries we expect from this build
+    pub expected_binaries: Vec<BinaryIdx>,
+    /// The directory of the CMakeLists.txt
+    pub source_dir: Utf8PathBuf,
+    /// The directory to configure and build in
+    pub build_dir: Utf8PathBuf,
+    /// The prefix to install to, where we'll look for the build outputs
+    pub install_dir: Utf8PathBuf,
+    /// Where to write the toolchain file for the target
+    pub toolchain_file: Utf8PathBuf,
+    /// Where to write the output of the build (each phase gets its own log next to this)
+    pub log_path: Utf8PathBuf,
+}
+
+/// What Go calls a target triple
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GoTarget {
+    /// GOOS, like `linux`
+    pub goos: &'static str,
+    /// GOARCH, like `amd64`
+    pub goarch: &'static str,
+    /// GOARM, the arm version (only for 32-bit arm)
+    pub goarm: Option<&'static str>,
 }
 
 /// An "extra" build step, producing new sidecar artifacts
@@ -417,6 +524,19 @@ pub struct ExtraBuildStep {
     pub artifact_relpaths: Vec<Utf8PathBuf>,
     /// The command to run to produce the expected binaries
     pub build_command: Vec<String>,
+    /// Where to write the output of the build command
+    pub log_path: Utf8PathBuf,
+}
+
+/// Merge several single-arch Mach-O binaries into one "fat" binary
+/// (and copy the output to various locations)
+#[derive(Debug)]
+pub struct LipoStep {
+    /// The universal binary we're producing
+    pub expected_binary: BinaryIdx,
+    /// Where each of the single-arch binaries will have been copied to
+    /// (and what target they're for)
+    pub src_paths: Vec<(TargetTriple, Utf8PathBuf)>,
 }
 
 /// A cargo build (and copy the outputs to various locations)
@@ -463,6 +583,34 @@ pub struct ChecksumImpl {
     pub for_artifact: Option<ArtifactId>,
 }
 
+/// Generate a software bill of materials (SBOM) for a variant
+#[derive(Debug, Clone)]
+pub struct SbomImpl {
+    /// the format to write it in
+    pub format: SbomFormat,
+    /// the release the variant belongs to
+    pub release: ReleaseIdx,
+    /// the variant whose binaries it describes
+    pub variant: ReleaseVariantIdx,
+    /// the Cargo.toml of the workspace the binaries are from (if they're from cargo)
+    pub cargo_manifest_path: Option<Utf8PathBuf>,
+    /// where to write it
+    pub dest_path: Utf8PathBuf,
+}
+
+/// Write a THIRD-PARTY-LICENSES file for a variant
+#[derive(Debug, Clone)]
+pub struct ThirdPartyLicensesImpl {
+    /// the release the variant belongs to
+    pub release: ReleaseIdx,
+    /// the variant whose binaries it covers
+    pub variant: ReleaseVariantIdx,
+    /// the Cargo.toml of the workspace the binaries are from
+    pub cargo_manifest_path: Utf8PathBuf,
+    /// where to write it
+    pub dest_path: Utf8PathBuf,
+}
+
 /// Create a source tarball
 #[derive(Debug, Clone)]
 pub struct SourceTarballStep {
@@ -487,7 +635,7 @@ pub struct UpdaterStep {
 }
 
 /// A kind of symbols (debuginfo)
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub enum SymbolKind {
     /// Microsoft pdbs
     Pdb,
@@ -495,6 +643,8 @@ pub enum SymbolKind {
     Dsym,
     /// DWARF DWPs
     Dwp,
+    /// ELF debuginfo split out by the `split-debuginfo` binary processor
+    Debug,
 }
 
 impl SymbolKind {
@@ -504,8 +654,21 @@ impl SymbolKind {
             SymbolKind::Pdb => "pdb",
             SymbolKind::Dsym => "dSYM",
             SymbolKind::Dwp => "dwp",
+            SymbolKind::Debug => "debug",
         }
     }
+
+    /// Get the kind of symbols with the given file extension
+    pub fn from_ext(ext: &str) -> Option<Self> {
+        [
+            SymbolKind::Pdb,
+            SymbolKind::Dsym,
+            SymbolKind::Dwp,
+            SymbolKind::Debug,
+        ]
+        .into_iter()
+        .find(|kind| kind.ext() == ext)
+    }
 }
 
 /// A distributable artifact we want to build
@@ -576,6 +739,12 @@ pub enum ArtifactKind {
     ExtraArtifact(ExtraArtifactImpl),
     /// An updater executable
     Updater(UpdaterImpl),
+    /// A software bill of materials
+    Sbom(SbomImpl),
+    /// Symbols laid out for a symbol server
+    SymbolServer(SymbolServerImpl),
+    /// Python wheels built by maturin
+    Wheel(WheelImpl),
 }
 
 /// An Archive containing binaries (aka ExecutableZip)
@@ -588,7 +757,35 @@ pub struct ExecutableZip {
 #[derive(Clone, Debug)]
 pub struct Symbols {
     /// The kind of symbols this is
-    kind: SymbolKind,
+    pub kind: SymbolKind,
+}
+
+/// An archive of a variant's symbols, laid out for a symbol server
+#[derive(Clone, Debug)]
+pub struct SymbolServerImpl {
+    // everything important is already part of Artifact
+}
+
+/// Python wheels for a variant, built by maturin
+///
+/// Wheel filenames include platform tags only maturin knows, so this artifact is just
+/// a placeholder: the build registers each wheel it finds as its own artifact.
+#[derive(Clone, Debug)]
+pub struct WheelImpl {
+    /// The --target triple to pass
+    pub target_triple: TargetTriple,
+    /// The directory with the pyproject.toml to build
+    pub working_dir: Utf8PathBuf,
+    /// The directory maturin should write wheels to
+    pub out_dir: Utf8PathBuf,
+    /// The local package index to publish the wheels to
+    pub index_dir: Utf8PathBuf,
+    /// The name of the release the wheels belong to
+    pub app_name: String,
+    /// The version of the release the wheels belong to
+    pub app_version: String,
+    /// Where to write the output of maturin
+    pub log_path: Utf8PathBuf,
 }
 
 /// A source tarball artifact
@@ -689,6 +886,8 @@ pub struct ReleaseVariant {
     pub static_assets: Vec<(StaticAssetKind, Utf8PathBuf)>,
     /// Artifacts that are "local" to this variant (binaries, symbols, msi-installer...)
     pub local_artifacts: Vec<ArtifactIdx>,
+    /// The THIRD-PARTY-LICENSES file to generate for bundles like archives, if any
+    pub third_party_licenses: Option<ThirdPartyLicensesImpl>,
 }
 
 /// A particular kind of static asset we're interested in
@@ -700,6 +899,8 @@ pub enum StaticAssetKind {
     License,
     /// A CHANGLEOG or RELEASES file
     Changelog,
+    /// A software bill of materials (generated by us)
+    Sbom,
     /// Some other miscellaneous file
     Other,
 }
@@ -836,6 +1037,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     || package_config.builds.cargo.all_features != cargo_build_config.all_features
                     || package_config.builds.cargo.default_features
                         != cargo_build_config.default_features
+                    || package_config.builds.cargo.target != cargo_build_config.target
                 {
                     packages_with_mismatched_features.push(
                         package
@@ -1018,6 +1220,8 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 repo_dir,
                 workspace_dir,
                 dist_dir,
+                source_date_epoch: None,
+                build_cache: None,
                 config,
                 signer,
                 tools,
@@ -1036,6 +1240,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             },
             manifest: DistManifest {
                 dist_version: Some(env!("CARGO_PKG_VERSION").to_owned()),
+                schema_version: Some(cargo_dist_schema::SCHEMA_VERSION),
                 system_info: None,
                 announcement_tag: None,
                 announcement_is_prerelease: false,
@@ -1047,6 +1252,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 artifacts: Default::default(),
                 systems,
                 assets: Default::default(),
+                build_logs: vec![],
                 publish_prereleases: global_publish_prereleases,
                 force_latest,
                 ci: None,
@@ -1178,93 +1384,28 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         }
 
         // Add all the binaries of the release to this variant
+        let release_id = release_id.clone();
         let mut binaries = vec![];
         for (pkg_idx, binary_name, kind) in packageables {
-            let package = self.workspaces.package(pkg_idx);
-            let package_config = self.package_config(pkg_idx);
-            let pkg_id = package.cargo_package_id.clone();
-            // For now we just use the name of the package as its package_spec.
-            // I'm not sure if there are situations where this is ambiguous when
-            // referring to a package in your workspace that you want to build an app for.
-            // If they do exist, that's deeply cursed and I want a user to tell me about it.
-            let pkg_spec = package.true_name.clone();
-            let kind_label = match kind {
-                BinaryKind::Executable => "exe",
-                BinaryKind::DynamicLibrary => "cdylib",
-                BinaryKind::StaticLibrary => "cstaticlib",
-            };
-            // FIXME: make this more of a GUID to allow variants to share binaries?
-            let bin_id = format!("{variant_id}-{kind_label}-{binary_name}");
-
-            let idx = if let Some(&idx) = self.binaries_by_id.get(&bin_id) {
-                // If we already are building this binary we don't need to do it again!
+            let idx = if target == TARGET_MACOS_UNIVERSAL2 {
+                // cargo can't build universal2 directly, so we build each component
+                // target and merge the results after the fact.
+                let mut lipo_inputs = vec![];
+                for component in MACOS_UNIVERSAL2_COMPONENTS {
+                    lipo_inputs.push(self.add_binary_for_target(
+                        &release_id,
+                        &component.to_string(),
+                        pkg_idx,
+                        binary_name.clone(),
+                        kind,
+                    )?);
+                }
+                let idx =
+                    self.add_binary_for_target(&release_id, &target, pkg_idx, binary_name, kind)?;
+                self.binary_mut(idx).lipo_inputs = lipo_inputs;
                 idx
             } else {
-                // Compute the rest of the details and add the binary
-                let features = CargoTargetFeatures {
-                    default_features: package_config.builds.cargo.default_features,
-                    features: if package_config.builds.cargo.all_features {
-                        CargoTargetFeatureList::All
-                    } else {
-                        CargoTargetFeatureList::List(package_config.builds.cargo.features.clone())
-                    },
-                };
-
-                let target_is_windows = target.contains("windows");
-                let platform_exe_ext;
-                let platform_lib_prefix;
-                if target_is_windows {
-                    platform_exe_ext = ".exe";
-                    platform_lib_prefix = "";
-                } else {
-                    platform_exe_ext = "";
-                    platform_lib_prefix = "lib";
-                };
-
-                let platform_lib_ext;
-                let platform_staticlib_ext;
-                if target_is_windows {
-                    platform_lib_ext = ".dll";
-                    platform_staticlib_ext = ".lib";
-                } else if target.contains("linux") {
-                    platform_lib_ext = ".so";
-                    platform_staticlib_ext = ".a";
-                } else if target.contains("darwin") {
-                    platform_lib_ext = ".dylib";
-                    platform_staticlib_ext = ".a";
-                } else {
-                    return Err(DistError::UnrecognizedTarget { target });
-                };
-
-                let file_name = match kind {
-                    BinaryKind::Executable => format!("{binary_name}{platform_exe_ext}"),
-                    BinaryKind::DynamicLibrary => {
-                        format!("{platform_lib_prefix}{binary_name}{platform_lib_ext}")
-                    }
-                    BinaryKind::StaticLibrary => {
-                        format!("{platform_lib_prefix}{binary_name}{platform_staticlib_ext}")
-                    }
-                };
-
-                info!("added binary {bin_id}");
-                let idx = BinaryIdx(self.inner.binaries.len());
-                let binary = Binary {
-                    id: bin_id.clone(),
-                    pkg_id,
-                    pkg_spec,
-                    pkg_idx,
-                    name: binary_name,
-                    file_name,
-                    target: target.clone(),
-                    copy_exe_to: vec![],
-                    copy_symbols_to: vec![],
-                    symbols_artifact: None,
-                    features,
-                    kind,
-                };
-                self.inner.binaries.push(binary);
-                self.binaries_by_id.insert(bin_id, idx);
-                idx
+                self.add_binary_for_target(&release_id, &target, pkg_idx, binary_name, kind)?
             };
 
             binaries.push(idx);
@@ -1276,10 +1417,117 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             local_artifacts: vec![],
             binaries,
             static_assets,
+            third_party_licenses: None,
         });
         Ok(idx)
     }
 
+    /// Add a Binary for the given target (or get the existing one if another
+    /// variant already needs the exact same binary)
+    fn add_binary_for_target(
+        &mut self,
+        release_id: &str,
+        target: &TargetTriple,
+        pkg_idx: PackageIdx,
+        binary_name: String,
+        kind: BinaryKind,
+    ) -> DistResult<BinaryIdx> {
+        let package = self.workspaces.package(pkg_idx);
+        let package_config = self.package_config(pkg_idx);
+        let pkg_id = package.cargo_package_id.clone();
+        // For now we just use the name of the package as its package_spec.
+        // I'm not sure if there are situations where this is ambiguous when
+        // referring to a package in your workspace that you want to build an app for.
+        // If they do exist, that's deeply cursed and I want a user to tell me about it.
+        let pkg_spec = package.true_name.clone();
+        let kind_label = match kind {
+            BinaryKind::Executable => "exe",
+            BinaryKind::DynamicLibrary => "cdylib",
+            BinaryKind::StaticLibrary => "cstaticlib",
+        };
+        // FIXME: make this more of a GUID to allow variants to share binaries?
+        let bin_id = format!("{release_id}-{target}-{kind_label}-{binary_name}");
+
+        if let Some(&idx) = self.binaries_by_id.get(&bin_id) {
+            // If we already are building this binary we don't need to do it again!
+            return Ok(idx);
+        }
+
+        // Compute the rest of the details and add the binary
+        let target_config = package_config.builds.cargo.for_target(target)?;
+        let features = CargoTargetFeatures {
+            default_features: target_config.default_features,
+            features: if target_config.all_features {
+                CargoTargetFeatureList::All
+            } else {
+                CargoTargetFeatureList::List(target_config.features)
+            },
+        };
+
+        let target_is_windows = target.contains("windows");
+        let platform_exe_ext;
+        let platform_lib_prefix;
+        if target_is_windows {
+            platform_exe_ext = ".exe";
+            platform_lib_prefix = "";
+        } else {
+            platform_exe_ext = "";
+            platform_lib_prefix = "lib";
+        };
+
+        let platform_lib_ext;
+        let platform_staticlib_ext;
+        if target_is_windows {
+            platform_lib_ext = ".dll";
+            platform_staticlib_ext = ".lib";
+        } else if target.contains("linux") {
+            platform_lib_ext = ".so";
+            platform_staticlib_ext = ".a";
+        } else if target.contains("darwin") {
+            platform_lib_ext = ".dylib";
+            platform_staticlib_ext = ".a";
+        } else {
+            return Err(DistError::UnrecognizedTarget {
+                target: target.clone(),
+            });
+        };
+
+        let file_name = match kind {
+            BinaryKind::Executable => format!("{binary_name}{platform_exe_ext}"),
+            BinaryKind::DynamicLibrary => {
+                format!("{platform_lib_prefix}{binary_name}{platform_lib_ext}")
+            }
+            BinaryKind::StaticLibrary => {
+                format!("{platform_lib_prefix}{binary_name}{platform_staticlib_ext}")
+            }
+        };
+
+        info!("added binary {bin_id}");
+        let idx = BinaryIdx(self.inner.binaries.len());
+        let binary = Binary {
+            id: bin_id.clone(),
+            pkg_id,
+            pkg_spec,
+            pkg_idx,
+            name: binary_name,
+            file_name,
+            target: target.clone(),
+            copy_exe_to: vec![],
+            copy_symbols_to: vec![],
+            copy_symbols_to_server: vec![],
+            symbols_artifact: None,
+            features,
+            rustflags: target_config.rustflags,
+            env: target_config.env,
+            profile: target_config.profile,
+            kind,
+            lipo_inputs: vec![],
+        };
+        self.inner.binaries.push(binary);
+        self.binaries_by_id.insert(bin_id, idx);
+        Ok(idx)
+    }
+
     fn add_binary(&mut self, to_release: ReleaseIdx, pkg_idx: PackageIdx, binary_name: String) {
         let release = self.release_mut(to_release);
         release.bins.push((pkg_idx, binary_name));
@@ -1532,6 +1780,187 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         }
     }
 
+    fn add_sbom(
+        &mut self,
+        release_idx: ReleaseIdx,
+        variant_idx: ReleaseVariantIdx,
+        format: SbomFormat,
+    ) {
+        if !self.local_artifacts_enabled() {
+            return;
+        }
+
+        let variant = self.variant(variant_idx);
+        let cargo_manifest_path = variant.binaries.iter().find_map(|&binary_idx| {
+            let pkg_idx = self.binary(binary_idx).pkg_idx;
+            let workspace = self
+                .workspaces
+                .workspace(self.workspaces.workspace_for_package(pkg_idx));
+            (workspace.kind == axoproject::WorkspaceKind::Rust)
+                .then(|| workspace.manifest_path.clone())
+        });
+        let filename = format!("{}{}", variant.id, format.ext());
+        let dest_path = self.inner.dist_dir.join(&filename);
+        let artifact = Artifact {
+            id: filename,
+            target_triples: vec![variant.target.clone()],
+            file_path: dest_path.clone(),
+            required_binaries: FastMap::new(),
+            archive: None,
+            kind: ArtifactKind::Sbom(SbomImpl {
+                format,
+                release: release_idx,
+                variant: variant_idx,
+                cargo_manifest_path,
+                dest_path: dest_path.clone(),
+            }),
+            checksum: None,
+            is_global: false,
+        };
+
+        // The SBOM is its own artifact, but we also want it in the archive. This needs to
+        // be added before the archive so that the SBOM gets generated before it's copied in.
+        self.add_local_artifact(variant_idx, artifact);
+        self.variant_mut(variant_idx)
+            .static_assets
+            .push((StaticAssetKind::Sbom, dest_path));
+    }
+
+    fn add_third_party_licenses(
+        &mut self,
+        release_idx: ReleaseIdx,
+        variant_idx: ReleaseVariantIdx,
+    ) {
+        if !self.local_artifacts_enabled() {
+            return;
+        }
+
+        // Only cargo tells us what went into the binaries
+        let variant = self.variant(variant_idx);
+        let Some(cargo_manifest_path) = variant.binaries.iter().find_map(|&binary_idx| {
+            let pkg_idx = self.binary(binary_idx).pkg_idx;
+            let workspace = self
+                .workspaces
+                .workspace(self.workspaces.workspace_for_package(pkg_idx));
+            (workspace.kind == axoproject::WorkspaceKind::Rust)
+                .then(|| workspace.manifest_path.clone())
+        }) else {
+            return;
+        };
+        // Archives get the file's name, so it needs a directory to itself
+        let dest_path = self
+            .inner
+            .dist_dir
+            .join(format!("{}-licenses", variant.id))
+            .join("THIRD-PARTY-LICENSES");
+
+        let variant = self.variant_mut(variant_idx);
+        variant.third_party_licenses = Some(ThirdPartyLicensesImpl {
+            release: release_idx,
+            variant: variant_idx,
+            cargo_manifest_path,
+            dest_path: dest_path.clone(),
+        });
+        variant
+            .static_assets
+            .push((StaticAssetKind::License, dest_path));
+    }
+
+    /// Add an archive of each variant's symbols, laid out for a symbol server
+    ///
+    /// Build ids are only known after the build, so this just reserves a directory
+    /// that the build adds each binary's symbols to.
+    fn add_symbol_server(&mut self, release_idx: ReleaseIdx) {
+        if !self.local_artifacts_enabled() {
+            return;
+        }
+        let release = self.release(release_idx);
+        if !release.config.artifacts.symbol_server {
+            return;
+        }
+        let variants = release.variants.clone();
+        for variant_idx in variants {
+            let variant = self.variant(variant_idx);
+            let binaries = variant
+                .binaries
+                .iter()
+                .copied()
+                .filter(|&binary_idx| self.binary(binary_idx).symbols_artifact.is_some())
+                .collect::<Vec<_>>();
+            // Nothing to serve
+            if binaries.is_empty() {
+                continue;
+            }
+
+            let zip_style = ZipStyle::Tar(CompressionImpl::Xzip);
+            let dir_name = format!("{}-symbol-server", variant.id);
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+            let artifact_name = format!("{dir_name}{}", zip_style.ext());
+            let artifact = Artifact {
+                id: artifact_name.clone(),
+                target_triples: vec![variant.target.clone()],
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style,
+                    static_assets: vec![],
+                }),
+                file_path: self.inner.dist_dir.join(artifact_name),
+                required_binaries: FastMap::new(),
+                kind: ArtifactKind::SymbolServer(SymbolServerImpl {}),
+                checksum: None,
+                is_global: false,
+            };
+            self.add_local_artifact(variant_idx, artifact);
+            for binary_idx in binaries {
+                self.binary_mut(binary_idx)
+                    .copy_symbols_to_server
+                    .push(dir_path.clone());
+            }
+        }
+    }
+
+    /// Add wheels for each of a maturin release's variants
+    fn add_wheels(&mut self, release_idx: ReleaseIdx, pkg_idx: PackageIdx) {
+        if !self.local_artifacts_enabled() {
+            return;
+        }
+        let release = self.release(release_idx);
+        let package = self.workspaces.package(pkg_idx);
+        let working_dir = package.package_root.clone();
+        let app_name = release.app_name.clone();
+        let app_version = release.version.to_string();
+        let index_dir = self
+            .inner
+            .dist_dir
+            .join(crate::build::maturin::PYTHON_INDEX_DIR);
+        for variant_idx in release.variants.clone() {
+            let variant = self.variant(variant_idx);
+            let id = format!("{}-wheels", variant.id);
+            let out_dir = self.inner.dist_dir.join(&id);
+            let log_path = self.inner.dist_dir.join(format!("build-{id}.log"));
+            let artifact = Artifact {
+                id,
+                target_triples: vec![variant.target.clone()],
+                file_path: out_dir.clone(),
+                required_binaries: FastMap::new(),
+                archive: None,
+                kind: ArtifactKind::Wheel(WheelImpl {
+                    target_triple: variant.target.clone(),
+                    working_dir: working_dir.clone(),
+                    out_dir,
+                    index_dir: index_dir.clone(),
+                    app_name: app_name.clone(),
+                    app_version: app_version.clone(),
+                    log_path,
+                }),
+                checksum: None,
+                is_global: false,
+            };
+            self.add_local_artifact(variant_idx, artifact);
+        }
+    }
+
     /// Make an executable zip for a variant, but don't yet integrate it into the graph
     ///
     /// This is useful for installers which want to know about *potential* executable zips
@@ -1595,6 +2024,55 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         )
     }
 
+    /// Work out what kind of symbols (if any) building a binary will produce
+    ///
+    /// This needs to be known up front, since it decides what artifacts exist:
+    ///
+    /// * msvc always produces a pdb
+    /// * macOS produces a dSYM, and Linux a dwp, if the cargo profile has debuginfo
+    ///   and `split-debuginfo = "packed"`
+    /// * the `split-debuginfo` binary processor produces a `.debug` for ELF binaries
+    ///   (even ones from generic builds)
+    fn binary_symbol_kind(&self, binary_idx: BinaryIdx) -> Option<SymbolKind> {
+        let binary = self.binary(binary_idx);
+        // Universal binaries are merged from other builds, and static libraries
+        // never get linked, so neither gets symbols of its own
+        if !binary.lipo_inputs.is_empty() || binary.kind == BinaryKind::StaticLibrary {
+            return None;
+        }
+        let target = &binary.target;
+        let is_elf = !target.contains("windows") && !target.contains("apple");
+        let splits_debuginfo = self.inner.config.builds.processors.iter().any(|processor| {
+            processor.0.processor == BinaryProcessorKind::SplitDebuginfo
+                && processor.0.wanted_for_target(target)
+        });
+        if is_elf && splits_debuginfo {
+            return Some(SymbolKind::Debug);
+        }
+
+        // Otherwise we're relying on what cargo produces
+        let workspace_idx = self.workspaces.workspace_for_package(binary.pkg_idx);
+        let workspace = self.workspaces.workspace(workspace_idx);
+        if workspace.kind != axoproject::WorkspaceKind::Rust {
+            return None;
+        }
+        if target.contains("windows-msvc") {
+            return Some(SymbolKind::Pdb);
+        }
+        let (debug, split_debuginfo) =
+            resolve_cargo_profile(&workspace.cargo_profiles, &binary.profile);
+        if debug == 0 || split_debuginfo.as_deref() != Some("packed") {
+            return None;
+        }
+        if target.contains("apple") {
+            Some(SymbolKind::Dsym)
+        } else if is_elf {
+            Some(SymbolKind::Dwp)
+        } else {
+            None
+        }
+    }
+
     /// Register that `for_artifact` requires `binary_idx` to actually be built for
     /// `for_variant`.
     ///
@@ -1621,32 +2099,46 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
 
         // Try to make a symbols artifact for this binary now that we're building it
         if binary.symbols_artifact.is_none() {
-            if let Some(symbol_kind) = target_symbol_kind(&binary.target) {
-                // FIXME: For some formats these won't be the same but for now stubbed out
-
-                // FIXME: rustc/cargo has so more complex logic to do platform-specifc name remapping
-                // (see should_replace_hyphens in src/cargo/core/compiler/build_context/target_info.rs)
-
-                // FIXME: feed info about the expected source symbol name down to build_cargo_target
-                // to unhardcode the use of .pdb ...!
-
-                // let src_symbol_ext = symbol_kind.ext();
-                let dest_symbol_ext = symbol_kind.ext();
-                // let base_name = &binary.name;
+            if let Some(symbol_kind) = self.binary_symbol_kind(binary_idx) {
+                let binary = self.binary(binary_idx);
                 let binary_id = &binary.id;
-                // let src_symbol_name = format!("{base_name}.{src_symbol_ext}");
-                let dest_symbol_name = format!("{binary_id}.{dest_symbol_ext}");
-                let artifact_path = dist_dir.join(&dest_symbol_name);
-
-                let artifact = Artifact {
-                    id: dest_symbol_name,
-                    target_triples: vec![binary.target.clone()],
-                    archive: None,
-                    file_path: artifact_path.clone(),
-                    required_binaries: FastMap::new(),
-                    kind: ArtifactKind::Symbols(Symbols { kind: symbol_kind }),
-                    checksum: None,
-                    is_global: false,
+                let dest_symbol_name = format!("{binary_id}.{}", symbol_kind.ext());
+
+                let (artifact, artifact_path) = if symbol_kind == SymbolKind::Dsym {
+                    // dSYMs are actually directories (bundles), so they get archived
+                    let zip_style = ZipStyle::Tar(CompressionImpl::Xzip);
+                    let dir_path = dist_dir.join(format!("{binary_id}-symbols"));
+                    let bundle_path = dir_path.join(format!("{}.dSYM", binary.file_name));
+                    let artifact_name = format!("{dest_symbol_name}{}", zip_style.ext());
+                    let artifact = Artifact {
+                        id: artifact_name.clone(),
+                        target_triples: vec![binary.target.clone()],
+                        archive: Some(Archive {
+                            with_root: None,
+                            dir_path,
+                            zip_style,
+                            static_assets: vec![],
+                        }),
+                        file_path: dist_dir.join(artifact_name),
+                        required_binaries: FastMap::new(),
+                        kind: ArtifactKind::Symbols(Symbols { kind: symbol_kind }),
+                        checksum: None,
+                        is_global: false,
+                    };
+                    (artifact, bundle_path)
+                } else {
+                    let artifact_path = dist_dir.join(&dest_symbol_name);
+                    let artifact = Artifact {
+                        id: dest_symbol_name,
+                        target_triples: vec![binary.target.clone()],
+                        archive: None,
+                        file_path: artifact_path.clone(),
+                        required_binaries: FastMap::new(),
+                        kind: ArtifactKind::Symbols(Symbols { kind: symbol_kind }),
+                        checksum: None,
+                        is_global: false,
+                    };
+                    (artifact, artifact_path)
                 };
 
                 // FIXME: strictly speaking a binary could plausibly be shared between Releases,
@@ -1797,9 +2289,10 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         let hint = format!("brew install {}", install_target);
         let desc = "Install prebuilt binaries via Homebrew".to_owned();
 
+        // If there's a universal2 macOS build, use it for both mac arches
         let artifacts = release
             .platform_support
-            .fragments()
+            .fragments_preferring(Some(TARGET_MACOS_UNIVERSAL2))
             .into_iter()
             .filter(|a| !a.target_triple.contains("windows-msvc"))
             .collect::<Vec<_>>();
@@ -2252,7 +2745,11 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         };
         require_nonempty_installer(release, &config)?;
         let version = release.version.clone();
-        let fragments = release.platform_support.fragments();
+        let fragments = release
+            .platform_support
+            .fragments_preferring(Some(TARGET_MACOS_UNIVERSAL2));
+        // A universal2 pkg works everywhere, so if we have one, don't bother with the others
+        let has_universal2 = release.targets.iter().any(|t| t == TARGET_MACOS_UNIVERSAL2);
 
         let variants = release.variants.clone();
         let checksum = self.inner.config.artifacts.checksum;
@@ -2266,6 +2763,9 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             if !target.contains("darwin") {
                 continue;
             }
+            if has_universal2 && target != TARGET_MACOS_UNIVERSAL2 {
+                continue;
+            }
 
             let variant_id = &variant.id;
             let artifact_name = format!("{variant_id}.pkg");
@@ -2291,13 +2791,21 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 }
             }
 
-            let Some(artifact) = fragments
+            // Fragments are per-platform, and universal2 isn't really a platform,
+            // so look for it under one of the arches it supports instead.
+            let fragment_targets = if target == TARGET_MACOS_UNIVERSAL2 {
+                MACOS_UNIVERSAL2_COMPONENTS
+            } else {
+                &[target.as_str()][..]
+            };
+            let Some(mut artifact) = fragments
                 .clone()
                 .into_iter()
-                .find(|a| a.target_triple == variant.target)
+                .find(|a| fragment_targets.contains(&a.target_triple.as_str()))
             else {
                 return Err(DistError::NoPackage { artifact_name })?;
             };
+            artifact.target_triple.clone_from(target);
 
             let bin_aliases = bin_aliases.for_target(&variant.target);
 
@@ -2389,17 +2897,34 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         let mut local_build_steps = vec![];
         let mut global_build_steps = vec![];
 
+        // This has to come first, as it's what makes us build the single-arch
+        // binaries that universal ones are made from
+        let lipo_builds = self.compute_lipo_builds();
+
         for workspace_idx in self.workspaces.all_workspace_indices() {
             let workspace_kind = self.workspaces.workspace(workspace_idx).kind;
             let builds = match workspace_kind {
                 axoproject::WorkspaceKind::Javascript => self.compute_generic_builds(workspace_idx),
                 axoproject::WorkspaceKind::Generic => self.compute_generic_builds(workspace_idx),
+                axoproject::WorkspaceKind::Python => self.compute_generic_builds(workspace_idx),
+                axoproject::WorkspaceKind::Go => self.compute_go_builds(workspace_idx)?,
+                axoproject::WorkspaceKind::Cmake => self.compute_cmake_builds(workspace_idx),
                 axoproject::WorkspaceKind::Rust => self.compute_cargo_builds(workspace_idx)?,
             };
             local_build_steps.extend(builds);
         }
+        local_build_steps.extend(lipo_builds);
         global_build_steps.extend(self.compute_extra_builds());
 
+        // These get copied into archives, so they need to come before the archives' steps
+        local_build_steps.extend(
+            self.inner
+                .variants
+                .iter()
+                .filter_map(|variant| variant.third_party_licenses.clone())
+                .map(BuildStep::ThirdPartyLicenses),
+        );
+
         Self::add_build_steps_for_artifacts(
             &self
                 .inner
@@ -2433,17 +2958,19 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 }
                 ArtifactKind::Symbols(symbols) => {
                     match symbols.kind {
-                        SymbolKind::Pdb => {
-                            // No additional steps needed, the file is PERFECT (for now)
+                        SymbolKind::Pdb | SymbolKind::Dwp | SymbolKind::Debug => {
+                            // No additional steps needed, the build copies the file into place
                         }
                         SymbolKind::Dsym => {
-                            // FIXME: compress the dSYM in a .tar.xz, it's a actually a directory!
-                        }
-                        SymbolKind::Dwp => {
-                            // No additional steps needed?
+                            // The build copies the bundle into the archive dir, and
+                            // artifact.archive handles the rest
                         }
                     }
                 }
+                ArtifactKind::SymbolServer(_) => {
+                    // The build adds each binary's symbols to the archive dir, and
+                    // artifact.archive handles the rest
+                }
                 ArtifactKind::Installer(installer) => {
                     // Installer generation is complex enough that they just get monolithic impls
                     build_steps.push(BuildStep::GenerateInstaller(installer.clone()));
@@ -2462,6 +2989,12 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 ArtifactKind::ExtraArtifact(_) => {
                     // compute_extra_builds handles this
                 }
+                ArtifactKind::Sbom(sbom) => {
+                    build_steps.push(BuildStep::Sbom(sbom.clone()));
+                }
+                ArtifactKind::Wheel(wheel) => {
+                    build_steps.push(BuildStep::Maturin(wheel.clone()));
+                }
                 ArtifactKind::Updater(_) => {
                     build_steps.push(BuildStep::Updater(UpdaterStep {
                         // There should only be one triple per artifact
@@ -2510,9 +3043,15 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             let workspace_idx = self.workspaces.workspace_for_package(release.package_idx);
             let package_workspace = self.workspaces.workspace(workspace_idx);
             let package_kind = package_workspace.kind;
+            // maturin knows how to build these, so there's no build command
+            let is_maturin = crate::build::maturin::is_maturin_package(package);
             if announcing.package.is_none() {
                 match package_kind {
-                    axoproject::WorkspaceKind::Generic | axoproject::WorkspaceKind::Javascript => {
+                    axoproject::WorkspaceKind::Generic
+                    | axoproject::WorkspaceKind::Javascript
+                    | axoproject::WorkspaceKind::Python
+                        if !is_maturin =>
+                    {
                         if let Some(build_command) = &package.build_command {
                             if build_command.len() == 1
                                 && build_command.first().unwrap().contains(' ')
@@ -2545,7 +3084,8 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                             });
                         }
                     }
-                    axoproject::WorkspaceKind::Rust => {
+                    // cargo, maturin and go packages, which know how to build themselves
+                    _ => {
                         if package.build_command.is_some() {
                             return Err(DistError::UnexpectedBuildCommand {
                                 manifest: package
@@ -2561,7 +3101,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         Ok(())
     }
 
-    fn compute_releases(
+    pub(crate) fn compute_releases(
         &mut self,
         cfg: &Config,
         announcing: &AnnouncementTag,
@@ -2576,6 +3116,23 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             // Create a Release for this binary
             let release = self.add_release(info.package_idx);
 
+            // maturin packages ship wheels, which install their scripts themselves,
+            // so they get none of the usual archives or installers
+            let package = self.workspaces.package(info.package_idx);
+            if crate::build::maturin::is_maturin_package(package) {
+                for target in triples {
+                    let use_target = bypass_package_target_prefs
+                        || app_config.targets.iter().any(|t| t == target);
+                    if use_target {
+                        self.add_variant(release, target.clone())?;
+                    }
+                }
+                self.add_wheels(release, info.package_idx);
+                self.add_source_tarball(&announcing.tag, release);
+                self.add_extra_artifacts(&app_config, release);
+                continue;
+            }
+
             // Don't bother with any of this without binaries
             // or C libraries
             // (releases a Rust library, nothing to Build)
@@ -2613,10 +3170,21 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 if self.inner.config.installers.updater {
                     self.add_updater(variant);
                 }
+
+                if let Some(format) = app_config.artifacts.sbom {
+                    self.add_sbom(release, variant, format);
+                }
+
+                if app_config.artifacts.archives.third_party_licenses {
+                    self.add_third_party_licenses(release, variant);
+                }
             }
             // Add executable zips to the Release
             self.add_executable_zip(release);
 
+            // Add symbol server layouts of the symbols the executable zips produced
+            self.add_symbol_server(release);
+
             // Get initial platform support for installers to use
             self.compute_platform_support(release);
 
@@ -2694,6 +3262,34 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         self.release_mut(release).platform_support = support;
     }
 
+    /// Group the binaries of a (non-cargo) workspace that need a real build
+    ///
+    /// `(target, pkg_idx)` uniquely identifies a build we need to do, so each
+    /// of those gets the binaries that build is expected to produce.
+    pub(crate) fn binaries_to_build(
+        &self,
+        workspace_idx: WorkspaceIdx,
+    ) -> SortedMap<(TargetTriple, PackageIdx), Vec<BinaryIdx>> {
+        let mut builds = SortedMap::<(TargetTriple, PackageIdx), Vec<BinaryIdx>>::new();
+        for (binary_idx, binary) in self.inner.binaries.iter().enumerate() {
+            // Only bother with binaries owned by this workspace
+            if self.workspaces.workspace_for_package(binary.pkg_idx) != workspace_idx {
+                continue;
+            }
+            // Universal binaries are merged from other builds, not built directly
+            if !binary.lipo_inputs.is_empty() {
+                continue;
+            }
+            if !binary.copy_exe_to.is_empty() || !binary.copy_symbols_to.is_empty() {
+                builds
+                    .entry((binary.target.clone(), binary.pkg_idx))
+                    .or_default()
+                    .push(BinaryIdx(binary_idx));
+            }
+        }
+        builds
+    }
+
     pub(crate) fn binary(&self, idx: BinaryIdx) -> &Binary {
         &self.inner.binaries[idx.0]
     }
@@ -2881,31 +3477,34 @@ pub fn get_host_target(cargo: String) -> DistResult<CargoInfo> {
     Err(DistError::FailedCargoVersion)
 }
 
-fn target_symbol_kind(target: &str) -> Option<SymbolKind> {
-    #[allow(clippy::if_same_then_else)]
-    if target.contains("windows-msvc") {
-        // Temporary disabled pending redesign of symbol handling!
-
-        // Some(SymbolKind::Pdb)
-        None
-    } else if target.contains("apple") {
-        // Macos dSYM files are real and work but things
-        // freak out because it turns out they're directories
-        // and not "real" files? Temporarily disabling this
-        // until I have time to figure out what to do
-
-        // Some(SymbolKind::Dsym)
-        None
-    } else {
-        // Linux has DWPs but cargo doesn't properly uplift them
-        // See: https://github.com/rust-lang/cargo/pull/11384
-
-        // Some(SymbolKind::Dwp)
-        None
+/// Get the settings of a cargo profile that affect debuginfo, following `inherits`
+///
+/// Returns `(debug, split_debuginfo)`.
+pub(crate) fn resolve_cargo_profile(profiles: &CargoProfiles, name: &str) -> (i64, Option<String>) {
+    let mut debug = None;
+    let mut split_debuginfo = None;
+    let mut name = name.to_owned();
+    let mut seen = vec![];
+    while let Some(profile) = profiles.get(&name) {
+        debug = debug.or(profile.debug);
+        split_debuginfo = split_debuginfo.or_else(|| profile.split_debuginfo.clone());
+        seen.push(name.clone());
+        match &profile.inherits {
+            // cargo rejects inheritance cycles, but let's not hang if it's broken
+            Some(parent) if !seen.contains(parent) => name.clone_from(parent),
+            _ => break,
+        }
     }
+    // Every chain ends at one of cargo's builtin profiles, and only dev has debuginfo
+    let builtin_debug = if name == "dev" || name == "test" {
+        2
+    } else {
+        0
+    };
+    (debug.unwrap_or(builtin_debug), split_debuginfo)
 }
 
-fn tool_info() -> DistResult<Tools> {
+pub(crate) fn tool_info() -> DistResult<Tools> {
     let cargo_cmd = cargo()?;
     let cargo = get_host_target(cargo_cmd)?;
     Ok(Tools {
@@ -2913,6 +3512,8 @@ fn tool_info() -> DistResult<Tools> {
         rustup: find_tool("rustup", "-V"),
         brew: find_tool("brew", "--version"),
         git: find_tool("git", "--version"),
+        cargo_zigbuild: find_tool("cargo-zigbuild", "--version"),
+        cross: find_tool("cross", "--version"),
         // Computed later if needed
         code_sign_tool: None,
     })
@@ -0,0 +1,137 @@
+use std::{
+    fs::File,
+    time::{Duration, UNIX_EPOCH},
+};
+
+use crate::build::archive::{archive_cache_key, archive_dir};
+use crate::build::cache::BuildCache;
+use crate::config::{CompressionImpl, ZipStyle};
+use camino::Utf8Path;
+use temp_dir::TempDir;
+
+const EPOCH: u64 = 1_700_000_000;
+
+/// Write the same files to `root`, in the given order, with the given mtime
+fn populate(root: &Utf8Path, names: &[&str], mtime: u64) {
+    for name in names {
+        let path = root.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, format!("contents of {name}")).unwrap();
+        File::options()
+            .write(true)
+            .open(&path)
+            .unwrap()
+            .set_modified(UNIX_EPOCH + Duration::from_secs(mtime))
+            .unwrap();
+    }
+}
+
+fn archive_twice(zip_style: ZipStyle) -> (Vec<u8>, Vec<u8>) {
+    let tempdir = TempDir::new().unwrap();
+    let root = Utf8Path::from_path(tempdir.path()).unwrap();
+    let (a, b) = (root.join("a"), root.join("b"));
+    populate(&a, &["README.md", "bin/axolotlsay", "LICENSE"], EPOCH + 100);
+    populate(&b, &["LICENSE", "bin/axolotlsay", "README.md"], EPOCH + 200);
+
+    let with_root = Utf8Path::new("axolotlsay-x86_64-unknown-linux-gnu");
+    // gzip records the file name, so these need to match
+    let (a_out, b_out) = (root.join("a-out/archive"), root.join("b-out/archive"));
+    std::fs::create_dir_all(a_out.parent().unwrap()).unwrap();
+    std::fs::create_dir_all(b_out.parent().unwrap()).unwrap();
+    archive_dir(&a, &a_out, &zip_style, Some(with_root), Some(EPOCH)).unwrap();
+    archive_dir(&b, &b_out, &zip_style, Some(with_root), Some(EPOCH)).unwrap();
+    (std::fs::read(a_out).unwrap(), std::fs::read(b_out).unwrap())
+}
+
+#[test]
+fn tarballs_are_reproducible() {
+    for compression in [
+        CompressionImpl::Gzip,
+        CompressionImpl::Xzip,
+        CompressionImpl::Zstd,
+    ] {
+        let (a, b) = archive_twice(ZipStyle::Tar(compression));
+        assert_eq!(a, b, "{compression:?} tarballs differed");
+    }
+}
+
+#[test]
+fn zips_are_reproducible() {
+    let (a, b) = archive_twice(ZipStyle::Zip);
+    assert_eq!(a, b);
+}
+
+#[test]
+fn tarball_entries_are_normalized() {
+    let (bytes, _) = archive_twice(ZipStyle::Tar(CompressionImpl::Gzip));
+    let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(&bytes[..]));
+
+    let mut names = vec![];
+    for entry in archive.entries().unwrap() {
+        let entry = entry.unwrap();
+        let header = entry.header();
+        assert_eq!(header.uid().unwrap(), 0);
+        assert_eq!(header.gid().unwrap(), 0);
+        assert_eq!(header.username().unwrap(), Some(""));
+        assert_eq!(header.mtime().unwrap(), EPOCH);
+        let expected_mode = if header.entry_type().is_dir() {
+            0o755
+        } else {
+            0o644
+        };
+        assert_eq!(header.mode().unwrap(), expected_mode);
+        names.push(entry.path().unwrap().display().to_string());
+    }
+    assert_eq!(
+        names,
+        vec![
+            "axolotlsay-x86_64-unknown-linux-gnu/",
+            "axolotlsay-x86_64-unknown-linux-gnu/LICENSE",
+            "axolotlsay-x86_64-unknown-linux-gnu/README.md",
+            "axolotlsay-x86_64-unknown-linux-gnu/bin/",
+            "axolotlsay-x86_64-unknown-linux-gnu/bin/axolotlsay",
+        ]
+    );
+}
+
+#[test]
+fn archive_cache_keys_track_inputs() {
+    let tempdir = TempDir::new().unwrap();
+    let root = Utf8Path::from_path(tempdir.path()).unwrap();
+    let src = root.join("src");
+    populate(&src, &["README.md", "bin/axolotlsay"], EPOCH + 100);
+    let dest = root.join("axolotlsay.tar.gz");
+    let style = ZipStyle::Tar(CompressionImpl::Gzip);
+    let key = || archive_cache_key(&src, &dest, &style, None, Some(EPOCH)).unwrap();
+
+    // Touching files doesn't matter once mtimes are clamped...
+    let original = key();
+    populate(&src, &["README.md", "bin/axolotlsay"], EPOCH + 200);
+    assert_eq!(key(), original);
+
+    // ...but changing their contents does
+    std::fs::write(src.join("bin/axolotlsay"), "a new build").unwrap();
+    assert_ne!(key(), original);
+
+    // As does the format
+    let zip_key = archive_cache_key(&src, &dest, &ZipStyle::Zip, None, Some(EPOCH)).unwrap();
+    assert_ne!(zip_key, key());
+}
+
+#[test]
+fn archive_cache_round_trips() {
+    let tempdir = TempDir::new().unwrap();
+    let root = Utf8Path::from_path(tempdir.path()).unwrap();
+    let cache = BuildCache::new(root.join("cache"));
+    let built = root.join("built.tar.gz");
+    let restored = root.join("restored.tar.gz");
+    std::fs::write(&built, "pretend this is an archive").unwrap();
+
+    assert!(!cache.restore("somekey", &restored).unwrap());
+    cache.store("somekey", &built).unwrap();
+    assert!(cache.restore("somekey", &restored).unwrap());
+    assert_eq!(
+        std::fs::read(&built).unwrap(),
+        std::fs::read(&restored).unwrap()
+    );
+}
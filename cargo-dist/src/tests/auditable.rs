@@ -0,0 +1,100 @@
+use super::mock::*;
+#[cfg(unix)]
+use camino::Utf8Path;
+
+use crate::{
+    config::CargoBuilder, BuildStep, CargoBuildStep, DistError, DistGraphBuilder, DistResult,
+};
+
+const HOST: &str = "x86_64-unknown-linux-gnu";
+
+/// Compute the cargo builds for a planned graph with auditable builds on
+fn auditable_builds(
+    graph: &mut DistGraphBuilder,
+    builder: CargoBuilder,
+) -> DistResult<Vec<CargoBuildStep>> {
+    let cargo = &mut graph.inner.config.builds.cargo;
+    cargo.auditable = true;
+    cargo.builders.insert(HOST.to_owned(), builder);
+    let root = graph.workspaces.root_workspace_idx();
+    let builds = graph.compute_cargo_builds(root)?;
+    Ok(builds
+        .into_iter()
+        .filter_map(|step| match step {
+            BuildStep::Cargo(step) => Some(step),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Build with the mock cargo in `dir`, returning the arguments it was run with
+#[cfg(unix)]
+fn build_args(builder: CargoBuilder, dir: &Utf8Path) -> String {
+    let mut workspaces = workspace_just_axo();
+    let mut graph = plan_releases(&mut workspaces, &[HOST]);
+    let mut step = auditable_builds(&mut graph, builder).unwrap().remove(0);
+    assert!(step.auditable);
+
+    // zigbuild is run through cargo, so it only needs to be known to exist
+    graph.inner.tools.cargo_zigbuild = Some(crate::Tool {
+        cmd: "cargo-zigbuild".to_owned(),
+        version: String::new(),
+    });
+    graph.inner.tools.cargo.cmd = mock_cargo(dir).into_string();
+    graph.inner.dist_dir = dir.to_owned();
+    // The planned homes for the binaries are in the mock workspace, so leave them be
+    for binary in &mut graph.inner.binaries {
+        binary.copy_exe_to.clear();
+        binary.copy_symbols_to.clear();
+        binary.copy_symbols_to_server.clear();
+    }
+    step.working_dir = dir.to_owned();
+    let mut manifest = graph.manifest.clone();
+    crate::build::cargo::build_cargo_target(&graph.inner, &mut manifest, &step).unwrap();
+
+    let log = std::fs::read_to_string(dir.join("cargo.log")).unwrap();
+    let (args, _rustflags) = log.lines().next().unwrap().split_once(" | ").unwrap();
+    args.to_owned()
+}
+
+#[test]
+fn auditable_with_cross() {
+    let mut workspaces = workspace_just_axo();
+    let mut graph = plan_releases(&mut workspaces, &[HOST]);
+    let builds = auditable_builds(&mut graph, CargoBuilder::Cross);
+    assert!(matches!(
+        builds,
+        Err(DistError::AuditableWithCross { target }) if target == HOST
+    ));
+}
+
+#[test]
+#[cfg(unix)]
+fn auditable_cargo_command() {
+    let tempdir = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tempdir.path()).unwrap();
+    let args = build_args(CargoBuilder::Cargo, dir);
+    // `cargo auditable` is a cargo subcommand that wraps the real one
+    assert!(
+        args.starts_with("auditable build --profile dist "),
+        "{args}"
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn auditable_zigbuild_command() {
+    let tempdir = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tempdir.path()).unwrap();
+    let args = build_args(
+        CargoBuilder::Zigbuild {
+            glibc_version: None,
+        },
+        dir,
+    );
+    assert!(
+        args.starts_with("auditable zigbuild --profile dist "),
+        "{args}"
+    );
+    assert!(args.contains(&format!("--target {HOST}")), "{args}");
+}
@@ -0,0 +1,127 @@
+//! Tests for `cargo dist changed`
+
+use super::mock::*;
+use axoasset::LocalAsset;
+use axoproject::{local_repo::LocalRepo, PackageInfo};
+use camino::Utf8Path;
+
+use crate::changed::{compute_changes, ChangedReport};
+use crate::{config::ArtifactMode, DistGraphBuilder, Tool};
+
+/// Write a file in the test repo and commit it
+fn commit_file(dir: &Utf8Path, path: &str, contents: &str) {
+    LocalAsset::write_new_all(contents, dir.join(path)).unwrap();
+    git(dir, &["add", "."]);
+    git(dir, &["commit", "-q", "-m", &format!("update {path}")]);
+}
+
+/// Move a mock package into the test repo
+fn in_repo(dir: &Utf8Path, package: PackageInfo) -> PackageInfo {
+    let package_root = dir.join("crates").join(&package.name);
+    PackageInfo {
+        manifest_path: package_root.join("Cargo.toml"),
+        package_root,
+        ..package
+    }
+}
+
+fn changes_for_repo(dir: &Utf8Path, packages: Vec<PackageInfo>) -> ChangedReport {
+    let mut workspace = mock_workspace(packages);
+    let repo = LocalRepo::new("git", dir).unwrap();
+    workspace.repo = Some(repo.clone());
+    let mut tools = mock_tools();
+    tools.git = Some(Tool {
+        cmd: "git".to_owned(),
+        version: String::new(),
+    });
+
+    let graph = DistGraphBuilder::new(
+        "a".to_owned(),
+        tools,
+        &mut workspace,
+        ArtifactMode::All,
+        true,
+        true,
+    )
+    .unwrap();
+    compute_changes(&graph, &repo, "git").unwrap()
+}
+
+#[test]
+fn changed_since_last_release() {
+    let tmp = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tmp.path()).unwrap();
+    git(dir, &["init", "-q"]);
+
+    let axo = PackageInfo {
+        local_dependencies: vec![dir.join("crates/some-lib/Cargo.toml")],
+        ..in_repo(dir, pkg_axo_bin())
+    };
+    let helper = PackageInfo {
+        version: Some(axoproject::Version::Cargo("1.1.0".parse().unwrap())),
+        ..in_repo(dir, pkg_helper_bin())
+    };
+    let quiet = PackageInfo {
+        binaries: vec!["quiet-bin".to_owned()],
+        ..in_repo(dir, mock_package("quiet-bin", "2.0.0"))
+    };
+    let packages = vec![
+        axo,
+        in_repo(dir, pkg_some_lib()),
+        helper,
+        quiet,
+        in_repo(dir, pkg_oddball_bin()),
+    ];
+    for package in &packages {
+        commit_file(dir, &format!("crates/{}/Cargo.toml", package.name), "");
+    }
+    git(dir, &["tag", "axolotlsay-v1.0.0"]);
+    git(dir, &["tag", "helper-bin-v1.0.0"]);
+    git(dir, &["tag", "quiet-bin-v2.0.0"]);
+    // Tags for versions newer than the current one don't count
+    git(dir, &["tag", "v3.0.0"]);
+
+    commit_file(dir, "crates/some-lib/src/lib.rs", "// changed");
+    commit_file(dir, "crates/helper-bin/src/main.rs", "// changed");
+    // Files that don't belong to any package don't change anything
+    commit_file(dir, "README.md", "# changed");
+
+    let report = changes_for_repo(dir, packages);
+    let summary = report
+        .packages
+        .iter()
+        .map(|package| {
+            (
+                package.name.as_str(),
+                package.last_release.as_deref(),
+                package.changed,
+                package.needs_bump,
+                package.changed_dependencies.clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        summary,
+        vec![
+            (
+                "axolotlsay",
+                Some("axolotlsay-v1.0.0"),
+                true,
+                true,
+                vec!["some-lib".to_owned()]
+            ),
+            ("helper-bin", Some("helper-bin-v1.0.0"), true, false, vec![]),
+            ("quiet-bin", Some("quiet-bin-v2.0.0"), false, false, vec![]),
+            ("oddball-bin", None, true, false, vec![]),
+        ]
+    );
+    assert_eq!(
+        report.packages[0].changed_files,
+        vec!["crates/some-lib/src/lib.rs".to_owned()]
+    );
+    // axolotlsay needs a bump before it has a tag to release with
+    assert_eq!(
+        report.tags(),
+        vec!["helper-bin-v1.1.0", "oddball-bin-v0.1.0"]
+    );
+}
@@ -0,0 +1,75 @@
+//! Tests for generating release notes from git history
+
+use super::mock::*;
+use axoproject::local_repo::LocalRepo;
+use camino::Utf8Path;
+
+use crate::announce::{select_tag, TagMode, TagSettings};
+use crate::{config::ArtifactMode, DistGraphBuilder, Tool};
+
+/// Compute the announcement notes for axolotlsay 1.0.0 in the given repo
+fn notes_for_repo(dir: &Utf8Path) -> Option<String> {
+    let mut workspace = workspace_just_axo();
+    workspace.repo = Some(LocalRepo::new("git", dir).unwrap());
+    let mut tools = mock_tools();
+    tools.git = Some(Tool {
+        cmd: "git".to_owned(),
+        version: String::new(),
+    });
+
+    let mut graph = DistGraphBuilder::new(
+        "a".to_owned(),
+        tools,
+        &mut workspace,
+        ArtifactMode::All,
+        true,
+        false,
+    )
+    .unwrap();
+    let settings = TagSettings {
+        needs_coherence: true,
+        tag: TagMode::Select(format!("v{BIN_AXO_VER}")),
+    };
+    let announcing = select_tag(&mut graph, &settings).unwrap();
+    graph.compute_announcement_info(&announcing);
+    graph.manifest.announcement_changelog
+}
+
+#[test]
+fn git_notes_since_previous_release() {
+    let tmp = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tmp.path()).unwrap();
+    git(dir, &["init", "-q"]);
+
+    commit(dir, "chore: initial commit");
+    git(dir, &["tag", "v0.9.0"]);
+    let feat = commit(dir, "feat: say hello in more languages (#12)");
+    // Tags that aren't releases of this package don't count
+    git(dir, &["tag", "some-other-thing"]);
+    let other = commit(dir, "Update the readme");
+    commit(dir, "Merge branch 'main' into feature");
+
+    assert_eq!(
+        notes_for_repo(dir).unwrap(),
+        format!(
+            "### Features
+
+- say hello in more languages ([#12]({REPO_URL}/pull/12)) ({feat})
+
+### Other Changes
+
+- Update the readme ({other})"
+        )
+    );
+}
+
+#[test]
+fn git_notes_need_previous_release() {
+    // On the first release there's nothing to compare to, so we don't dump the whole history
+    let tmp = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tmp.path()).unwrap();
+    git(dir, &["init", "-q"]);
+    commit(dir, "feat: everything");
+
+    assert_eq!(notes_for_repo(dir), None);
+}
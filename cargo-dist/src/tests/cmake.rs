@@ -0,0 +1,114 @@
+use axoasset::LocalAsset;
+use camino::Utf8PathBuf;
+use temp_dir::TempDir;
+
+use crate::build::{
+    cmake::{find_installed_files, toolchain_file},
+    generic::NativeBuildEnv,
+};
+
+const LINUX_HOST: &str = "x86_64-unknown-linux-gnu";
+const MAC_HOST: &str = "aarch64-apple-darwin";
+
+#[test]
+fn toolchain_for_host_is_not_cross() {
+    let file = toolchain_file(LINUX_HOST, LINUX_HOST, &NativeBuildEnv::default());
+    assert!(!file.contains("CMAKE_SYSTEM_NAME"), "{file}");
+    assert!(!file.contains("CMAKE_SYSTEM_PROCESSOR"), "{file}");
+    // Neither is changing libc
+    let file = toolchain_file(
+        "x86_64-unknown-linux-musl",
+        LINUX_HOST,
+        &NativeBuildEnv::default(),
+    );
+    assert!(!file.contains("CMAKE_SYSTEM_NAME"), "{file}");
+}
+
+#[test]
+fn toolchain_for_other_arch_is_cross() {
+    let file = toolchain_file(
+        "aarch64-unknown-linux-gnu",
+        LINUX_HOST,
+        &NativeBuildEnv::default(),
+    );
+    assert!(file.contains("set(CMAKE_SYSTEM_NAME \"Linux\")"), "{file}");
+    assert!(
+        file.contains("set(CMAKE_SYSTEM_PROCESSOR \"aarch64\")"),
+        "{file}"
+    );
+}
+
+#[test]
+fn toolchain_for_mac_sets_architectures() {
+    let file = toolchain_file("x86_64-apple-darwin", MAC_HOST, &NativeBuildEnv::default());
+    assert!(
+        file.contains("set(CMAKE_OSX_ARCHITECTURES \"x86_64\")"),
+        "{file}"
+    );
+    assert!(!file.contains("CMAKE_SYSTEM_NAME"), "{file}");
+    let file = toolchain_file(MAC_HOST, MAC_HOST, &NativeBuildEnv::default());
+    assert!(
+        file.contains("set(CMAKE_OSX_ARCHITECTURES \"arm64\")"),
+        "{file}"
+    );
+}
+
+#[test]
+fn toolchain_for_windows_sets_platform() {
+    let file = toolchain_file(
+        "aarch64-pc-windows-msvc",
+        "x86_64-pc-windows-msvc",
+        &NativeBuildEnv::default(),
+    );
+    assert_eq!(
+        file,
+        r#"# Generated by cargo-dist for aarch64-pc-windows-msvc
+if(CMAKE_GENERATOR MATCHES "Visual Studio")
+  set(CMAKE_GENERATOR_PLATFORM "ARM64")
+endif()
+"#
+    );
+}
+
+#[test]
+fn toolchain_passes_brew_flags() {
+    let native_env = NativeBuildEnv {
+        extra_env: vec![],
+        cflags: Some("-I/opt/homebrew/opt/libssh2/include".to_owned()),
+        ldflags: Some("-L/opt/homebrew/opt/libssh2/lib".to_owned()),
+    };
+    let file = toolchain_file(MAC_HOST, MAC_HOST, &native_env);
+    assert!(
+        file.contains("set(CMAKE_C_FLAGS_INIT \"-I/opt/homebrew/opt/libssh2/include\")"),
+        "{file}"
+    );
+    assert!(
+        file.contains("set(CMAKE_SHARED_LINKER_FLAGS_INIT \"-L/opt/homebrew/opt/libssh2/lib\")"),
+        "{file}"
+    );
+}
+
+#[test]
+fn installed_files_are_found_anywhere() {
+    let tempdir = TempDir::new().unwrap();
+    let install_dir = Utf8PathBuf::from_path_buf(tempdir.path().to_owned()).unwrap();
+    for file in [
+        "bin/app",
+        "bin/app.dll",
+        "lib/app.dll",
+        "lib/x86_64-linux-gnu/libapp.so",
+        "share/doc/app/README",
+    ] {
+        LocalAsset::write_new_all("", install_dir.join(file)).unwrap();
+    }
+
+    let found = find_installed_files(&install_dir).unwrap();
+    assert_eq!(found["app"], install_dir.join("bin/app"));
+    // bin/ wins over lib/
+    assert_eq!(found["app.dll"], install_dir.join("bin/app.dll"));
+    assert_eq!(
+        found["libapp.so"],
+        install_dir.join("lib/x86_64-linux-gnu/libapp.so")
+    );
+    assert_eq!(found.len(), 4);
+}
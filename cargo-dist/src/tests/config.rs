@@ -1,6 +1,6 @@
 use crate::{
     backend::diff_source,
-    config::{parse_generic_config, parse_metadata_table, DistMetadata},
+    config::{parse_generic_config, parse_metadata_table, CargoBuilder, DistMetadata},
     init::apply_dist_to_workspace_toml,
     DistResult,
 };
@@ -22,6 +22,15 @@ fn parse_config(src: &SourceFile, input_kind: WorkspaceKind) -> DistResult<DistM
         WorkspaceKind::Javascript => {
             unimplemented!("npm packages don't have [package.metadata.dist]")
         }
+        WorkspaceKind::Python => {
+            unimplemented!("python packages don't have [package.metadata.dist]")
+        }
+        WorkspaceKind::Go => {
+            unimplemented!("go modules don't have [package.metadata.dist]")
+        }
+        WorkspaceKind::Cmake => {
+            unimplemented!("cmake projects don't have [package.metadata.dist]")
+        }
         WorkspaceKind::Rust => parse_rust_config(src.clone()),
         WorkspaceKind::Generic => parse_generic_config(src.clone()),
     }
@@ -43,6 +52,9 @@ fn format_config(
 fn source(input: &str, input_kind: WorkspaceKind) -> SourceFile {
     let src_name = match input_kind {
         WorkspaceKind::Javascript => "package.json",
+        WorkspaceKind::Python => "pyproject.toml",
+        WorkspaceKind::Go => "go.mod",
+        WorkspaceKind::Cmake => "CMakeLists.txt",
         WorkspaceKind::Rust => "Cargo.toml",
         WorkspaceKind::Generic => "dist.toml",
     };
@@ -208,3 +220,148 @@ targets = ["aarch64-apple-darwin", "x86_64-apple-darwin"]
         .map_err(miette::Report::new)
         .unwrap();
 }
+
+#[test]
+fn cargo_builders_parse() {
+    let input_kind = WorkspaceKind::Rust;
+    let input = r##"
+[workspace.metadata.dist.cargo-builders]
+aarch64-unknown-linux-gnu = "zigbuild.2.17"
+aarch64-unknown-linux-musl = "cross"
+x86_64-unknown-linux-gnu = "cargo"
+"##;
+    let config = parse_config(&source(input, input_kind), input_kind).unwrap();
+    let builders = config.cargo_builders.unwrap();
+    assert_eq!(
+        builders["aarch64-unknown-linux-gnu"],
+        CargoBuilder::Zigbuild {
+            glibc_version: Some(cargo_dist_schema::GlibcVersion {
+                major: 2,
+                series: 17
+            })
+        }
+    );
+    assert_eq!(
+        builders["aarch64-unknown-linux-gnu"].to_string(),
+        "zigbuild.2.17"
+    );
+    assert_eq!(builders["aarch64-unknown-linux-musl"], CargoBuilder::Cross);
+    assert_eq!(builders["x86_64-unknown-linux-gnu"], CargoBuilder::Cargo);
+
+    let input = r##"
+[workspace.metadata.dist.cargo-builders]
+aarch64-unknown-linux-gnu = "zigbuild.two"
+"##;
+    assert!(parse_config(&source(input, input_kind), input_kind).is_err());
+}
+
+#[test]
+fn cargo_target_overrides() {
+    use crate::config::v1::builds::{cargo::AppCargoBuildConfig, CommonBuildConfig};
+
+    let input_kind = WorkspaceKind::Rust;
+    let input = r##"
+[workspace.metadata.dist]
+features = ["cli"]
+
+[workspace.metadata.dist.target.'cfg(target_os = "linux")']
+features = ["cli", "jemalloc"]
+rustflags = "-Ctarget-cpu=x86-64-v2"
+
+[workspace.metadata.dist.target.'cfg(unix)']
+env = { MY_BUILD_FLAG = "1" }
+
+[workspace.metadata.dist.target.x86_64-pc-windows-msvc]
+profile = "dist-windows"
+"##;
+    let config = parse_config(&source(input, input_kind), input_kind).unwrap();
+    let cargo = AppCargoBuildConfig {
+        common: CommonBuildConfig {},
+        features: config.features.unwrap(),
+        default_features: true,
+        all_features: false,
+        target: config.target.unwrap(),
+    };
+
+    let linux = cargo
+        .for_target(&"x86_64-unknown-linux-gnu".to_owned())
+        .unwrap();
+    assert_eq!(linux.features, vec!["cli", "jemalloc"]);
+    assert_eq!(linux.rustflags.trim(), "-Ctarget-cpu=x86-64-v2");
+    assert_eq!(linux.env["MY_BUILD_FLAG"], "1");
+    assert_eq!(linux.profile, "dist");
+
+    let mac = cargo
+        .for_target(&"aarch64-apple-darwin".to_owned())
+        .unwrap();
+    assert_eq!(mac.features, vec!["cli"]);
+    assert_eq!(mac.rustflags, "");
+    assert_eq!(mac.env["MY_BUILD_FLAG"], "1");
+
+    let windows = cargo
+        .for_target(&"x86_64-pc-windows-msvc".to_owned())
+        .unwrap();
+    assert_eq!(windows.features, vec!["cli"]);
+    assert!(windows.env.is_empty());
+    assert_eq!(windows.profile, "dist-windows");
+
+    let input = r##"
+[workspace.metadata.dist.target.'cfg(target_os = )']
+profile = "broken"
+"##;
+    let config = parse_config(&source(input, input_kind), input_kind).unwrap();
+    let cargo = AppCargoBuildConfig {
+        target: config.target.unwrap(),
+        ..cargo
+    };
+    assert!(cargo
+        .for_target(&"x86_64-unknown-linux-gnu".to_owned())
+        .is_err());
+}
+
+#[test]
+fn binary_processors_parse() {
+    use crate::config::BinaryProcessorKind;
+
+    let input_kind = WorkspaceKind::Rust;
+    let input = r##"
+[workspace.metadata.dist]
+binary-processors = [
+    "split-debuginfo",
+    "strip",
+    { processor = "upx", targets = ["x86_64-unknown-linux-gnu"] },
+    { processor = "rpath", rpath = "$ORIGIN/../lib" },
+]
+"##;
+    let config = parse_config(&source(input, input_kind), input_kind).unwrap();
+    let processors = config
+        .binary_processors
+        .unwrap()
+        .into_iter()
+        .map(|processor| processor.0)
+        .collect::<Vec<_>>();
+    assert_eq!(
+        processors
+            .iter()
+            .map(|processor| processor.processor)
+            .collect::<Vec<_>>(),
+        vec![
+            BinaryProcessorKind::SplitDebuginfo,
+            BinaryProcessorKind::Strip,
+            BinaryProcessorKind::Upx,
+            BinaryProcessorKind::Rpath,
+        ]
+    );
+    let linux = "x86_64-unknown-linux-gnu".to_owned();
+    let mac = "aarch64-apple-darwin".to_owned();
+    assert!(processors[0].wanted_for_target(&mac));
+    assert!(processors[2].wanted_for_target(&linux));
+    assert!(!processors[2].wanted_for_target(&mac));
+    assert_eq!(processors[3].rpath.as_deref(), Some("$ORIGIN/../lib"));
+
+    let input = r##"
+[workspace.metadata.dist]
+binary-processors = ["compress-really-hard"]
+"##;
+    assert!(parse_config(&source(input, input_kind), input_kind).is_err());
+}
@@ -0,0 +1,26 @@
+use axoprocess::Cmd;
+use camino::Utf8Path;
+use temp_dir::TempDir;
+
+use crate::build::generic::run_logged;
+
+#[test]
+#[cfg(unix)]
+fn build_output_is_logged() {
+    let tempdir = TempDir::new().unwrap();
+    let log_path = Utf8Path::from_path(tempdir.path())
+        .unwrap()
+        .join("build.log");
+
+    let mut command = Cmd::new("sh", "run a failing build");
+    command
+        .arg("-c")
+        .arg("echo to-stdout; echo to-stderr >&2; exit 3");
+    let status = run_logged(command, &log_path).unwrap();
+
+    // The status is handed back unchecked, so the caller can report it
+    assert_eq!(status.code(), Some(3));
+    let log = std::fs::read_to_string(&log_path).unwrap();
+    assert!(log.contains("to-stdout\n"), "{log}");
+    assert!(log.contains("to-stderr\n"), "{log}");
+}
@@ -0,0 +1,32 @@
+use crate::{build::go::go_target, GoTarget};
+
+#[test]
+fn go_targets_from_triples() {
+    let cases = [
+        ("x86_64-unknown-linux-gnu", Some(("linux", "amd64", None))),
+        ("x86_64-unknown-linux-musl", Some(("linux", "amd64", None))),
+        ("aarch64-apple-darwin", Some(("darwin", "arm64", None))),
+        ("x86_64-pc-windows-msvc", Some(("windows", "amd64", None))),
+        ("i686-pc-windows-gnu", Some(("windows", "386", None))),
+        (
+            "armv7-unknown-linux-gnueabihf",
+            Some(("linux", "arm", Some("7"))),
+        ),
+        ("aarch64-linux-android", Some(("android", "arm64", None))),
+        (
+            "riscv64gc-unknown-linux-gnu",
+            Some(("linux", "riscv64", None)),
+        ),
+        ("x86_64-unknown-freebsd", Some(("freebsd", "amd64", None))),
+        ("wasm32-unknown-unknown", None),
+        ("x86_64-unknown-redox", None),
+    ];
+    for (triple, expected) in cases {
+        let expected = expected.map(|(goos, goarch, goarm)| GoTarget {
+            goos,
+            goarch,
+            goarm,
+        });
+        assert_eq!(go_target(triple), expected, "converting {triple}");
+    }
+}
@@ -1,9 +1,14 @@
 use super::mock::*;
 use crate::announce::{select_tag, AnnouncementTag, TagMode, TagSettings};
 use crate::config::{CiStyle, HostingStyle};
-use crate::host::select_hosting;
+use crate::host::{
+    github_remote_file, parse_github_release_assets, plan_upload, select_hosting, RemoteFile,
+    UploadAction,
+};
+use crate::net::{RetryPolicy, Transient};
 use crate::DistError;
 use crate::{config::ArtifactMode, DistGraphBuilder};
+use axoasset::SourceFile;
 use axoproject::errors::AxoprojectError;
 use axoproject::{PackageIdx, WorkspaceGraph};
 use semver::Version;
@@ -268,3 +273,238 @@ fn github_trail_slash() {
     assert_eq!(hosting.project, REPO_PROJECT);
     assert_eq!(hosting.source_host, "github");
 }
+
+fn local_checksum() -> crate::DistResult<String> {
+    Ok("abc123".to_owned())
+}
+
+#[test]
+fn upload_missing() {
+    let action = plan_upload("host", "a.tar.gz", &RemoteFile::Missing, false, || {
+        panic!("shouldn't need to checksum missing files")
+    });
+    assert_eq!(action.unwrap(), UploadAction::Upload);
+}
+
+#[test]
+fn upload_partial() {
+    let action = plan_upload(
+        "host",
+        "a.tar.gz",
+        &RemoteFile::Partial,
+        false,
+        local_checksum,
+    );
+    assert_eq!(action.unwrap(), UploadAction::Replace);
+}
+
+#[test]
+fn upload_matching() {
+    let remote = RemoteFile::Present {
+        checksum: Some("ABC123".to_owned()),
+    };
+    let action = plan_upload("host", "a.tar.gz", &remote, false, local_checksum);
+    assert_eq!(action.unwrap(), UploadAction::Skip);
+}
+
+#[test]
+fn upload_mismatched() {
+    let remote = RemoteFile::Present {
+        checksum: Some("def456".to_owned()),
+    };
+    let action = plan_upload("host", "a.tar.gz", &remote, false, local_checksum);
+    assert!(matches!(
+        action,
+        Err(DistError::UploadMismatch { name, local, remote, .. })
+            if name == "a.tar.gz" && local == "abc123" && remote == "def456"
+    ));
+
+    let action = plan_upload("host", "a.tar.gz", &remote, true, local_checksum);
+    assert_eq!(action.unwrap(), UploadAction::Replace);
+}
+
+#[test]
+fn upload_unknown_checksum() {
+    // Can't prove it's a different file, so just make sure the host has ours
+    let remote = RemoteFile::Present { checksum: None };
+    let action = plan_upload("host", "a.tar.gz", &remote, false, || {
+        panic!("shouldn't need to checksum files we can't compare")
+    });
+    assert_eq!(action.unwrap(), UploadAction::Replace);
+}
+
+/// `gh release view --json assets` for a Release uploaded before Github computed digests
+const GITHUB_ASSETS_NO_DIGEST: &str = r#"{
+  "assets": [
+    { "name": "a.tar.gz", "state": "uploaded", "size": 1234 },
+    { "name": "a.tar.gz.sha256", "state": "uploaded", "size": 75 },
+    { "name": "b.tar.gz", "state": "uploaded", "size": 1234 },
+    { "name": "c.tar.gz", "state": "starter", "size": 0 }
+  ]
+}"#;
+
+#[test]
+fn github_asset_no_digest() {
+    let src = SourceFile::new("gh release view", GITHUB_ASSETS_NO_DIGEST.to_owned());
+    let assets = parse_github_release_assets(&src).unwrap();
+    assert_eq!(assets["a.tar.gz"], RemoteFile::Present { checksum: None });
+    assert_eq!(assets["c.tar.gz"], RemoteFile::Partial);
+
+    // The digest is missing, so the published checksum file is used instead
+    let remote = github_remote_file(&assets, "a.tar.gz", Some("a.tar.gz.sha256"), |name| {
+        assert_eq!(name, "a.tar.gz.sha256");
+        Ok("ABC123  a.tar.gz\n".to_owned())
+    })
+    .unwrap();
+    assert_eq!(
+        remote,
+        RemoteFile::Present {
+            checksum: Some("ABC123".to_owned())
+        }
+    );
+    let action = plan_upload("host", "a.tar.gz", &remote, false, local_checksum);
+    assert_eq!(action.unwrap(), UploadAction::Skip);
+
+    let remote = github_remote_file(&assets, "a.tar.gz", Some("a.tar.gz.sha256"), |_| {
+        Ok("def456  a.tar.gz\n".to_owned())
+    })
+    .unwrap();
+    let action = plan_upload("host", "a.tar.gz", &remote, false, local_checksum);
+    assert!(matches!(
+        action,
+        Err(DistError::UploadMismatch { local, remote, .. })
+            if local == "abc123" && remote == "def456"
+    ));
+
+    // No checksum file was published, so there's nothing to compare against
+    let remote = github_remote_file(&assets, "b.tar.gz", Some("b.tar.gz.sha256"), |_| {
+        panic!("shouldn't fetch checksum files the Release doesn't have")
+    })
+    .unwrap();
+    assert_eq!(remote, RemoteFile::Present { checksum: None });
+    let action = plan_upload("host", "b.tar.gz", &remote, false, local_checksum);
+    assert_eq!(action.unwrap(), UploadAction::Replace);
+
+    let remote = github_remote_file(&assets, "d.tar.gz", None, |_| {
+        panic!("shouldn't fetch checksum files for missing files")
+    })
+    .unwrap();
+    assert_eq!(remote, RemoteFile::Missing);
+}
+
+/// An error for testing retries
+#[derive(Debug, PartialEq, Eq)]
+struct Flake {
+    call: u32,
+    transient: bool,
+}
+
+impl std::fmt::Display for Flake {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "failure {}", self.call)
+    }
+}
+
+impl Transient for Flake {
+    fn is_transient(&self) -> bool {
+        self.transient
+    }
+}
+
+#[test]
+fn retry_until_success() {
+    let retry = RetryPolicy {
+        attempts: 3,
+        initial_delay: std::time::Duration::ZERO,
+    };
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    let mut calls = 0;
+    let result = runtime.block_on(retry.run("flake", || {
+        calls += 1;
+        let call = calls;
+        async move {
+            if call < 3 {
+                Err(Flake {
+                    call,
+                    transient: true,
+                })
+            } else {
+                Ok(call)
+            }
+        }
+    }));
+    assert_eq!(result, Ok(3));
+
+    let mut calls = 0;
+    let result: Result<(), Flake> = runtime.block_on(retry.run("fail", || {
+        calls += 1;
+        let call = calls;
+        async move {
+            Err(Flake {
+                call,
+                transient: true,
+            })
+        }
+    }));
+    assert_eq!(
+        result,
+        Err(Flake {
+            call: 3,
+            transient: true
+        })
+    );
+}
+
+#[test]
+fn retry_only_transient() {
+    let retry = RetryPolicy {
+        attempts: 3,
+        initial_delay: std::time::Duration::ZERO,
+    };
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+
+    // Retrying something that will never work is a waste of time
+    let mut calls = 0;
+    let result: Result<(), Flake> = runtime.block_on(retry.run("fail", || {
+        calls += 1;
+        let call = calls;
+        async move {
+            Err(Flake {
+                call,
+                transient: false,
+            })
+        }
+    }));
+    assert_eq!(
+        result,
+        Err(Flake {
+            call: 1,
+            transient: false
+        })
+    );
+}
+
+#[test]
+fn transient_errors() {
+    let fetch = |status| DistError::FetchFailed {
+        url: "https://example.com/a.tar.gz".to_owned(),
+        status,
+    };
+    assert!(fetch(500).is_transient());
+    assert!(fetch(503).is_transient());
+    assert!(fetch(429).is_transient());
+    assert!(!fetch(403).is_transient());
+    assert!(!fetch(410).is_transient());
+
+    let gh = |stderr: &str| DistError::GithubCliFailed {
+        desc: "upload a.tar.gz".to_owned(),
+        stderr: stderr.to_owned(),
+    };
+    assert!(gh("HTTP 502: Bad Gateway (https://uploads.github.com/...)").is_transient());
+    assert!(gh("dial tcp 140.82.112.6:443: i/o timeout").is_transient());
+    assert!(!gh("HTTP 422: Validation Failed").is_transient());
+    assert!(!gh("release not found").is_transient());
+
+    assert!(!DistError::ChangedNoRepo.is_transient());
+}
@@ -0,0 +1,139 @@
+use axoasset::LocalAsset;
+use camino::Utf8Path;
+
+use crate::licenses::{
+    group_by_license, license_texts, render_third_party_licenses, LicenseText, LicensedCrate,
+    ThirdPartyLicenses,
+};
+
+fn licensed_crate(name: &str, license: Option<&str>, texts: &[(&str, &str)]) -> LicensedCrate {
+    LicensedCrate {
+        name: name.to_owned(),
+        version: "1.0.0".to_owned(),
+        license: license.map(str::to_owned),
+        texts: texts
+            .iter()
+            .map(|(file_name, text)| LicenseText {
+                file_name: file_name.to_string(),
+                text: text.to_string(),
+            })
+            .collect(),
+    }
+}
+
+#[test]
+fn finds_license_texts() {
+    let tmp = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tmp.path()).unwrap();
+    for (path, contents) in [
+        ("LICENSE-MIT", "mit"),
+        ("LICENSE-APACHE", "apache"),
+        ("NOTICE", "notice"),
+        ("Copying.txt", "copying"),
+        ("README.md", "readme"),
+        ("legal/custom.txt", "custom"),
+    ] {
+        LocalAsset::write_new_all(contents, dir.join(path)).unwrap();
+    }
+
+    let texts = license_texts(dir, Some(&dir.join("legal/custom.txt"))).unwrap();
+    let names = texts
+        .iter()
+        .map(|text| (text.file_name.as_str(), text.text.as_str()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        names,
+        vec![
+            ("Copying.txt", "copying"),
+            ("LICENSE-APACHE", "apache"),
+            ("LICENSE-MIT", "mit"),
+            ("NOTICE", "notice"),
+            ("custom.txt", "custom"),
+        ]
+    );
+
+    // Crates that somehow went missing just don't have any texts
+    assert_eq!(license_texts(&dir.join("missing"), None).unwrap(), vec![]);
+}
+
+#[test]
+fn renders_licenses_grouped_by_spdx() {
+    let groups = group_by_license(vec![
+        licensed_crate(
+            "serde",
+            Some("MIT OR Apache-2.0"),
+            &[
+                ("LICENSE-APACHE", "apache\n"),
+                ("LICENSE-MIT", "serde mit\n"),
+            ],
+        ),
+        licensed_crate("itoa", Some("MIT"), &[("LICENSE", "itoa mit\n")]),
+        licensed_crate(
+            "anyhow",
+            Some("MIT OR Apache-2.0"),
+            &[("LICENSE-APACHE", "apache"), ("LICENSE-MIT", "anyhow mit")],
+        ),
+        licensed_crate("mystery", None, &[]),
+    ]);
+    let licenses = ThirdPartyLicenses {
+        app_name: "axolotlsay".to_owned(),
+        app_version: "0.1.0".to_owned(),
+        target: "x86_64-unknown-linux-gnu".to_owned(),
+        groups,
+    };
+
+    let heavy = "=".repeat(80);
+    let light = "-".repeat(80);
+    assert_eq!(
+        render_third_party_licenses(&licenses),
+        format!(
+            "THIRD-PARTY LICENSES
+
+axolotlsay 0.1.0 (x86_64-unknown-linux-gnu) includes the following third-party crates, grouped by license.
+
+{heavy}
+MIT
+{heavy}
+
+* itoa 1.0.0
+
+{light}
+LICENSE (itoa 1.0.0)
+{light}
+
+itoa mit
+
+{heavy}
+MIT OR Apache-2.0
+{heavy}
+
+* anyhow 1.0.0
+* serde 1.0.0
+
+{light}
+LICENSE-APACHE (anyhow 1.0.0, serde 1.0.0)
+{light}
+
+apache
+
+{light}
+LICENSE-MIT (anyhow 1.0.0)
+{light}
+
+anyhow mit
+
+{light}
+LICENSE-MIT (serde 1.0.0)
+{light}
+
+serde mit
+
+{heavy}
+Unspecified license
+{heavy}
+
+* mystery 1.0.0 (no license text found)
+"
+        )
+    );
+}
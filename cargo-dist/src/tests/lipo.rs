@@ -0,0 +1,124 @@
+use crate::build::lipo::merge_macho;
+use crate::DistError;
+use camino::Utf8PathBuf;
+use goblin::mach::{
+    constants::cputype::{
+        CPU_SUBTYPE_ARM64_ALL, CPU_SUBTYPE_X86_64_ALL, CPU_TYPE_ARM64, CPU_TYPE_X86_64,
+    },
+    Mach,
+};
+
+const X64_MAC: &str = "x86_64-apple-darwin";
+const ARM64_MAC: &str = "aarch64-apple-darwin";
+
+/// The smallest thing goblin will accept as a 64-bit Mach-O executable
+fn thin_macho(cputype: u32, cpusubtype: u32) -> Vec<u8> {
+    let mut bytes = vec![];
+    // magic, cputype, cpusubtype, filetype (MH_EXECUTE), ncmds, sizeofcmds, flags, reserved
+    for field in [0xfeed_facf, cputype, cpusubtype, 2, 0, 0, 0, 0] {
+        bytes.extend(u32::to_le_bytes(field));
+    }
+    bytes
+}
+
+/// An ar archive (static library) with a single member
+fn ar_archive(member: &[u8]) -> Vec<u8> {
+    let mut bytes = b"!<arch>\n".to_vec();
+    let header = format!(
+        "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`\n",
+        "lib.o/",
+        0,
+        0,
+        0,
+        644,
+        member.len()
+    );
+    bytes.extend(header.as_bytes());
+    bytes.extend(member);
+    bytes
+}
+
+fn input(name: &str, target: &str, bytes: Vec<u8>) -> (Utf8PathBuf, String, Vec<u8>) {
+    (Utf8PathBuf::from(name), target.to_owned(), bytes)
+}
+
+#[test]
+fn merges_into_fat_macho() {
+    let x64 = thin_macho(CPU_TYPE_X86_64, CPU_SUBTYPE_X86_64_ALL);
+    let arm64 = thin_macho(CPU_TYPE_ARM64, CPU_SUBTYPE_ARM64_ALL);
+    let fat = merge_macho(
+        &[
+            input("x64/axolotlsay", X64_MAC, x64.clone()),
+            input("arm64/axolotlsay", ARM64_MAC, arm64.clone()),
+        ],
+        "universal/axolotlsay".into(),
+    )
+    .unwrap();
+
+    let Mach::Fat(multi) = Mach::parse(&fat).unwrap() else {
+        panic!("output wasn't a fat Mach-O");
+    };
+    let arches = multi.arches().unwrap();
+    assert_eq!(arches.len(), 2);
+
+    assert_eq!(arches[0].cputype, CPU_TYPE_X86_64);
+    assert_eq!(arches[0].align, 12);
+    assert_eq!(arches[0].offset % (1 << 12), 0);
+    assert_eq!(arches[0].slice(&fat), &x64[..]);
+
+    assert_eq!(arches[1].cputype, CPU_TYPE_ARM64);
+    assert_eq!(arches[1].align, 14);
+    assert_eq!(arches[1].offset % (1 << 14), 0);
+    assert_eq!(arches[1].slice(&fat), &arm64[..]);
+}
+
+#[test]
+fn merges_static_libraries_by_target() {
+    let archive = ar_archive(&thin_macho(0, 0));
+    let fat = merge_macho(
+        &[
+            input("x64/libaxolotlsay.a", X64_MAC, archive.clone()),
+            input("arm64/libaxolotlsay.a", ARM64_MAC, archive),
+        ],
+        "universal/libaxolotlsay.a".into(),
+    )
+    .unwrap();
+
+    let Mach::Fat(multi) = Mach::parse(&fat).unwrap() else {
+        panic!("output wasn't a fat Mach-O");
+    };
+    let cputypes = multi
+        .arches()
+        .unwrap()
+        .iter()
+        .map(|arch| arch.cputype)
+        .collect::<Vec<_>>();
+    assert_eq!(cputypes, vec![CPU_TYPE_X86_64, CPU_TYPE_ARM64]);
+}
+
+#[test]
+fn rejects_bad_inputs() {
+    let x64 = thin_macho(CPU_TYPE_X86_64, CPU_SUBTYPE_X86_64_ALL);
+
+    let res = merge_macho(
+        &[
+            input("a/axolotlsay", X64_MAC, x64.clone()),
+            input("b/axolotlsay", ARM64_MAC, x64.clone()),
+        ],
+        "universal/axolotlsay".into(),
+    );
+    assert!(matches!(res, Err(DistError::LipoDuplicateArch { path }) if path == "b/axolotlsay"));
+
+    let res = merge_macho(
+        &[
+            input("a/axolotlsay", X64_MAC, x64),
+            input(
+                "b/axolotlsay.exe",
+                ARM64_MAC,
+                b"MZ this is not a mac".to_vec(),
+            ),
+        ],
+        "universal/axolotlsay".into(),
+    );
+    assert!(res.is_err());
+}
@@ -0,0 +1,137 @@
+use crate::manifest_diff::{diff_manifests, render_human, TargetDiff};
+use crate::platform::LibcVersion;
+use cargo_dist_schema::DistManifest;
+
+const LINUX: &str = "x86_64-unknown-linux-gnu";
+const MAC: &str = "aarch64-apple-darwin";
+
+/// Make a manifest for a release of `myapp` with the given archives
+///
+/// Each archive is (target, binary, system libraries, builder glibc series)
+fn mock_manifest(version: &str, archives: &[(&str, &str, &[&str], u64)]) -> DistManifest {
+    let mut artifacts = serde_json::Map::new();
+    let mut assets = serde_json::Map::new();
+    let mut systems = serde_json::Map::new();
+    let mut release_artifacts = vec!["myapp-installer.sh".to_owned()];
+    artifacts.insert(
+        "myapp-installer.sh".to_owned(),
+        serde_json::json!({ "name": "myapp-installer.sh", "kind": "installer" }),
+    );
+    for (target, bin, libs, glibc_series) in archives {
+        let name = format!("myapp-{target}.tar.xz");
+        let asset_id = format!("{target}-{bin}");
+        let system_id = format!("builder-{target}");
+        artifacts.insert(
+            name.clone(),
+            serde_json::json!({
+                "name": name,
+                "kind": "executable-zip",
+                "target_triples": [target],
+                "assets": [{ "id": asset_id, "name": bin, "path": bin, "kind": "executable" }],
+            }),
+        );
+        let system = libs
+            .iter()
+            .map(|lib| serde_json::json!({ "path": lib, "source": "libc6", "package_manager": "Apt" }))
+            .collect::<Vec<_>>();
+        assets.insert(
+            asset_id.clone(),
+            serde_json::json!({
+                "id": asset_id,
+                "name": bin,
+                "system": system_id,
+                "target_triples": [target],
+                "linkage": { "system": system },
+            }),
+        );
+        systems.insert(
+            system_id.clone(),
+            serde_json::json!({
+                "id": system_id,
+                "cargo_version_line": null,
+                "build_environment": {
+                    "linux": { "glibc_version": { "major": 2, "series": glibc_series } }
+                },
+            }),
+        );
+        release_artifacts.push(name);
+    }
+    serde_json::from_value(serde_json::json!({
+        "releases": [{
+            "app_name": "myapp",
+            "app_version": version,
+            "artifacts": release_artifacts,
+        }],
+        "artifacts": artifacts,
+        "assets": assets,
+        "systems": systems,
+    }))
+    .unwrap()
+}
+
+#[test]
+fn diff_identical() {
+    let manifest = mock_manifest("1.0.0", &[(LINUX, "myapp", &["libc.so.6"], 31)]);
+    let diff = diff_manifests(&manifest, &manifest);
+    assert!(diff.is_empty());
+    assert_eq!(render_human(&diff), "no differences\n");
+}
+
+#[test]
+fn diff_targets() {
+    let old = mock_manifest("1.0.0", &[(LINUX, "myapp", &["libc.so.6"], 31)]);
+    let new = mock_manifest(
+        "1.1.0",
+        &[(LINUX, "myapp", &["libc.so.6"], 31), (MAC, "myapp", &[], 0)],
+    );
+    let diff = diff_manifests(&old, &new);
+    let release = &diff.releases[0];
+    assert_eq!(release.old_version, "1.0.0");
+    assert_eq!(release.new_version, "1.1.0");
+    assert_eq!(release.added_targets, vec![MAC.to_owned()]);
+    assert!(release.removed_targets.is_empty());
+    assert_eq!(release.added_artifacts, vec![format!("myapp-{MAC}.tar.xz")]);
+    assert!(release.targets.is_empty());
+}
+
+#[test]
+fn diff_binaries_and_linkage() {
+    let old = mock_manifest("1.0.0", &[(LINUX, "myapp", &["libc.so.6"], 31)]);
+    let new = mock_manifest(
+        "1.0.0",
+        &[(LINUX, "my-app", &["libc.so.6", "libssl.so.3"], 35)],
+    );
+    let diff = diff_manifests(&old, &new);
+    assert_eq!(
+        diff.releases[0].targets,
+        vec![TargetDiff {
+            target: LINUX.to_owned(),
+            added_binaries: vec![],
+            removed_binaries: vec![],
+            renamed_binaries: vec![("myapp".to_owned(), "my-app".to_owned())],
+            added_linkage: vec!["system: libssl.so.3".to_owned()],
+            removed_linkage: vec![],
+            min_glibc_version: Some((
+                Some(LibcVersion {
+                    major: 2,
+                    series: 31
+                }),
+                Some(LibcVersion {
+                    major: 2,
+                    series: 35
+                })
+            )),
+        }]
+    );
+    assert_eq!(
+        render_human(&diff),
+        format!(
+            "myapp 1.0.0
+  {LINUX}:
+    binary myapp -> my-app
+    + linkage system: libssl.so.3
+    min glibc 2.31 -> 2.35
+"
+        )
+    );
+}
@@ -0,0 +1,48 @@
+use axoasset::LocalAsset;
+use camino::Utf8PathBuf;
+
+use crate::build::maturin::{add_wheel_to_index, normalize_project_name};
+
+#[test]
+fn project_names_are_normalized() {
+    let names = [
+        ("my-app", "my-app"),
+        ("my_app", "my-app"),
+        ("My.App", "my-app"),
+        ("my__-.app", "my-app"),
+    ];
+    for (name, expected) in names {
+        assert_eq!(normalize_project_name(name), expected, "normalizing {name}");
+    }
+}
+
+#[test]
+fn wheels_are_added_to_index() {
+    let tmp = temp_dir::TempDir::new().unwrap();
+    let root = Utf8PathBuf::from_path_buf(tmp.path().to_owned()).unwrap();
+    let index_dir = root.join("python-index");
+    let wheels = [
+        "my_app-1.0.0-py3-none-manylinux_2_17_x86_64.whl",
+        "my_app-1.0.0-py3-none-win_amd64.whl",
+        "other.tool-0.1.0-py3-none-any.whl",
+    ];
+    for wheel in wheels {
+        let path = root.join(wheel);
+        LocalAsset::write_new_all("fake wheel", &path).unwrap();
+        add_wheel_to_index(&index_dir, &path).unwrap();
+    }
+
+    let simple = index_dir.join("simple");
+    let root_page = LocalAsset::load_string(simple.join("index.html")).unwrap();
+    assert!(root_page.contains(r#"<a href="my-app/">my-app</a>"#));
+    assert!(root_page.contains(r#"<a href="other-tool/">other-tool</a>"#));
+
+    let project_page = LocalAsset::load_string(simple.join("my-app/index.html")).unwrap();
+    // sha256 of "fake wheel"
+    let sha256 = "f3325b107cf9e8876482e9e84a04b9ebc95859cd6b352434c84c5b39ecf2ede4";
+    for wheel in &wheels[..2] {
+        assert!(simple.join("my-app").join(wheel).exists());
+        assert!(project_page.contains(&format!(r#"<a href="{wheel}#sha256={sha256}">{wheel}</a>"#)));
+    }
+    assert!(!project_page.contains("other"));
+}
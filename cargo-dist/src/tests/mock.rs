@@ -1,11 +1,15 @@
 //! Mock testing utils, mostly you want the `workspace_*` functions,
 //! but other functions/consts will help you assert the results
 
-use crate::{announce::ReleaseArtifacts, CargoInfo, Tools};
+use crate::{
+    announce::{select_tag, ReleaseArtifacts, TagMode, TagSettings},
+    config::{ArtifactMode, Config},
+    CargoInfo, DistGraphBuilder, Tools,
+};
 use axoproject::{
     AutoIncludes, PackageIdx, PackageInfo, WorkspaceGraph, WorkspaceInfo, WorkspaceStructure,
 };
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use serde_json::json;
 
 pub const REPO_URL: &str = "https://github.com/axodotdev/axolotlsay";
@@ -79,6 +83,8 @@ pub fn mock_tools() -> Tools {
         rustup: None,
         brew: None,
         git: None,
+        cargo_zigbuild: None,
+        cross: None,
         code_sign_tool: None,
     }
 }
@@ -112,8 +118,10 @@ pub fn mock_package(name: &str, ver: &str) -> PackageInfo {
         cargo_metadata_table: None,
         cargo_package_id: None,
         npm_scope: None,
+        python_build_backend: None,
         build_command: None,
         axoupdater_versions: Default::default(),
+        local_dependencies: vec![],
     }
 }
 
@@ -313,3 +321,106 @@ pub fn workspace_disjoint() -> WorkspaceGraph {
         pkg_test_bin2(),
     ])
 }
+
+/// Run git in a test repo, returning its (trimmed) stdout
+pub fn git(dir: &Utf8Path, args: &[&str]) -> String {
+    let output = std::process::Command::new("git")
+        .args(["-c", "user.name=test", "-c", "user.email=test@example.com"])
+        .args(args)
+        .current_dir(dir)
+        .output()
+        .unwrap();
+    assert!(output.status.success(), "git {args:?} failed");
+    String::from_utf8(output.stdout).unwrap().trim().to_owned()
+}
+
+/// Make an empty commit in a test repo and return its short sha
+pub fn commit(dir: &Utf8Path, message: &str) -> String {
+    git(dir, &["commit", "--allow-empty", "-q", "-m", message]);
+    git(dir, &["rev-parse", "--short=7", "HEAD"])
+}
+
+/// Plan the releases of a workspace for the given targets
+///
+/// This is everything up to computing the build steps, so tests can tweak
+/// the config before doing that.
+pub fn plan_releases<'a>(
+    workspaces: &'a mut WorkspaceGraph,
+    targets: &[&str],
+) -> DistGraphBuilder<'a> {
+    let mut graph = DistGraphBuilder::new(
+        "a".to_owned(),
+        mock_tools(),
+        workspaces,
+        ArtifactMode::All,
+        true,
+        false,
+    )
+    .unwrap();
+    let tag_settings = TagSettings {
+        needs_coherence: true,
+        tag: TagMode::Select(format!("v{BIN_AXO_VER}")),
+    };
+    let announcing = select_tag(&mut graph, &tag_settings).unwrap();
+    let targets = targets.iter().map(|&t| t.to_owned()).collect::<Vec<_>>();
+    let cfg = Config {
+        tag_settings,
+        create_hosting: false,
+        artifact_mode: ArtifactMode::All,
+        no_local_paths: false,
+        allow_all_dirty: true,
+        targets: targets.clone(),
+        ci: vec![],
+        installers: vec![],
+        root_cmd: "plan".to_owned(),
+    };
+    graph
+        .compute_releases(&cfg, &announcing, &targets, true)
+        .unwrap();
+    graph
+}
+
+/// Write a fake cargo into `dir` that pretends to build axolotlsay
+///
+/// Each run appends its arguments and RUSTFLAGS to `cargo.log` in `dir`, as
+/// `<args> | <rustflags>`. Returns the path to run it with.
+#[cfg(unix)]
+pub fn mock_cargo(dir: &Utf8Path) -> Utf8PathBuf {
+    use std::os::unix::fs::PermissionsExt;
+
+    let bin = dir.join(BIN_AXO_NAME);
+    let message = json!({
+        "reason": "compiler-artifact",
+        "package_id": "",
+        "manifest_path": package_manifest(BIN_AXO_NAME),
+        "target": {
+            "name": BIN_AXO_NAME,
+            "kind": ["bin"],
+            "crate_types": ["bin"],
+            "required-features": [],
+            "src_path": package_dir(BIN_AXO_NAME).join("src/main.rs"),
+            "edition": "2021",
+            "doctest": false,
+            "test": true,
+            "doc": true,
+        },
+        "profile": {
+            "opt_level": "3",
+            "debuginfo": 0,
+            "debug_assertions": false,
+            "overflow_checks": false,
+            "test": false,
+        },
+        "features": [],
+        "filenames": [bin],
+        "executable": bin,
+        "fresh": false,
+    });
+    let script = format!(
+        "#!/bin/sh\necho \"$* | $RUSTFLAGS\" >> '{dir}/cargo.log'\ntouch '{bin}'\necho '{message}'\n"
+    );
+    let path = dir.join("cargo");
+    std::fs::write(&path, script).unwrap();
+    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).unwrap();
+    path
+}
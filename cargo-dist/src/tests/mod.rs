@@ -1,4 +1,20 @@
+mod archive;
+mod auditable;
+mod changed;
+mod changelog;
+mod cmake;
 mod config;
+mod generic;
+mod go;
 mod host;
+mod licenses;
+mod lipo;
+mod manifest_diff;
+mod maturin;
 mod mock;
+mod pgo;
+mod release;
+mod sbom;
+mod symbols;
 mod tag;
+mod verify;
@@ -0,0 +1,134 @@
+use super::mock::*;
+#[cfg(unix)]
+use camino::Utf8Path;
+use camino::Utf8PathBuf;
+
+use crate::{
+    build::pgo::{profile_generate_rustflags, profile_use_rustflags},
+    config::PgoConfig,
+    BuildStep, CargoBuildStep, DistError, DistGraphBuilder, DistResult,
+};
+
+const HOST: &str = "x86_64-unknown-linux-gnu";
+const OTHER: &str = "aarch64-unknown-linux-gnu";
+
+/// Compute the cargo builds for a planned graph, with the given pgo training command
+fn cargo_builds(
+    graph: &mut DistGraphBuilder,
+    training_command: &[&str],
+) -> DistResult<Vec<CargoBuildStep>> {
+    graph.inner.config.builds.cargo.pgo = Some(PgoConfig {
+        training_command: training_command.iter().map(|&s| s.to_owned()).collect(),
+    });
+    let root = graph.workspaces.root_workspace_idx();
+    let builds = graph.compute_cargo_builds(root)?;
+    Ok(builds
+        .into_iter()
+        .filter_map(|step| match step {
+            BuildStep::Cargo(step) => Some(step),
+            _ => None,
+        })
+        .collect())
+}
+
+/// Run a cargo build against the mock cargo in `dir`, returning the cargo.log lines
+#[cfg(unix)]
+fn build_in(
+    graph: &mut DistGraphBuilder,
+    mut step: CargoBuildStep,
+    dir: &Utf8Path,
+) -> (DistResult<()>, Vec<String>) {
+    graph.inner.tools.cargo.cmd = mock_cargo(dir).into_string();
+    graph.inner.dist_dir = dir.to_owned();
+    // The planned homes for the binaries are in the mock workspace, so leave them be
+    for binary in &mut graph.inner.binaries {
+        binary.copy_exe_to.clear();
+        binary.copy_symbols_to.clear();
+        binary.copy_symbols_to_server.clear();
+    }
+    step.working_dir = dir.to_owned();
+    if let Some(pgo) = &mut step.pgo {
+        pgo.profile_dir = dir.join("profiles");
+    }
+    let mut manifest = graph.manifest.clone();
+    let result = crate::build::cargo::build_cargo_target(&graph.inner, &mut manifest, &step);
+    let log = std::fs::read_to_string(dir.join("cargo.log")).unwrap_or_default();
+    (result, log.lines().map(ToOwned::to_owned).collect())
+}
+
+#[test]
+fn pgo_empty_training_command() {
+    let mut workspaces = workspace_just_axo();
+    let mut graph = plan_releases(&mut workspaces, &[HOST]);
+    let builds = cargo_builds(&mut graph, &[]);
+    assert!(matches!(builds, Err(DistError::PgoEmptyTrainingCommand {})));
+}
+
+#[test]
+fn pgo_planned_for_every_target() {
+    let mut workspaces = workspace_just_axo();
+    let mut graph = plan_releases(&mut workspaces, &[HOST, OTHER]);
+    let builds = cargo_builds(&mut graph, &["./train.sh"]).unwrap();
+    assert_eq!(builds.len(), 2);
+    for step in builds {
+        let pgo = step.pgo.unwrap();
+        assert_eq!(pgo.training_command, ["./train.sh"]);
+        assert!(pgo.profile_dir.ends_with(&step.target_triple));
+    }
+}
+
+#[test]
+fn pgo_rustflags_order() {
+    let profile_dir = Utf8PathBuf::from("target/pgo-profiles").join(HOST);
+    let generate = profile_generate_rustflags("-Cfoo", &profile_dir);
+    assert_eq!(generate, format!("-Cfoo -Cprofile-generate={profile_dir}"));
+
+    let merged = profile_dir.join("merged.profdata");
+    let used = profile_use_rustflags("-Cfoo", &merged);
+    assert_eq!(used, format!("-Cfoo -Cprofile-use={merged}"));
+    assert!(!used.contains("profile-generate"), "{used}");
+}
+
+#[test]
+#[cfg(unix)]
+fn pgo_training_without_profiles() {
+    let tempdir = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tempdir.path()).unwrap();
+    let mut workspaces = workspace_just_axo();
+    let mut graph = plan_releases(&mut workspaces, &[HOST]);
+    // "Trains" the binary without it writing any profiles
+    let training = ["sh", "-c", "test -f \"$CARGO_DIST_BINARY\""];
+    let step = cargo_builds(&mut graph, &training).unwrap().remove(0);
+
+    let (result, log) = build_in(&mut graph, step, dir);
+    assert!(matches!(
+        result,
+        Err(DistError::PgoNoProfiles { profile_dir }) if profile_dir == dir.join("profiles")
+    ));
+    // Only the instrumented build ran
+    assert_eq!(log.len(), 1, "{log:?}");
+    let generate = format!("-Cprofile-generate={}", dir.join("profiles"));
+    assert!(log[0].ends_with(&generate), "{log:?}");
+    assert!(dir
+        .join(format!("pgo-train-{BIN_AXO_NAME}-{HOST}.log"))
+        .exists());
+}
+
+#[test]
+#[cfg(unix)]
+fn pgo_skipped_for_other_targets() {
+    let tempdir = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tempdir.path()).unwrap();
+    let mut workspaces = workspace_just_axo();
+    let mut graph = plan_releases(&mut workspaces, &[OTHER]);
+    let training = ["sh", "-c", "echo training can't run here; exit 1"];
+    let step = cargo_builds(&mut graph, &training).unwrap().remove(0);
+    assert_eq!(step.target_triple, OTHER);
+
+    let (result, log) = build_in(&mut graph, step, dir);
+    result.unwrap();
+    // Just the one normal build, without any profile flags
+    assert_eq!(log.len(), 1, "{log:?}");
+    assert!(!log[0].contains("-Cprofile"), "{log:?}");
+    assert!(!dir.join("profiles").exists());
+}
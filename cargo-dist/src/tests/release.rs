@@ -0,0 +1,248 @@
+//! Tests for `cargo dist release`
+
+use super::mock::*;
+use axoasset::{toml_edit, LocalAsset};
+use axoproject::{local_repo::LocalRepo, WorkspaceGraph};
+use camino::Utf8Path;
+use semver::Version;
+
+use crate::announce::{TagMode, TagSettings};
+use crate::config::{ArtifactMode, Config, ReleaseArgs, VersionBump};
+use crate::release::{
+    release_project, release_tag, set_dependency_versions, set_json_version, set_toml_version,
+};
+use crate::{DistError, DistResult, SortedSet, Tool};
+
+fn bump(bump: &str, version: &str) -> String {
+    let bump: VersionBump = bump.parse().unwrap();
+    let version: Version = version.parse().unwrap();
+    bump.apply(&version).to_string()
+}
+
+#[test]
+fn version_bumps() {
+    assert_eq!(bump("major", "1.2.3"), "2.0.0");
+    assert_eq!(bump("minor", "1.2.3"), "1.3.0");
+    assert_eq!(bump("patch", "1.2.3"), "1.2.4");
+    assert_eq!(bump("2.0.0-rc.1", "1.2.3"), "2.0.0-rc.1");
+    assert_eq!(bump("v2.0.0", "1.2.3"), "2.0.0");
+
+    // Prereleases are bumped to the release they were leading up to
+    assert_eq!(bump("major", "2.0.0-rc.1"), "2.0.0");
+    assert_eq!(bump("major", "2.1.0-rc.1"), "3.0.0");
+    assert_eq!(bump("minor", "1.3.0-alpha.2"), "1.3.0");
+    assert_eq!(bump("minor", "1.3.1-alpha.2"), "1.4.0");
+    assert_eq!(bump("patch", "1.3.1-alpha.2"), "1.3.1");
+
+    assert!("huge".parse::<VersionBump>().is_err());
+}
+
+#[test]
+fn release_tags() {
+    let version = Version::new(1, 2, 3);
+    assert_eq!(release_tag(None, &version, None), "v1.2.3");
+    assert_eq!(release_tag(Some("my-app"), &version, None), "my-app-v1.2.3");
+    assert_eq!(
+        release_tag(Some("my-app"), &version, Some("owo")),
+        "owo/my-app-v1.2.3"
+    );
+}
+
+#[test]
+fn toml_versions() {
+    let mut toml: toml_edit::DocumentMut = r#"
+[package]
+name = "app"
+version = "1.0.0" # keep me
+
+[dependencies]
+lib = { path = "../lib", version = "=1.0.0" }
+renamed = { package = "lib2", path = "../lib2", version = "1.0" }
+serde = "1.0.0"
+other = { path = "../other", version = "1.0.0" }
+
+[target.'cfg(unix)'.dev-dependencies.lib]
+path = "../lib"
+version = "1.0.0"
+"#
+    .parse()
+    .unwrap();
+
+    assert!(set_toml_version(&mut toml, &["package"], "1.1.0"));
+    assert!(!set_toml_version(
+        &mut toml,
+        &["workspace", "package"],
+        "1.1.0"
+    ));
+    let crates = SortedSet::from(["lib".to_owned(), "lib2".to_owned(), "serde".to_owned()]);
+    assert!(set_dependency_versions(&mut toml, &crates, "1.1.0"));
+
+    assert_eq!(
+        toml.to_string(),
+        r#"
+[package]
+name = "app"
+version = "1.1.0" # keep me
+
+[dependencies]
+lib = { path = "../lib", version = "=1.1.0" }
+renamed = { package = "lib2", path = "../lib2", version = "1.1.0" }
+serde = "1.0.0"
+other = { path = "../other", version = "1.0.0" }
+
+[target.'cfg(unix)'.dev-dependencies.lib]
+path = "../lib"
+version = "1.1.0"
+"#
+    );
+
+    // Inherited versions are left for the workspace to bump
+    let mut toml: toml_edit::DocumentMut = "[package]\nversion.workspace = true\n".parse().unwrap();
+    assert!(!set_toml_version(&mut toml, &["package"], "1.1.0"));
+}
+
+#[test]
+fn json_versions() {
+    let json = r#"{
+  "name": "app",
+  "description": "has a \"version\": \"0.1.0\" in it",
+  "dependencies": {
+    "version": "^2.0.0"
+  },
+  "version": "1.0.0",
+  "scripts": {}
+}
+"#;
+    assert_eq!(
+        set_json_version(json, "1.1.0").unwrap(),
+        json.replace(r#""version": "1.0.0""#, r#""version": "1.1.0""#)
+    );
+    assert_eq!(set_json_version(r#"{ "name": "app" }"#, "1.1.0"), None);
+}
+
+/// Set up a git repo with a one-package cargo workspace in it, at version 0.1.0
+fn release_repo(dir: &Utf8Path) {
+    git(dir, &["init", "-q"]);
+    // Releasing commits and tags with whatever identity git has
+    git(dir, &["config", "user.name", "test"]);
+    git(dir, &["config", "user.email", "test@example.com"]);
+    let manifest = r#"[package]
+name = "app"
+version = "0.1.0" # keep me
+edition = "2021"
+repository = "https://github.com/axodotdev/app"
+"#;
+    LocalAsset::write_new_all(manifest, dir.join("Cargo.toml")).unwrap();
+    LocalAsset::write_new_all("fn main() {}\n", dir.join("src/main.rs")).unwrap();
+    let changelog = "# Changelog\n\n## Unreleased\n\n- did a thing\n\n## 0.1.0\n\n- first\n";
+    LocalAsset::write_new_all(changelog, dir.join("CHANGELOG.md")).unwrap();
+    // Loading the project makes cargo write a Cargo.lock, which should be committed too
+    load_release_repo(dir);
+    git(dir, &["add", "."]);
+    commit(dir, "initial commit");
+}
+
+fn load_release_repo(dir: &Utf8Path) -> WorkspaceGraph {
+    let repo = LocalRepo::new("git", dir).unwrap();
+    WorkspaceGraph::find_from_git(dir, Some(repo)).unwrap()
+}
+
+/// Run `cargo dist release` in the repo
+fn release_in(dir: &Utf8Path, bump: &str) -> DistResult<()> {
+    let mut workspaces = load_release_repo(dir);
+    let mut tools = mock_tools();
+    tools.cargo.cmd = std::env::var("CARGO").unwrap_or_else(|_| "cargo".to_owned());
+    tools.git = Some(Tool {
+        cmd: "git".to_owned(),
+        version: String::new(),
+    });
+    let cfg = Config {
+        tag_settings: TagSettings {
+            needs_coherence: false,
+            tag: TagMode::Infer,
+        },
+        create_hosting: false,
+        artifact_mode: ArtifactMode::All,
+        no_local_paths: false,
+        allow_all_dirty: true,
+        targets: vec![],
+        ci: vec![],
+        installers: vec![],
+        root_cmd: "release".to_owned(),
+    };
+    let args = ReleaseArgs {
+        bump: bump.parse().unwrap(),
+        package: None,
+        dry_run: false,
+    };
+    release_project(&cfg, &args, tools, &mut workspaces)
+}
+
+#[test]
+fn release_commits_and_tags() {
+    let tmp = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tmp.path()).unwrap();
+    release_repo(dir);
+    let read = |path: &str| std::fs::read_to_string(dir.join(path)).unwrap();
+
+    // Uncommitted changes could end up in the release commit, so they're refused
+    LocalAsset::write_new_all("wip", dir.join("notes.txt")).unwrap();
+    let result = release_in(dir, "minor");
+    assert!(
+        matches!(&result, Err(DistError::ReleaseDirtyRepo { status }) if status.contains("notes.txt")),
+        "{result:?}"
+    );
+    assert!(read("Cargo.toml").contains(r#"version = "0.1.0""#));
+    LocalAsset::remove_file(dir.join("notes.txt")).unwrap();
+
+    let result = release_in(dir, "minor");
+    assert!(result.is_ok(), "{result:?}");
+    assert!(
+        read("Cargo.toml").contains(r#"version = "0.2.0" # keep me"#),
+        "{}",
+        read("Cargo.toml")
+    );
+    let changelog = read("CHANGELOG.md");
+    assert!(
+        changelog.contains("## 0.2.0\n\n- did a thing"),
+        "{changelog}"
+    );
+    assert!(!changelog.contains("Unreleased"), "{changelog}");
+    assert!(
+        read("Cargo.lock").contains("name = \"app\"\nversion = \"0.2.0\""),
+        "{}",
+        read("Cargo.lock")
+    );
+
+    // Everything it changed is committed, under an annotated tag
+    assert_eq!(git(dir, &["status", "--porcelain"]), "");
+    assert_eq!(
+        git(dir, &["log", "-1", "--format=%s"]),
+        "chore: release v0.2.0"
+    );
+    let changed = git(dir, &["show", "--name-only", "--format=", "HEAD"]);
+    assert_eq!(changed, "CHANGELOG.md\nCargo.lock\nCargo.toml");
+    assert_eq!(git(dir, &["cat-file", "-t", "v0.2.0"]), "tag");
+    assert_eq!(
+        git(dir, &["rev-parse", "v0.2.0^{commit}"]),
+        git(dir, &["rev-parse", "HEAD"])
+    );
+}
+
+#[test]
+fn release_tag_exists() {
+    let tmp = temp_dir::TempDir::new().unwrap();
+    let dir = Utf8Path::from_path(tmp.path()).unwrap();
+    release_repo(dir);
+    git(dir, &["tag", "v0.2.0"]);
+    let head = git(dir, &["rev-parse", "HEAD"]);
+
+    let result = release_in(dir, "minor");
+    assert!(
+        matches!(&result, Err(DistError::ReleaseTagExists { tag }) if tag == "v0.2.0"),
+        "{result:?}"
+    );
+    // Nothing was touched
+    assert_eq!(git(dir, &["status", "--porcelain"]), "");
+    assert_eq!(git(dir, &["rev-parse", "HEAD"]), head);
+}
@@ -0,0 +1,159 @@
+use crate::sbom::{cyclonedx, spdx, spdx_license, Sbom, SbomComponent};
+use cargo_dist_schema::{Library, PackageManager};
+
+fn crate_component(name: &str, version: &str, depends_on: &[&str]) -> SbomComponent {
+    SbomComponent {
+        name: name.to_owned(),
+        version: version.to_owned(),
+        description: None,
+        license: Some("MIT OR Apache-2.0".to_owned()),
+        checksum: Some(format!("{name}-checksum")),
+        download_url: Some(format!(
+            "https://crates.io/api/v1/crates/{name}/{version}/download"
+        )),
+        purl: format!("pkg:cargo/{name}@{version}"),
+        depends_on: depends_on.iter().map(|d| d.to_string()).collect(),
+    }
+}
+
+fn sbom() -> Sbom {
+    Sbom {
+        name: "axolotlsay-x86_64-unknown-linux-gnu".to_owned(),
+        target: "x86_64-unknown-linux-gnu".to_owned(),
+        timestamp: 1_700_000_000,
+        app: SbomComponent {
+            description: Some("💬 a CLI for learning to distribute CLIs in rust".to_owned()),
+            checksum: None,
+            download_url: None,
+            license: Some("MIT".to_owned()),
+            ..crate_component(
+                "axolotlsay",
+                "0.1.0",
+                &["pkg:cargo/clap@4.0.0", "pkg:cargo/miette@5.0.0"],
+            )
+        },
+        crates: vec![
+            crate_component("clap", "4.0.0", &["pkg:cargo/miette@5.0.0"]),
+            crate_component("miette", "5.0.0", &[]),
+        ],
+        libraries: vec![Library {
+            path: "/lib/x86_64-linux-gnu/libc.so.6".to_owned(),
+            source: Some("libc6".to_owned()),
+            package_manager: Some(PackageManager::Apt),
+        }],
+    }
+}
+
+#[test]
+fn cyclonedx_sbom() {
+    let bom = cyclonedx(&sbom());
+    assert_eq!(bom["bomFormat"], "CycloneDX");
+    assert_eq!(bom["specVersion"], "1.5");
+    assert_eq!(bom["metadata"]["timestamp"], "2023-11-14T22:13:20Z");
+    assert_eq!(
+        bom["metadata"]["component"]["bom-ref"],
+        "pkg:cargo/axolotlsay@0.1.0"
+    );
+    assert_eq!(
+        bom["metadata"]["component"]["licenses"][0]["expression"],
+        "MIT"
+    );
+
+    let components = bom["components"].as_array().unwrap();
+    assert_eq!(components.len(), 3);
+    assert_eq!(components[0]["purl"], "pkg:cargo/clap@4.0.0");
+    assert_eq!(components[0]["hashes"][0]["alg"], "SHA-256");
+    assert_eq!(components[0]["hashes"][0]["content"], "clap-checksum");
+    assert_eq!(components[2]["name"], "libc.so.6");
+    assert_eq!(
+        components[2]["bom-ref"],
+        "library:/lib/x86_64-linux-gnu/libc.so.6"
+    );
+
+    let dependencies = bom["dependencies"].as_array().unwrap();
+    assert_eq!(dependencies[0]["ref"], "pkg:cargo/axolotlsay@0.1.0");
+    assert_eq!(
+        dependencies[0]["dependsOn"],
+        serde_json::json!([
+            "pkg:cargo/clap@4.0.0",
+            "pkg:cargo/miette@5.0.0",
+            "library:/lib/x86_64-linux-gnu/libc.so.6"
+        ])
+    );
+    assert_eq!(dependencies[1]["ref"], "pkg:cargo/clap@4.0.0");
+    assert_eq!(
+        dependencies[1]["dependsOn"],
+        serde_json::json!(["pkg:cargo/miette@5.0.0"])
+    );
+}
+
+#[test]
+fn spdx_sbom() {
+    let mut sbom = sbom();
+    // Dependencies that aren't listed as packages get no relationship
+    sbom.crates[1]
+        .depends_on
+        .push("pkg:cargo/local-helper@0.1.0".to_owned());
+    let doc = spdx(&sbom);
+    assert_eq!(doc["spdxVersion"], "SPDX-2.3");
+    assert_eq!(doc["creationInfo"]["created"], "2023-11-14T22:13:20Z");
+    assert_eq!(
+        doc["documentNamespace"],
+        "https://spdx.org/spdxdocs/axolotlsay-x86_64-unknown-linux-gnu"
+    );
+
+    let packages = doc["packages"].as_array().unwrap();
+    let ids = packages
+        .iter()
+        .map(|p| p["SPDXID"].as_str().unwrap())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        ids,
+        vec![
+            "SPDXRef-Package-axolotlsay-0.1.0",
+            "SPDXRef-Package-clap-4.0.0",
+            "SPDXRef-Package-miette-5.0.0",
+            "SPDXRef-Library--lib-x86-64-linux-gnu-libc.so.6",
+        ]
+    );
+    assert_eq!(packages[0]["primaryPackagePurpose"], "APPLICATION");
+    assert_eq!(packages[0]["licenseDeclared"], "MIT");
+    assert_eq!(
+        packages[1]["checksums"][0]["checksumValue"],
+        "clap-checksum"
+    );
+
+    let relationships = doc["relationships"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .map(|r| {
+            format!(
+                "{} {} {}",
+                r["spdxElementId"].as_str().unwrap(),
+                r["relationshipType"].as_str().unwrap(),
+                r["relatedSpdxElement"].as_str().unwrap()
+            )
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        relationships,
+        vec![
+            "SPDXRef-DOCUMENT DESCRIBES SPDXRef-Package-axolotlsay-0.1.0",
+            "SPDXRef-Package-clap-4.0.0 DEPENDS_ON SPDXRef-Package-miette-5.0.0",
+            "SPDXRef-Package-axolotlsay-0.1.0 DEPENDS_ON SPDXRef-Package-clap-4.0.0",
+            "SPDXRef-Package-axolotlsay-0.1.0 DEPENDS_ON SPDXRef-Package-miette-5.0.0",
+            "SPDXRef-Package-axolotlsay-0.1.0 DYNAMIC_LINK SPDXRef-Library--lib-x86-64-linux-gnu-libc.so.6",
+        ]
+    );
+}
+
+#[test]
+fn licenses_are_normalized() {
+    assert_eq!(spdx_license("MIT").as_deref(), Some("MIT"));
+    assert_eq!(
+        spdx_license("MIT/Apache-2.0").as_deref(),
+        Some("MIT OR Apache-2.0")
+    );
+    assert_eq!(spdx_license("the license of my heart"), None);
+}
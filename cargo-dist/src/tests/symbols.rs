@@ -0,0 +1,202 @@
+use super::mock::*;
+use axoproject::rust::{CargoProfile, CargoProfiles};
+use camino::Utf8Path;
+use cargo_dist_schema::{BuildId, BuildIdKind};
+
+use crate::{
+    announce::{select_tag, TagMode, TagSettings},
+    build::{symbols::symbol_server_path, symbols_are_for_binary},
+    config::{ArtifactMode, Config},
+    tasks::resolve_cargo_profile,
+    ArtifactKind, DistGraphBuilder, SymbolKind,
+};
+
+/// Plan axolotlsay for a few targets with the given `dist` profile and
+/// return the symbol artifacts that got planned, as (target, kind)
+fn planned_symbols(debug: Option<i64>, split_debuginfo: Option<&str>) -> Vec<(String, SymbolKind)> {
+    let mut workspaces = workspace_just_axo();
+    let root = workspaces.root_workspace_idx();
+    workspaces.workspace_mut(root).cargo_profiles.insert(
+        "dist".to_owned(),
+        CargoProfile {
+            inherits: Some("release".to_owned()),
+            debug,
+            split_debuginfo: split_debuginfo.map(ToOwned::to_owned),
+        },
+    );
+
+    let mut graph = DistGraphBuilder::new(
+        "a".to_owned(),
+        mock_tools(),
+        &mut workspaces,
+        ArtifactMode::All,
+        true,
+        false,
+    )
+    .unwrap();
+    let tag_settings = TagSettings {
+        needs_coherence: true,
+        tag: TagMode::Select(format!("v{BIN_AXO_VER}")),
+    };
+    let announcing = select_tag(&mut graph, &tag_settings).unwrap();
+    let triples = [
+        "x86_64-unknown-linux-gnu".to_owned(),
+        "aarch64-apple-darwin".to_owned(),
+        "x86_64-pc-windows-msvc".to_owned(),
+    ];
+    let cfg = Config {
+        tag_settings,
+        create_hosting: false,
+        artifact_mode: ArtifactMode::All,
+        no_local_paths: false,
+        allow_all_dirty: true,
+        targets: triples.to_vec(),
+        ci: vec![],
+        installers: vec![],
+        root_cmd: "plan".to_owned(),
+    };
+    graph
+        .compute_releases(&cfg, &announcing, &triples, true)
+        .unwrap();
+
+    let mut symbols = graph
+        .inner
+        .artifacts
+        .iter()
+        .filter_map(|artifact| match &artifact.kind {
+            ArtifactKind::Symbols(symbols) => {
+                Some((artifact.target_triples[0].clone(), symbols.kind))
+            }
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    symbols.sort_by(|a, b| a.0.cmp(&b.0));
+    symbols
+}
+
+#[test]
+fn symbols_pair_with_binaries() {
+    let pairs = [
+        ("target/dist/my-app.exe", "target/dist/my_app.pdb", true),
+        ("target/dist/my-app", "target/dist/my-app.dSYM", true),
+        (
+            "target/dist/libmy_lib.dylib",
+            "target/dist/libmy_lib.dylib.dSYM",
+            true,
+        ),
+        ("target/dist/my-app", "target/dist/my_app.dwp", true),
+        (
+            "target/dist/libmy_lib.so",
+            "target/dist/libmy_lib.so.debug",
+            true,
+        ),
+        (
+            "target/dist/my-app.exe",
+            "target/dist/my_app_helper.pdb",
+            false,
+        ),
+        ("target/dist/my-app", "target/dist/other.dwp", false),
+    ];
+    for (bin, sym, expected) in pairs {
+        assert_eq!(
+            symbols_are_for_binary(Utf8Path::new(bin), Utf8Path::new(sym)),
+            expected,
+            "{bin} {sym}"
+        );
+    }
+}
+
+#[test]
+fn cargo_profiles_resolve_through_inherits() {
+    let profile = |inherits: Option<&str>, debug: Option<i64>, split: Option<&str>| CargoProfile {
+        inherits: inherits.map(ToOwned::to_owned),
+        debug,
+        split_debuginfo: split.map(ToOwned::to_owned),
+    };
+    let mut profiles = CargoProfiles::new();
+    profiles.insert("release".to_owned(), profile(None, None, Some("packed")));
+    profiles.insert("dist".to_owned(), profile(Some("release"), Some(1), None));
+    profiles.insert(
+        "dist-fast".to_owned(),
+        profile(Some("dist"), Some(0), Some("off")),
+    );
+    profiles.insert("loop-a".to_owned(), profile(Some("loop-b"), None, None));
+    profiles.insert("loop-b".to_owned(), profile(Some("loop-a"), None, None));
+
+    assert_eq!(
+        resolve_cargo_profile(&profiles, "dist"),
+        (1, Some("packed".to_owned()))
+    );
+    assert_eq!(
+        resolve_cargo_profile(&profiles, "dist-fast"),
+        (0, Some("off".to_owned()))
+    );
+    assert_eq!(
+        resolve_cargo_profile(&profiles, "release"),
+        (0, Some("packed".to_owned()))
+    );
+    // Unlisted builtin profiles get cargo's defaults
+    assert_eq!(resolve_cargo_profile(&profiles, "dev"), (2, None));
+    // Broken configs don't hang
+    assert_eq!(resolve_cargo_profile(&profiles, "loop-a"), (0, None));
+}
+
+#[test]
+fn split_symbols_need_packed_debuginfo() {
+    // msvc always makes a pdb, but nothing else gets symbols by default
+    let pdb_only = vec![("x86_64-pc-windows-msvc".to_owned(), SymbolKind::Pdb)];
+    assert_eq!(planned_symbols(None, None), pdb_only);
+    assert_eq!(planned_symbols(Some(2), None), pdb_only);
+    assert_eq!(planned_symbols(Some(2), Some("unpacked")), pdb_only);
+    assert_eq!(planned_symbols(Some(0), Some("packed")), pdb_only);
+
+    // Only debuginfo that's packed into its own file gets shipped
+    assert_eq!(
+        planned_symbols(Some(1), Some("packed")),
+        vec![
+            ("aarch64-apple-darwin".to_owned(), SymbolKind::Dsym),
+            ("x86_64-pc-windows-msvc".to_owned(), SymbolKind::Pdb),
+            ("x86_64-unknown-linux-gnu".to_owned(), SymbolKind::Dwp),
+        ]
+    );
+}
+
+#[test]
+fn symbol_server_layouts() {
+    let gnu = BuildId {
+        kind: BuildIdKind::GnuBuildId,
+        id: "2e2db1d961b34964a60d8512ec57e5da5fee0843".to_owned(),
+        debug_file: None,
+    };
+    assert_eq!(
+        symbol_server_path(&gnu).unwrap(),
+        "2e/2db1d961b34964a60d8512ec57e5da5fee0843.debug"
+    );
+
+    let macho = BuildId {
+        kind: BuildIdKind::MachoUuid,
+        id: "ae28beecef8ad7e56366050c92b18fb3".to_owned(),
+        debug_file: None,
+    };
+    assert_eq!(
+        symbol_server_path(&macho).unwrap(),
+        "ae/28beecef8ad7e56366050c92b18fb3.debug"
+    );
+
+    let pdb = BuildId {
+        kind: BuildIdKind::Pdb,
+        id: "B3B5214180F19371994CF89643F458231".to_owned(),
+        debug_file: Some("my_app.pdb".to_owned()),
+    };
+    assert_eq!(
+        symbol_server_path(&pdb).unwrap(),
+        "my_app.pdb/B3B5214180F19371994CF89643F458231/my_app.pdb"
+    );
+
+    // SymStore can't do anything without the pdb's name
+    let nameless_pdb = BuildId {
+        debug_file: None,
+        ..pdb
+    };
+    assert_eq!(symbol_server_path(&nameless_pdb), None);
+}
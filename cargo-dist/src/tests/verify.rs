@@ -0,0 +1,182 @@
+use crate::config::ChecksumStyle;
+use crate::generate_checksum;
+use crate::verify::{verify_release, ReleaseSource};
+use crate::DistError;
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use temp_dir::TempDir;
+
+const ARCHIVE: &str = "myapp-x86_64-unknown-linux-gnu.tar.gz";
+
+/// Make a little release with one archive and its checksum
+fn mock_release(root: &Utf8Path, asset_path: &str) -> Utf8PathBuf {
+    let stage = root.join("stage");
+    let release = root.join("release");
+    LocalAsset::write_new_all("#!/bin/sh\necho hi\n", stage.join("myapp")).unwrap();
+    LocalAsset::create_dir_all(&release).unwrap();
+
+    let archive = release.join(ARCHIVE);
+    LocalAsset::tar_gz_dir(&stage, &archive, Some("myapp-x86_64-unknown-linux-gnu")).unwrap();
+    let sha256 = generate_checksum(&ChecksumStyle::Sha256, &archive).unwrap();
+    LocalAsset::write_new(
+        &format!("{sha256} *{ARCHIVE}\n"),
+        release.join(format!("{ARCHIVE}.sha256")),
+    )
+    .unwrap();
+
+    let manifest = serde_json::json!({
+        "announcement_tag": "v1.0.0",
+        "releases": [{
+            "app_name": "myapp",
+            "app_version": "1.0.0",
+            "artifacts": [ARCHIVE, format!("{ARCHIVE}.sha256")],
+        }],
+        "artifacts": {
+            ARCHIVE: {
+                "name": ARCHIVE,
+                "kind": "executable-zip",
+                "target_triples": ["x86_64-unknown-linux-gnu"],
+                "assets": [{
+                    "name": "myapp",
+                    "path": asset_path,
+                    "kind": "executable",
+                }],
+                "checksum": format!("{ARCHIVE}.sha256"),
+                "checksums": { "sha256": sha256 },
+            },
+            format!("{ARCHIVE}.sha256"): {
+                "name": format!("{ARCHIVE}.sha256"),
+                "kind": "checksum",
+            },
+        },
+    });
+    LocalAsset::write_new(&manifest.to_string(), release.join("dist-manifest.json")).unwrap();
+
+    release
+}
+
+fn problems(result: crate::DistResult<()>) -> Vec<String> {
+    match result {
+        Err(DistError::VerifyFailed { problems }) => problems,
+        other => panic!("expected verification to fail, got {other:?}"),
+    }
+}
+
+#[test]
+fn parse_sources() {
+    assert_eq!(
+        ReleaseSource::parse("https://example.com/releases/download/v1.0.0/dist-manifest.json"),
+        (
+            ReleaseSource::Url("https://example.com/releases/download/v1.0.0".to_owned()),
+            "dist-manifest.json".to_owned()
+        )
+    );
+    assert_eq!(
+        ReleaseSource::parse("https://example.com/releases/download/v1.0.0/"),
+        (
+            ReleaseSource::Url("https://example.com/releases/download/v1.0.0".to_owned()),
+            "dist-manifest.json".to_owned()
+        )
+    );
+    assert_eq!(
+        ReleaseSource::parse("file:///tmp/release/my-manifest.json"),
+        (
+            ReleaseSource::Dir("/tmp/release".into()),
+            "my-manifest.json".to_owned()
+        )
+    );
+    assert_eq!(
+        ReleaseSource::parse("target/distrib"),
+        (
+            ReleaseSource::Dir("target/distrib".into()),
+            "dist-manifest.json".to_owned()
+        )
+    );
+    assert_eq!(
+        ReleaseSource::parse("dist-manifest.json"),
+        (
+            ReleaseSource::Dir(".".into()),
+            "dist-manifest.json".to_owned()
+        )
+    );
+}
+
+#[test]
+fn verify_good_release() {
+    let tempdir = TempDir::new().unwrap();
+    let root = Utf8Path::from_path(tempdir.path()).unwrap();
+    let release = mock_release(root, "myapp");
+
+    let (source, manifest) = ReleaseSource::parse(&format!("file://{release}"));
+    verify_release(&source, &manifest, false).unwrap();
+}
+
+#[test]
+fn verify_missing_asset() {
+    let tempdir = TempDir::new().unwrap();
+    let root = Utf8Path::from_path(tempdir.path()).unwrap();
+    let release = mock_release(root, "myapp-but-renamed");
+
+    let result = verify_release(&ReleaseSource::Dir(release), "dist-manifest.json", false);
+    assert_eq!(
+        problems(result),
+        vec![format!("{ARCHIVE}: archive is missing myapp-but-renamed")]
+    );
+}
+
+#[test]
+fn verify_corrupted_archive() {
+    let tempdir = TempDir::new().unwrap();
+    let root = Utf8Path::from_path(tempdir.path()).unwrap();
+    let release = mock_release(root, "myapp");
+    LocalAsset::write_new("not a tarball", release.join(ARCHIVE)).unwrap();
+
+    let result = verify_release(&ReleaseSource::Dir(release), "dist-manifest.json", false);
+    let problems = problems(result);
+    assert!(problems
+        .iter()
+        .any(|p| p.starts_with(&format!("{ARCHIVE}: sha256 checksum is"))));
+    assert!(problems
+        .iter()
+        .any(|p| p.starts_with(&format!("{ARCHIVE}.sha256: says {ARCHIVE} is"))));
+}
+
+#[test]
+fn verify_missing_artifact() {
+    let tempdir = TempDir::new().unwrap();
+    let root = Utf8Path::from_path(tempdir.path()).unwrap();
+    let release = mock_release(root, "myapp");
+    std::fs::remove_file(release.join(format!("{ARCHIVE}.sha256"))).unwrap();
+
+    let result = verify_release(&ReleaseSource::Dir(release), "dist-manifest.json", false);
+    assert_eq!(
+        problems(result),
+        vec![format!("{ARCHIVE}.sha256: missing from the release")]
+    );
+}
+
+#[test]
+fn parse_tags() {
+    assert!(ReleaseSource::is_source(
+        "https://example.com/releases/download/v1.0.0"
+    ));
+    assert!(ReleaseSource::is_source("file:///tmp/release"));
+    assert!(ReleaseSource::is_source("my-manifest.json"));
+    assert!(ReleaseSource::is_source("."));
+    assert!(!ReleaseSource::is_source("v1.0.0"));
+    assert!(!ReleaseSource::is_source("my-app-v1.0.0"));
+}
+
+#[test]
+fn verify_bad_signature() {
+    let tempdir = TempDir::new().unwrap();
+    let root = Utf8Path::from_path(tempdir.path()).unwrap();
+    let release = mock_release(root, "myapp");
+    LocalAsset::write_new("not a signature", release.join(format!("{ARCHIVE}.sig"))).unwrap();
+
+    // Whether or not gpg is installed, this can't verify
+    let result = verify_release(&ReleaseSource::Dir(release), "dist-manifest.json", false);
+    let problems = problems(result);
+    assert_eq!(problems.len(), 1);
+    assert!(problems[0].starts_with(&format!("{ARCHIVE}: signature {ARCHIVE}.sig didn't verify")));
+}
@@ -0,0 +1,466 @@
+//! Checking published releases
+//!
+//! This is the machinery behind `cargo dist verify`, which downloads everything
+//! a dist-manifest.json says a release contains and checks that it's actually
+//! there and intact: checksums match, signatures verify, archives contain their
+//! assets, and installers actually install things.
+
+use axoasset::{LocalAsset, SourceFile};
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{Artifact, ArtifactKind, AssetKind, DistManifest};
+use temp_dir::TempDir;
+
+use crate::{
+    announce::{TagMode, TagSettings},
+    config::{ChecksumStyle, Config, VerifyArgs},
+    errors::{DistError, DistResult},
+    gather_work, generate_checksum,
+    net::{create_axoasset_client, fetch_file, ClientSettings},
+    SortedMap,
+};
+
+/// Where the files of a release can be found
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReleaseSource {
+    /// A local directory (possibly specified with a file:// URL)
+    Dir(Utf8PathBuf),
+    /// A URL that files can be downloaded from by appending their name
+    Url(String),
+}
+
+impl ReleaseSource {
+    /// Whether the input looks like a path or URL to a dist-manifest.json (or the directory
+    /// containing one), as opposed to a tag
+    pub fn is_source(input: &str) -> bool {
+        input.starts_with("http://")
+            || input.starts_with("https://")
+            || input.starts_with("file://")
+            || input.ends_with(".json")
+            || Utf8Path::new(input).exists()
+    }
+
+    /// Interpret a path or URL to a dist-manifest.json (or the directory containing one)
+    ///
+    /// Returns the source and the name of the manifest within it.
+    pub fn parse(input: &str) -> (Self, String) {
+        let default_manifest = "dist-manifest.json".to_owned();
+        if input.starts_with("http://") || input.starts_with("https://") {
+            return match input.rsplit_once('/') {
+                Some((base, name)) if name.ends_with(".json") => {
+                    (Self::Url(base.to_owned()), name.to_owned())
+                }
+                _ => (
+                    Self::Url(input.trim_end_matches('/').to_owned()),
+                    default_manifest,
+                ),
+            };
+        }
+
+        let path = Utf8Path::new(input.strip_prefix("file://").unwrap_or(input));
+        match (path.extension(), path.file_name()) {
+            (Some("json"), Some(name)) => {
+                let dir = path
+                    .parent()
+                    .filter(|dir| !dir.as_str().is_empty())
+                    .unwrap_or(Utf8Path::new("."));
+                (Self::Dir(dir.to_owned()), name.to_owned())
+            }
+            _ => (Self::Dir(path.to_owned()), default_manifest),
+        }
+    }
+
+    /// Get a copy of the named file on the local filesystem, or None if the release doesn't have it
+    ///
+    /// Files in local directories are used in place, everything else gets downloaded to `dest_dir`.
+    fn fetch(
+        &self,
+        settings: &ClientSettings,
+        name: &str,
+        dest_dir: &Utf8Path,
+    ) -> DistResult<Option<Utf8PathBuf>> {
+        match self {
+            Self::Dir(dir) => {
+                let path = dir.join(name);
+                Ok(path.exists().then_some(path))
+            }
+            Self::Url(base) => {
+                let client = create_axoasset_client(settings)?;
+                let url = format!("{base}/{name}");
+                let desc = format!("download {name}");
+                let contents = tokio::runtime::Handle::current()
+                    .block_on(settings.retry.run(&desc, || fetch_file(&client, &url)))?;
+                let Some(contents) = contents else {
+                    return Ok(None);
+                };
+                let path = dest_dir.join(name);
+                LocalAsset::create_dir_all(dest_dir)?;
+                std::fs::write(&path, contents)?;
+                Ok(Some(path))
+            }
+        }
+    }
+}
+
+impl std::fmt::Display for ReleaseSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Dir(dir) => dir.fmt(f),
+            Self::Url(url) => url.fmt(f),
+        }
+    }
+}
+
+/// Verify a published release
+pub fn do_verify(cfg: &Config, args: &VerifyArgs) -> DistResult<()> {
+    let (source, manifest_name) = match (&args.source, &args.tag) {
+        (Some(input), _) => ReleaseSource::parse(input),
+        (None, Some(tag)) => {
+            let cfg = Config {
+                tag_settings: TagSettings {
+                    needs_coherence: true,
+                    tag: TagMode::Select(tag.clone()),
+                },
+                ..cfg.clone()
+            };
+            (source_for_tag(&cfg)?, "dist-manifest.json".to_owned())
+        }
+        (None, None) => (source_for_tag(cfg)?, "dist-manifest.json".to_owned()),
+    };
+    verify_release(&source, &manifest_name, !args.skip_installers)
+}
+
+/// Find where the release for the current tag is hosted
+fn source_for_tag(cfg: &Config) -> DistResult<ReleaseSource> {
+    let (_dist, manifest) = gather_work(cfg)?;
+    manifest
+        .releases
+        .iter()
+        .find_map(|release| release.hosting.github.as_ref())
+        .map(|github| ReleaseSource::Url(github.artifact_download_url.clone()))
+        .ok_or_else(|| DistError::VerifyNoHosting {
+            tag: manifest.announcement_tag.clone().unwrap_or_default(),
+        })
+}
+
+/// Verify the release described by the named dist-manifest.json in `source`
+pub fn verify_release(
+    source: &ReleaseSource,
+    manifest_name: &str,
+    run_installers: bool,
+) -> DistResult<()> {
+    let settings = ClientSettings::new();
+    let tempdir = TempDir::new()?;
+    let workdir =
+        Utf8PathBuf::from_path_buf(tempdir.path().to_owned()).expect("temp dir wasn't utf8?!");
+    let downloads = workdir.join("downloads");
+
+    eprintln!("verifying release from {source}");
+    let manifest_path = source
+        .fetch(&settings, manifest_name, &downloads)?
+        .ok_or_else(|| DistError::VerifyNoManifest {
+            location: source.to_string(),
+            manifest: manifest_name.to_owned(),
+        })?;
+    let manifest: DistManifest = SourceFile::load_local(manifest_path)?.deserialize_json()?;
+
+    // Get everything up front, as checksum files need their siblings
+    let mut problems = vec![];
+    let mut files = SortedMap::new();
+    for artifact in manifest.artifacts.values() {
+        let Some(name) = artifact.name.as_deref() else {
+            continue;
+        };
+        match source.fetch(&settings, name, &downloads)? {
+            Some(path) => {
+                files.insert(name.to_owned(), path);
+            }
+            None => problems.push(format!("{name}: missing from the release")),
+        }
+    }
+
+    let attestation_repo = if manifest.github_attestations {
+        manifest
+            .releases
+            .iter()
+            .find_map(|release| release.hosting.github.as_ref())
+            .map(|github| format!("{}/{}", github.owner, github.repo))
+    } else {
+        None
+    };
+
+    for (id, artifact) in &manifest.artifacts {
+        let Some(name) = artifact.name.as_deref() else {
+            continue;
+        };
+        let Some(path) = files.get(name) else {
+            continue;
+        };
+        let mut found = check_checksums(artifact, path)?;
+        match artifact.kind {
+            ArtifactKind::Checksum => found.extend(check_checksum_file(name, path, &files)?),
+            ArtifactKind::ExecutableZip => {
+                let dest = workdir.join("unpacked").join(name);
+                found.extend(check_archive(artifact, path, &dest)?);
+            }
+            ArtifactKind::Installer if run_installers => {
+                let dest = workdir.join("installed").join(name);
+                found.extend(check_installer(&manifest, id, source, path, &dest)?);
+            }
+            _ => {}
+        }
+        if !matches!(artifact.kind, ArtifactKind::Checksum) {
+            found.extend(check_signatures(source, &settings, name, path, &downloads)?);
+            if let Some(repo) = &attestation_repo {
+                found.extend(check_attestation(repo, path));
+            }
+        }
+
+        if found.is_empty() {
+            eprintln!("  {name}: ok");
+        }
+        problems.extend(
+            found
+                .into_iter()
+                .map(|problem| format!("{name}: {problem}")),
+        );
+    }
+
+    if !problems.is_empty() {
+        return Err(DistError::VerifyFailed { problems });
+    }
+    eprintln!("release verified!");
+    Ok(())
+}
+
+/// Check the checksums the manifest has for this artifact
+fn check_checksums(artifact: &Artifact, path: &Utf8Path) -> DistResult<Vec<String>> {
+    let mut problems = vec![];
+    for (ext, expected) in &artifact.checksums {
+        let Some(style) = ChecksumStyle::from_ext(ext) else {
+            problems.push(format!("unknown checksum kind {ext}"));
+            continue;
+        };
+        let actual = generate_checksum(&style, path)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            problems.push(format!(
+                "{ext} checksum is {actual}, but the manifest says {expected}"
+            ));
+        }
+    }
+    Ok(problems)
+}
+
+/// Check that a checksum file (`my-app.tar.gz.sha256` or `sha256.sum`) matches the files it lists
+fn check_checksum_file(
+    name: &str,
+    path: &Utf8Path,
+    files: &SortedMap<String, Utf8PathBuf>,
+) -> DistResult<Vec<String>> {
+    let ext = match name.rsplit_once('.') {
+        Some((stem, "sum")) => stem.rsplit('.').next().unwrap_or(stem),
+        Some((_, ext)) => ext,
+        None => name,
+    };
+    let Some(style) = ChecksumStyle::from_ext(ext) else {
+        return Ok(vec![format!("unknown checksum kind {ext}")]);
+    };
+
+    let mut problems = vec![];
+    let contents = LocalAsset::load_string(path)?;
+    for line in contents.lines().filter(|line| !line.trim().is_empty()) {
+        // <checksum> <mode><path>, where mode is ' ' or '*'
+        let Some((expected, file)) = line.split_once(' ') else {
+            problems.push(format!("couldn't parse line {line:?}"));
+            continue;
+        };
+        let file = file.trim_start_matches([' ', '*']);
+        let Some(file_path) = files.get(file) else {
+            problems.push(format!("lists {file}, which isn't in the release"));
+            continue;
+        };
+        let actual = generate_checksum(&style, file_path)?;
+        if !actual.eq_ignore_ascii_case(expected) {
+            problems.push(format!("says {file} is {expected}, but it's {actual}"));
+        }
+    }
+    Ok(problems)
+}
+
+/// Unpack an archive and check that all the assets the manifest claims are in it
+fn check_archive(artifact: &Artifact, path: &Utf8Path, dest: &Utf8Path) -> DistResult<Vec<String>> {
+    let name = path.file_name().unwrap_or_default();
+    let unpacked = if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        LocalAsset::untar_gz_all(path, dest)
+    } else if name.ends_with(".tar.xz") {
+        LocalAsset::untar_xz_all(path, dest)
+    } else if name.ends_with(".tar.zst") {
+        LocalAsset::untar_zstd_all(path, dest)
+    } else if name.ends_with(".zip") {
+        LocalAsset::unzip_all(path, dest)
+    } else {
+        return Ok(vec!["unrecognized archive format".to_owned()]);
+    };
+    if let Err(e) = unpacked {
+        return Ok(vec![format!("couldn't unpack archive: {e}")]);
+    }
+
+    // Tarballs have everything under a root dir, zips don't
+    let root = single_subdir(dest)?.unwrap_or_else(|| dest.to_owned());
+    let problems = artifact
+        .assets
+        .iter()
+        .filter_map(|asset| asset.path.as_deref())
+        .filter(|asset_path| !root.join(asset_path).exists() && !dest.join(asset_path).exists())
+        .map(|asset_path| format!("archive is missing {asset_path}"))
+        .collect();
+    Ok(problems)
+}
+
+/// If `dir` contains nothing but a single directory, get it
+fn single_subdir(dir: &Utf8Path) -> DistResult<Option<Utf8PathBuf>> {
+    let mut entries = dir.read_dir_utf8()?;
+    let (Some(entry), None) = (entries.next().transpose()?, entries.next()) else {
+        return Ok(None);
+    };
+    Ok(entry.file_type()?.is_dir().then(|| entry.path().to_owned()))
+}
+
+/// Run an installer in a sandboxed install dir and check that it installed the release's binaries
+fn check_installer(
+    manifest: &DistManifest,
+    id: &str,
+    source: &ReleaseSource,
+    path: &Utf8Path,
+    dest: &Utf8Path,
+) -> DistResult<Vec<String>> {
+    let name = path.file_name().unwrap_or_default();
+    let mut cmd = if name.ends_with(".sh") && cfg!(unix) {
+        let mut cmd = Cmd::new("sh", "run shell installer");
+        cmd.arg(path);
+        cmd
+    } else if name.ends_with(".ps1") && cfg!(windows) {
+        let mut cmd = Cmd::new("powershell", "run powershell installer");
+        cmd.arg("-ExecutionPolicy")
+            .arg("Bypass")
+            .arg("-File")
+            .arg(path);
+        cmd
+    } else {
+        // Other installers either need a package manager or aren't for this platform
+        return Ok(vec![]);
+    };
+
+    // Keep the installer from touching anything outside of dest
+    let install_dir = dest.join("install");
+    let home = dest.join("home");
+    LocalAsset::create_dir_all(&install_dir)?;
+    LocalAsset::create_dir_all(&home)?;
+    let program = cmd.inner.get_program().to_string_lossy().into_owned();
+    cmd.env("CARGO_DIST_FORCE_INSTALL_DIR", &install_dir)
+        .env("INSTALLER_NO_MODIFY_PATH", "1")
+        .env("HOME", &home)
+        .env("ZDOTDIR", &home)
+        .env_remove("CARGO_HOME");
+    if let ReleaseSource::Dir(dir) = source {
+        // Point the installer at these files, instead of wherever they'll be hosted
+        let dir = dir.canonicalize_utf8()?;
+        cmd.env("INSTALLER_DOWNLOAD_URL", format!("file://{dir}"));
+    }
+    let problems = run_check(cmd, &program, "installer failed");
+    if !problems.is_empty() {
+        return Ok(problems);
+    }
+
+    // Check that the binaries from the release's archives got installed
+    let Some(release) = manifest
+        .releases
+        .iter()
+        .find(|release| release.artifacts.iter().any(|artifact| artifact == id))
+    else {
+        return Ok(vec![]);
+    };
+    let mut bins = manifest
+        .artifacts_for_release(release)
+        .filter(|(_, artifact)| matches!(artifact.kind, ArtifactKind::ExecutableZip))
+        .flat_map(|(_, artifact)| &artifact.assets)
+        .filter(|asset| matches!(asset.kind, AssetKind::Executable(_)))
+        .filter_map(|asset| Utf8Path::new(asset.path.as_deref()?).file_name())
+        .map(|bin| bin.trim_end_matches(".exe"))
+        .collect::<Vec<_>>();
+    bins.sort();
+    bins.dedup();
+
+    let problems = bins
+        .into_iter()
+        .filter(|bin| {
+            // Depending on the install layout binaries are either at the root or in bin/
+            let candidates = [install_dir.clone(), install_dir.join("bin")];
+            !candidates
+                .iter()
+                .any(|dir| dir.join(bin).exists() || dir.join(format!("{bin}.exe")).exists())
+        })
+        .map(|bin| format!("installer didn't install {bin}"))
+        .collect();
+    Ok(problems)
+}
+
+/// Check the file's detached signatures (`my-app.tar.gz.sig` or `my-app.tar.gz.asc`), if it has any
+///
+/// These are OpenPGP signatures, checked against the keys gpg knows about.
+fn check_signatures(
+    source: &ReleaseSource,
+    settings: &ClientSettings,
+    name: &str,
+    path: &Utf8Path,
+    dest_dir: &Utf8Path,
+) -> DistResult<Vec<String>> {
+    let mut problems = vec![];
+    for ext in ["sig", "asc"] {
+        let sig_name = format!("{name}.{ext}");
+        let Some(sig_path) = source.fetch(settings, &sig_name, dest_dir)? else {
+            continue;
+        };
+        let mut cmd = Cmd::new("gpg", "verify signature");
+        cmd.arg("--verify").arg(sig_path).arg(path);
+        problems.extend(run_check(
+            cmd,
+            "gpg",
+            &format!("signature {sig_name} didn't verify"),
+        ));
+    }
+    Ok(problems)
+}
+
+/// Check the file's GitHub Artifact Attestation
+fn check_attestation(repo: &str, path: &Utf8Path) -> Vec<String> {
+    let mut cmd = Cmd::new("gh", "verify GitHub attestation");
+    cmd.arg("attestation")
+        .arg("verify")
+        .arg(path)
+        .arg("--repo")
+        .arg(repo);
+    run_check(cmd, "gh", "GitHub attestation didn't verify")
+}
+
+/// Run a command that checks something, turning failure into a problem
+///
+/// Not being able to run the command at all (most likely because it isn't installed)
+/// is also a problem, rather than something that stops the rest of the verification.
+fn run_check(mut cmd: Cmd, program: &str, failed: &str) -> Vec<String> {
+    cmd.check(false);
+    match cmd.output() {
+        Ok(output) if output.status.success() => vec![],
+        Ok(output) => {
+            // The last thing it says is usually the most relevant
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            match stderr.lines().rev().find(|line| !line.trim().is_empty()) {
+                Some(reason) => vec![format!("{failed} ({}): {}", output.status, reason.trim())],
+                None => vec![format!("{failed} ({})", output.status)],
+            }
+        }
+        Err(_) => vec![format!(
+            "{failed}: couldn't run {program}, is it installed?"
+        )],
+    }
+}